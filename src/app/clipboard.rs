@@ -0,0 +1,31 @@
+//! System clipboard access (requires the `clipboard` feature)
+
+use anyhow::Result;
+
+/// The system clipboard, opened on the first copy. On X11 copied text is
+/// served by the process that owns the clipboard handle and is gone once it
+/// drops, so the app keeps this for as long as it runs.
+#[derive(Default)]
+pub struct Clipboard {
+    #[cfg(feature = "clipboard")]
+    handle: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Places text on the system clipboard
+    #[cfg(feature = "clipboard")]
+    pub fn copy_text(&mut self, text: &str) -> Result<()> {
+        let handle = match self.handle {
+            Some(ref mut handle) => handle,
+            None => self.handle.insert(arboard::Clipboard::new()?),
+        };
+        handle.set_text(text)?;
+        Ok(())
+    }
+
+    /// Places text on the system clipboard
+    #[cfg(not(feature = "clipboard"))]
+    pub fn copy_text(&mut self, _text: &str) -> Result<()> {
+        anyhow::bail!("built without the `clipboard` feature")
+    }
+}
@@ -0,0 +1,48 @@
+//! Runs the app without a terminal, for tests
+//!
+//! [`drive`] feeds keys to an [`App`] the way the event loop of
+//! [`App::run`] would: before each key, the work a waiting user would let
+//! happen (a running sync, analyze-all) runs to completion and every frame
+//! is drawn, to an off-screen buffer. Nothing depends on timing, so a flow
+//! like analyze → resolve a conflict → sync plays out the same everywhere.
+
+use anyhow::Result;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+use super::App;
+
+/// Size of the off-screen terminal frames are drawn to
+pub const DRIVE_WIDTH: u16 = 120;
+pub const DRIVE_HEIGHT: u16 = 40;
+
+/// Presses `keys` in order, then lets pending work finish
+pub fn drive(app: &mut App, keys: &[KeyCode]) -> Result<()> {
+    let events: Vec<Event> = keys
+        .iter()
+        .map(|&code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+        .collect();
+    drive_events(app, &events)
+}
+
+/// Like [`drive`], for any terminal events, e.g. mouse clicks or resizes
+pub fn drive_events(app: &mut App, events: &[Event]) -> Result<()> {
+    let mut terminal = Terminal::new(TestBackend::new(DRIVE_WIDTH, DRIVE_HEIGHT))?;
+    for event in events {
+        settle(app, &mut terminal)?;
+        app.handle_event(event.clone());
+    }
+    settle(app, &mut terminal)
+}
+
+/// Draws frames until the app has nothing left to do without input
+fn settle(app: &mut App, terminal: &mut Terminal<TestBackend>) -> Result<()> {
+    loop {
+        let worked = app.tick();
+        terminal.draw(|frame| app.render(frame))?;
+        if !worked || app.should_quit {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Running an external editor on a file with the TUI suspended
+
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+
+/// Editors tried in order when neither `$VISUAL` nor `$EDITOR` is set
+#[cfg(windows)]
+const FALLBACK_EDITORS: &[&str] = &["notepad"];
+#[cfg(not(windows))]
+const FALLBACK_EDITORS: &[&str] = &["nano", "vi"];
+
+/// Editor commands to try: `$VISUAL` or `$EDITOR` (split on whitespace, so
+/// `code -w` works) if set, the fallbacks otherwise. `var` looks up a variable.
+fn editor_commands(var: impl Fn(&str) -> Option<String>) -> Vec<Vec<String>> {
+    let configured = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| var(name))
+        .map(|cmd| cmd.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .find(|cmd| !cmd.is_empty());
+    match configured {
+        Some(cmd) => vec![cmd],
+        None => FALLBACK_EDITORS
+            .iter()
+            .map(|editor| vec![editor.to_string()])
+            .collect(),
+    }
+}
+
+/// Leaves raw mode and the alternate screen; dropping it re-enters both,
+/// also when the editor could not be started or the caller bails early
+struct Suspended;
+
+impl Suspended {
+    fn new() -> Result<Self> {
+        // Made first, so leaving that fails partway is undone when it drops
+        let suspended = Self;
+        execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen)?;
+        disable_raw_mode()?;
+        Ok(suspended)
+    }
+}
+
+impl Drop for Suspended {
+    fn drop(&mut self) {
+        let _ = enable_raw_mode();
+        let _ = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture);
+    }
+}
+
+/// Runs the first editor that starts on `path`
+fn run_editor(path: &Path, commands: &[Vec<String>]) -> Result<(String, ExitStatus)> {
+    for cmd in commands {
+        match Command::new(&cmd[0]).args(&cmd[1..]).arg(path).status() {
+            Ok(status) => return Ok((cmd[0].clone(), status)),
+            // Not installed: try the next fallback
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to start editor '{}'", cmd[0]))
+            }
+        }
+    }
+    let tried: Vec<_> = commands.iter().map(|cmd| cmd[0].as_str()).collect();
+    bail!("No editor found (tried {}); set $EDITOR", tried.join(", "))
+}
+
+/// Opens `path` in the user's editor and waits for it to exit. The terminal
+/// is handed over to the editor meanwhile and fully redrawn afterwards.
+pub fn edit_file(terminal: &mut ratatui::DefaultTerminal, path: &Path) -> Result<()> {
+    let commands = editor_commands(|name| std::env::var(name).ok());
+    let result = {
+        let _suspended = Suspended::new()?;
+        run_editor(path, &commands)
+    };
+    terminal.clear()?;
+
+    let (editor, status) = result?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_commands() {
+        let vars = |visual: Option<&str>, editor: Option<&str>| {
+            let (visual, editor) = (visual.map(String::from), editor.map(String::from));
+            editor_commands(move |name| match name {
+                "VISUAL" => visual.clone(),
+                "EDITOR" => editor.clone(),
+                _ => None,
+            })
+        };
+
+        assert_eq!(vars(Some("code -w"), Some("vim")), [["code", "-w"]]);
+        assert_eq!(vars(None, Some("vim")), [["vim"]]);
+        // Blank variables count as unset
+        let fallbacks: Vec<Vec<String>> = FALLBACK_EDITORS
+            .iter()
+            .map(|editor| vec![editor.to_string()])
+            .collect();
+        assert_eq!(vars(Some("  "), None), fallbacks);
+    }
+
+    #[test]
+    fn test_missing_editors_are_skipped() {
+        let missing = vec!["rahzom-no-such-editor".to_string()];
+        let err = run_editor(Path::new("file"), &[missing.clone(), missing]).unwrap_err();
+        assert!(err.to_string().contains("set $EDITOR"));
+    }
+}
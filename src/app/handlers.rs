@@ -1,60 +1,100 @@
 //! Event handling for the application
 
 use anyhow::Result;
+use chrono::Utc;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use std::collections::HashSet;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
-use super::{App, Dialog, NewProjectDialog, Screen, SettingsDialog, UserAction};
+use super::{
+    keymap, App, Dialog, HelpDialog, NewProjectDialog, ProjectFileDialog, Screen, ScriptDialog,
+    SettingsDialog, UserAction,
+};
+use crate::config::history::PathHistory;
+use crate::config::project::is_plain_file_name;
+use crate::sync::differ::{ConflictReason, SyncAction};
+use crate::sync::utils::{conflict_file_name, local_hostname};
 
 impl App {
     /// Handle input events
     pub(super) fn handle_events(&mut self) -> Result<()> {
-        // Use shorter poll timeout during sync for responsiveness
-        let poll_timeout = if self.screen == Screen::Syncing {
-            Duration::from_millis(10)
-        } else {
-            Duration::from_millis(100)
+        // Without a timeout nothing needs redrawing until input arrives
+        let ready = match self.poll_timeout() {
+            Some(timeout) => event::poll(timeout)?,
+            None => true,
         };
 
-        if event::poll(poll_timeout)? {
-            match event::read()? {
-                Event::Key(key) if key.kind == KeyEventKind::Press => {
-                    self.handle_key(key.code);
-                }
-                Event::Mouse(mouse) => {
-                    self.handle_mouse(mouse);
-                }
-                Event::Resize(_, _) => {}
-                _ => {}
-            }
+        if ready {
+            self.handle_event(event::read()?);
         }
         Ok(())
     }
 
+    /// Handle one terminal event
+    pub fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                self.handle_key(key.code);
+            }
+            Event::Mouse(mouse) => {
+                self.handle_mouse(mouse);
+                self.show_queued_dialog();
+            }
+            // Returning redraws right away, at the new size
+            Event::Resize(_, _) => self.resized = true,
+            _ => {}
+        }
+    }
+
     /// Handle keyboard input
     pub fn handle_key(&mut self, code: KeyCode) {
+        self.dispatch_key(code);
+        self.show_queued_dialog();
+    }
+
+    fn dispatch_key(&mut self, code: KeyCode) {
         match &self.dialog {
             Dialog::None => self.handle_key_normal(code),
             Dialog::NewProject(_) => self.handle_key_new_project(code),
+            Dialog::ProjectFile(_) => self.handle_key_project_file(code),
+            Dialog::Script(_) => self.handle_key_script(code),
             Dialog::DeleteConfirm(_) => self.handle_key_delete_confirm(code),
+            Dialog::SwapSidesConfirm => self.handle_key_swap_sides_confirm(code),
             Dialog::CreateDirConfirm { .. } => self.handle_key_create_dir_confirm(code),
             Dialog::Error(_) => self.handle_key_error(code),
             Dialog::SyncConfirm(_) => self.handle_key_sync_confirm(code),
             Dialog::CancelSyncConfirm => self.handle_key_cancel_sync_confirm(code),
+            Dialog::QuitConfirm { .. } => self.handle_key_quit_confirm(code),
+            Dialog::ResolveAllConfirm { .. } => self.handle_key_resolve_all_confirm(code),
             Dialog::ExclusionsInfo(_) => self.handle_key_exclusions_info(code),
             Dialog::DiskSpaceWarning(_) => self.handle_key_disk_space_warning(code),
+            Dialog::ClockSkewWarning(_) => self.handle_key_error(code),
+            Dialog::IncompleteTransfers { .. } => self.handle_key_incomplete_transfers(code),
+            Dialog::LargePreview(_) => self.handle_key_large_preview(code),
+            Dialog::MetadataInspect(_) => self.handle_key_error(code),
             Dialog::FileError(_) => self.handle_key_file_error(code),
             Dialog::ProjectSettings(_) => self.handle_key_settings(code),
+            Dialog::TargetStatus(_) => self.handle_key_error(code),
+            Dialog::BackupBrowser(_) => self.handle_key_backup_browser(code),
+            Dialog::VerifyReport(_) => self.handle_key_verify_report(code),
+            Dialog::Help(_) => self.handle_key_help(code),
         }
     }
 
     fn handle_key_normal(&mut self, code: KeyCode) {
+        if code == KeyCode::Char('?') {
+            self.dialog = Dialog::Help(HelpDialog::default());
+            return;
+        }
         match self.screen {
             Screen::ProjectList => self.handle_key_project_list(code),
             Screen::ProjectView => self.handle_key_project_view(code),
             Screen::Preview => self.handle_key_preview(code),
             Screen::Syncing => self.handle_key_syncing(code),
             Screen::SyncComplete => self.handle_key_sync_complete(code),
+            Screen::AnalyzeAll => self.handle_key_analyze_all(code),
+            Screen::History => self.handle_key_history(code),
             _ => {}
         }
     }
@@ -62,10 +102,10 @@ impl App {
     fn handle_key_project_list(&mut self, code: KeyCode) {
         match code {
             KeyCode::Char('q') | KeyCode::Char('Q') => {
-                self.should_quit = true;
+                self.request_quit();
             }
             KeyCode::Esc => {
-                self.should_quit = true;
+                self.request_quit();
             }
             KeyCode::Up | KeyCode::Char('k') => {
                 self.select_previous_project();
@@ -77,7 +117,13 @@ impl App {
                 self.open_selected_project();
             }
             KeyCode::Char('n') | KeyCode::Char('N') => {
-                self.dialog = Dialog::NewProject(NewProjectDialog::new());
+                // A missing or unreadable history just means no suggestions
+                let history = self
+                    .project_manager
+                    .as_ref()
+                    .and_then(|pm| PathHistory::load(pm.config_dir()).ok())
+                    .unwrap_or_default();
+                self.dialog = Dialog::NewProject(NewProjectDialog::with_history(history));
             }
             KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete => {
                 if let Some(selected) = self.list_state.selected() {
@@ -86,15 +132,59 @@ impl App {
                     }
                 }
             }
-            KeyCode::Home => {
-                if !self.projects.is_empty() {
-                    self.list_state.select(Some(0));
+            KeyCode::Char('a') | KeyCode::Char('A') => {
+                self.start_analyze_all();
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                if let Some(selected) = self.list_state.selected() {
+                    if let Some(name) = self.projects.get(selected) {
+                        self.dialog = Dialog::ProjectFile(ProjectFileDialog::export(name));
+                    }
                 }
             }
+            KeyCode::Char('i') | KeyCode::Char('I') => {
+                self.dialog = Dialog::ProjectFile(ProjectFileDialog::import());
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                self.toggle_pin_selected();
+            }
+            KeyCode::Home if !self.projects.is_empty() => {
+                self.list_state.select(Some(0));
+            }
+            KeyCode::End if !self.projects.is_empty() => {
+                self.list_state.select(Some(self.projects.len() - 1));
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_analyze_all(&mut self, code: KeyCode) {
+        let Some(ref mut state) = self.analyze_all else {
+            return;
+        };
+        let last = state.entries.len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Backspace => {
+                self.analyze_all = None;
+                self.screen = Screen::ProjectList;
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                self.request_quit();
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.selected = state.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.selected = (state.selected + 1).min(last);
+            }
+            KeyCode::Home => {
+                state.selected = 0;
+            }
             KeyCode::End => {
-                if !self.projects.is_empty() {
-                    self.list_state.select(Some(self.projects.len() - 1));
-                }
+                state.selected = last;
+            }
+            KeyCode::Enter => {
+                self.open_analyzed_project();
             }
             _ => {}
         }
@@ -107,26 +197,81 @@ impl App {
                 self.current_project = None;
             }
             KeyCode::Char('q') | KeyCode::Char('Q') => {
-                self.should_quit = true;
+                self.request_quit();
             }
             KeyCode::Char('a') | KeyCode::Char('A') => {
                 self.run_analyze();
             }
+            KeyCode::Char('f') | KeyCode::Char('F') => {
+                self.run_full_rescan();
+            }
             KeyCode::Char('c') | KeyCode::Char('C') => {
                 self.show_settings_dialog();
             }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.show_target_status_dialog();
+            }
+            KeyCode::Char('v') | KeyCode::Char('V') => {
+                self.run_verify();
+            }
+            KeyCode::Char('w') | KeyCode::Char('W') => {
+                self.dialog = Dialog::SwapSidesConfirm;
+            }
+            KeyCode::Char('h') | KeyCode::Char('H') => {
+                self.show_history();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_history(&mut self, code: KeyCode) {
+        let Some(ref mut state) = self.history else {
+            return;
+        };
+        let last = state.runs.len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Backspace => {
+                self.history = None;
+                self.screen = Screen::ProjectView;
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                self.request_quit();
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.scroll = state.scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                state.scroll = (state.scroll + 1).min(last);
+            }
+            KeyCode::Home => {
+                state.scroll = 0;
+            }
+            KeyCode::End => {
+                state.scroll = last;
+            }
             _ => {}
         }
     }
 
     fn handle_key_preview(&mut self, code: KeyCode) {
         match code {
+            KeyCode::Esc
+                if self
+                    .preview
+                    .as_ref()
+                    .is_some_and(|p| p.range_anchor.is_some()) =>
+            {
+                if let Some(ref mut preview) = self.preview {
+                    preview.range_anchor = None;
+                }
+            }
             KeyCode::Esc | KeyCode::Backspace => {
+                self.save_review_session();
                 self.screen = Screen::ProjectView;
                 self.preview = None;
             }
             KeyCode::Char('q') | KeyCode::Char('Q') => {
-                self.should_quit = true;
+                self.request_quit();
             }
             KeyCode::Up | KeyCode::Char('k') => {
                 self.select_previous_action();
@@ -137,15 +282,47 @@ impl App {
             KeyCode::Char('f') | KeyCode::Char('F') => {
                 self.cycle_filter();
             }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                self.cycle_side_filter();
+            }
+            KeyCode::Char('v') | KeyCode::Char('V') => {
+                self.toggle_show_skips();
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.copy_summary_to_clipboard();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                self.select_conflict(true);
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => {
+                self.select_conflict(false);
+            }
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                if let Some(ref project) = self.current_project {
+                    self.dialog = Dialog::Script(ScriptDialog::new(&project.name));
+                }
+            }
             KeyCode::Left | KeyCode::Char('h') => {
                 self.change_action_to_left();
             }
             KeyCode::Right | KeyCode::Char('l') => {
                 self.change_action_to_right();
             }
+            KeyCode::Char('<') => {
+                self.confirm_resolve_all_conflicts(true);
+            }
+            KeyCode::Char('>') => {
+                self.confirm_resolve_all_conflicts(false);
+            }
             KeyCode::Char('s') | KeyCode::Char('S') => {
                 self.skip_selected_action();
             }
+            KeyCode::Char('a') | KeyCode::Char('A') => {
+                self.accept_selected_conflict();
+            }
+            KeyCode::Char('K') => {
+                self.keep_both_selected_conflict();
+            }
             KeyCode::Char('r') | KeyCode::Char('R') => {
                 self.reset_selected_action();
             }
@@ -155,13 +332,34 @@ impl App {
             KeyCode::Char(' ') => {
                 self.toggle_selection();
             }
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                self.toggle_range();
+            }
             KeyCode::Char('e') | KeyCode::Char('E') => {
                 self.show_exclusions_dialog();
             }
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                self.show_backup_browser();
+            }
+            KeyCode::Char('m') | KeyCode::Char('M') => {
+                self.show_metadata_inspector();
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.toggle_grouping();
+            }
+            KeyCode::Char('i') | KeyCode::Char('I') => {
+                if let Some(ref mut preview) = self.preview {
+                    preview.show_details = !preview.show_details;
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(ref mut preview) = self.preview {
+                    preview.toggle_selected_group();
+                }
+            }
             KeyCode::Home => {
                 if let Some(ref mut preview) = self.preview {
-                    let indices = preview.filtered_indices();
-                    if !indices.is_empty() {
+                    if !preview.rows().is_empty() {
                         preview.selected = 0;
                         preview.scroll_offset = 0;
                     }
@@ -169,12 +367,25 @@ impl App {
             }
             KeyCode::End => {
                 if let Some(ref mut preview) = self.preview {
-                    let indices = preview.filtered_indices();
-                    if !indices.is_empty() {
-                        preview.selected = indices.len() - 1;
+                    let rows = preview.rows();
+                    if !rows.is_empty() {
+                        preview.selected = rows.len() - 1;
                     }
                 }
             }
+            KeyCode::PageUp => {
+                if let Some(ref mut preview) = self.preview {
+                    let page = preview.page_rows.get().max(1);
+                    preview.selected = preview.selected.saturating_sub(page);
+                }
+            }
+            KeyCode::PageDown => {
+                if let Some(ref mut preview) = self.preview {
+                    let page = preview.page_rows.get().max(1);
+                    let last = preview.rows().len().saturating_sub(1);
+                    preview.selected = (preview.selected + page).min(last);
+                }
+            }
             _ => {}
         }
     }
@@ -186,20 +397,35 @@ impl App {
                     self.dialog = Dialog::None;
                 }
                 KeyCode::Tab => {
-                    dialog.next_field();
+                    // Completes a highlighted suggestion, otherwise moves on
+                    let completed = dialog.accept_suggestion();
+                    if !completed {
+                        dialog.next_field();
+                    }
                 }
                 KeyCode::BackTab => {
                     dialog.prev_field();
                 }
+                KeyCode::Down => {
+                    dialog.cycle_suggestion(true);
+                }
+                KeyCode::Up => {
+                    dialog.cycle_suggestion(false);
+                }
+                KeyCode::Right => {
+                    dialog.accept_suggestion();
+                }
                 KeyCode::Enter => {
                     self.try_create_project();
                 }
                 KeyCode::Backspace => {
                     dialog.focused_value_mut().pop();
+                    dialog.suggestion = None;
                     dialog.error = None;
                 }
                 KeyCode::Char(c) => {
                     dialog.focused_value_mut().push(c);
+                    dialog.suggestion = None;
                     dialog.error = None;
                 }
                 _ => {}
@@ -207,6 +433,57 @@ impl App {
         }
     }
 
+    fn handle_key_project_file(&mut self, code: KeyCode) {
+        let Dialog::ProjectFile(ref mut dialog) = self.dialog else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Enter => {
+                self.finish_project_file();
+            }
+            KeyCode::Backspace => {
+                dialog.path.pop();
+                dialog.error = None;
+            }
+            KeyCode::Char(c) => {
+                dialog.path.push(c);
+                dialog.error = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_script(&mut self, code: KeyCode) {
+        let Dialog::Script(ref mut dialog) = self.dialog else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Enter => {
+                self.finish_script();
+            }
+            KeyCode::Tab => {
+                dialog.toggle_platform();
+            }
+            KeyCode::Backspace => {
+                dialog.path.pop();
+                dialog.error = None;
+                dialog.overwrite = false;
+            }
+            KeyCode::Char(c) => {
+                dialog.path.push(c);
+                dialog.error = None;
+                dialog.overwrite = false;
+            }
+            _ => {}
+        }
+    }
+
     fn handle_key_delete_confirm(&mut self, code: KeyCode) {
         match code {
             KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
@@ -223,6 +500,69 @@ impl App {
         }
     }
 
+    fn handle_key_swap_sides_confirm(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.dialog = Dialog::None;
+                self.swap_project_sides();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_large_preview(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Char('L') => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Char('g') | KeyCode::Char('G') => {
+                self.dialog = Dialog::None;
+                self.show_sync_confirmation();
+            }
+            KeyCode::Esc => {
+                self.dialog = Dialog::None;
+                self.screen = Screen::ProjectView;
+                self.preview = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_resolve_all_confirm(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Dialog::ResolveAllConfirm { to_left, .. } = self.dialog {
+                    self.dialog = Dialog::None;
+                    if to_left {
+                        self.resolve_all_conflicts_left();
+                    } else {
+                        self.resolve_all_conflicts_right();
+                    }
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_incomplete_transfers(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.dialog = Dialog::None;
+                self.remove_incomplete_transfers();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Enter | KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            _ => {}
+        }
+    }
+
     fn handle_key_create_dir_confirm(&mut self, code: KeyCode) {
         match code {
             KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
@@ -257,6 +597,10 @@ impl App {
     }
 
     fn handle_key_sync_confirm(&mut self, code: KeyCode) {
+        let Dialog::SyncConfirm(ref mut dialog) = self.dialog else {
+            return;
+        };
+
         match code {
             KeyCode::Enter => {
                 self.dialog = Dialog::None;
@@ -265,6 +609,12 @@ impl App {
             KeyCode::Esc => {
                 self.dialog = Dialog::None;
             }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.scroll = dialog.scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if dialog.scroll + 1 < dialog.line_count() => {
+                dialog.scroll += 1;
+            }
             _ => {}
         }
     }
@@ -284,6 +634,19 @@ impl App {
         }
     }
 
+    fn handle_key_quit_confirm(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.dialog = Dialog::None;
+                self.should_quit = true;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            _ => {}
+        }
+    }
+
     fn handle_key_exclusions_info(&mut self, code: KeyCode) {
         match code {
             KeyCode::Esc => {
@@ -292,11 +655,96 @@ impl App {
             KeyCode::Char('t') | KeyCode::Char('T') | KeyCode::Enter => {
                 self.create_exclusions_template();
             }
+            KeyCode::Char('l') | KeyCode::Char('L') => {
+                self.edit_exclusions(true);
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                self.edit_exclusions(false);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_verify_report(&mut self, code: KeyCode) {
+        let Dialog::VerifyReport(ref mut dialog) = self.dialog else {
+            return;
+        };
+
+        match code {
+            KeyCode::Esc | KeyCode::Enter => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.scroll = dialog.scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j')
+                if dialog.scroll + 1 < dialog.report.issues.len() =>
+            {
+                dialog.scroll += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_help(&mut self, code: KeyCode) {
+        let Dialog::Help(ref mut dialog) = self.dialog else {
+            return;
+        };
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('?') => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.scroll = dialog.scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if dialog.scroll + 1 < keymap::help_line_count() => {
+                dialog.scroll += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_backup_browser(&mut self, code: KeyCode) {
+        let Dialog::BackupBrowser(ref mut dialog) = self.dialog else {
+            return;
+        };
+
+        if dialog.confirm {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    self.restore_selected_backup();
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    dialog.confirm = false;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc => {
+                self.dialog = Dialog::None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.selected = dialog.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if dialog.selected + 1 < dialog.versions.len() => {
+                dialog.selected += 1;
+            }
+            KeyCode::Enter => {
+                dialog.confirm = true;
+            }
             _ => {}
         }
     }
 
     fn handle_key_disk_space_warning(&mut self, code: KeyCode) {
+        let Dialog::DiskSpaceWarning(ref mut dialog) = self.dialog else {
+            return;
+        };
+
         match code {
             KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
                 // User wants to continue anyway - start sync (skip further disk checks)
@@ -307,6 +755,12 @@ impl App {
                 // User cancelled
                 self.dialog = Dialog::None;
             }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.scroll = dialog.scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if dialog.scroll + 1 < dialog.line_count() => {
+                dialog.scroll += 1;
+            }
             _ => {}
         }
     }
@@ -389,6 +843,7 @@ impl App {
                         }
                     }
                     self.dialog = Dialog::None;
+                    self.notify("Settings saved");
                 }
                 Err(e) => {
                     if let Dialog::ProjectSettings(ref mut d) = self.dialog {
@@ -400,8 +855,14 @@ impl App {
     }
 
     fn handle_key_syncing(&mut self, code: KeyCode) {
-        if code == KeyCode::Esc {
-            self.dialog = Dialog::CancelSyncConfirm;
+        match code {
+            KeyCode::Esc => {
+                self.dialog = Dialog::CancelSyncConfirm;
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => {
+                self.request_quit();
+            }
+            _ => {}
         }
     }
 
@@ -419,19 +880,19 @@ impl App {
                     }
                 }
             }
+            KeyCode::Tab => {
+                if let Some(ref mut complete) = self.sync_complete {
+                    complete.list = complete.list.next();
+                }
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 if let Some(ref mut complete) = self.sync_complete {
-                    if complete.scroll_offset > 0 {
-                        complete.scroll_offset -= 1;
-                    }
+                    complete.scroll(false);
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 if let Some(ref mut complete) = self.sync_complete {
-                    let max_scroll = complete.failed.len().saturating_sub(1);
-                    if complete.scroll_offset < max_scroll {
-                        complete.scroll_offset += 1;
-                    }
+                    complete.scroll(true);
                 }
             }
             _ => {}
@@ -507,9 +968,8 @@ impl App {
                         if let Some(ref mut preview) = self.preview {
                             let relative_y = mouse.row.saturating_sub(content_area.y + 1);
                             let index = relative_y as usize + preview.scroll_offset;
-                            let indices = preview.filtered_indices();
 
-                            if index < indices.len() {
+                            if index < preview.rows().len() {
                                 preview.selected = index;
                             }
                         }
@@ -564,8 +1024,8 @@ impl App {
 
     fn select_next_action(&mut self) {
         if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if !indices.is_empty() && preview.selected < indices.len() - 1 {
+            let rows = preview.rows();
+            if !rows.is_empty() && preview.selected < rows.len() - 1 {
                 preview.selected += 1;
             }
         }
@@ -581,16 +1041,58 @@ impl App {
 
     fn cycle_filter(&mut self) {
         if let Some(ref mut preview) = self.preview {
+            let selected = preview.selected_action();
             preview.filter = preview.filter.next();
+            preview.reselect(selected);
+        }
+    }
+
+    fn cycle_side_filter(&mut self) {
+        if let Some(ref mut preview) = self.preview {
+            let selected = preview.selected_action();
+            preview.side_filter = preview.side_filter.next();
+            preview.reselect(selected);
+        }
+    }
+
+    fn toggle_show_skips(&mut self) {
+        if let Some(ref mut preview) = self.preview {
+            let selected = preview.selected_action();
+            preview.show_skips = !preview.show_skips;
+            preview.reselect(selected);
+        }
+    }
+
+    fn toggle_grouping(&mut self) {
+        if let Some(ref mut preview) = self.preview {
+            preview.grouped = !preview.grouped;
             preview.selected = 0;
             preview.scroll_offset = 0;
         }
     }
 
+    fn select_conflict(&mut self, forward: bool) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        if !preview.select_conflict(forward) {
+            self.notify("No conflicts shown");
+        }
+    }
+
+    fn copy_summary_to_clipboard(&mut self) {
+        let Some(text) = self.preview_summary_text() else {
+            return;
+        };
+        match self.clipboard.copy_text(&text) {
+            Ok(()) => self.notify("Summary copied to clipboard"),
+            Err(e) => self.notify(format!("Clipboard unavailable: {}", e)),
+        }
+    }
+
     fn toggle_selection(&mut self) {
         if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if let Some(&real_idx) = indices.get(preview.selected) {
+            if let Some(real_idx) = preview.selected_action() {
                 if preview.selected_items.contains(&real_idx) {
                     preview.selected_items.remove(&real_idx);
                 } else {
@@ -600,60 +1102,198 @@ impl App {
         }
     }
 
-    fn change_action_to_left(&mut self) {
-        if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if let Some(&real_idx) = indices.get(preview.selected) {
-                if let Some(action) = preview.actions.get(real_idx) {
-                    let path = action.path().clone();
-                    // CopyToLeft means source is RIGHT side
-                    // If file exists on right, copy to left
-                    // If file doesn't exist on right, delete from left
-                    if let Some(size) = preview.get_file_size_from_right(&path) {
-                        preview.actions[real_idx] = UserAction::CopyToLeft { path, size };
-                    } else {
-                        preview.actions[real_idx] = UserAction::DeleteLeft { path };
-                    }
-                }
+    /// Starts a range at the selected action, or drops the one started
+    fn toggle_range(&mut self) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        if preview.range_anchor.take().is_none() {
+            preview.range_anchor = preview.selected_action();
+            if preview.range_anchor.is_none() {
+                self.notify("Select an item to start a range");
             }
         }
     }
 
+    fn change_action_to_left(&mut self) {
+        self.direct_targets(true);
+    }
+
     fn change_action_to_right(&mut self) {
-        if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if let Some(&real_idx) = indices.get(preview.selected) {
-                if let Some(action) = preview.actions.get(real_idx) {
-                    let path = action.path().clone();
-                    // CopyToRight means source is LEFT side
-                    // If file exists on left, copy to right
-                    // If file doesn't exist on left, delete from right
-                    if let Some(size) = preview.get_file_size_from_left(&path) {
-                        preview.actions[real_idx] = UserAction::CopyToRight { path, size };
-                    } else {
-                        preview.actions[real_idx] = UserAction::DeleteRight { path };
-                    }
-                }
-            }
+        self.direct_targets(false);
+    }
+
+    fn direct_targets(&mut self, to_left: bool) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let ranged = preview.range_anchor.is_some();
+        let targets = preview.take_targets();
+        for &idx in &targets {
+            preview.direct_action(idx, to_left);
         }
+        if ranged {
+            // Items moved out of the filter may leave the cursor past the end
+            preview.selected = preview.selected.min(preview.rows().len().saturating_sub(1));
+            let arrow = if to_left { "←" } else { "→" };
+            self.notify(format!("{} items {}", targets.len(), arrow));
+        }
+    }
+
+    /// Asks before resolving every conflict in one direction
+    fn confirm_resolve_all_conflicts(&mut self, to_left: bool) {
+        let Some(ref preview) = self.preview else {
+            return;
+        };
+        let count = preview.unresolved_conflicts().len();
+        if count == 0 {
+            self.notify("No unresolved conflicts");
+            return;
+        }
+        self.dialog = Dialog::ResolveAllConfirm { to_left, count };
+    }
+
+    /// Resolves every unresolved conflict by copying the right side's file
+    /// to the left, or deleting the left one where the right has none
+    fn resolve_all_conflicts_left(&mut self) {
+        self.resolve_all_conflicts(true);
+    }
+
+    /// Resolves every unresolved conflict by copying the left side's file
+    /// to the right, or deleting the right one where the left has none
+    fn resolve_all_conflicts_right(&mut self) {
+        self.resolve_all_conflicts(false);
+    }
+
+    fn resolve_all_conflicts(&mut self, to_left: bool) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let conflicts = preview.unresolved_conflicts();
+        for &idx in &conflicts {
+            preview.direct_action(idx, to_left);
+        }
+        let arrow = if to_left { "←" } else { "→" };
+        self.notify(format!("Resolved {} conflicts {}", conflicts.len(), arrow));
     }
 
     fn skip_selected_action(&mut self) {
-        if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if let Some(&real_idx) = indices.get(preview.selected) {
-                if let Some(action) = preview.actions.get(real_idx) {
-                    let path = action.path().clone();
-                    preview.actions[real_idx] = UserAction::Skip { path };
-                }
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let ranged = preview.range_anchor.is_some();
+        let targets = preview.take_targets();
+        for &idx in &targets {
+            let path = preview.actions[idx].path().clone();
+            preview.set_action(idx, UserAction::Skip { path });
+        }
+        if ranged {
+            preview.selected = preview.selected.min(preview.rows().len().saturating_sub(1));
+            self.notify(format!("Skipped {} items", targets.len()));
+        }
+    }
+
+    /// Marks the selected conflict as resolved, keeping both files as they are
+    fn accept_selected_conflict(&mut self) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let Some(real_idx) = preview.selected_action() else {
+            return;
+        };
+
+        match &preview.actions[real_idx] {
+            UserAction::Original(SyncAction::Conflict {
+                path,
+                left: Some(_),
+                right: Some(_),
+                ..
+            }) => {
+                let path = path.clone();
+                preview.set_action(real_idx, UserAction::AcceptCurrent { path });
+            }
+            UserAction::Original(SyncAction::Conflict { .. }) => {
+                self.notify("Only conflicts with the file on both sides can be accepted");
+            }
+            _ => self.notify("Accept applies to conflicts only"),
+        }
+    }
+
+    /// Resolves the selected conflict by keeping both files, the right one
+    /// under a name from the project's conflict rename template
+    fn keep_both_selected_conflict(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        if project.settings.compressed_right {
+            self.notify("Keeping both isn't available with a compressed right side");
+            return;
+        }
+        let template = project.settings.conflict_rename_template.clone();
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let Some(real_idx) = preview.selected_action() else {
+            return;
+        };
+
+        let (path, left_size, right_size) = match &preview.actions[real_idx] {
+            UserAction::Original(SyncAction::Conflict {
+                path,
+                reason,
+                left: Some(left),
+                right: Some(right),
+            }) if *reason != ConflictReason::CaseConflict => (path.clone(), left.size, right.size),
+            UserAction::Original(SyncAction::Conflict { .. }) => {
+                self.notify("Only conflicts with the same file on both sides can keep both");
+                return;
+            }
+            _ => {
+                self.notify("Keep both applies to conflicts only");
+                return;
             }
+        };
+
+        // The copy must be new on both sides and not picked for another conflict
+        let taken: HashSet<&Path> = preview
+            .left_scan
+            .iter()
+            .chain(&preview.right_scan)
+            .flat_map(|scan| &scan.entries)
+            .map(|entry| entry.path.as_path())
+            .chain(preview.actions.iter().filter_map(|action| match action {
+                UserAction::KeepBoth { copy, .. } => Some(copy.as_path()),
+                _ => None,
+            }))
+            .collect();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let name = conflict_file_name(
+            &path.file_name().unwrap_or_default().to_string_lossy(),
+            &template,
+            &local_hostname(),
+            Utc::now(),
+            |candidate| taken.contains(dir.join(candidate).as_path()),
+        );
+        // The template is validated, but host names come from the system
+        if !is_plain_file_name(&name) {
+            self.notify(format!("Can't keep both as '{}'", name));
+            return;
         }
+        let copy = dir.join(name);
+        preview.set_action(
+            real_idx,
+            UserAction::KeepBoth {
+                path,
+                copy,
+                left_size,
+                right_size,
+            },
+        );
     }
 
     fn reset_selected_action(&mut self) {
         if let Some(ref mut preview) = self.preview {
-            let indices = preview.filtered_indices();
-            if let Some(&_real_idx) = indices.get(preview.selected) {
+            if let Some(_real_idx) = preview.selected_action() {
                 // We need to restore the original action - but we don't have it stored separately
                 // For now, action reset is not fully implemented
                 // In a full implementation, we'd store original DiffResult
@@ -665,10 +1305,14 @@ impl App {
         if let Some(selected) = self.list_state.selected() {
             if let Some(name) = self.projects.get(selected) {
                 if let Some(ref pm) = self.project_manager {
-                    match pm.load_project(name) {
-                        Ok(project) => {
+                    match pm.load_project_with_warnings(name) {
+                        Ok((project, warnings)) => {
+                            self.remember_last_project(&project.name);
                             self.current_project = Some(project);
                             self.screen = Screen::ProjectView;
+                            if let Some(warning) = warnings.first() {
+                                self.notify(warning.clone());
+                            }
                         }
                         Err(e) => {
                             self.dialog = Dialog::Error(format!("Failed to load project: {}", e));
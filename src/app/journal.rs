@@ -0,0 +1,59 @@
+//! Totals of a sync in progress, kept on disk while it runs. A sync that
+//! stops before it finishes (the app is killed, or quit mid-sync) leaves
+//! them behind, and the next sync of the project picks them up, so its
+//! progress and summary cover the whole operation across the restart.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::sync::metadata::SyncMetadata;
+
+/// Journal file inside the left side's `.rahzom/`
+pub const SYNC_JOURNAL_FILE: &str = "sync-journal.json";
+
+/// Actions and bytes a sync has got through so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncJournal {
+    pub completed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub bytes_transferred: u64,
+}
+
+impl SyncJournal {
+    /// Actions done, however they ended
+    pub fn actions(&self) -> usize {
+        self.completed + self.failed + self.skipped
+    }
+
+    /// Path of the journal of the project whose left side is `left_root`
+    pub fn file_path(left_root: &Path) -> PathBuf {
+        SyncMetadata::metadata_dir_path(left_root).join(SYNC_JOURNAL_FILE)
+    }
+
+    /// Loads the journal an unfinished sync left; a missing or unreadable
+    /// file is none
+    pub fn load(left_root: &Path) -> Option<Self> {
+        let content = fs::read(Self::file_path(left_root)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub fn save(&self, left_root: &Path) -> Result<()> {
+        let path = Self::file_path(left_root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let content = serde_json::to_vec_pretty(self)?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write sync journal: {:?}", path))
+    }
+
+    /// Deletes the journal once its sync has finished
+    pub fn remove(left_root: &Path) {
+        let _ = fs::remove_file(Self::file_path(left_root));
+    }
+}
@@ -0,0 +1,240 @@
+//! Keybindings of each screen: the single source for footer hints and the help overlay
+
+use super::Screen;
+
+/// Badge color of a key in the footer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAccent {
+    Normal,
+    Success,
+    Warning,
+    Error,
+}
+
+/// When a binding is offered in the footer; the help overlay lists all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintWhen {
+    Always,
+    /// The project list is not empty
+    HasProjects,
+    /// The current project uses the scan cache
+    ScanCache,
+    /// Files changed while the finished sync was running
+    ChangedDuringSync,
+    /// Too minor for the footer
+    HelpOnly,
+}
+
+/// One keybinding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub keys: &'static str,
+    /// Short footer label
+    pub label: &'static str,
+    /// Help overlay description
+    pub description: &'static str,
+    pub accent: KeyAccent,
+    pub when: HintWhen,
+}
+
+const fn key(keys: &'static str, label: &'static str, description: &'static str) -> KeyBinding {
+    KeyBinding {
+        keys,
+        label,
+        description,
+        accent: KeyAccent::Normal,
+        when: HintWhen::Always,
+    }
+}
+
+impl KeyBinding {
+    const fn accent(mut self, accent: KeyAccent) -> Self {
+        self.accent = accent;
+        self
+    }
+
+    const fn when(mut self, when: HintWhen) -> Self {
+        self.when = when;
+        self
+    }
+}
+
+/// Available on every screen without a dialog
+pub const GLOBAL: &[KeyBinding] = &[key("?", "Help", "Show or close this help")];
+
+const PROJECT_LIST: &[KeyBinding] = &[
+    key("↑↓", "Nav", "Move the selection").when(HintWhen::HasProjects),
+    key("Enter", "Open", "Open the selected project").when(HintWhen::HasProjects),
+    key("N", "New", "Create a new project"),
+    key("D", "Del", "Delete the selected project").when(HintWhen::HasProjects),
+    key("A", "Analyze all", "Analyze every project").when(HintWhen::HasProjects),
+    key("E", "Export", "Export the selected project to a file").when(HintWhen::HasProjects),
+    key("I", "Import", "Import a project from an exported file"),
+    key(
+        "P",
+        "Pin",
+        "Pin the selected project to the top, or unpin it",
+    )
+    .when(HintWhen::HasProjects),
+    key("Home/End", "", "Jump to the first/last project").when(HintWhen::HelpOnly),
+    key("Q", "Quit", "Quit"),
+];
+
+const PROJECT_VIEW: &[KeyBinding] = &[
+    key("A", "Analyze", "Scan both sides and preview the changes").accent(KeyAccent::Success),
+    key("C", "Config", "Edit project settings"),
+    key(
+        "T",
+        "Test",
+        "Test that both sides are reachable and writable",
+    ),
+    key("V", "Verify", "Compare both sides by content"),
+    key("W", "Swap", "Swap the left and right sides"),
+    key("H", "History", "Show past syncs of the project"),
+    key("F", "Full rescan", "Analyze without the scan cache").when(HintWhen::ScanCache),
+    key("Esc", "Back", "Back to the project list"),
+    key("Q", "Quit", "Quit"),
+];
+
+const PREVIEW: &[KeyBinding] = &[
+    key("↑↓", "Nav", "Move the selection"),
+    key("←→", "Dir", "Copy the selected item to the left/right"),
+    key(
+        "<>",
+        "",
+        "Resolve every conflict to the left/right (asks first)",
+    )
+    .when(HintWhen::HelpOnly),
+    key("N/P", "", "Jump to the next/previous conflict").when(HintWhen::HelpOnly),
+    key("S", "Skip", "Skip the selected item"),
+    key("A", "Accept", "Accept a conflict as it is on both sides"),
+    key(
+        "K",
+        "",
+        "Keep both files of a conflict, the right one under a new name",
+    )
+    .when(HintWhen::HelpOnly),
+    key("G", "Go", "Start the sync").accent(KeyAccent::Success),
+    key("E", "Excl", "Show exclusion patterns"),
+    key("B", "Backups", "Browse backups of the selected file"),
+    key(
+        "M",
+        "",
+        "Show the sync metadata recorded for the selected item",
+    )
+    .when(HintWhen::HelpOnly),
+    key("F", "Filter", "Cycle the action filter"),
+    key("D", "Side", "Cycle the side filter"),
+    key("V", "Skips", "Show or hide skipped items"),
+    key(
+        "C",
+        "Group",
+        "Group actions by type, or back to a flat list",
+    ),
+    key("Enter", "", "Fold or unfold the section under the cursor").when(HintWhen::HelpOnly),
+    key(
+        "I",
+        "Details",
+        "Show sizes and mtimes of both sides of the selected item",
+    ),
+    key(
+        "Y",
+        "Copy summary",
+        "Copy a plain-text summary to the clipboard",
+    ),
+    key(
+        "X",
+        "",
+        "Save the actions as a shell/batch script (no backups or trash)",
+    )
+    .when(HintWhen::HelpOnly),
+    key(
+        "Space",
+        "",
+        "Mark or unmark the item; only marked items sync",
+    )
+    .when(HintWhen::HelpOnly),
+    key(
+        "O",
+        "",
+        "Start a range; ←→/S then apply to it (O or Esc drops it)",
+    )
+    .when(HintWhen::HelpOnly),
+    key("Home/End", "", "Jump to the first/last item").when(HintWhen::HelpOnly),
+    key("PgUp/PgDn", "", "Move a page up/down").when(HintWhen::HelpOnly),
+    key("Esc", "Back", "Back to the project view"),
+    key("Q", "Quit", "Quit").when(HintWhen::HelpOnly),
+];
+
+const SYNCING: &[KeyBinding] = &[
+    key("Esc", "Cancel sync", "Cancel the running sync").accent(KeyAccent::Error),
+    key("Q", "Quit", "Quit"),
+];
+
+const SYNC_COMPLETE: &[KeyBinding] = &[
+    key("Enter", "Back", "Back to the project view"),
+    key(
+        "R",
+        "Re-analyze",
+        "Re-analyze files that changed during the sync",
+    )
+    .accent(KeyAccent::Warning)
+    .when(HintWhen::ChangedDuringSync),
+    key(
+        "Tab",
+        "Lists",
+        "Switch between completed, failed and skipped actions",
+    ),
+    key("↑↓", "Scroll", "Scroll the list"),
+];
+
+const ANALYZE_ALL: &[KeyBinding] = &[
+    key("↑↓", "Nav", "Move the selection"),
+    key("Enter", "Preview", "Open the selected project's preview"),
+    key("Esc", "Back", "Back to the project list"),
+    key("Q", "Quit", "Quit"),
+];
+
+const HISTORY: &[KeyBinding] = &[
+    key("↑↓", "Scroll", "Scroll the list"),
+    key("Home/End", "", "Jump to the newest/oldest sync").when(HintWhen::HelpOnly),
+    key("Esc", "Back", "Back to the project view"),
+    key("Q", "Quit", "Quit"),
+];
+
+const QUIT_ONLY: &[KeyBinding] = &[key("Q", "Quit", "Quit")];
+
+/// Bindings of `screen`, in footer order
+pub fn bindings(screen: Screen) -> &'static [KeyBinding] {
+    match screen {
+        Screen::ProjectList => PROJECT_LIST,
+        Screen::ProjectView => PROJECT_VIEW,
+        Screen::Preview => PREVIEW,
+        Screen::Syncing => SYNCING,
+        Screen::SyncComplete => SYNC_COMPLETE,
+        Screen::AnalyzeAll => ANALYZE_ALL,
+        Screen::History => HISTORY,
+        Screen::Analyzing => QUIT_ONLY,
+    }
+}
+
+/// Screens listed in the help overlay, with their section titles
+pub const HELP_SECTIONS: &[(&str, Screen)] = &[
+    ("Project list", Screen::ProjectList),
+    ("Project view", Screen::ProjectView),
+    ("Preview", Screen::Preview),
+    ("Syncing", Screen::Syncing),
+    ("Sync complete", Screen::SyncComplete),
+    ("Analyze all", Screen::AnalyzeAll),
+    ("History", Screen::History),
+];
+
+/// Lines of the help overlay: a title, the bindings and a blank line per
+/// section, with the global bindings first
+pub fn help_line_count() -> usize {
+    let sections: usize = HELP_SECTIONS
+        .iter()
+        .map(|&(_, screen)| bindings(screen).len() + 2)
+        .sum();
+    GLOBAL.len() + 2 + sections
+}
@@ -1,53 +1,112 @@
 //! Application module
 
+mod clipboard;
+pub mod drive;
+mod editor;
 mod handlers;
+mod journal;
+pub mod keymap;
+mod session;
 pub mod state;
 
 pub use state::{
-    is_conflict_action, is_skip_action, Dialog, DialogField, DiskSpaceWarningDialog,
-    ExclusionsInfoDialog, FileErrorDialog, NewProjectDialog, PreviewFilter, PreviewState,
-    PreviewSummary, Screen, SettingsDialog, SettingsField, SyncCompleteState, SyncConfirmDialog,
-    SyncingState, UserAction,
+    is_conflict_action, is_skip_action, ActionGroup, AnalyzeAllState, BackupBrowserDialog,
+    BackupVersion, CompleteList, Dialog, DialogField, DiskSpaceWarningDialog, ExclusionsInfoDialog,
+    FileErrorDialog, FileStamp, HelpDialog, HistoryState, MetadataInspectDialog, MetadataSide,
+    NewProjectDialog, PreviewFilter, PreviewRow, PreviewState, PreviewSummary, ProjectFileDialog,
+    ProjectStatus, Screen, ScriptDialog, SettingsDialog, SettingsField, SideFilter,
+    SyncCompleteState, SyncConfirmDialog, SyncingState, TargetStatusDialog, UserAction,
+    VerifyReportDialog, LARGE_PREVIEW_ACTIONS,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use ratatui::{
     layout::{Constraint, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, ListState, Paragraph},
     Frame,
 };
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Instant;
-
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::config::app::{AppConfig, ResumeMode};
+use crate::config::blocklist;
+use crate::config::expand::expand_path;
 use crate::config::project::{Project, ProjectManager};
-use crate::sync::differ::{diff, SyncAction};
+use crate::config::ui_state::UiState;
+use crate::sync::compress;
+use crate::sync::differ::{
+    detect_renames, diff_since, diff_with_options, explain_renames, recorded_scan,
+    skip_content_elsewhere, DiffOptions, DiffResult, SyncAction,
+};
 use crate::sync::exclusions::Exclusions;
 use crate::sync::executor::{
-    check_disk_space, ExecutionResult, Executor, ExecutorConfig, FailedAction, FileSnapshot,
-    NoopProgress, SyncErrorKind,
+    check_disk_space, list_backups, remove_partial_copies, remove_partials, restore_backup,
+    validate_target, CompletedAction, ExecutionResult, Executor, ExecutorConfig, FailedAction,
+    FileSnapshot, NoopProgress, OverwritePolicy, ProgressCallback, SkippedAction, SyncErrorKind,
 };
-use crate::sync::metadata::{DeletedFile, FileAttributes, FileState, SyncMetadata};
-use crate::sync::scanner::scan_with_exclusions;
+#[cfg(feature = "testing")]
+use crate::sync::faults::Faults;
+use crate::sync::hash::hash_file;
+use crate::sync::lock::SyncLock;
+use crate::sync::manifest;
+use crate::sync::metadata::{DeletedFile, FileAttributes, FileId, FileState, SyncMetadata};
+use crate::sync::rules::ConflictRules;
+use crate::sync::scanner::{
+    self as scanner, scan_with_options, ScanCacheMode, ScanOptions, ScanResult, SkippedEntry,
+};
+use crate::sync::skew::{estimate_clock_skew, ClockSkew};
+use crate::sync::stats::{self, SyncRun};
+use crate::sync::trash;
+use crate::sync::utils::{path_key, system_time_to_utc};
+use crate::sync::verify::verify_with;
+use crate::sync::webhook::{self, SyncReport};
 use crate::ui::{
-    render_cancel_sync_confirm_dialog, render_create_dir_confirm_dialog,
+    format_bytes, render_analyze_all, render_backup_browser_dialog,
+    render_cancel_sync_confirm_dialog, render_clock_skew_dialog, render_create_dir_confirm_dialog,
     render_delete_confirm_dialog, render_disk_space_warning_dialog, render_error_dialog,
-    render_exclusions_info_dialog, render_file_error_dialog, render_new_project_dialog,
-    render_preview, render_project_list, render_project_view, render_settings_dialog,
-    render_sync_complete, render_sync_confirm_dialog, render_syncing,
+    render_exclusions_info_dialog, render_file_error_dialog, render_help_dialog, render_history,
+    render_incomplete_transfers_dialog, render_large_preview_dialog,
+    render_metadata_inspect_dialog, render_new_project_dialog, render_preview,
+    render_project_file_dialog, render_project_list, render_project_view,
+    render_quit_confirm_dialog, render_resolve_all_confirm_dialog, render_script_dialog,
+    render_settings_dialog, render_swap_sides_confirm_dialog, render_sync_complete,
+    render_sync_confirm_dialog, render_syncing, render_target_status_dialog,
+    render_verify_report_dialog, Theme,
 };
 use chrono::Utc;
+use clipboard::Clipboard;
+use journal::SyncJournal;
+use keymap::{HintWhen, KeyAccent};
+use session::ReviewSession;
+
+/// How long a notification stays visible
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(2);
+
+/// Skip reason of locked files when the project skips them automatically
+const FILE_IN_USE: &str = "file in use";
+
+/// Shown for operations that need to read or write the right side's files
+const MANIFEST_READ_ONLY: &str = "Not available: the right side is a snapshot manifest";
+
+/// Past syncs shown in the history
+const HISTORY_RUNS: usize = 100;
 
 /// Main application state
 pub struct App {
     pub screen: Screen,
     pub should_quit: bool,
     pub dialog: Dialog,
+    /// Dialogs of the preview waiting for the current one to close
+    pub queued_dialogs: VecDeque<Dialog>,
 
     // Project list state
     pub projects: Vec<String>,
+    /// Projects of `projects` that are pinned to the top
+    pub pinned_projects: HashSet<String>,
     pub list_state: ListState,
     pub project_manager: Option<ProjectManager>,
 
@@ -62,14 +121,49 @@ pub struct App {
 
     // Sync complete state
     pub sync_complete: Option<SyncCompleteState>,
+    /// Webhook deliveries still running on their own threads, by URL
+    reports: Vec<(String, Receiver<Result<()>>)>,
+
+    // Analyze-all overview state
+    pub analyze_all: Option<AnalyzeAllState>,
+
+    // Sync history of the current project
+    pub history: Option<HistoryState>,
 
     // Exclusions state
     pub left_exclusions: Option<Exclusions>,
     pub right_exclusions: Option<Exclusions>,
+    /// Roots of other projects left out by `exclude_other_projects`
+    pub auto_excluded: Vec<PathBuf>,
+
+    // Color theme used by all render functions
+    pub theme: Theme,
+
+    // Application-wide settings
+    pub config: AppConfig,
+
+    // A sync finished this session and its result has not been re-analyzed yet
+    unreviewed_sync: bool,
+
+    // Transient status message and when it was posted
+    notification: Option<(String, Instant)>,
 
     // Mouse tracking
     last_click: Option<(u16, u16, Instant)>,
     content_area: Option<Rect>,
+
+    // The terminal was resized; the next frame repaints every cell
+    resized: bool,
+
+    // File to open in the editor before the next frame
+    pending_edit: Option<PathBuf>,
+
+    // System clipboard, kept open so what was copied stays available
+    clipboard: Clipboard,
+
+    // Simulated executor failures (from `RAHZOM_FAULTS` in `new`)
+    #[cfg(feature = "testing")]
+    pub faults: Faults,
 }
 
 impl Default for App {
@@ -84,17 +178,32 @@ impl App {
             screen: Screen::ProjectList,
             should_quit: false,
             dialog: Dialog::None,
+            queued_dialogs: VecDeque::new(),
             projects: Vec::new(),
+            pinned_projects: HashSet::new(),
             list_state: ListState::default(),
             project_manager: None,
             current_project: None,
             preview: None,
             syncing: None,
             sync_complete: None,
+            reports: Vec::new(),
+            analyze_all: None,
+            history: None,
             left_exclusions: None,
             right_exclusions: None,
+            auto_excluded: Vec::new(),
+            theme: Theme::default(),
+            config: AppConfig::default(),
+            unreviewed_sync: false,
+            notification: None,
             last_click: None,
             content_area: None,
+            resized: false,
+            pending_edit: None,
+            clipboard: Clipboard::default(),
+            #[cfg(feature = "testing")]
+            faults: Faults::from_env(),
         };
 
         // Try to initialize project manager
@@ -102,10 +211,23 @@ impl App {
             Ok(pm) => {
                 if let Ok(projects) = pm.list_projects() {
                     app.projects = projects;
+                    app.pinned_projects = pm.pinned_projects();
                     if !app.projects.is_empty() {
                         app.list_state.select(Some(0));
                     }
                 }
+                match Theme::load(pm.config_dir()) {
+                    Ok(theme) => app.theme = theme,
+                    Err(e) => {
+                        app.dialog = Dialog::Error(format!("Failed to load theme: {:#}", e));
+                    }
+                }
+                match AppConfig::load(pm.config_dir()) {
+                    Ok(config) => app.config = config,
+                    Err(e) => {
+                        app.dialog = Dialog::Error(format!("Failed to load config: {:#}", e));
+                    }
+                }
                 app.project_manager = Some(pm);
             }
             Err(e) => {
@@ -119,6 +241,7 @@ impl App {
     /// Create app with custom project manager (for testing)
     pub fn with_project_manager(pm: ProjectManager) -> Self {
         let projects = pm.list_projects().unwrap_or_default();
+        let pinned_projects = pm.pinned_projects();
         let mut list_state = ListState::default();
         if !projects.is_empty() {
             list_state.select(Some(0));
@@ -128,18 +251,74 @@ impl App {
             screen: Screen::ProjectList,
             should_quit: false,
             dialog: Dialog::None,
+            queued_dialogs: VecDeque::new(),
             projects,
+            pinned_projects,
             list_state,
             project_manager: Some(pm),
             current_project: None,
             preview: None,
             syncing: None,
             sync_complete: None,
+            reports: Vec::new(),
+            analyze_all: None,
+            history: None,
             left_exclusions: None,
             right_exclusions: None,
+            auto_excluded: Vec::new(),
+            theme: Theme::default(),
+            config: AppConfig::default(),
+            unreviewed_sync: false,
+            notification: None,
             last_click: None,
             content_area: None,
+            resized: false,
+            pending_edit: None,
+            clipboard: Clipboard::default(),
+            #[cfg(feature = "testing")]
+            faults: Faults::default(),
+        }
+    }
+
+    /// Selects the project opened last run, or opens it with `resume =
+    /// "open"`. A remembered project that no longer exists is ignored.
+    pub fn resume_last_project(&mut self) {
+        if self.config.resume == ResumeMode::Off {
+            return;
+        }
+        let Some(ref pm) = self.project_manager else {
+            return;
+        };
+        let Some(name) = UiState::load(pm.config_dir())
+            .ok()
+            .and_then(|state| state.last_project)
+        else {
+            return;
+        };
+        let Some(pos) = self.projects.iter().position(|p| *p == name) else {
+            return;
+        };
+
+        self.list_state.select(Some(pos));
+        // Not over a startup error, which would go unseen
+        if self.config.resume == ResumeMode::Open && self.dialog == Dialog::None {
+            self.open_selected_project();
+        }
+    }
+
+    /// Records `name` as the project to resume on the next start
+    fn remember_last_project(&self, name: &str) {
+        let Some(ref pm) = self.project_manager else {
+            return;
+        };
+        if self.config.resume == ResumeMode::Off {
+            return;
         }
+        let state = UiState {
+            last_project: Some(name.to_string()),
+        };
+        // Losing it only costs a few keystrokes on the next start
+        let _ = state.save(pm.config_dir());
     }
 
     /// Refresh project list from disk
@@ -148,6 +327,7 @@ impl App {
             if let Ok(projects) = pm.list_projects() {
                 let was_empty = self.projects.is_empty();
                 self.projects = projects;
+                self.pinned_projects = pm.pinned_projects();
 
                 if self.projects.is_empty() {
                     self.list_state.select(None);
@@ -162,22 +342,189 @@ impl App {
         }
     }
 
+    /// Pins the selected project to the top of the list, or unpins it
+    pub(super) fn toggle_pin_selected(&mut self) {
+        let Some(name) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.projects.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(ref pm) = self.project_manager else {
+            return;
+        };
+        let pinned = !self.pinned_projects.contains(&name);
+        if let Err(e) = pm.set_pinned(&name, pinned) {
+            self.dialog = Dialog::Error(format!("Failed to save project: {:#}", e));
+            return;
+        }
+        self.refresh_projects();
+        // The project moved; keep it selected
+        if let Some(i) = self.projects.iter().position(|p| *p == name) {
+            self.list_state.select(Some(i));
+        }
+        let verb = if pinned { "Pinned" } else { "Unpinned" };
+        self.notify(format!("{} {}", verb, name));
+    }
+
     /// Main application loop
     pub fn run(&mut self, terminal: &mut ratatui::DefaultTerminal) -> Result<()> {
         while !self.should_quit {
-            terminal.draw(|frame| self.render(frame))?;
-
-            // If syncing and no dialog, execute one action per frame
-            if self.screen == Screen::Syncing && matches!(self.dialog, Dialog::None) {
-                self.execute_next_sync_action();
+            // This runs before drawing so the final frame of a sync is shown
+            // even when the loop then blocks waiting for input
+            self.tick();
+
+            // Some terminals reflow the old screen on resize; a full repaint
+            // keeps stale cells of a larger frame from showing through
+            if std::mem::take(&mut self.resized) {
+                terminal.clear()?;
+            }
+            if let Some(path) = self.pending_edit.take() {
+                self.run_pending_edit(terminal, &path);
             }
+            terminal.draw(|frame| self.render(frame))?;
 
             self.handle_events()?;
         }
         Ok(())
     }
 
+    /// Does the work due before each frame: while syncing or analyzing all
+    /// projects with no dialog open (help doesn't pause), one action or
+    /// project. Returns whether there was any.
+    pub fn tick(&mut self) -> bool {
+        let busy = matches!(self.dialog, Dialog::None | Dialog::Help(_));
+        let mut worked = false;
+        if self.screen == Screen::Syncing && busy {
+            self.execute_next_sync_action();
+            worked = true;
+        }
+        let analyzing = self
+            .analyze_all
+            .as_ref()
+            .is_some_and(|state| state.next < state.entries.len());
+        if self.screen == Screen::AnalyzeAll && busy && analyzing {
+            self.analyze_next_project();
+            worked = true;
+        }
+        if let Some(ref mut syncing) = self.syncing {
+            syncing.sample_throughput(Instant::now());
+        }
+        self.collect_reports();
+        worked
+    }
+
+    /// Quits the application, asking first if that would interrupt a sync
+    /// or leave the result of a sync unreviewed
+    pub fn request_quit(&mut self) {
+        if self.screen == Screen::Preview {
+            self.save_review_session();
+        }
+        let sync_running = self.syncing.is_some();
+        if sync_running || (self.config.confirm_on_quit && self.unreviewed_sync) {
+            self.dialog = Dialog::QuitConfirm { sync_running };
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    /// Plain-text summary of the current preview, meant for pasting into chat
+    pub fn preview_summary_text(&self) -> Option<String> {
+        let preview = self.preview.as_ref()?;
+        let summary = preview.summary();
+        let name = self
+            .current_project
+            .as_ref()
+            .map(|p| p.name.as_str())
+            .unwrap_or("unnamed");
+
+        Some(format!(
+            "rahzom preview: {}\n\
+             Copy to right: {} files ({})\n\
+             Copy to left: {} files ({})\n\
+             Delete: {} right, {} left\n\
+             Conflicts: {} ({} accepted as-is)\n\
+             Dirs to create: {}\n\
+             Attributes to update: {}\n\
+             Skipped: {}\n\
+             Total transfer: {}",
+            name,
+            summary.copy_to_right,
+            format_bytes(summary.bytes_to_right),
+            summary.copy_to_left,
+            format_bytes(summary.bytes_to_left),
+            summary.delete_right,
+            summary.delete_left,
+            summary.conflicts,
+            summary.accepted,
+            summary.dirs_to_create,
+            summary.attributes_to_update,
+            summary.skipped,
+            format_bytes(summary.bytes_to_right + summary.bytes_to_left),
+        ))
+    }
+
+    /// Keeps the decisions of the current preview for the next analysis of
+    /// the project, or forgets earlier ones if none were made
+    fn save_review_session(&mut self) {
+        let (Some(preview), Some(project)) = (&self.preview, &self.current_project) else {
+            return;
+        };
+        let session = ReviewSession::capture(preview);
+        if session.is_empty() {
+            ReviewSession::remove(&project.left_path);
+        } else if let Err(e) = session.save(&project.left_path) {
+            self.notify(format!("Failed to save review session: {:#}", e));
+        }
+    }
+
+    /// Shows a transient, non-modal message above the footer
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.notification = Some((message.into(), Instant::now()));
+    }
+
+    /// How long the event loop may wait for input before the screen needs
+    /// updating; `None` means it can block until input arrives
+    pub fn poll_timeout(&self) -> Option<Duration> {
+        let analyzing_all = self.analyze_all.as_ref().is_some_and(|a| !a.is_done());
+        if self.screen == Screen::Syncing || (self.screen == Screen::AnalyzeAll && analyzing_all) {
+            return Some(Duration::from_millis(self.config.sync_poll_ms));
+        }
+
+        let idle = self.config.idle_poll_ms.map(Duration::from_millis);
+        // Wake up to clear a visible toast once it expires
+        let toast = self
+            .notification
+            .as_ref()
+            .map(|(_, posted)| NOTIFICATION_DURATION.saturating_sub(posted.elapsed()))
+            .filter(|remaining| !remaining.is_zero());
+        // Wake up to report a webhook delivery once it ends
+        let reporting =
+            (!self.reports.is_empty()).then(|| Duration::from_millis(self.config.sync_poll_ms));
+
+        [idle, toast, reporting].into_iter().flatten().min()
+    }
+
+    /// Returns the current notification unless it has expired
+    pub fn notification(&self) -> Option<&str> {
+        self.notification
+            .as_ref()
+            .filter(|(_, posted)| posted.elapsed() < NOTIFICATION_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
     fn run_analyze(&mut self) {
+        self.analyze_current_project(false);
+    }
+
+    /// Analyzes without reusing the scan cache, then refreshes it
+    fn run_full_rescan(&mut self) {
+        self.analyze_current_project(true);
+    }
+
+    fn analyze_current_project(&mut self, full_rescan: bool) {
         let Some(ref project) = self.current_project else {
             return;
         };
@@ -201,6 +548,14 @@ impl App {
             return;
         }
 
+        if !right_exists && project.settings.manifest_right {
+            self.dialog = Dialog::Error(format!(
+                "Manifest not found: {}",
+                project.right_path.display()
+            ));
+            return;
+        }
+
         if !right_exists {
             self.dialog = Dialog::CreateDirConfirm {
                 path: project.right_path.clone(),
@@ -209,44 +564,183 @@ impl App {
             return;
         }
 
-        // Load exclusions (opt-in: returns empty if file doesn't exist)
-        let left_exclusions = Exclusions::load(&project.left_path).ok();
-        let right_exclusions = Exclusions::load(&project.right_path).ok();
-
-        // Store exclusions for UI
-        self.left_exclusions = left_exclusions.clone();
-        self.right_exclusions = right_exclusions.clone();
-
-        // Scan both sides with exclusions
-        let left_scan = match scan_with_exclusions(&project.left_path, left_exclusions.as_ref()) {
-            Ok(s) => s,
+        let other_roots = other_project_roots(self.project_manager.as_ref(), project);
+        match analyze_project(project, full_rescan, &self.config, &other_roots) {
+            Ok(analysis) => {
+                // Store exclusions for UI
+                self.left_exclusions = analysis.left_exclusions;
+                self.right_exclusions = analysis.right_exclusions;
+                self.auto_excluded = analysis.auto_excluded;
+                let mut preview = analysis.preview;
+                let restored = ReviewSession::load(&project.left_path)
+                    .map(|session| session.restore(&mut preview));
+                // A corrupt state file was moved aside, so that side looks never synced
+                let recovered: Vec<&str> =
+                    [("left", &preview.left_meta), ("right", &preview.right_meta)]
+                        .into_iter()
+                        .filter(|(_, meta)| meta.recovered_from.is_some())
+                        .map(|(side, _)| side)
+                        .collect();
+                let action_count = preview.actions.len();
+                self.preview = Some(preview);
+                self.screen = Screen::Preview;
+                self.unreviewed_sync = false;
+                let (left_partials, right_partials) = self
+                    .preview
+                    .as_ref()
+                    .map(PreviewState::partial_counts)
+                    .unwrap_or_default();
+                // Shown one after another; the large preview guard last, as
+                // it can leave the preview or go straight to syncing
+                self.queued_dialogs.clear();
+                if let Some(skew) = analysis.clock_skew {
+                    self.queued_dialogs
+                        .push_back(Dialog::ClockSkewWarning(skew));
+                }
+                if left_partials + right_partials > 0 {
+                    self.queued_dialogs.push_back(Dialog::IncompleteTransfers {
+                        left: left_partials,
+                        right: right_partials,
+                    });
+                }
+                if action_count > LARGE_PREVIEW_ACTIONS {
+                    self.queued_dialogs
+                        .push_back(Dialog::LargePreview(action_count));
+                }
+                self.show_queued_dialog();
+                match restored {
+                    Some(r) if r.stale > 0 => self.notify(format!(
+                        "Resumed review: {} decisions restored, {} outdated dropped",
+                        r.decisions, r.stale
+                    )),
+                    Some(r) if r.decisions > 0 => self.notify(format!(
+                        "Resumed review: {} decisions restored",
+                        r.decisions
+                    )),
+                    _ => {}
+                }
+                if !recovered.is_empty() {
+                    self.notify(format!(
+                        "Sync state of the {} side was corrupt and moved to \
+                         .rahzom/state.json.corrupt; compared as a first sync",
+                        recovered.join(" and ")
+                    ));
+                }
+            }
             Err(e) => {
-                self.dialog = Dialog::Error(format!("Failed to scan left: {}", e));
-                return;
+                self.dialog = Dialog::Error(format!("{:#}", e));
             }
+        }
+    }
+
+    /// Opens the analyze-all overview and starts analyzing every project
+    fn start_analyze_all(&mut self) {
+        if self.projects.is_empty() {
+            return;
+        }
+        self.analyze_all = Some(AnalyzeAllState::new(&self.projects));
+        self.screen = Screen::AnalyzeAll;
+    }
+
+    /// Analyzes the next pending project of the analyze-all overview
+    fn analyze_next_project(&mut self) {
+        let Some(ref mut state) = self.analyze_all else {
+            return;
+        };
+        let Some((name, status)) = state.entries.get_mut(state.next) else {
+            return;
+        };
+        let Some(ref pm) = self.project_manager else {
+            return;
         };
 
-        let right_scan = match scan_with_exclusions(&project.right_path, right_exclusions.as_ref()) {
-            Ok(s) => s,
-            Err(e) => {
-                self.dialog = Dialog::Error(format!("Failed to scan right: {}", e));
-                return;
+        *status = match pm.load_project(name) {
+            Ok(project) => {
+                let missing = [("Left", &project.left_path), ("Right", &project.right_path)]
+                    .into_iter()
+                    .find(|(_, path)| !path.exists());
+                match missing {
+                    Some((side, path)) => {
+                        ProjectStatus::Error(format!("{} path missing: {}", side, path.display()))
+                    }
+                    None => match analyze_project(
+                        &project,
+                        false,
+                        &self.config,
+                        &other_project_roots(Some(pm), &project),
+                    ) {
+                        Ok(analysis) => ProjectStatus::Analyzed(analysis.preview.summary()),
+                        Err(e) => ProjectStatus::Error(format!("{:#}", e)),
+                    },
+                }
             }
+            Err(e) => ProjectStatus::Error(format!("{:#}", e)),
         };
+        state.next += 1;
+    }
 
-        // Load metadata with project's retention setting
-        let retention = project.settings.deleted_retention_days as i64;
-        let left_meta =
-            SyncMetadata::load_with_retention(&project.left_path, retention).unwrap_or_default();
-        let right_meta =
-            SyncMetadata::load_with_retention(&project.right_path, retention).unwrap_or_default();
+    /// Drills from the analyze-all overview into the selected project's preview
+    fn open_analyzed_project(&mut self) {
+        let Some(ref state) = self.analyze_all else {
+            return;
+        };
+        let Some((name, status)) = state.entries.get(state.selected) else {
+            return;
+        };
 
-        // Run diff
-        let diff_result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+        match status {
+            ProjectStatus::Pending => {}
+            ProjectStatus::Error(e) => {
+                self.dialog = Dialog::Error(e.clone());
+            }
+            ProjectStatus::Analyzed(_) => {
+                let Some(ref pm) = self.project_manager else {
+                    return;
+                };
+                match pm.load_project(name) {
+                    Ok(project) => {
+                        self.current_project = Some(project);
+                        self.analyze_all = None;
+                        self.screen = Screen::ProjectView;
+                        self.run_analyze();
+                    }
+                    Err(e) => {
+                        self.dialog = Dialog::Error(format!("Failed to load project: {}", e));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deletes the partial copies of an interrupted sync the analysis found
+    pub(super) fn remove_incomplete_transfers(&mut self) {
+        let Some(ref mut preview) = self.preview else {
+            return;
+        };
+        let (left, right) = preview.removable_partials();
+        let mut removed = 0;
+        for (scan, partials) in [
+            (&mut preview.left_scan, left),
+            (&mut preview.right_scan, right),
+        ] {
+            if let Some(scan) = scan {
+                removed += remove_partials(&scan.root, &partials);
+                scan.partials.retain(|p| !partials.contains(p));
+            }
+        }
+        self.notify(format!("Removed {} incomplete transfers", removed));
+    }
 
-        // Create preview state
-        self.preview = Some(PreviewState::new(diff_result, left_scan, right_scan));
-        self.screen = Screen::Preview;
+    /// Opens the next queued dialog once none is open. The queue only
+    /// belongs to the preview it was made for.
+    pub(super) fn show_queued_dialog(&mut self) {
+        if self.screen != Screen::Preview {
+            self.queued_dialogs.clear();
+        } else if self.dialog == Dialog::None {
+            if let Some(next) = self.queued_dialogs.pop_front() {
+                self.dialog = next;
+            }
+        }
     }
 
     fn show_sync_confirmation(&mut self) {
@@ -254,28 +748,149 @@ impl App {
             return;
         };
 
-        let summary = preview.summary();
+        if self
+            .current_project
+            .as_ref()
+            .is_some_and(|p| p.settings.manifest_right)
+        {
+            self.dialog = Dialog::Error(MANIFEST_READ_ONLY.to_string());
+            return;
+        }
+
+        let summary = if preview.syncs_selection() {
+            preview.selected_summary()
+        } else {
+            preview.summary()
+        };
 
         // Check if there's anything to sync
         let total_operations = summary.copy_to_right
             + summary.copy_to_left
             + summary.delete_right
             + summary.delete_left
-            + summary.dirs_to_create;
+            + summary.dirs_to_create
+            + summary.attributes_to_update
+            + summary.accepted;
 
+        if total_operations == 0 && preview.syncs_selection() {
+            self.dialog =
+                Dialog::Error("Nothing to sync - all marked items are skipped".to_string());
+            return;
+        }
         if total_operations == 0 {
             self.dialog = Dialog::Error("Nothing to sync - all items are skipped".to_string());
             return;
         }
 
+        let settings = self.current_project.as_ref().map(|p| &p.settings);
+
         self.dialog = Dialog::SyncConfirm(SyncConfirmDialog {
             files_to_copy: summary.copy_to_right + summary.copy_to_left,
+            files_to_overwrite: preview.overwrite_count(),
             files_to_delete: summary.delete_right + summary.delete_left,
             bytes_to_transfer: summary.bytes_to_right + summary.bytes_to_left,
             dirs_to_create: summary.dirs_to_create,
+            attributes_to_update: summary.attributes_to_update,
+            conflicts_accepted: summary.accepted,
+            backup_versions: settings.map_or(0, |s| s.backup_versions),
+            soft_delete: settings.is_some_and(|s| s.soft_delete),
+            selection: preview
+                .syncs_selection()
+                .then_some((preview.selected_items.len(), preview.actions.len())),
+            scroll: 0,
         });
     }
 
+    /// Loads project `name`, analyzes it and starts a sync of what the
+    /// analysis decided, for a run without the TUI; conflicts stay as they
+    /// are. Returns whether there is anything to sync.
+    pub fn start_headless_sync(&mut self, name: &str) -> Result<bool> {
+        let pm = self
+            .project_manager
+            .as_ref()
+            .context("No configuration directory")?;
+        let (project, _) = pm.load_project_with_warnings(name)?;
+        self.current_project = Some(project);
+
+        self.run_analyze();
+        let Some(ref preview) = self.preview else {
+            bail!("{}", self.dialog_failure());
+        };
+        if preview
+            .sync_scope()
+            .all(|action| action.to_sync_action().is_none())
+        {
+            return Ok(false);
+        }
+        // Nobody to read the warnings an analysis may raise
+        self.queued_dialogs.clear();
+        self.dialog = Dialog::None;
+
+        self.start_sync(false);
+        if self.screen != Screen::Syncing {
+            bail!("{}", self.dialog_failure());
+        }
+        Ok(true)
+    }
+
+    /// Runs the sync `start_headless_sync` started to the end, reporting to
+    /// `progress`. A file that can't be written is skipped rather than
+    /// waiting for a decision. Returns what this run did.
+    pub fn run_headless_sync(&mut self, progress: &mut impl ProgressCallback) -> ExecutionResult {
+        while self.screen == Screen::Syncing {
+            let Some(ref syncing) = self.syncing else {
+                break;
+            };
+            // Counts of this run only, like the bytes the progress adds up
+            let earlier = syncing.earlier.actions();
+            let done = syncing.completed_actions - earlier;
+            let failed = syncing.result.failed.len();
+            let next = syncing.actions.get(syncing.current_index).cloned();
+            if let Some(ref action) = next {
+                progress.on_action_start(done, action);
+                progress.on_progress(done + 1, syncing.total_actions - earlier, action.path());
+            }
+
+            self.execute_next_sync_action();
+            let blocked = matches!(self.dialog, Dialog::FileError(_));
+            if blocked {
+                self.dialog = Dialog::None;
+                self.skip_current_sync_action();
+            }
+            if let Some(action) = next {
+                let success = !blocked
+                    && self
+                        .syncing
+                        .as_ref()
+                        .is_some_and(|s| s.result.failed.len() == failed);
+                progress.on_file_complete(&action, success);
+            }
+        }
+
+        self.sync_complete
+            .as_ref()
+            .map(|complete| ExecutionResult {
+                completed: complete.completed.clone(),
+                failed: complete.failed.clone(),
+                skipped: complete.skipped.clone(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Why the dialog up keeps an analysis or sync from going ahead
+    fn dialog_failure(&self) -> String {
+        match &self.dialog {
+            Dialog::Error(message) => message.clone(),
+            Dialog::CreateDirConfirm { path, .. } => {
+                format!("Directory does not exist: {}", path.display())
+            }
+            Dialog::DiskSpaceWarning(warning) => {
+                format!("Not enough disk space on {}", warning.path.display())
+            }
+            _ => "The sync did not start".to_string(),
+        }
+    }
+
     fn start_sync(&mut self, skip_disk_check: bool) {
         let Some(ref preview) = self.preview else {
             return;
@@ -286,12 +901,16 @@ impl App {
 
         // Convert UserActions to SyncActions, filtering out Skip/Conflict
         let actions: Vec<SyncAction> = preview
-            .actions
-            .iter()
+            .sync_scope()
             .filter_map(|ua| ua.to_sync_action())
             .collect();
+        let accepted: Vec<PathBuf> = preview
+            .sync_scope()
+            .filter(|ua| matches!(ua, UserAction::AcceptCurrent { .. }))
+            .map(|ua| ua.path().clone())
+            .collect();
 
-        if actions.is_empty() {
+        if actions.is_empty() && accepted.is_empty() {
             self.dialog = Dialog::Error("No actions to execute".to_string());
             return;
         }
@@ -325,11 +944,34 @@ impl App {
             }
         }
 
-        // Calculate bytes per direction
+        let (left_hardlinks, right_hardlinks) = if project.settings.preserve_hardlinks {
+            (
+                preview
+                    .left_scan
+                    .as_ref()
+                    .map(|s| s.hardlink_groups())
+                    .unwrap_or_default(),
+                preview
+                    .right_scan
+                    .as_ref()
+                    .map(|s| s.hardlink_groups())
+                    .unwrap_or_default(),
+            )
+        } else {
+            (HashMap::new(), HashMap::new())
+        };
+
+        // Calculate bytes per direction; keeping both writes the right file
+        // to both sides next to the left one
         let bytes_to_right: u64 = actions
             .iter()
             .map(|a| match a {
                 SyncAction::CopyToRight { size, .. } => *size,
+                SyncAction::KeepBoth {
+                    left_size,
+                    right_size,
+                    ..
+                } => left_size + right_size,
                 _ => 0,
             })
             .sum();
@@ -338,36 +980,61 @@ impl App {
             .iter()
             .map(|a| match a {
                 SyncAction::CopyToLeft { size, .. } => *size,
+                SyncAction::KeepBoth { right_size, .. } => *right_size,
                 _ => 0,
             })
             .sum();
 
         let total_bytes = bytes_to_right + bytes_to_left;
 
+        // Files and directories each side gets, each taking an inode
+        let created = |to_left: bool| -> u64 {
+            actions
+                .iter()
+                .filter(|a| match a {
+                    SyncAction::CopyToLeft { .. } | SyncAction::CreateDirLeft { .. } => to_left,
+                    SyncAction::CopyToRight { .. } | SyncAction::CreateDirRight { .. } => !to_left,
+                    SyncAction::KeepBoth { .. } => true,
+                    _ => false,
+                })
+                .count() as u64
+        };
+        let (inodes_to_right, inodes_to_left) = (created(false), created(true));
+
         // Check disk space before starting sync (unless user already confirmed)
         if !skip_disk_check {
-            if bytes_to_right > 0 {
-                if let Ok(info) = check_disk_space(&project.right_path, bytes_to_right) {
+            if bytes_to_right > 0 || inodes_to_right > 0 {
+                if let Ok(info) =
+                    check_disk_space(&project.right_path, bytes_to_right, inodes_to_right)
+                {
                     if !info.sufficient {
                         self.dialog = Dialog::DiskSpaceWarning(DiskSpaceWarningDialog {
                             is_left: false,
                             path: project.right_path.clone(),
                             available: info.available,
                             required: info.required,
+                            available_inodes: info.available_inodes,
+                            required_inodes: info.required_inodes,
+                            scroll: 0,
                         });
                         return;
                     }
                 }
             }
 
-            if bytes_to_left > 0 {
-                if let Ok(info) = check_disk_space(&project.left_path, bytes_to_left) {
+            if bytes_to_left > 0 || inodes_to_left > 0 {
+                if let Ok(info) =
+                    check_disk_space(&project.left_path, bytes_to_left, inodes_to_left)
+                {
                     if !info.sufficient {
                         self.dialog = Dialog::DiskSpaceWarning(DiskSpaceWarningDialog {
                             is_left: true,
                             path: project.left_path.clone(),
                             available: info.available,
                             required: info.required,
+                            available_inodes: info.available_inodes,
+                            required_inodes: info.required_inodes,
+                            scroll: 0,
                         });
                         return;
                     }
@@ -375,25 +1042,81 @@ impl App {
             }
         }
 
+        // Another instance syncing either side would corrupt backups and metadata
+        let locks = match [&project.left_path, &project.right_path]
+            .into_iter()
+            .map(|root| SyncLock::acquire(root))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(locks) => locks,
+            Err(e) => {
+                self.dialog = Dialog::Error(format!("Cannot start sync: {}", e));
+                return;
+            }
+        };
+        let (kept_left, kept_right) = preview.kept_paths();
+        // A journal left behind is a sync that never finished; this one
+        // carries on with its totals
+        let earlier = SyncJournal::load(&project.left_path).unwrap_or_default();
+        let continued = earlier.actions();
+        let taken_over: Vec<String> = locks
+            .iter()
+            .filter_map(|lock| lock.taken_over.as_ref())
+            .map(|owner| owner.describe())
+            .collect();
+
         self.syncing = Some(SyncingState {
-            total_actions: actions.len(),
-            completed_actions: 0,
-            total_bytes,
-            transferred_bytes: 0,
+            total_actions: earlier.actions() + actions.len(),
+            completed_actions: earlier.actions(),
+            total_bytes: earlier.bytes_transferred + total_bytes,
+            transferred_bytes: earlier.bytes_transferred,
             current_file: PathBuf::new(),
             start_time: Instant::now(),
             cancel_requested: false,
             current_index: 0,
             actions,
+            accepted,
             snapshots,
+            left_hardlinks,
+            right_hardlinks,
+            kept_left,
+            kept_right,
             result: ExecutionResult::default(),
+            throughput: VecDeque::new(),
+            last_sample_time: Instant::now(),
+            last_sample_bytes: earlier.bytes_transferred,
+            locks,
+            trash_session: trash::new_session_id(),
+            one_way: preview.one_way,
+            earlier,
         });
+        self.save_sync_journal();
+
+        // The decisions are carried out; a later preview starts afresh
+        ReviewSession::remove(&project.left_path);
 
         self.dialog = Dialog::None;
         self.screen = Screen::Syncing;
+        if continued > 0 {
+            self.notify(format!(
+                "Continuing an unfinished sync: {} actions were done before",
+                continued
+            ));
+        }
+        if !taken_over.is_empty() {
+            self.notify(format!(
+                "Took over a stale sync lock of {}",
+                taken_over.join(", ")
+            ));
+        }
     }
 
     fn execute_next_sync_action(&mut self) {
+        self.run_next_sync_action();
+        self.save_sync_journal();
+    }
+
+    fn run_next_sync_action(&mut self) {
         let Some(ref project) = self.current_project else {
             return;
         };
@@ -420,15 +1143,64 @@ impl App {
 
         // Create executor for this action using project settings
         let config = ExecutorConfig {
-            backup_enabled: true,
+            backup_enabled: project.settings.backup_versions > 0,
             backup_versions: project.settings.backup_versions,
             soft_delete: project.settings.soft_delete,
+            soft_delete_target_left: project.settings.soft_delete_target_left,
+            soft_delete_target_right: project.settings.soft_delete_target_right,
+            preserve_dir_mtime: project.settings.preserve_dir_mtime,
+            dry_run: false,
+            copy_acls: project.settings.copy_acls,
+            preserve_hardlinks: project.settings.preserve_hardlinks,
+            fsync_interval_bytes: project.settings.fsync_interval_bytes,
+            normalize_eol: project.settings.normalize_eol.clone(),
+            clear_readonly_on_overwrite: project.settings.clear_readonly_on_overwrite,
+            preserve_ownership: project.settings.preserve_ownership,
+            preserve_permissions: project.settings.preserve_permissions,
+            compress_right: project.settings.compressed_right,
+            // Ordering is up to the sync queue; the executor gets one action
+            copy_in_path_order: false,
+            adaptive_buffer: project.settings.adaptive_buffer,
+            try_reflink: project.settings.try_reflink,
+            replace_blocking_files: project.settings.replace_blocking_files,
+            recreate_fifos: project.settings.recreate_fifos,
+            // A two-way diff already turns edits on both sides into conflicts
+            overwrite_policy: if syncing.one_way {
+                project.settings.overwrite_policy
+            } else {
+                OverwritePolicy::Always
+            },
+        };
+        // Only this action's siblings are relevant to a single-action run
+        let siblings = |groups: &HashMap<PathBuf, Vec<PathBuf>>| {
+            groups
+                .get(action.path())
+                .map(|s| HashMap::from([(action.path().clone(), s.clone())]))
+                .unwrap_or_default()
+        };
+        // Only the action's ancestors can be in the way of its directories
+        let kept = |paths: &HashSet<PathBuf>| {
+            action
+                .path()
+                .ancestors()
+                .skip(1)
+                .filter(|ancestor| paths.contains(*ancestor))
+                .map(Path::to_path_buf)
+                .collect()
         };
         let executor = Executor::new(
             project.left_path.clone(),
             project.right_path.clone(),
             config,
-        );
+        )
+        .with_hardlinks(
+            siblings(&syncing.left_hardlinks),
+            siblings(&syncing.right_hardlinks),
+        )
+        .with_kept_paths(kept(&syncing.kept_left), kept(&syncing.kept_right))
+        .with_trash_session(syncing.trash_session.clone());
+        #[cfg(feature = "testing")]
+        let executor = executor.with_faults(self.faults.clone());
 
         // Execute single action
         let single_action = vec![action.clone()];
@@ -436,6 +1208,15 @@ impl App {
             Ok(result) => {
                 // Check for recoverable errors that should show dialog
                 if let Some(failed) = result.failed.first() {
+                    if failed.kind == SyncErrorKind::FileLocked && project.settings.skip_locked {
+                        syncing.result.skipped.push(SkippedAction {
+                            action,
+                            reason: FILE_IN_USE.to_string(),
+                        });
+                        syncing.completed_actions += 1;
+                        syncing.current_index += 1;
+                        return;
+                    }
                     if matches!(
                         failed.kind,
                         SyncErrorKind::FileLocked | SyncErrorKind::PermissionDenied
@@ -474,8 +1255,6 @@ impl App {
 
     /// Skip the current sync action and move to next
     fn skip_current_sync_action(&mut self) {
-        use crate::sync::executor::SkippedAction;
-
         let Some(ref mut syncing) = self.syncing else {
             return;
         };
@@ -491,6 +1270,23 @@ impl App {
         });
         syncing.completed_actions += 1;
         syncing.current_index += 1;
+        self.save_sync_journal();
+    }
+
+    /// Records the running sync's totals so far, for a sync that continues
+    /// it should this one never finish
+    fn save_sync_journal(&self) {
+        let (Some(project), Some(syncing)) = (&self.current_project, &self.syncing) else {
+            return;
+        };
+        let journal = SyncJournal {
+            completed: syncing.earlier.completed + syncing.result.completed.len(),
+            failed: syncing.earlier.failed + syncing.result.failed.len(),
+            skipped: syncing.earlier.skipped + syncing.result.skipped.len(),
+            bytes_transferred: syncing.transferred_bytes,
+        };
+        // Best effort: only the totals after a restart depend on it
+        let _ = journal.save(&project.left_path);
     }
 
     fn finish_sync(&mut self, cancelled: bool) {
@@ -515,30 +1311,154 @@ impl App {
                 _ => None,
             })
             .collect();
+        let in_use: Vec<PathBuf> = syncing
+            .result
+            .skipped
+            .iter()
+            .filter(|s| s.reason == FILE_IN_USE)
+            .map(|s| s.action.path().clone())
+            .collect();
+
+        // Copies never finish into their destination when the run stops early
+        // or fails; sweep partials of the actions this run got to
+        if cancelled || !syncing.result.failed.is_empty() {
+            if let Some(ref project) = self.current_project {
+                let processed = (syncing.current_index + 1).min(syncing.actions.len());
+                remove_partial_copies(
+                    &project.left_path,
+                    &project.right_path,
+                    &syncing.actions[..processed],
+                    project.settings.compressed_right,
+                );
+            }
+        }
 
         // Update metadata if sync was successful (not cancelled)
         if !cancelled {
-            if let Err(e) = self.save_sync_metadata(&syncing.result) {
+            if let Err(e) = self.save_sync_metadata(&syncing.result, &syncing.accepted) {
                 // Log error but don't fail
                 eprintln!("Failed to save metadata: {}", e);
             }
         }
 
-        self.sync_complete = Some(SyncCompleteState {
-            completed: syncing.result.completed,
-            failed: syncing.result.failed,
-            skipped: syncing.result.skipped,
-            duration,
+        // Finished, even if cancelled: the next sync starts afresh
+        let earlier = syncing.earlier;
+        if let Some(ref project) = self.current_project {
+            SyncJournal::remove(&project.left_path);
+        }
+
+        self.report_sync(
+            cancelled,
+            &syncing.result,
+            &earlier,
             bytes_transferred,
-            scroll_offset: 0,
+            duration,
+        );
+        if let Some(ref project) = self.current_project {
+            let run = SyncRun::new(
+                cancelled,
+                earlier.completed + syncing.result.completed.len(),
+                earlier.failed + syncing.result.failed.len(),
+                earlier.skipped + syncing.result.skipped.len(),
+                bytes_transferred,
+                duration,
+            );
+            if let Err(e) = stats::append(&project.left_path, &run) {
+                self.notify(format!("Failed to record the sync in its history: {:#}", e));
+            }
+        }
+
+        self.sync_complete = Some(SyncCompleteState {
             changed_during_sync,
+            in_use,
+            earlier,
+            ..SyncCompleteState::new(
+                syncing.result.completed,
+                syncing.result.failed,
+                syncing.result.skipped,
+                duration,
+                bytes_transferred,
+            )
         });
 
         self.preview = None;
         self.screen = Screen::SyncComplete;
+        self.unreviewed_sync = true;
     }
 
-    fn save_sync_metadata(&self, result: &ExecutionResult) -> Result<()> {
+    /// Posts a summary of the finished sync to the project's webhook, if any
+    fn report_sync(
+        &mut self,
+        cancelled: bool,
+        result: &ExecutionResult,
+        earlier: &SyncJournal,
+        bytes_transferred: u64,
+        duration: Duration,
+    ) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        let Some(ref url) = project.settings.on_complete_webhook else {
+            return;
+        };
+        if !webhook::AVAILABLE {
+            self.notify("on_complete_webhook needs a build with the `webhook` feature");
+            return;
+        }
+        let mut report = SyncReport::new(
+            &project.name,
+            cancelled,
+            earlier.completed + result.completed.len(),
+            &result.failed,
+            &result.skipped,
+            bytes_transferred,
+            duration,
+        );
+        // Only this run's failures are known by path
+        report.failed += earlier.failed;
+        report.skipped += earlier.skipped;
+        report.ok &= earlier.failed == 0;
+
+        // A slow or unreachable server must not freeze the UI; the outcome is
+        // picked up by `tick`
+        let (sender, receiver) = mpsc::channel();
+        let target = url.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(webhook::post(&target, &report));
+        });
+        self.reports.push((url.clone(), receiver));
+    }
+
+    /// Notifies of webhook deliveries that have failed since the last call
+    fn collect_reports(&mut self) {
+        let mut failures = Vec::new();
+        self.reports
+            .retain(|(url, receiver)| match receiver.try_recv() {
+                Ok(Ok(())) | Err(TryRecvError::Disconnected) => false,
+                Ok(Err(e)) => {
+                    failures.push(report_failure(url, &e));
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+            });
+        for failure in failures {
+            self.notify(failure);
+        }
+    }
+
+    /// Waits for the webhook deliveries still running, e.g. before the
+    /// process exits, and returns their failures
+    pub fn wait_for_reports(&mut self) -> Vec<String> {
+        self.reports
+            .drain(..)
+            .filter_map(|(url, receiver)| match receiver.recv() {
+                Ok(Err(e)) => Some(report_failure(&url, &e)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn save_sync_metadata(&self, result: &ExecutionResult, accepted: &[PathBuf]) -> Result<()> {
         let Some(ref project) = self.current_project else {
             return Ok(());
         };
@@ -551,32 +1471,73 @@ impl App {
             SyncMetadata::load_with_retention(&project.right_path, retention).unwrap_or_default();
 
         let now = Utc::now();
+        let record_ids = project.settings.records_file_ids();
+        let file_id = |path: &Path| {
+            if record_ids {
+                FileId::read_from_path(path)
+            } else {
+                None
+            }
+        };
+
+        // An archive holds a synced path compressed, under another name
+        let compressed = project.settings.compressed_right;
+        let right_file = |path: &Path| {
+            if compressed {
+                project.right_path.join(compress::archive_path(path))
+            } else {
+                project.right_path.join(path)
+            }
+        };
+        let file_size = |file: &Path, metadata: &std::fs::Metadata, archived: bool| {
+            if archived {
+                compress::content_size(file).unwrap_or(metadata.len())
+            } else {
+                metadata.len()
+            }
+        };
+
+        // Recorded as they now are on both sides: identical files whose
+        // attributes were updated, and both files of a conflict kept twice
+        let mut on_disk = Vec::new();
 
         // Update metadata based on completed actions
         for completed in &result.completed {
             match &completed.action {
                 SyncAction::CopyToRight { path, .. } => {
                     // Read actual file metadata from disk (destination file)
-                    let dest_path = project.right_path.join(path);
+                    let dest_path = right_file(path);
                     if let Ok(metadata) = std::fs::metadata(&dest_path) {
                         let mtime = metadata
                             .modified()
                             .ok()
-                            .and_then(|t| chrono::DateTime::<Utc>::from(t).into())
+                            .map(system_time_to_utc)
                             .unwrap_or(now);
-                        let size = metadata.len();
+                        let size = file_size(&dest_path, &metadata, compressed);
                         let attributes = FileAttributes::read_from_path(&dest_path);
 
                         let file_state = FileState {
-                            path: path.to_string_lossy().to_string(),
+                            path: path_key(path),
                             size,
                             mtime,
-                            hash: None,
+                            hash: completed.content_hash.clone(),
                             attributes,
+                            file_id: None,
                             last_synced: now,
                         };
-                        left_meta.upsert_file(file_state.clone());
-                        right_meta.upsert_file(file_state);
+                        left_meta.upsert_file(FileState {
+                            file_id: file_id(&project.left_path.join(path)),
+                            size: source_side_size(completed, &project.left_path.join(path), size),
+                            // Its own owner, so a copy whose owner couldn't be set isn't copied again
+                            attributes: FileAttributes::read_from_path(
+                                &project.left_path.join(path),
+                            ),
+                            ..file_state.clone()
+                        });
+                        right_meta.upsert_file(FileState {
+                            file_id: file_id(&dest_path),
+                            ..file_state
+                        });
                     }
                 }
                 SyncAction::CopyToLeft { path, .. } => {
@@ -586,25 +1547,35 @@ impl App {
                         let mtime = metadata
                             .modified()
                             .ok()
-                            .and_then(|t| chrono::DateTime::<Utc>::from(t).into())
+                            .map(system_time_to_utc)
                             .unwrap_or(now);
                         let size = metadata.len();
                         let attributes = FileAttributes::read_from_path(&dest_path);
 
                         let file_state = FileState {
-                            path: path.to_string_lossy().to_string(),
+                            path: path_key(path),
                             size,
                             mtime,
-                            hash: None,
+                            hash: completed.content_hash.clone(),
                             attributes,
+                            file_id: None,
                             last_synced: now,
                         };
-                        left_meta.upsert_file(file_state.clone());
-                        right_meta.upsert_file(file_state);
+                        left_meta.upsert_file(FileState {
+                            file_id: file_id(&project.left_path.join(path)),
+                            ..file_state.clone()
+                        });
+                        let source = right_file(path);
+                        right_meta.upsert_file(FileState {
+                            file_id: file_id(&source),
+                            size: source_side_size(completed, &source, size),
+                            attributes: FileAttributes::read_from_path(&source),
+                            ..file_state
+                        });
                     }
                 }
                 SyncAction::DeleteRight { path } => {
-                    let path_str = path.to_string_lossy().to_string();
+                    let path_str = path_key(path);
                     right_meta.mark_deleted(DeletedFile {
                         path: path_str,
                         size: 0,
@@ -614,7 +1585,7 @@ impl App {
                     });
                 }
                 SyncAction::DeleteLeft { path } => {
-                    let path_str = path.to_string_lossy().to_string();
+                    let path_str = path_key(path);
                     left_meta.mark_deleted(DeletedFile {
                         path: path_str,
                         size: 0,
@@ -623,10 +1594,39 @@ impl App {
                         deleted_at: now,
                     });
                 }
+                SyncAction::UpdateAttributesRight { path }
+                | SyncAction::UpdateAttributesLeft { path } => on_disk.push(path),
+                SyncAction::KeepBoth { path, copy, .. } => on_disk.extend([path, copy]),
                 _ => {}
             }
         }
 
+        // Accepted conflicts: each side keeps its own file, recorded as synced
+        for path in accepted.iter().chain(on_disk) {
+            let path_str = path_key(path);
+            for (full_path, archived, meta) in [
+                (project.left_path.join(path), false, &mut left_meta),
+                (right_file(path), compressed, &mut right_meta),
+            ] {
+                if let Ok(metadata) = std::fs::metadata(&full_path) {
+                    let mtime = metadata
+                        .modified()
+                        .ok()
+                        .map(system_time_to_utc)
+                        .unwrap_or(now);
+                    meta.upsert_file(FileState {
+                        path: path_str.clone(),
+                        size: file_size(&full_path, &metadata, archived),
+                        mtime,
+                        hash: None,
+                        attributes: FileAttributes::read_from_path(&full_path),
+                        file_id: file_id(&full_path),
+                        last_synced: now,
+                    });
+                }
+            }
+        }
+
         left_meta.last_sync = Some(now);
         right_meta.last_sync = Some(now);
 
@@ -662,15 +1662,34 @@ impl App {
                 PathBuf::from(&dialog.left_path),
                 PathBuf::from(&dialog.right_path),
             );
+            let blocked = [&project.left_path, &project.right_path]
+                .into_iter()
+                .find_map(|path| {
+                    blocklist::check_root(&expand_path(path).path, &self.config).err()
+                });
+            if let Some(e) = blocked {
+                if let Dialog::NewProject(ref mut d) = self.dialog {
+                    d.error = Some(e.to_string());
+                }
+                return;
+            }
 
             if let Some(ref pm) = self.project_manager {
                 match pm.save_project(&project) {
                     Ok(()) => {
+                        let mut history = dialog.history.clone();
+                        history.record(&dialog.left_path);
+                        history.record(&dialog.right_path);
+                        let history_saved = history.save(pm.config_dir());
+
                         self.dialog = Dialog::None;
                         self.refresh_projects();
                         if let Some(pos) = self.projects.iter().position(|p| p == &project.name) {
                             self.list_state.select(Some(pos));
                         }
+                        if let Err(e) = history_saved {
+                            self.notify(format!("Failed to save path history: {}", e));
+                        }
                     }
                     Err(e) => {
                         if let Dialog::NewProject(ref mut d) = self.dialog {
@@ -682,6 +1701,98 @@ impl App {
         }
     }
 
+    /// Carries out the export or import the project file dialog asks for
+    fn finish_project_file(&mut self) {
+        let Dialog::ProjectFile(ref dialog) = self.dialog else {
+            return;
+        };
+        let Some(ref pm) = self.project_manager else {
+            return;
+        };
+        let path = expand_path(Path::new(&dialog.path)).path;
+        let outcome = match dialog.export {
+            Some(ref name) => pm
+                .export_project(name)
+                .and_then(|data| {
+                    std::fs::write(&path, data)
+                        .with_context(|| format!("Failed to write {}", path.display()))
+                })
+                .map(|()| format!("Exported '{}' to {}", name, path.display())),
+            None => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))
+                .and_then(|data| {
+                    let project = ProjectManager::parse_exported(&data)?;
+                    for root in [&project.left_path, &project.right_path] {
+                        blocklist::check_root(&expand_path(root).path, &self.config)?;
+                    }
+                    pm.import_project(&data)
+                })
+                .map(|name| {
+                    self.refresh_projects();
+                    if let Some(pos) = self.projects.iter().position(|p| *p == name) {
+                        self.list_state.select(Some(pos));
+                    }
+                    format!("Imported project '{}'", name)
+                }),
+        };
+        match outcome {
+            Ok(message) => {
+                self.dialog = Dialog::None;
+                self.notify(message);
+            }
+            Err(e) => {
+                if let Dialog::ProjectFile(ref mut dialog) = self.dialog {
+                    dialog.error = Some(format!("{:#}", e));
+                }
+            }
+        }
+    }
+
+    /// Writes the preview as the script the script dialog asks for
+    fn finish_script(&mut self) {
+        let Dialog::Script(ref dialog) = self.dialog else {
+            return;
+        };
+        let (Some(ref preview), Some(ref project)) = (&self.preview, &self.current_project) else {
+            return;
+        };
+        // Resolved actions as they would be synced; open conflicts are noted
+        let actions = preview.actions.iter().filter_map(|ua| match ua {
+            UserAction::Original(action @ SyncAction::Conflict { .. }) => Some(action.clone()),
+            _ => ua.to_sync_action(),
+        });
+        let diff = DiffResult {
+            actions: actions.collect(),
+            ..Default::default()
+        };
+        let script = diff.to_script(dialog.platform, &project.left_path, &project.right_path);
+        let path = expand_path(Path::new(&dialog.path)).path;
+        if !dialog.overwrite && std::fs::symlink_metadata(&path).is_ok() {
+            if let Dialog::Script(ref mut dialog) = self.dialog {
+                dialog.error = Some(format!(
+                    "{} exists; Enter again to replace it",
+                    path.display()
+                ));
+                dialog.overwrite = true;
+            }
+            return;
+        }
+        match std::fs::write(&path, script) {
+            Ok(()) => {
+                self.dialog = Dialog::None;
+                self.notify(format!(
+                    "Wrote {} (no backups or trash, review before running)",
+                    path.display()
+                ));
+            }
+            Err(e) => {
+                if let Dialog::Script(ref mut dialog) = self.dialog {
+                    dialog.error = Some(format!("Failed to write {}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+
     fn delete_project(&mut self, name: &str) {
         if let Some(ref pm) = self.project_manager {
             if let Err(e) = pm.delete_project(name) {
@@ -692,6 +1803,26 @@ impl App {
         }
     }
 
+    fn swap_project_sides(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        let Some(ref pm) = self.project_manager else {
+            return;
+        };
+
+        match pm.swap_sides(&project.name) {
+            Ok(project) => {
+                self.current_project = Some(project);
+                std::mem::swap(&mut self.left_exclusions, &mut self.right_exclusions);
+                self.notify("Swapped left and right");
+            }
+            Err(e) => {
+                self.dialog = Dialog::Error(format!("Failed to swap sides: {}", e));
+            }
+        }
+    }
+
     fn show_exclusions_dialog(&mut self) {
         let Some(ref project) = self.current_project else {
             return;
@@ -703,6 +1834,12 @@ impl App {
         let right_exists = right_path.exists();
         let left_count = self.left_exclusions.as_ref().map(|e| e.len()).unwrap_or(0);
         let right_count = self.right_exclusions.as_ref().map(|e| e.len()).unwrap_or(0);
+        let empty = Exclusions::empty();
+        let differences = self
+            .left_exclusions
+            .as_ref()
+            .unwrap_or(&empty)
+            .diff(self.right_exclusions.as_ref().unwrap_or(&empty));
 
         self.dialog = Dialog::ExclusionsInfo(ExclusionsInfoDialog {
             left_path,
@@ -711,9 +1848,214 @@ impl App {
             right_exists,
             left_count,
             right_count,
+            left_only: differences.only_left.len(),
+            right_only: differences.only_right.len(),
+            auto_excluded: self.auto_excluded.clone(),
+        });
+    }
+
+    fn show_metadata_inspector(&mut self) {
+        if let Some(dialog) = self.preview.as_ref().and_then(|p| p.inspect_selected()) {
+            self.dialog = Dialog::MetadataInspect(Box::new(dialog));
+        }
+    }
+
+    fn show_target_status_dialog(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+
+        let check = |path: &std::path::Path| validate_target(path).map_err(|e| e.to_string());
+
+        self.dialog = Dialog::TargetStatus(TargetStatusDialog {
+            left_path: project.left_path.clone(),
+            right_path: project.right_path.clone(),
+            left: check(&project.left_path),
+            right: check(&project.right_path),
+        });
+    }
+
+    /// Opens the history of the current project's syncs
+    fn show_history(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        match stats::load_recent(&project.left_path, HISTORY_RUNS) {
+            Ok(runs) => {
+                self.history = Some(HistoryState { runs, scroll: 0 });
+                self.screen = Screen::History;
+            }
+            Err(e) => {
+                self.dialog = Dialog::Error(format!("Failed to read the sync history: {:#}", e));
+            }
+        }
+    }
+
+    /// Compares both sides byte by byte, ignoring sync history
+    fn run_verify(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        if project.settings.manifest_right {
+            self.dialog = Dialog::Error(MANIFEST_READ_ONLY.to_string());
+            return;
+        }
+        if let Err(e) = check_compression(project) {
+            self.dialog = Dialog::Error(e.to_string());
+            return;
+        }
+
+        let scan_options = ScanOptions {
+            record_file_ids: false,
+            include_reparse_points: project.settings.include_reparse_points,
+            // Content comparison must not trust earlier scans
+            cache: ScanCacheMode::Off,
+            one_filesystem: project.settings.one_filesystem,
+            min_age: days(project.settings.min_age_days),
+            max_age: days(project.settings.max_age_days),
+            // A FIFO has no content to verify
+            recreate_fifos: false,
+        };
+        let other_roots = other_project_roots(self.project_manager.as_ref(), project);
+        let exclusions = |root: &Path| {
+            exclude_dirs(
+                Exclusions::load(root).ok().as_ref(),
+                &nested_roots(root, &other_roots),
+            )
+        };
+        let (left_exclusions, right_exclusions) = match (
+            exclusions(&project.left_path),
+            exclusions(&project.right_path),
+        ) {
+            (Ok(left), Ok(right)) => (left, right),
+            (Err(e), _) | (_, Err(e)) => {
+                self.dialog = Dialog::Error(format!("{:#}", e));
+                return;
+            }
+        };
+
+        let (mut left_scan, mut right_scan) = match (
+            scan_with_options(&project.left_path, left_exclusions.as_ref(), &scan_options),
+            scan_with_options(
+                &project.right_path,
+                right_exclusions.as_ref(),
+                &scan_options,
+            ),
+        ) {
+            (Ok(left), Ok(right)) => (left, right),
+            (Err(e), _) | (_, Err(e)) => {
+                self.dialog = Dialog::Error(format!("Failed to scan: {:#}", e));
+                return;
+            }
+        };
+        // Archived files are read back through the decompressor
+        let hash_right = if project.settings.compressed_right {
+            compress::unarchive_scan(&mut right_scan);
+            drop_unreadable_archives(&mut left_scan, &right_scan);
+            compress::hash_archived
+        } else {
+            hash_file
+        };
+        let (left_ignore_files, right_ignore_files) = (
+            left_scan.nested_exclusions(),
+            right_scan.nested_exclusions(),
+        );
+        drop_excluded(
+            &mut left_scan,
+            right_exclusions.as_ref(),
+            &right_ignore_files,
+        );
+        drop_excluded(
+            &mut right_scan,
+            left_exclusions.as_ref(),
+            &left_ignore_files,
+        );
+        drop_unreadable(&mut left_scan, &right_scan);
+        drop_unreadable(&mut right_scan, &left_scan);
+        drop_other_filesystem(&mut left_scan, &right_scan);
+        drop_other_filesystem(&mut right_scan, &left_scan);
+
+        self.dialog = Dialog::VerifyReport(VerifyReportDialog {
+            report: verify_with(&left_scan, &right_scan, hash_right),
+            scroll: 0,
+        });
+    }
+
+    fn show_backup_browser(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        let Some(ref preview) = self.preview else {
+            return;
+        };
+        let Some(action) = preview
+            .selected_action()
+            .and_then(|i| preview.actions.get(i))
+        else {
+            return;
+        };
+
+        let path = action.path().clone();
+        let Some(filename) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            return;
+        };
+
+        let mut versions = Vec::new();
+        for (is_left, root) in [(true, &project.left_path), (false, &project.right_path)] {
+            match list_backups(root, &filename) {
+                Ok(entries) => versions.extend(
+                    entries
+                        .into_iter()
+                        .map(|entry| BackupVersion { is_left, entry }),
+                ),
+                Err(e) => {
+                    self.dialog = Dialog::Error(format!("Failed to list backups: {}", e));
+                    return;
+                }
+            }
+        }
+
+        if versions.is_empty() {
+            self.dialog = Dialog::Error(format!("No backups found for {}", filename));
+            return;
+        }
+
+        versions.sort_by_key(|v| std::cmp::Reverse(v.entry.timestamp));
+        self.dialog = Dialog::BackupBrowser(BackupBrowserDialog {
+            path,
+            versions,
+            selected: 0,
+            confirm: false,
         });
     }
 
+    fn restore_selected_backup(&mut self) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        let Dialog::BackupBrowser(ref dialog) = self.dialog else {
+            return;
+        };
+        let Some(version) = dialog.versions.get(dialog.selected) else {
+            return;
+        };
+
+        let root = if version.is_left {
+            &project.left_path
+        } else {
+            &project.right_path
+        };
+
+        if let Err(e) = restore_backup(root, &dialog.path, &version.entry) {
+            self.dialog = Dialog::Error(format!("Failed to restore backup: {}", e));
+            return;
+        }
+
+        // Re-analyze so the preview reflects the restored file
+        self.dialog = Dialog::None;
+        self.run_analyze();
+    }
+
     fn create_exclusions_template(&mut self) {
         let Some(ref project) = self.current_project else {
             return;
@@ -744,6 +2086,39 @@ impl App {
         self.run_analyze();
     }
 
+    /// Opens one side's exclusions file in the editor, creating it from the
+    /// template first if needed. The editor runs from the main loop, which
+    /// owns the terminal; the project is re-analyzed afterwards.
+    fn edit_exclusions(&mut self, left: bool) {
+        let Some(ref project) = self.current_project else {
+            return;
+        };
+        let root = if left {
+            &project.left_path
+        } else {
+            &project.right_path
+        };
+        let path = Exclusions::file_path(root);
+
+        if !path.exists() {
+            if let Err(e) = std::fs::write(&path, Exclusions::default_template()) {
+                self.dialog = Dialog::Error(format!("Failed to create exclusions: {}", e));
+                return;
+            }
+        }
+        self.dialog = Dialog::None;
+        self.pending_edit = Some(path);
+    }
+
+    /// Hands the terminal to the editor for `path`, then re-analyzes
+    fn run_pending_edit(&mut self, terminal: &mut ratatui::DefaultTerminal, path: &Path) {
+        if let Err(e) = editor::edit_file(terminal, path) {
+            self.dialog = Dialog::Error(format!("{:#}", e));
+            return;
+        }
+        self.run_analyze();
+    }
+
     /// Render the application
     fn render(&mut self, frame: &mut Frame) {
         let area = frame.area();
@@ -758,38 +2133,78 @@ impl App {
         self.render_header(frame, chunks[0]);
         self.render_content(frame, chunks[1]);
         self.render_footer(frame, chunks[2]);
+        self.render_notification(frame, chunks[1]);
 
         match &self.dialog {
             Dialog::None => {}
             Dialog::NewProject(dialog) => {
-                render_new_project_dialog(frame, dialog);
+                render_new_project_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::ProjectFile(dialog) => {
+                render_project_file_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::Script(dialog) => {
+                render_script_dialog(frame, dialog, &self.theme);
             }
             Dialog::DeleteConfirm(name) => {
-                render_delete_confirm_dialog(frame, name);
+                render_delete_confirm_dialog(frame, name, &self.theme);
+            }
+            Dialog::SwapSidesConfirm => {
+                render_swap_sides_confirm_dialog(frame, &self.theme);
             }
             Dialog::CreateDirConfirm { path, is_left } => {
-                render_create_dir_confirm_dialog(frame, path, *is_left);
+                render_create_dir_confirm_dialog(frame, path, *is_left, &self.theme);
             }
             Dialog::Error(msg) => {
-                render_error_dialog(frame, msg);
+                render_error_dialog(frame, msg, &self.theme);
             }
             Dialog::SyncConfirm(dialog) => {
-                render_sync_confirm_dialog(frame, dialog);
+                render_sync_confirm_dialog(frame, dialog, &self.theme);
             }
             Dialog::CancelSyncConfirm => {
-                render_cancel_sync_confirm_dialog(frame);
+                render_cancel_sync_confirm_dialog(frame, &self.theme);
+            }
+            Dialog::QuitConfirm { sync_running } => {
+                render_quit_confirm_dialog(frame, *sync_running, &self.theme);
+            }
+            Dialog::ResolveAllConfirm { to_left, count } => {
+                render_resolve_all_confirm_dialog(frame, *to_left, *count, &self.theme);
             }
             Dialog::ExclusionsInfo(dialog) => {
-                render_exclusions_info_dialog(frame, dialog);
+                render_exclusions_info_dialog(frame, dialog, &self.theme);
             }
             Dialog::DiskSpaceWarning(dialog) => {
-                render_disk_space_warning_dialog(frame, dialog);
+                render_disk_space_warning_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::ClockSkewWarning(skew) => {
+                render_clock_skew_dialog(frame, skew, &self.theme);
+            }
+            Dialog::IncompleteTransfers { left, right } => {
+                render_incomplete_transfers_dialog(frame, *left, *right, &self.theme);
+            }
+            Dialog::LargePreview(count) => {
+                render_large_preview_dialog(frame, *count, &self.theme);
+            }
+            Dialog::MetadataInspect(dialog) => {
+                render_metadata_inspect_dialog(frame, dialog, &self.theme);
             }
             Dialog::FileError(dialog) => {
-                render_file_error_dialog(frame, dialog);
+                render_file_error_dialog(frame, dialog, &self.theme);
             }
             Dialog::ProjectSettings(dialog) => {
-                render_settings_dialog(frame, dialog);
+                render_settings_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::TargetStatus(dialog) => {
+                render_target_status_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::BackupBrowser(dialog) => {
+                render_backup_browser_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::VerifyReport(dialog) => {
+                render_verify_report_dialog(frame, dialog, &self.theme);
+            }
+            Dialog::Help(dialog) => {
+                render_help_dialog(frame, dialog, &self.theme);
             }
         }
     }
@@ -810,29 +2225,41 @@ impl App {
             Screen::Analyzing => "Analyzing...".to_string(),
             Screen::Preview => {
                 if let Some(ref preview) = self.preview {
-                    format!("Preview [{}]", preview.filter.label())
+                    let mut labels = vec![preview.filter.label()];
+                    if preview.side_filter != SideFilter::Both {
+                        labels.push(preview.side_filter.label());
+                    }
+                    if !preview.show_skips {
+                        labels.push("skips hidden");
+                    }
+                    if preview.grouped {
+                        labels.push("grouped");
+                    }
+                    format!("Preview [{}]", labels.join(", "))
                 } else {
                     "Preview".to_string()
                 }
             }
             Screen::Syncing => "Syncing...".to_string(),
             Screen::SyncComplete => "Sync Complete".to_string(),
+            Screen::AnalyzeAll => "Analyze All".to_string(),
+            Screen::History => "History".to_string(),
         };
 
         let header = Paragraph::new(Line::from(vec![
             Span::styled(
                 title,
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(self.theme.title)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("— "),
-            Span::styled(screen_indicator, Style::default().fg(Color::Yellow)),
+            Span::styled(screen_indicator, Style::default().fg(self.theme.focus)),
         ]))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(self.theme.border)),
         );
 
         frame.render_widget(header, area);
@@ -843,176 +2270,535 @@ impl App {
 
         match self.screen {
             Screen::ProjectList => {
-                render_project_list(frame, area, &self.projects, &mut self.list_state);
+                render_project_list(
+                    frame,
+                    area,
+                    &self.projects,
+                    &self.pinned_projects,
+                    &mut self.list_state,
+                    &self.theme,
+                );
             }
             Screen::ProjectView => {
-                render_project_view(frame, area, self.current_project.as_ref());
+                render_project_view(frame, area, self.current_project.as_ref(), &self.theme);
             }
             Screen::Preview => {
                 if let Some(ref preview) = self.preview {
-                    render_preview(frame, area, preview);
+                    render_preview(frame, area, preview, &self.theme);
                 }
             }
             Screen::Syncing => {
                 if let Some(ref syncing) = self.syncing {
-                    render_syncing(frame, area, syncing);
+                    render_syncing(frame, area, syncing, &self.theme);
                 }
             }
             Screen::SyncComplete => {
                 if let Some(ref complete) = self.sync_complete {
-                    render_sync_complete(frame, area, complete);
+                    render_sync_complete(frame, area, complete, &self.theme);
+                }
+            }
+            Screen::AnalyzeAll => {
+                if let Some(ref state) = self.analyze_all {
+                    render_analyze_all(frame, area, state, &self.theme);
+                }
+            }
+            Screen::History => {
+                if let Some(ref state) = self.history {
+                    render_history(frame, area, state, &self.theme);
                 }
             }
             _ => {}
         }
     }
 
+    /// Whether a binding is offered in the footer right now
+    fn hint_visible(&self, when: HintWhen) -> bool {
+        match when {
+            HintWhen::Always => true,
+            HintWhen::HasProjects => !self.projects.is_empty(),
+            HintWhen::ScanCache => self
+                .current_project
+                .as_ref()
+                .is_some_and(|p| p.settings.scan_cache),
+            HintWhen::ChangedDuringSync => self
+                .sync_complete
+                .as_ref()
+                .is_some_and(|c| !c.changed_during_sync.is_empty()),
+            HintWhen::HelpOnly => false,
+        }
+    }
+
+    fn key_accent_style(&self, accent: KeyAccent) -> Style {
+        match accent {
+            KeyAccent::Normal => self.theme.key_style(),
+            KeyAccent::Success => self.theme.badge(self.theme.success),
+            KeyAccent::Warning => self.theme.badge(self.theme.warning),
+            KeyAccent::Error => self.theme.badge(self.theme.error),
+        }
+    }
+
     fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        let hints = match self.screen {
-            Screen::ProjectList => {
-                if self.projects.is_empty() {
-                    vec![
-                        Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" New  "),
-                        Span::styled(" Q ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" Quit "),
-                    ]
-                } else {
-                    vec![
-                        Span::styled(" ↑↓ ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" Nav  "),
-                        Span::styled(" Enter ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" Open  "),
-                        Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" New  "),
-                        Span::styled(" D ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" Del  "),
-                        Span::styled(" Q ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                        Span::raw(" Quit "),
-                    ]
-                }
-            }
-            Screen::ProjectView => {
-                vec![
-                    Span::styled(" A ", Style::default().fg(Color::Black).bg(Color::Green)),
-                    Span::raw(" Analyze  "),
-                    Span::styled(" C ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Config  "),
-                    Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Back  "),
-                    Span::styled(" Q ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Quit "),
-                ]
-            }
-            Screen::Preview => {
-                vec![
-                    Span::styled(" ↑↓ ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Nav  "),
-                    Span::styled(" ←→ ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Dir  "),
-                    Span::styled(" S ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Skip  "),
-                    Span::styled(" G ", Style::default().fg(Color::Black).bg(Color::Green)),
-                    Span::raw(" Go  "),
-                    Span::styled(" E ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Excl  "),
-                    Span::styled(" F ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Filter  "),
-                    Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Back "),
-                ]
-            }
-            Screen::Syncing => {
-                vec![
-                    Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Red)),
-                    Span::raw(" Cancel "),
-                ]
-            }
-            Screen::SyncComplete => {
-                let mut hints = vec![
-                    Span::styled(" Enter ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                    Span::raw(" Back  "),
-                ];
-                if let Some(ref complete) = self.sync_complete {
-                    if !complete.changed_during_sync.is_empty() {
-                        hints.extend(vec![
-                            Span::styled(
-                                " R ",
-                                Style::default().fg(Color::Black).bg(Color::Yellow),
-                            ),
-                            Span::raw(" Re-analyze  "),
-                        ]);
-                    }
-                    if !complete.failed.is_empty() {
-                        hints.extend(vec![
-                            Span::styled(" ↑↓ ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                            Span::raw(" Scroll "),
-                        ]);
-                    }
-                }
-                hints
-            }
-            _ => vec![
-                Span::styled(" Q ", Style::default().fg(Color::Black).bg(Color::Gray)),
-                Span::raw(" Quit "),
-            ],
-        };
+        let visible: Vec<_> = keymap::bindings(self.screen)
+            .iter()
+            .chain(keymap::GLOBAL)
+            .filter(|b| self.hint_visible(b.when))
+            .collect();
+        let mut hints = Vec::new();
+        for (i, binding) in visible.iter().enumerate() {
+            let gap = if i + 1 < visible.len() { "  " } else { " " };
+            hints.push(Span::styled(
+                format!(" {} ", binding.keys),
+                self.key_accent_style(binding.accent),
+            ));
+            hints.push(Span::raw(format!(" {}{}", binding.label, gap)));
+        }
 
         let footer = Paragraph::new(Line::from(hints)).block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Keyboard ")
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(self.theme.border)),
         );
 
         frame.render_widget(footer, area);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::sync::differ::diff;
-    use crate::sync::scanner::scan_with_exclusions;
-    use crossterm::event::KeyCode;
-    use tempfile::TempDir;
+    /// Draws the notification on the bottom line of `area`, clearing it once expired
+    fn render_notification(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(message) = self.notification() else {
+            self.notification = None;
+            return;
+        };
+        if area.height == 0 {
+            return;
+        }
 
-    fn create_test_app() -> (App, TempDir) {
-        let temp = TempDir::new().expect("Failed to create temp dir");
-        let pm = ProjectManager::with_config_dir(temp.path().to_path_buf());
-        let app = App::with_project_manager(pm);
-        (app, temp)
-    }
+        let text = format!(" {} ", message);
+        let width = (text.chars().count() as u16).min(area.width);
+        let toast_area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + area.height - 1,
+            width,
+            1,
+        );
 
-    #[test]
-    fn test_app_initial_state() {
-        let (app, _temp) = create_test_app();
-        assert_eq!(app.screen, Screen::ProjectList);
-        assert!(!app.should_quit);
-        assert!(app.projects.is_empty());
+        frame.render_widget(Clear, toast_area);
+        frame.render_widget(
+            Paragraph::new(text).style(self.theme.badge(self.theme.title)),
+            toast_area,
+        );
     }
+}
 
-    #[test]
-    fn test_quit_on_q() {
-        let (mut app, _temp) = create_test_app();
-        app.handle_key(KeyCode::Char('q'));
-        assert!(app.should_quit);
-    }
+/// Scan and diff of one project, ready to be shown as a preview
+struct ProjectAnalysis {
+    preview: PreviewState,
+    left_exclusions: Option<Exclusions>,
+    right_exclusions: Option<Exclusions>,
+    /// A systematic mtime offset between the sides worth warning about
+    clock_skew: Option<ClockSkew>,
+    /// Roots of other projects left out of the scans
+    auto_excluded: Vec<PathBuf>,
+}
 
-    #[test]
-    fn test_quit_on_esc() {
-        let (mut app, _temp) = create_test_app();
-        app.handle_key(KeyCode::Esc);
-        assert!(app.should_quit);
+/// Scans both sides of `project` and diffs them against the sync metadata.
+/// Both paths are expected to exist. `full_rescan` ignores the scan cache.
+/// Directories among `other_roots` are left out of the scans.
+fn analyze_project(
+    project: &Project,
+    full_rescan: bool,
+    config: &AppConfig,
+    other_roots: &[PathBuf],
+) -> Result<ProjectAnalysis> {
+    let manifest_right = project.settings.manifest_right;
+    blocklist::check_root(&project.left_path, config)?;
+    if !manifest_right {
+        blocklist::check_root(&project.right_path, config)?;
     }
-
-    #[test]
-    fn test_open_new_project_dialog() {
-        let (mut app, _temp) = create_test_app();
-        app.handle_key(KeyCode::Char('n'));
-        assert!(matches!(app.dialog, Dialog::NewProject(_)));
+    check_compression(project)?;
+
+    // Load exclusions (opt-in: returns empty if file doesn't exist)
+    let left_exclusions = Exclusions::load(&project.left_path).ok();
+    let right_exclusions = if manifest_right {
+        None
+    } else {
+        Exclusions::load(&project.right_path).ok()
+    };
+    // Other projects nested inside this one are theirs to sync
+    let left_nested = nested_roots(&project.left_path, other_roots);
+    let right_nested = if manifest_right {
+        Vec::new()
+    } else {
+        nested_roots(&project.right_path, other_roots)
+    };
+    let left_scan_exclusions = exclude_dirs(left_exclusions.as_ref(), &left_nested)?;
+    let right_scan_exclusions = exclude_dirs(right_exclusions.as_ref(), &right_nested)?;
+
+    // Scan both sides with exclusions
+    let scan_options = ScanOptions {
+        record_file_ids: project.settings.records_file_ids(),
+        include_reparse_points: project.settings.include_reparse_points,
+        cache: match (project.settings.scan_cache, full_rescan) {
+            (false, _) => ScanCacheMode::Off,
+            (true, false) => ScanCacheMode::Reuse,
+            (true, true) => ScanCacheMode::Rebuild,
+        },
+        one_filesystem: project.settings.one_filesystem,
+        min_age: days(project.settings.min_age_days),
+        max_age: days(project.settings.max_age_days),
+        recreate_fifos: project.settings.recreate_fifos,
+    };
+    // Load metadata with project's retention setting. A manifest has no sync
+    // history, so the sides are compared as on a first sync.
+    let retention = project.settings.deleted_retention_days as i64;
+    let (left_meta, right_meta) = if manifest_right {
+        (SyncMetadata::default(), SyncMetadata::default())
+    } else {
+        (
+            SyncMetadata::load_with_retention(&project.left_path, retention).unwrap_or_default(),
+            SyncMetadata::load_with_retention(&project.right_path, retention).unwrap_or_default(),
+        )
+    };
+
+    // Quick sync takes the right side as recorded at the last sync instead
+    // of scanning it. With nothing recorded yet, the sync runs in full.
+    let quick_since = left_meta
+        .last_sync
+        .filter(|_| project.settings.quick_sync && !manifest_right);
+
+    let mut left_scan = scan_with_options(
+        &project.left_path,
+        left_scan_exclusions.as_ref(),
+        &scan_options,
+    )
+    .context("Failed to scan left")?;
+    let mut right_scan = if manifest_right {
+        manifest::load(&project.right_path)?
+    } else if quick_since.is_some() {
+        recorded_scan(&project.right_path, &right_meta)
+    } else {
+        scan_with_options(
+            &project.right_path,
+            right_scan_exclusions.as_ref(),
+            &scan_options,
+        )
+        .context("Failed to scan right")?
+    };
+    if project.settings.compressed_right && quick_since.is_none() {
+        compress::unarchive_scan(&mut right_scan);
+        // A damaged archive must not look like a file deleted on the right
+        drop_unreadable_archives(&mut left_scan, &right_scan);
     }
-
+    // Each side applies its own exclusions. A path one side excludes is left
+    // alone on the other too: it is neither copied into the excluding side
+    // nor, once there, seen as deleted from it. The same goes for the ignore
+    // files below each root, which only their own side's scan applied.
+    let (left_ignore_files, right_ignore_files) = (
+        left_scan.nested_exclusions(),
+        right_scan.nested_exclusions(),
+    );
+    drop_excluded(
+        &mut left_scan,
+        right_scan_exclusions.as_ref(),
+        &right_ignore_files,
+    );
+    drop_excluded(
+        &mut right_scan,
+        left_scan_exclusions.as_ref(),
+        &left_ignore_files,
+    );
+    // Ages differ per side; a file filtered out on one side must not look
+    // deleted there, so it is left out on both
+    drop_age_filtered(&mut left_scan, &right_scan);
+    drop_age_filtered(&mut right_scan, &left_scan);
+    // Nothing is known about the contents of a directory one side couldn't
+    // list, so they are left alone on the other side as well
+    drop_unreadable(&mut left_scan, &right_scan);
+    drop_unreadable(&mut right_scan, &left_scan);
+    // A mount point left out on one side must not look deleted there
+    drop_other_filesystem(&mut left_scan, &right_scan);
+    drop_other_filesystem(&mut right_scan, &left_scan);
+
+    // Run diff
+    let options = DiffOptions {
+        skip_empty_dirs: project.settings.skip_empty_dirs,
+        append_only_right: project.settings.append_only_right,
+        conflict_rules: ConflictRules::compile(&project.settings.conflict_rules)?,
+        compare_ownership: project.settings.preserve_ownership,
+        // An archive file's attributes aren't those of the file it holds
+        compare_attributes: project.settings.sync_attributes && !project.settings.compressed_right,
+        conflict_age_threshold: project
+            .settings
+            .conflict_age_threshold_secs
+            .map(|secs| chrono::Duration::seconds(secs as i64)),
+    };
+    let mut diff_result = match quick_since {
+        Some(since) => diff_since(&left_scan, &right_scan, since, &options),
+        None => diff_with_options(&left_scan, &right_scan, &left_meta, &right_meta, &options),
+    };
+    if project.settings.detect_renames {
+        for (scan, meta, side) in [
+            (&left_scan, &left_meta, "left"),
+            (&right_scan, &right_meta, "right"),
+        ] {
+            explain_renames(&mut diff_result, &detect_renames(scan, meta), side);
+        }
+    }
+    // A manifest has no content to hash
+    if project.settings.skip_content_elsewhere && !manifest_right {
+        let hash_right = if project.settings.compressed_right {
+            compress::hash_archived
+        } else {
+            hash_file
+        };
+        skip_content_elsewhere(&mut diff_result, &left_scan, &right_scan, hash_right);
+    }
+
+    let clock_skew = estimate_clock_skew(&left_scan, &right_scan).filter(ClockSkew::is_significant);
+
+    // Create preview state
+    let mut preview = PreviewState::new(diff_result, left_scan, right_scan);
+    preview.left_meta = left_meta;
+    preview.right_meta = right_meta;
+    preview.one_way = quick_since.is_some();
+    preview.filter = project.settings.preview_filter;
+    preview.show_skips = !project.settings.hide_skips;
+
+    Ok(ProjectAnalysis {
+        preview,
+        left_exclusions,
+        right_exclusions,
+        clock_skew,
+        auto_excluded: left_nested
+            .iter()
+            .map(|dir| project.left_path.join(dir))
+            .chain(right_nested.iter().map(|dir| project.right_path.join(dir)))
+            .collect(),
+    })
+}
+
+/// Roots of the other projects, if `project` leaves them out
+fn other_project_roots(pm: Option<&ProjectManager>, project: &Project) -> Vec<PathBuf> {
+    match pm {
+        Some(pm) if project.settings.exclude_other_projects => {
+            pm.other_project_roots(&project.name)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Roots among `other_roots` strictly inside `root`, relative to it
+fn nested_roots(root: &Path, other_roots: &[PathBuf]) -> Vec<PathBuf> {
+    other_roots
+        .iter()
+        .filter_map(|other| other.strip_prefix(root).ok())
+        .filter(|rel| !rel.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// `exclusions` plus the given directories
+fn exclude_dirs(exclusions: Option<&Exclusions>, dirs: &[PathBuf]) -> Result<Option<Exclusions>> {
+    if dirs.is_empty() {
+        return Ok(exclusions.cloned());
+    }
+    let exclusions = exclusions.cloned().unwrap_or_default();
+    Ok(Some(exclusions.with_dirs(dirs)?))
+}
+
+/// Fails for a compressed archive project if this build can't read archives
+fn check_compression(project: &Project) -> Result<()> {
+    if project.settings.compressed_right && !compress::AVAILABLE {
+        anyhow::bail!(
+            "The right side is a compressed archive, but rahzom was built \
+             without the `compression` feature"
+        );
+    }
+    Ok(())
+}
+
+/// Message for a webhook delivery to `url` that failed
+fn report_failure(url: &str, error: &anyhow::Error) -> String {
+    format!("Failed to report the sync to {}: {}", url, error)
+}
+
+/// Duration of an optional number of days
+fn days(days: Option<u64>) -> Option<Duration> {
+    days.map(|d| Duration::from_secs(d.saturating_mul(86_400)))
+}
+
+/// Removes from `scan` the entries that the other side's `exclusions`, or
+/// its `nested` ignore files below the root, match. Run both ways, so a path
+/// excluded on either side is out of the diff on both: never copied into the
+/// side that excludes it, nor deleted from or copied out of the other.
+fn drop_excluded(
+    scan: &mut ScanResult,
+    exclusions: Option<&Exclusions>,
+    nested: &[(usize, PathBuf, Exclusions)],
+) {
+    let Some(exclusions) = exclusions else {
+        return;
+    };
+    if exclusions.is_empty() && nested.is_empty() {
+        return;
+    }
+    let root = scan.root.clone();
+    scan.entries.retain(|e| {
+        if !scanner::is_excluded(exclusions, nested, &e.path, e.is_dir) {
+            return true;
+        }
+        scan.skipped.push(SkippedEntry {
+            path: root.join(&e.path),
+            reason: "Excluded on the other side".to_string(),
+        });
+        false
+    });
+}
+
+/// Removes from `scan` the files that `other` skipped by age
+fn drop_age_filtered(scan: &mut ScanResult, other: &ScanResult) {
+    let filtered: HashSet<PathBuf> = other.skipped_by_age().map(Path::to_path_buf).collect();
+    if filtered.is_empty() {
+        return;
+    }
+    let root = scan.root.clone();
+    scan.entries.retain(|e| {
+        if e.is_dir || !filtered.contains(&e.path) {
+            return true;
+        }
+        scan.skipped.push(SkippedEntry {
+            path: root.join(&e.path),
+            reason: "Outside the age window on the other side".to_string(),
+        });
+        false
+    });
+}
+
+/// Removes from `scan` the entries inside directories `other` couldn't list
+fn drop_unreadable(scan: &mut ScanResult, other: &ScanResult) {
+    let unreadable: Vec<PathBuf> = other.unreadable_dirs().map(Path::to_path_buf).collect();
+    if unreadable.is_empty() {
+        return;
+    }
+    let root = scan.root.clone();
+    scan.entries.retain(|e| {
+        let inside = unreadable
+            .iter()
+            .any(|dir| e.path != *dir && e.path.starts_with(dir));
+        if !inside {
+            return true;
+        }
+        scan.skipped.push(SkippedEntry {
+            path: root.join(&e.path),
+            reason: "Unreadable on the other side".to_string(),
+        });
+        false
+    });
+}
+
+/// Removes from the left `scan` the files whose archive on the right
+/// `archive` couldn't be read
+fn drop_unreadable_archives(scan: &mut ScanResult, archive: &ScanResult) {
+    let unreadable: HashSet<PathBuf> = compress::unreadable_archives(archive)
+        .map(Path::to_path_buf)
+        .collect();
+    if unreadable.is_empty() {
+        return;
+    }
+    let root = scan.root.clone();
+    scan.entries.retain(|e| {
+        if e.is_dir || !unreadable.contains(&e.path) {
+            return true;
+        }
+        scan.skipped.push(SkippedEntry {
+            path: root.join(&e.path),
+            reason: "Archive unreadable on the other side".to_string(),
+        });
+        false
+    });
+}
+
+/// Removes from `scan` the directories `other` left out as being on another
+/// filesystem, along with their contents
+fn drop_other_filesystem(scan: &mut ScanResult, other: &ScanResult) {
+    let mounts: Vec<PathBuf> = other
+        .other_filesystem_dirs()
+        .map(Path::to_path_buf)
+        .collect();
+    if mounts.is_empty() {
+        return;
+    }
+    let root = scan.root.clone();
+    scan.entries.retain(|e| {
+        if !mounts.iter().any(|dir| e.path.starts_with(dir)) {
+            return true;
+        }
+        scan.skipped.push(SkippedEntry {
+            path: root.join(&e.path),
+            reason: "On another filesystem on the other side".to_string(),
+        });
+        false
+    });
+}
+
+/// Size to record for the source side of a completed copy. A copy with
+/// normalized line endings differs in size from its source, so the source
+/// keeps its own size; otherwise both sides share the destination's.
+fn source_side_size(completed: &CompletedAction, source: &Path, copied_size: u64) -> u64 {
+    if !completed.eol_normalized {
+        return copied_size;
+    }
+    std::fs::metadata(source)
+        .map(|m| m.len())
+        .unwrap_or(copied_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::history::PathHistory;
+    use crate::sync::differ::diff;
+    use crate::sync::executor::TargetStatus;
+    use crate::sync::scanner::scan_with_exclusions;
+    use crossterm::event::KeyCode;
+    use tempfile::TempDir;
+
+    fn create_test_app() -> (App, TempDir) {
+        let temp = TempDir::new().expect("Failed to create temp dir");
+        let pm = ProjectManager::with_config_dir(temp.path().to_path_buf());
+        let app = App::with_project_manager(pm);
+        (app, temp)
+    }
+
+    #[test]
+    fn test_app_initial_state() {
+        let (app, _temp) = create_test_app();
+        assert_eq!(app.screen, Screen::ProjectList);
+        assert!(!app.should_quit);
+        assert!(app.projects.is_empty());
+    }
+
+    #[test]
+    fn test_quit_on_q() {
+        let (mut app, _temp) = create_test_app();
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_quit_on_esc() {
+        let (mut app, _temp) = create_test_app();
+        app.handle_key(KeyCode::Esc);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_open_new_project_dialog() {
+        let (mut app, _temp) = create_test_app();
+        app.handle_key(KeyCode::Char('n'));
+        assert!(matches!(app.dialog, Dialog::NewProject(_)));
+    }
+
     #[test]
     fn test_close_dialog_on_esc() {
         let (mut app, _temp) = create_test_app();
@@ -1022,131 +2808,2730 @@ mod tests {
     }
 
     #[test]
-    fn test_new_project_dialog_tab_navigation() {
-        let (mut app, _temp) = create_test_app();
-        app.dialog = Dialog::NewProject(NewProjectDialog::new());
+    fn test_new_project_dialog_tab_navigation() {
+        let (mut app, _temp) = create_test_app();
+        app.dialog = Dialog::NewProject(NewProjectDialog::new());
+
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.focused_field, DialogField::Name);
+        }
+
+        app.handle_key(KeyCode::Tab);
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.focused_field, DialogField::LeftPath);
+        }
+
+        app.handle_key(KeyCode::Tab);
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.focused_field, DialogField::RightPath);
+        }
+
+        app.handle_key(KeyCode::Tab);
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.focused_field, DialogField::Name);
+        }
+    }
+
+    #[test]
+    fn test_create_project() {
+        let (mut app, _temp) = create_test_app();
+
+        app.dialog = Dialog::NewProject(NewProjectDialog {
+            name: "test-project".to_string(),
+            left_path: "/path/left".to_string(),
+            right_path: "/path/right".to_string(),
+            focused_field: DialogField::Name,
+            error: None,
+            history: PathHistory::default(),
+            suggestion: None,
+        });
+
+        app.try_create_project();
+
+        assert!(matches!(app.dialog, Dialog::None));
+        assert_eq!(app.projects.len(), 1);
+        assert_eq!(app.projects[0], "test-project");
+    }
+
+    #[test]
+    fn test_blocked_paths_are_refused() {
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+
+        // A filesystem root can't be a side
+        app.dialog = Dialog::NewProject(NewProjectDialog {
+            name: "oops".to_string(),
+            left_path: left.path().display().to_string(),
+            right_path: "/".to_string(),
+            focused_field: DialogField::Name,
+            error: None,
+            history: PathHistory::default(),
+            suggestion: None,
+        });
+        app.try_create_project();
+        let Dialog::NewProject(ref dialog) = app.dialog else {
+            panic!("expected the dialog to stay open");
+        };
+        assert!(dialog.error.as_ref().unwrap().contains("filesystem root"));
+        assert!(app.projects.is_empty());
+
+        // Nor a configured one, checked again when analyzing
+        app.dialog = Dialog::None;
+        app.config.blocked_paths = vec![right.path().to_path_buf()];
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        assert!(app.preview.is_none());
+        assert!(matches!(app.dialog, Dialog::Error(ref e) if e.contains("blocked_paths")));
+    }
+
+    #[test]
+    fn test_export_and_import_project_file() {
+        let (mut app, _temp) = create_test_app();
+        let files = TempDir::new().unwrap();
+        let pm = app.project_manager.as_ref().unwrap();
+        pm.save_project(&Project::new(
+            "docs",
+            PathBuf::from("/path/left"),
+            PathBuf::from("/path/right"),
+        ))
+        .unwrap();
+        app.refresh_projects();
+
+        // Export the selected project to a file of the user's choosing
+        let file = files.path().join("docs.toml");
+        app.handle_key(KeyCode::Char('e'));
+        let Dialog::ProjectFile(ref mut dialog) = app.dialog else {
+            panic!("expected the project file dialog, got {:?}", app.dialog);
+        };
+        assert_eq!(dialog.path, "docs.rahzom.toml");
+        dialog.path = file.display().to_string();
+        app.handle_key(KeyCode::Enter);
+        assert!(matches!(app.dialog, Dialog::None));
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("/path/right"));
+
+        // Importing it back collides with the original, so it is renamed
+        app.handle_key(KeyCode::Char('i'));
+        for c in file.display().to_string().chars() {
+            app.handle_key(KeyCode::Char(c));
+        }
+        app.handle_key(KeyCode::Enter);
+        assert!(matches!(app.dialog, Dialog::None));
+        assert_eq!(app.projects, vec!["docs", "docs-2"]);
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        // A blocked root is refused before anything is saved
+        std::fs::write(
+            &file,
+            std::fs::read_to_string(&file)
+                .unwrap()
+                .replace("/path/right", "/"),
+        )
+        .unwrap();
+        app.dialog = Dialog::ProjectFile(ProjectFileDialog {
+            export: None,
+            path: file.display().to_string(),
+            error: None,
+        });
+        app.handle_key(KeyCode::Enter);
+        let Dialog::ProjectFile(ref dialog) = app.dialog else {
+            panic!("expected the dialog to stay open");
+        };
+        assert!(dialog.error.as_ref().unwrap().contains("filesystem root"));
+        assert_eq!(app.projects.len(), 2);
+    }
+
+    #[test]
+    fn test_new_project_path_suggestions() {
+        let (mut app, temp) = create_test_app();
+        let mut history = PathHistory::default();
+        history.record("/mnt/usb/backup");
+        history.record("/home/me/docs");
+        history.save(temp.path()).unwrap();
+
+        app.handle_key(KeyCode::Char('n'));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char('/'));
+        app.handle_key(KeyCode::Char('m'));
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.suggestions(), vec!["/mnt/usb/backup"]);
+        }
+
+        // Tab completes the highlighted suggestion instead of moving on
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Tab);
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.left_path, "/mnt/usb/backup");
+            assert_eq!(d.focused_field, DialogField::LeftPath);
+        }
+
+        // Nothing highlighted: Tab moves to the next field as before
+        app.handle_key(KeyCode::Tab);
+        if let Dialog::NewProject(ref d) = app.dialog {
+            assert_eq!(d.focused_field, DialogField::RightPath);
+            assert_eq!(d.suggestions().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_create_project_records_path_history() {
+        let (mut app, temp) = create_test_app();
+
+        app.dialog = Dialog::NewProject(NewProjectDialog {
+            name: "test-project".to_string(),
+            left_path: "/path/left".to_string(),
+            right_path: "/path/right".to_string(),
+            ..NewProjectDialog::new()
+        });
+        app.try_create_project();
+
+        let history = PathHistory::load(temp.path()).unwrap();
+        assert_eq!(history.paths, vec!["/path/right", "/path/left"]);
+    }
+
+    #[test]
+    fn test_select_next_wraps() {
+        let (mut app, _temp) = create_test_app();
+        app.projects = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        app.list_state.select(Some(2));
+
+        app.select_next_project();
+
+        assert_eq!(app.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_select_previous_wraps() {
+        let (mut app, _temp) = create_test_app();
+        app.projects = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        app.list_state.select(Some(0));
+
+        app.select_previous_project();
+
+        assert_eq!(app.list_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_open_project() {
+        let (mut app, _temp) = create_test_app();
+
+        let pm = app.project_manager.as_ref().unwrap();
+        let project = Project::new("test", PathBuf::from("/left"), PathBuf::from("/right"));
+        pm.save_project(&project).unwrap();
+        app.refresh_projects();
+
+        app.list_state.select(Some(0));
+        app.open_selected_project();
+
+        assert_eq!(app.screen, Screen::ProjectView);
+        assert!(app.current_project.is_some());
+    }
+
+    #[test]
+    fn test_resume_last_project() {
+        let (mut app, temp) = create_test_app();
+        let pm = app.project_manager.as_ref().unwrap();
+        for name in ["alpha", "beta"] {
+            let project = Project::new(name, PathBuf::from("/left"), PathBuf::from("/right"));
+            pm.save_project(&project).unwrap();
+        }
+        app.refresh_projects();
+        app.list_state.select(Some(1));
+        app.open_selected_project();
+
+        // The next start selects it
+        let pm = ProjectManager::with_config_dir(temp.path().to_path_buf());
+        let mut app = App::with_project_manager(pm);
+        app.resume_last_project();
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert_eq!(app.screen, Screen::ProjectList);
+
+        // Or opens it
+        app.config.resume = ResumeMode::Open;
+        app.resume_last_project();
+        assert_eq!(app.screen, Screen::ProjectView);
+        assert_eq!(app.current_project.as_ref().unwrap().name, "beta");
+
+        // A deleted project is forgotten quietly
+        let pm = ProjectManager::with_config_dir(temp.path().to_path_buf());
+        pm.delete_project("beta").unwrap();
+        let mut app = App::with_project_manager(pm);
+        app.resume_last_project();
+        assert_eq!(app.list_state.selected(), Some(0));
+        assert_eq!(app.screen, Screen::ProjectList);
+    }
+
+    #[test]
+    fn test_poll_timeout() {
+        let (mut app, _temp) = create_test_app();
+
+        // Idle with nothing to update: block until input
+        assert_eq!(app.poll_timeout(), None);
+
+        app.notify("Saved");
+        let timeout = app.poll_timeout().unwrap();
+        assert!(timeout > Duration::ZERO && timeout <= NOTIFICATION_DURATION);
+
+        app.config.idle_poll_ms = Some(50);
+        assert_eq!(app.poll_timeout(), Some(Duration::from_millis(50)));
+
+        app.screen = Screen::Syncing;
+        assert_eq!(app.poll_timeout(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_notification_expires() {
+        let (mut app, _temp) = create_test_app();
+        assert_eq!(app.notification(), None);
+
+        app.notify("Added pattern");
+        assert_eq!(app.notification(), Some("Added pattern"));
+
+        if let Some(posted) = Instant::now().checked_sub(NOTIFICATION_DURATION) {
+            app.notification = Some(("Old".to_string(), posted));
+            assert_eq!(app.notification(), None);
+        }
+    }
+
+    #[test]
+    fn test_quit_confirmation_after_unreviewed_sync() {
+        let (mut app, _temp) = create_test_app();
+
+        app.unreviewed_sync = true;
+        app.handle_key(KeyCode::Char('q'));
+        assert_eq!(
+            app.dialog,
+            Dialog::QuitConfirm {
+                sync_running: false
+            }
+        );
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(!app.should_quit);
+
+        app.handle_key(KeyCode::Esc);
+        app.handle_key(KeyCode::Char('y'));
+        assert!(app.should_quit);
+
+        let (mut app, _temp) = create_test_app();
+        app.unreviewed_sync = true;
+        app.config.confirm_on_quit = false;
+        app.handle_key(KeyCode::Char('q'));
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_swap_sides_after_confirmation() {
+        let (mut app, _temp) = create_test_app();
+
+        let pm = app.project_manager.as_ref().unwrap();
+        let project = Project::new("test", PathBuf::from("/left"), PathBuf::from("/right"));
+        pm.save_project(&project).unwrap();
+
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(project);
+
+        app.handle_key(KeyCode::Char('w'));
+        assert_eq!(app.dialog, Dialog::SwapSidesConfirm);
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.dialog, Dialog::None);
+        assert_eq!(
+            app.current_project.as_ref().unwrap().left_path,
+            PathBuf::from("/left")
+        );
+
+        app.handle_key(KeyCode::Char('w'));
+        app.handle_key(KeyCode::Char('y'));
+        assert_eq!(app.dialog, Dialog::None);
+
+        assert_eq!(app.notification(), Some("Swapped left and right"));
+
+        let current = app.current_project.as_ref().unwrap();
+        assert_eq!(current.left_path, PathBuf::from("/right"));
+        assert_eq!(current.right_path, PathBuf::from("/left"));
+
+        let pm = app.project_manager.as_ref().unwrap();
+        let loaded = pm.load_project("test").unwrap();
+        assert_eq!(loaded.left_path, PathBuf::from("/right"));
+    }
+
+    #[test]
+    fn test_back_from_project_view() {
+        let (mut app, _temp) = create_test_app();
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(Project::new(
+            "test",
+            PathBuf::from("/l"),
+            PathBuf::from("/r"),
+        ));
+
+        app.handle_key(KeyCode::Esc);
+
+        assert_eq!(app.screen, Screen::ProjectList);
+        assert!(app.current_project.is_none());
+    }
+
+    #[test]
+    fn test_preview_filter_cycle() {
+        let filter = PreviewFilter::All;
+        assert_eq!(filter.next(), PreviewFilter::Changes);
+        assert_eq!(filter.next().next(), PreviewFilter::Conflicts);
+        assert_eq!(filter.next().next().next(), PreviewFilter::All);
+    }
+
+    #[test]
+    fn test_preview_state_creation() {
+        use std::fs;
+
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+
+        fs::write(temp_left.path().join("file.txt"), "content").unwrap();
+
+        let left_scan = scan_with_exclusions(temp_left.path(), None).unwrap();
+        let right_scan = scan_with_exclusions(temp_right.path(), None).unwrap();
+        let left_meta = SyncMetadata::default();
+        let right_meta = SyncMetadata::default();
+
+        let diff_result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+        let preview = PreviewState::new(diff_result, left_scan, right_scan);
+
+        assert!(!preview.actions.is_empty());
+        assert_eq!(preview.filter, PreviewFilter::All);
+        assert_eq!(preview.selected, 0);
+    }
+
+    #[test]
+    fn test_analyze_hides_skips_by_default() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+
+        fs::write(temp_left.path().join("new.txt"), "new").unwrap();
+        fs::create_dir(temp_left.path().join("shared")).unwrap();
+        fs::create_dir(temp_right.path().join("shared")).unwrap();
+
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+
+        app.run_analyze();
+
+        let preview = app.preview.as_ref().unwrap();
+        assert!(!preview.show_skips);
+        assert_eq!(preview.actions.len(), 2);
+        assert_eq!(preview.filtered_indices().len(), 1);
+
+        app.handle_key(KeyCode::Char('v'));
+        let preview = app.preview.as_ref().unwrap();
+        assert!(preview.show_skips);
+        assert_eq!(preview.filtered_indices().len(), 2);
+    }
+
+    #[test]
+    fn test_side_filter() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("b.txt"), "right").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.filtered_indices().len(), 2);
+
+        app.handle_key(KeyCode::Char('d'));
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.side_filter, SideFilter::ToRight);
+        let indices = preview.filtered_indices();
+        assert_eq!(indices.len(), 1);
+        assert_eq!(preview.actions[indices[0]].path(), Path::new("a.txt"));
+
+        app.handle_key(KeyCode::Char('d'));
+        let preview = app.preview.as_ref().unwrap();
+        let indices = preview.filtered_indices();
+        assert_eq!(indices.len(), 1);
+        assert_eq!(preview.actions[indices[0]].path(), Path::new("b.txt"));
+
+        // Combined with the Conflicts filter nothing directional remains
+        app.handle_key(KeyCode::Char('f'));
+        app.handle_key(KeyCode::Char('f'));
+        assert!(app.preview.as_ref().unwrap().filtered_indices().is_empty());
+    }
+
+    #[test]
+    fn test_filters_keep_selection() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("b.txt"), "right").unwrap();
+        fs::write(temp_left.path().join("c.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("c.txt"), "right side").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        let selected_path = |app: &App| {
+            let preview = app.preview.as_ref().unwrap();
+            preview.actions[preview.selected_action().unwrap()]
+                .path()
+                .clone()
+        };
+
+        let select = |app: &mut App, name: &str| {
+            let preview = app.preview.as_mut().unwrap();
+            preview.selected = preview
+                .rows()
+                .iter()
+                .position(|row| {
+                    matches!(row, PreviewRow::Action(i) if preview.actions[*i].path() == Path::new(name))
+                })
+                .unwrap();
+        };
+
+        // The selected conflict stays selected through Conflicts and back
+        select(&mut app, "c.txt");
+        app.handle_key(KeyCode::Char('f'));
+        app.handle_key(KeyCode::Char('f'));
+        assert_eq!(selected_path(&app), PathBuf::from("c.txt"));
+        app.handle_key(KeyCode::Char('f'));
+        assert_eq!(selected_path(&app), PathBuf::from("c.txt"));
+
+        // A selection filtered out moves to the nearest action still shown
+        select(&mut app, "b.txt");
+        app.handle_key(KeyCode::Char('d'));
+        assert_eq!(selected_path(&app), PathBuf::from("a.txt"));
+        app.handle_key(KeyCode::Char('d'));
+        assert_eq!(selected_path(&app), PathBuf::from("b.txt"));
+    }
+
+    #[test]
+    fn test_incomplete_transfers_reported_and_removed() {
+        use crate::sync::utils::temp_path_for;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "left").unwrap();
+        let partial = temp_path_for(&temp_right.path().join("a.txt"));
+        fs::write(&partial, "par").unwrap();
+        // A partial, but of no path in the diff
+        let foreign = temp_path_for(&temp_right.path().join("notes.txt"));
+        fs::write(&foreign, "mine").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        // Not offered as something to sync
+        let preview = app.preview.as_ref().unwrap();
+        let paths: Vec<&PathBuf> = preview.actions.iter().map(|a| a.path()).collect();
+        assert_eq!(paths, [&PathBuf::from("a.txt")]);
+        assert_eq!(
+            app.dialog,
+            Dialog::IncompleteTransfers { left: 0, right: 1 }
+        );
+
+        // Enter keeps them
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(partial.exists());
+
+        app.run_analyze();
+        app.handle_key(KeyCode::Char('y'));
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(!partial.exists());
+        assert!(foreign.exists());
+        assert_eq!(app.preview.as_ref().unwrap().partial_counts(), (0, 0));
+    }
+
+    #[test]
+    fn test_queued_dialogs_follow_each_other() {
+        let (mut app, _temp) = create_test_app();
+        app.screen = Screen::Preview;
+        app.queued_dialogs
+            .push_back(Dialog::IncompleteTransfers { left: 1, right: 0 });
+        app.queued_dialogs
+            .push_back(Dialog::LargePreview(LARGE_PREVIEW_ACTIONS + 1));
+        app.show_queued_dialog();
+        assert_eq!(
+            app.dialog,
+            Dialog::IncompleteTransfers { left: 1, right: 0 }
+        );
+
+        // Closing one shows the next, so the large preview guard isn't lost
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.dialog, Dialog::LargePreview(LARGE_PREVIEW_ACTIONS + 1));
+
+        // Leaving the preview drops whatever is still queued
+        app.queued_dialogs
+            .push_back(Dialog::Error("late".to_string()));
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.screen, Screen::ProjectView);
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(app.queued_dialogs.is_empty());
+    }
+
+    #[test]
+    fn test_range_skip_and_direction() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            fs::write(temp_left.path().join(name), "left").unwrap();
+        }
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.preview.as_mut().unwrap().show_skips = true;
+        let skipped = |app: &App| {
+            let preview = app.preview.as_ref().unwrap();
+            preview
+                .actions
+                .iter()
+                .filter(|a| matches!(a, UserAction::Skip { .. }))
+                .count()
+        };
+
+        // Down from the second item over the next two
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Char('o'));
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(
+            app.preview.as_ref().unwrap().range_indices().unwrap().len(),
+            3
+        );
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(skipped(&app), 3);
+        assert_eq!(app.preview.as_ref().unwrap().range_anchor, None);
+
+        // A range upwards, pointed back to the right
+        app.handle_key(KeyCode::Char('o'));
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Right);
+        assert_eq!(skipped(&app), 1);
+
+        // Esc drops a range without leaving the preview
+        app.handle_key(KeyCode::Char('o'));
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.screen, Screen::Preview);
+        assert_eq!(app.preview.as_ref().unwrap().range_anchor, None);
+    }
+
+    #[test]
+    fn test_jump_between_conflicts() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "left").unwrap();
+        for name in ["c.txt", "e.txt"] {
+            fs::write(temp_left.path().join(name), "left").unwrap();
+            fs::write(temp_right.path().join(name), "right side").unwrap();
+        }
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        let selected_path = |app: &App| {
+            let preview = app.preview.as_ref().unwrap();
+            preview.actions[preview.selected_action().unwrap()]
+                .path()
+                .clone()
+        };
+
+        let position = |app: &App| app.preview.as_ref().unwrap().conflict_position();
+
+        app.handle_key(KeyCode::Char('n'));
+        let first = selected_path(&app);
+        let (at, count) = position(&app).unwrap();
+        assert_eq!(count, 2);
+        app.handle_key(KeyCode::Char('n'));
+        assert_ne!(selected_path(&app), first);
+        assert_eq!(position(&app), Some((at % 2 + 1, 2)));
+
+        // Wraps around both ways
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(selected_path(&app), first);
+        app.handle_key(KeyCode::Char('p'));
+        let second = selected_path(&app);
+        assert_ne!(second, first);
+
+        // A resolved conflict is no longer a stop
+        app.handle_key(KeyCode::Right);
+        assert_eq!(position(&app), None);
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(selected_path(&app), first);
+        assert_eq!(position(&app), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_cached_rows_follow_edits_and_paging() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        for i in 0..5 {
+            fs::write(temp_left.path().join(format!("{}.txt", i)), "new").unwrap();
+        }
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.handle_key(KeyCode::Char('c'));
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.rows().len(), 6);
+        assert_eq!(preview.shown_count(), 5);
+
+        // A skipped action moves to its section and out of the Changes filter
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Char('s'));
+        app.handle_key(KeyCode::Char('f'));
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.rows().len(), 5);
+        assert_eq!(preview.shown_count(), 4);
+
+        // Paging moves by the rows last drawn and stops at the ends
+        assert_eq!(preview.selected, 1);
+        preview.page_rows.set(3);
+        app.handle_key(KeyCode::PageDown);
+        assert_eq!(app.preview.as_ref().unwrap().selected, 4);
+        app.handle_key(KeyCode::PageDown);
+        assert_eq!(app.preview.as_ref().unwrap().selected, 4);
+        app.handle_key(KeyCode::PageUp);
+        assert_eq!(app.preview.as_ref().unwrap().selected, 1);
+        app.handle_key(KeyCode::PageUp);
+        assert_eq!(app.preview.as_ref().unwrap().selected, 0);
+    }
+
+    #[test]
+    fn test_large_preview_can_skip_the_list() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "new").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        assert_eq!(app.dialog, Dialog::None);
+
+        // Shown for diffs above the cap; G goes straight to confirmation
+        app.dialog = Dialog::LargePreview(LARGE_PREVIEW_ACTIONS + 1);
+        app.handle_key(KeyCode::Char('g'));
+        assert!(matches!(app.dialog, Dialog::SyncConfirm(_)));
+
+        app.dialog = Dialog::LargePreview(LARGE_PREVIEW_ACTIONS + 1);
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.screen, Screen::ProjectView);
+        assert!(app.preview.is_none());
+    }
+
+    #[test]
+    fn test_details_of_selected_action() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("both.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("both.txt"), "right side").unwrap();
+        fs::write(temp_left.path().join("new.txt"), "new").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.handle_key(KeyCode::Char('i'));
+
+        let preview = app.preview.as_mut().unwrap();
+        assert!(preview.show_details);
+        let select = |preview: &mut PreviewState, name: &str| {
+            preview.selected = preview
+                .rows()
+                .iter()
+                .position(|row| {
+                    matches!(row, PreviewRow::Action(i) if preview.actions[*i].path() == Path::new(name))
+                })
+                .unwrap();
+        };
+
+        // A first-sync conflict carries both sides
+        select(preview, "both.txt");
+        let (left, right) = preview.selected_details().unwrap();
+        assert_eq!(left.map(|s| s.size), Some(4));
+        assert_eq!(right.map(|s| s.size), Some(10));
+        assert_eq!(
+            preview.selected_explanation().unwrap(),
+            "Files differ and neither side was synced before"
+        );
+
+        // A copy is looked up in the scans
+        select(preview, "new.txt");
+        let (left, right) = preview.selected_details().unwrap();
+        assert_eq!(left.map(|s| s.size), Some(3));
+        assert!(right.is_none());
+        assert_eq!(preview.selected_explanation().unwrap(), "New on left");
+
+        // A changed action names the user, keeping the diff's reason
+        let idx = preview.selected_action().unwrap();
+        preview.set_action(
+            idx,
+            UserAction::DeleteLeft {
+                path: PathBuf::from("new.txt"),
+            },
+        );
+        assert_eq!(
+            preview.selected_explanation().unwrap(),
+            "Changed by you (the diff's reason: New on left)"
+        );
+    }
+
+    #[test]
+    fn test_grouped_preview() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "a").unwrap();
+        fs::write(temp_left.path().join("c.txt"), "c").unwrap();
+        fs::write(temp_right.path().join("b.txt"), "b").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.handle_key(KeyCode::Char('v'));
+        app.handle_key(KeyCode::Char('c'));
+
+        let headers = |app: &App| -> Vec<(ActionGroup, usize)> {
+            app.preview
+                .as_ref()
+                .unwrap()
+                .rows()
+                .iter()
+                .filter_map(|row| match *row {
+                    PreviewRow::Header { group, count } => Some((group, count)),
+                    PreviewRow::Action(_) => None,
+                })
+                .collect()
+        };
+        assert_eq!(
+            headers(&app),
+            vec![(ActionGroup::CopyToRight, 2), (ActionGroup::CopyToLeft, 1)]
+        );
+
+        // Actions don't apply to a header; Enter folds its section
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(app.preview.as_ref().unwrap().summary().skipped, 0);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.preview.as_ref().unwrap().rows().len(), 3);
+
+        // Navigation crosses into the next section
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        let preview = app.preview.as_ref().unwrap();
+        let selected = preview.selected_action().unwrap();
+        assert_eq!(preview.actions[selected].path(), Path::new("b.txt"));
+
+        // A changed action moves to its new section
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(
+            headers(&app),
+            vec![(ActionGroup::CopyToRight, 2), (ActionGroup::Skipped, 1)]
+        );
+
+        app.handle_key(KeyCode::Char('c'));
+        assert_eq!(app.preview.as_ref().unwrap().rows().len(), 3);
+        assert!(headers(&app).is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_state_reported_on_analyze() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::create_dir_all(temp_left.path().join(".rahzom")).unwrap();
+        fs::write(temp_left.path().join(".rahzom/state.json"), "{ truncated").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+
+        app.run_analyze();
+
+        let notification = app.notification().unwrap();
+        assert!(notification.contains("left side was corrupt"));
+        assert!(temp_left.path().join(".rahzom/state.json.corrupt").exists());
+    }
+
+    #[test]
+    fn test_preview_summary_text() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        assert_eq!(app.preview_summary_text(), None);
+
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("new.txt"), "new").unwrap();
+
+        app.current_project = Some(Project::new(
+            "docs",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        let text = app.preview_summary_text().unwrap();
+        assert!(text.starts_with("rahzom preview: docs\n"));
+        assert!(text.contains("Copy to right: 1 files (3 B)"));
+        assert!(text.contains("Conflicts: 0"));
+
+        // Feedback goes through a toast whether or not a clipboard is available
+        app.handle_key(KeyCode::Char('y'));
+        assert!(app.notification().is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_keep_their_own_metadata() {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        // Both decode lossily to "caf\u{FFFD}.txt"
+        let latin1 = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+        let cp437 = Path::new(OsStr::from_bytes(b"caf\x82.txt"));
+        fs::write(left.path().join(latin1), "latin-1").unwrap();
+        fs::write(left.path().join(cp437), "cp437").unwrap();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        project.settings.scan_cache = true;
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        assert_eq!(fs::read(right.path().join(cp437)).unwrap(), b"cp437");
+
+        // Each change is judged against its own file's record
+        fs::remove_file(left.path().join(latin1)).unwrap();
+        fs::write(right.path().join(cp437), "cp437, edited").unwrap();
+        app.run_analyze();
+        let mut pending: Vec<SyncAction> = app
+            .preview
+            .as_ref()
+            .unwrap()
+            .actions
+            .iter()
+            .filter_map(|action| action.to_sync_action())
+            .collect();
+        pending.sort_by(|a, b| a.path().cmp(b.path()));
+        assert_eq!(
+            pending,
+            vec![
+                SyncAction::CopyToLeft {
+                    path: cp437.to_path_buf(),
+                    size: 13
+                },
+                SyncAction::DeleteRight {
+                    path: latin1.to_path_buf()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_headless_sync() {
+        use crate::ui::ConsoleProgress;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "12345").unwrap();
+        fs::write(right.path().join("b.txt"), "123").unwrap();
+        let pm = app.project_manager.as_ref().unwrap();
+        pm.save_project(&Project::new(
+            "backup",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ))
+        .unwrap();
+
+        assert!(app.start_headless_sync("missing").is_err());
+        assert!(app.start_headless_sync("backup").unwrap());
+        let mut out = Vec::new();
+        let mut progress = ConsoleProgress::with_writer(&mut out, 8, false);
+        let result = app.run_headless_sync(&mut progress);
+        progress.finish(&result).unwrap();
+
+        assert_eq!(result.completed.len(), 2);
+        assert!(result.failed.is_empty());
+        assert_eq!(
+            fs::read_to_string(right.path().join("a.txt")).unwrap(),
+            "12345"
+        );
+        assert_eq!(
+            fs::read_to_string(left.path().join("b.txt")).unwrap(),
+            "123"
+        );
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("1/2 files, 0 B/8 B\n"));
+        assert!(text.contains("2/2 files, 8 B/8 B\n"));
+
+        // In sync now
+        assert!(!app.start_headless_sync("backup").unwrap());
+    }
+
+    #[test]
+    fn test_resumed_sync_reports_totals_of_both_runs() {
+        use std::fs;
+
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(left.path().join(name), "12345").unwrap();
+        }
+        let project = || {
+            Project::new(
+                "test",
+                left.path().to_path_buf(),
+                right.path().to_path_buf(),
+            )
+        };
+
+        // The first run gets one copy done before the app goes away
+        let (mut app, _temp) = create_test_app();
+        app.current_project = Some(project());
+        app.run_analyze();
+        app.start_sync(true);
+        app.execute_next_sync_action();
+        drop(app);
+        let journal = SyncJournal::load(left.path()).unwrap();
+        assert_eq!(journal.completed, 1);
+        assert_eq!(journal.bytes_transferred, 5);
+
+        // The next sync does the rest and counts both
+        let (mut app, _temp) = create_test_app();
+        app.current_project = Some(project());
+        app.run_analyze();
+        app.start_sync(true);
+        let syncing = app.syncing.as_ref().unwrap();
+        assert_eq!((syncing.completed_actions, syncing.total_actions), (1, 3));
+        assert_eq!((syncing.transferred_bytes, syncing.total_bytes), (5, 15));
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.completed.len(), 2);
+        assert_eq!(complete.earlier.completed, 1);
+        assert_eq!(complete.bytes_transferred, 15);
+        assert_eq!(SyncJournal::load(left.path()), None);
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_compressed_archive_is_in_sync_afterwards() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let archive = TempDir::new().unwrap();
+        fs::create_dir(left.path().join("docs")).unwrap();
+        fs::write(left.path().join("docs/log.txt"), "line\n".repeat(500)).unwrap();
+
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            archive.path().to_path_buf(),
+        );
+        project.settings.compressed_right = true;
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        assert!(app.sync_complete.as_ref().unwrap().failed.is_empty());
+        let archived = archive.path().join("docs/log.txt.rahzom.zst");
+        assert!(fs::metadata(&archived).unwrap().len() < 2500);
+
+        // The archive compares as the original: nothing left to do
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_left + summary.copy_to_right, 0);
+        assert_eq!(summary.conflicts, 0);
+
+        app.run_verify();
+        let Dialog::VerifyReport(ref dialog) = app.dialog else {
+            panic!("expected a verify report, got {:?}", app.dialog);
+        };
+        assert!(dialog.report.is_clean());
+        assert_eq!(dialog.report.files_compared, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_damaged_archive_does_not_delete_the_original() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let archive = TempDir::new().unwrap();
+        fs::write(left.path().join("doc.txt"), "content").unwrap();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            archive.path().to_path_buf(),
+        );
+        project.settings.compressed_right = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        fs::write(archive.path().join("doc.txt.rahzom.zst"), "garbage").unwrap();
+        app.run_analyze();
+        let preview = app.preview.as_ref().unwrap();
+        assert!(preview
+            .actions
+            .iter()
+            .all(|a| matches!(a, UserAction::Original(SyncAction::Skip { .. }))));
+        assert!(left.path().join("doc.txt").exists());
+    }
+
+    #[test]
+    fn test_quick_sync_trusts_the_recorded_right_side() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        fs::write(left.path().join("b.txt"), "b").unwrap();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        project.settings.quick_sync = true;
+        app.current_project = Some(project);
+
+        let sync = |app: &mut App| {
+            app.start_sync(true);
+            while app.screen == Screen::Syncing {
+                app.execute_next_sync_action();
+            }
+            assert!(app.sync_complete.as_ref().unwrap().failed.is_empty());
+        };
+
+        // Nothing recorded yet: the first sync runs in full
+        app.run_analyze();
+        assert_eq!(app.preview.as_ref().unwrap().summary().copy_to_right, 2);
+        sync(&mut app);
+
+        fs::write(left.path().join("a.txt"), "changed").unwrap();
+        fs::remove_file(left.path().join("b.txt")).unwrap();
+        fs::write(right.path().join("stray.txt"), "out of band").unwrap();
+
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_right, 1);
+        assert_eq!(summary.delete_right, 1);
+        // The right side isn't scanned, so its own changes go unnoticed
+        assert_eq!(summary.copy_to_left, 0);
+        assert!(app.preview.as_ref().unwrap().one_way);
+        sync(&mut app);
+
+        assert_eq!(
+            fs::read_to_string(right.path().join("a.txt")).unwrap(),
+            "changed"
+        );
+        assert!(!right.path().join("b.txt").exists());
+        assert!(right.path().join("stray.txt").exists());
+
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_right + summary.delete_right, 0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "compression"))]
+    fn test_compressed_archive_needs_the_feature() {
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        project.settings.compressed_right = true;
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        assert!(app.preview.is_none());
+        assert!(matches!(app.dialog, Dialog::Error(ref e) if e.contains("`compression` feature")));
+    }
+
+    #[test]
+    fn test_sync_complete_lists() {
+        let (mut app, _temp) = create_test_app();
+        let completed = [("small.txt", 10), ("big.iso", 3000), ("mid.txt", 200)]
+            .into_iter()
+            .map(|(name, size)| CompletedAction {
+                action: SyncAction::CopyToRight {
+                    path: PathBuf::from(name),
+                    size,
+                },
+                bytes_transferred: size,
+                warnings: Vec::new(),
+                eol_normalized: false,
+                content_hash: None,
+                duration: Duration::from_millis(10),
+            })
+            .collect();
+        let failed = vec![FailedAction {
+            action: SyncAction::DeleteRight {
+                path: PathBuf::from("gone.txt"),
+            },
+            error: "Permission denied".to_string(),
+            kind: SyncErrorKind::PermissionDenied,
+        }];
+        app.sync_complete = Some(SyncCompleteState::new(
+            completed,
+            failed,
+            Vec::new(),
+            Duration::from_secs(1),
+            3210,
+        ));
+        app.screen = Screen::SyncComplete;
+
+        // Failures come first; the completed list has the largest first
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.list, CompleteList::Failed);
+        assert_eq!(complete.completed[0].action.path(), Path::new("big.iso"));
+
+        // Each list scrolls on its own, never past its last line
+        drive::drive(
+            &mut app,
+            &[KeyCode::Tab, KeyCode::Down, KeyCode::Tab, KeyCode::Down],
+        )
+        .unwrap();
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.list, CompleteList::Completed);
+        assert_eq!(complete.scroll_offsets, [1, 0, 0]);
+        drive::drive(&mut app, &[KeyCode::Down, KeyCode::Down]).unwrap();
+        assert_eq!(app.sync_complete.as_ref().unwrap().scroll_offset(), 2);
+    }
+
+    #[test]
+    fn test_failed_webhook_does_not_fail_the_sync() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        // Nothing listens on a port just given back
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        project.settings.on_complete_webhook = Some(format!("http://127.0.0.1:{}/hook", port));
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.completed.len(), 1);
+        assert!(complete.failed.is_empty());
+        assert!(right.path().join("a.txt").exists());
+
+        // The delivery runs in the background and is reported when it ends
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while !app.reports.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            app.tick();
+        }
+        let notification = app.notification().unwrap();
+        if webhook::AVAILABLE {
+            assert!(
+                notification.starts_with("Failed to report the sync"),
+                "{}",
+                notification
+            );
+        } else {
+            assert!(
+                notification.contains("`webhook` feature"),
+                "{}",
+                notification
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalized_copy_is_in_sync_afterwards() {
+        use crate::sync::eol::EolNormalization;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("notes.txt"), "a\r\nb\r\n").unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.normalize_eol = Some(EolNormalization::default());
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert!(complete.completed[0].eol_normalized);
+        assert_eq!(
+            fs::read(temp_right.path().join("notes.txt")).unwrap(),
+            b"a\nb\n"
+        );
+
+        // Sizes now differ, but neither side changed since the sync
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_left + summary.copy_to_right, 0);
+        assert_eq!(summary.conflicts, 0);
+    }
+
+    #[test]
+    fn test_accept_conflict_records_both_sides() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        // Differing files with no sync history conflict
+        fs::write(temp_left.path().join("merged.txt"), "left version").unwrap();
+        fs::write(temp_right.path().join("merged.txt"), "right").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+
+        app.run_analyze();
+        assert_eq!(app.preview.as_ref().unwrap().summary().conflicts, 1);
+        let idx = app
+            .preview
+            .as_ref()
+            .unwrap()
+            .filtered_indices()
+            .into_iter()
+            .position(|i| is_conflict_action(&app.preview.as_ref().unwrap().actions[i]))
+            .unwrap();
+        app.preview.as_mut().unwrap().selected = idx;
+        app.handle_key(KeyCode::Char('a'));
+        assert_eq!(app.preview.as_ref().unwrap().summary().accepted, 1);
+
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        // Nothing was copied and the conflict is gone
+        assert_eq!(
+            fs::read_to_string(temp_right.path().join("merged.txt")).unwrap(),
+            "right"
+        );
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.conflicts, 0);
+        assert_eq!(summary.copy_to_left + summary.copy_to_right, 0);
+    }
+
+    #[test]
+    fn test_script_asks_before_replacing_a_file() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        let out = TempDir::new().unwrap();
+        let script = out.path().join("sync.sh");
+        fs::write(&script, "keep me").unwrap();
+        app.handle_key(KeyCode::Char('x'));
+        let Dialog::Script(ref mut dialog) = app.dialog else {
+            panic!("expected the script dialog");
+        };
+        dialog.path = script.to_string_lossy().to_string();
+
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "keep me");
+        let Dialog::Script(ref dialog) = app.dialog else {
+            panic!("expected the script dialog to stay open");
+        };
+        assert!(dialog.error.as_ref().unwrap().contains("exists"));
+
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.dialog, Dialog::None);
+        assert!(fs::read_to_string(&script).unwrap().contains("a.txt"));
+    }
+
+    #[test]
+    fn test_keep_both_saves_right_file_under_template_name() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("notes.txt"), "left version").unwrap();
+        fs::write(temp_right.path().join("notes.txt"), "right").unwrap();
+        // Taken on the left, so the counter steps past it
+        fs::write(temp_left.path().join("notes (kept).txt"), "older copy").unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.conflict_rename_template = "{name} (kept){ext}".to_string();
+        app.current_project = Some(project);
+
+        app.run_analyze();
+        let idx = app
+            .preview
+            .as_ref()
+            .unwrap()
+            .filtered_indices()
+            .into_iter()
+            .position(|i| is_conflict_action(&app.preview.as_ref().unwrap().actions[i]))
+            .unwrap();
+        app.preview.as_mut().unwrap().selected = idx;
+        app.handle_key(KeyCode::Char('K'));
+
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        for root in [temp_left.path(), temp_right.path()] {
+            assert_eq!(
+                fs::read_to_string(root.join("notes.txt")).unwrap(),
+                "left version"
+            );
+            assert_eq!(
+                fs::read_to_string(root.join("notes (kept) 2.txt")).unwrap(),
+                "right"
+            );
+        }
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.conflicts, 0);
+        assert_eq!(summary.copy_to_left + summary.copy_to_right, 0);
+    }
+
+    #[test]
+    fn test_rename_on_left_explained_when_detected() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("draft.txt"), "chapter one").unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.detect_renames = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        let meta = SyncMetadata::load(temp_left.path()).unwrap();
+        assert!(meta.find_file("draft.txt").unwrap().file_id.is_some());
+
+        fs::rename(
+            temp_left.path().join("draft.txt"),
+            temp_left.path().join("final.txt"),
+        )
+        .unwrap();
+        app.run_analyze();
+
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(
+            preview.explanations[Path::new("final.txt")],
+            "New on left; renamed from draft.txt on the left"
+        );
+        assert!(preview.explanations[Path::new("draft.txt")]
+            .ends_with("; renamed to final.txt on the left"));
+    }
+
+    #[test]
+    fn test_review_session_resumes_unchanged_decisions() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt"] {
+            fs::write(temp_left.path().join(name), "left version").unwrap();
+            fs::write(temp_right.path().join(name), "right").unwrap();
+        }
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+
+        app.run_analyze();
+        let preview = app.preview.as_mut().unwrap();
+        for idx in preview.unresolved_conflicts() {
+            preview.direct_action(idx, false);
+        }
+        preview.filter = PreviewFilter::Conflicts;
+        app.handle_key(KeyCode::Esc);
+        assert!(ReviewSession::file_path(temp_left.path()).exists());
+
+        // b.txt changes in the meantime: its decision no longer applies
+        fs::write(temp_right.path().join("b.txt"), "edited").unwrap();
+        app.run_analyze();
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.filter, PreviewFilter::Conflicts);
+        let summary = preview.summary();
+        assert_eq!(summary.copy_to_right, 1);
+        assert_eq!(summary.conflicts, 1);
+        assert!(matches!(
+            preview
+                .actions
+                .iter()
+                .find(|a| a.path() == Path::new("a.txt")),
+            Some(UserAction::CopyToRight { .. })
+        ));
+
+        // Syncing carries the decisions out and forgets them
+        app.start_sync(true);
+        assert!(!ReviewSession::file_path(temp_left.path()).exists());
+    }
+
+    #[test]
+    fn test_metadata_inspector_shows_recorded_and_current_state() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "first").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        fs::write(temp_left.path().join("a.txt"), "second version").unwrap();
+        app.run_analyze();
+        app.handle_key(KeyCode::Char('m'));
+        let Dialog::MetadataInspect(ref dialog) = app.dialog else {
+            panic!("expected the metadata inspector, got {:?}", app.dialog);
+        };
+        assert_eq!(dialog.path, Path::new("a.txt"));
+        assert!(dialog.last_sync.is_some());
+        assert_eq!(dialog.left.current.unwrap().size, 14);
+        assert_eq!(dialog.left.recorded.as_ref().unwrap().size, 5);
+        assert_eq!(dialog.right.current.unwrap().size, 5);
+        assert_eq!(dialog.right.recorded.as_ref().unwrap().size, 5);
+        assert_eq!(dialog.left.deleted, None);
+
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.dialog, Dialog::None);
+    }
+
+    #[test]
+    fn test_resolve_all_conflicts_in_one_direction() {
+        use crate::sync::differ::{ConflictReason, DiffResult};
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("both.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("both.txt"), "right!").unwrap();
+        fs::write(temp_right.path().join("right_only.txt"), "r").unwrap();
+
+        let conflict = |path: &str| SyncAction::Conflict {
+            path: PathBuf::from(path),
+            reason: ConflictReason::BothModified,
+            left: None,
+            right: None,
+        };
+        let diff_result = DiffResult {
+            actions: vec![
+                conflict("both.txt"),
+                conflict("right_only.txt"),
+                SyncAction::CopyToRight {
+                    path: PathBuf::from("new.txt"),
+                    size: 1,
+                },
+            ],
+            conflicts: 2,
+            ..Default::default()
+        };
+        let left_scan = scan_with_exclusions(temp_left.path(), None).unwrap();
+        let right_scan = scan_with_exclusions(temp_right.path(), None).unwrap();
+        app.preview = Some(PreviewState::new(diff_result, left_scan, right_scan));
+        app.screen = Screen::Preview;
+
+        // Sweeping, so it asks first
+        app.handle_key(KeyCode::Char('>'));
+        assert_eq!(
+            app.dialog,
+            Dialog::ResolveAllConfirm {
+                to_left: false,
+                count: 2
+            }
+        );
+        app.handle_key(KeyCode::Char('n'));
+        assert_eq!(app.preview.as_ref().unwrap().summary().conflicts, 2);
+
+        app.handle_key(KeyCode::Char('>'));
+        app.handle_key(KeyCode::Char('y'));
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(
+            preview.actions,
+            [
+                UserAction::CopyToRight {
+                    path: PathBuf::from("both.txt"),
+                    size: 4
+                },
+                UserAction::DeleteRight {
+                    path: PathBuf::from("right_only.txt")
+                },
+                UserAction::Original(SyncAction::CopyToRight {
+                    path: PathBuf::from("new.txt"),
+                    size: 1
+                }),
+            ]
+        );
+
+        // Nothing left to resolve
+        app.handle_key(KeyCode::Char('<'));
+        assert_eq!(app.dialog, Dialog::None);
+    }
+
+    #[test]
+    fn test_cancel_mid_copy_removes_partial_file() {
+        use crate::sync::utils::temp_path_for;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "aaa").unwrap();
+        fs::write(temp_left.path().join("b.txt"), "bbb").unwrap();
+        // Not from this run; must survive the sweep
+        let other = temp_path_for(&temp_right.path().join("other.txt"));
+        fs::write(&other, "?").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        app.execute_next_sync_action();
+
+        // The second copy is interrupted halfway when the user cancels
+        let current = app.syncing.as_ref().unwrap().actions[1].path().clone();
+        let partial = temp_path_for(&temp_right.path().join(current));
+        fs::write(&partial, "b").unwrap();
+        app.syncing.as_mut().unwrap().cancel_requested = true;
+        app.execute_next_sync_action();
+
+        assert_eq!(app.screen, Screen::SyncComplete);
+        assert!(!partial.exists());
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn test_sync_confirm_separates_overwrites() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("new.txt"), "new").unwrap();
+        fs::write(temp_left.path().join("both.txt"), "left").unwrap();
+        fs::write(temp_right.path().join("both.txt"), "right side").unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.backup_versions = 0;
+        app.current_project = Some(project);
+        app.run_analyze();
+
+        // Resolve the first-sync conflict in favor of the left side
+        let preview = app.preview.as_mut().unwrap();
+        let idx = preview
+            .actions
+            .iter()
+            .position(|a| a.path() == Path::new("both.txt"))
+            .unwrap();
+        preview.set_action(
+            idx,
+            UserAction::CopyToRight {
+                path: PathBuf::from("both.txt"),
+                size: 4,
+            },
+        );
+        app.show_sync_confirmation();
+
+        let Dialog::SyncConfirm(dialog) = &app.dialog else {
+            panic!("expected the sync confirmation");
+        };
+        assert_eq!(dialog.files_to_copy, 2);
+        assert_eq!(dialog.files_to_overwrite, 1);
+        assert_eq!(dialog.new_files(), 1);
+        assert_eq!(dialog.destructive(), 1);
+        assert_eq!(dialog.backup_versions, 0);
+        assert_eq!(dialog.unbacked_overwrites(), 1);
+        assert!(dialog.soft_delete);
+    }
+
+    #[test]
+    fn test_readonly_change_synced_without_copying() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        let (left_file, right_file) = (
+            temp_left.path().join("doc.txt"),
+            temp_right.path().join("doc.txt"),
+        );
+        fs::write(&left_file, "content").unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.sync_attributes = true;
+        app.current_project = Some(project);
+        let sync = |app: &mut App| {
+            app.start_sync(true);
+            while app.screen == Screen::Syncing {
+                app.execute_next_sync_action();
+            }
+        };
+        app.run_analyze();
+        sync(&mut app);
+
+        let mut permissions = fs::metadata(&left_file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&left_file, permissions).unwrap();
+
+        app.run_analyze();
+        let preview = app.preview.as_ref().unwrap();
+        assert!(matches!(
+            &preview.actions[..],
+            [UserAction::Original(
+                SyncAction::UpdateAttributesRight { .. }
+            )]
+        ));
+        assert_eq!(preview.summary().attributes_to_update, 1);
+        assert_eq!(preview.summary().copy_to_right, 0);
+        sync(&mut app);
+
+        assert!(fs::metadata(&right_file).unwrap().permissions().readonly());
+        assert_eq!(fs::read_to_string(&right_file).unwrap(), "content");
+        // Recorded as synced, so nothing is left to do
+        app.run_analyze();
+        assert_eq!(app.preview.as_ref().unwrap().summary().pending_changes(), 0);
+    }
+
+    #[test]
+    fn test_sync_confirm_summarizes_marked_items() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "aa").unwrap();
+        fs::write(temp_left.path().join("b.txt"), "bbbb").unwrap();
+        fs::write(temp_right.path().join("c.txt"), "c").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        let preview = app.preview.as_mut().unwrap();
+        let position = |preview: &PreviewState, name: &str| {
+            preview
+                .actions
+                .iter()
+                .position(|a| a.path() == Path::new(name))
+                .unwrap()
+        };
+        let (b, c) = (position(preview, "b.txt"), position(preview, "c.txt"));
+        preview.selected_items.extend([b, c]);
+
+        let summary = preview.selected_summary();
+        assert_eq!(summary.copy_to_right, 1);
+        assert_eq!(summary.bytes_to_right, 4);
+        assert_eq!(summary.copy_to_left, 1);
+        assert_eq!(summary.bytes_to_left, 1);
+        assert_eq!(preview.summary().copy_to_right, 2);
+
+        app.show_sync_confirmation();
+        let Dialog::SyncConfirm(dialog) = &app.dialog else {
+            panic!("expected the sync confirmation");
+        };
+        assert_eq!(dialog.selection, Some((2, 3)));
+        assert_eq!(dialog.files_to_copy, 2);
+        assert_eq!(dialog.bytes_to_transfer, 5);
+
+        // Only the marked items are synced
+        app.dialog = Dialog::None;
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        assert!(temp_right.path().join("b.txt").exists());
+        assert!(temp_left.path().join("c.txt").exists());
+        assert!(!temp_right.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_manifest_right_side_shows_drift_without_sync() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let golden = TempDir::new().unwrap();
+        fs::write(golden.path().join("kept.txt"), "same").unwrap();
+        fs::write(golden.path().join("removed.txt"), "gone").unwrap();
+        let snapshots = TempDir::new().unwrap();
+        let manifest_path = snapshots.path().join("golden.json");
+        manifest::create(golden.path(), &manifest_path).unwrap();
+
+        // The working copy drifted: one file removed, one added
+        let work = TempDir::new().unwrap();
+        fs::copy(golden.path().join("kept.txt"), work.path().join("kept.txt")).unwrap();
+        fs::write(work.path().join("added.txt"), "new").unwrap();
+        let mtime = fs::metadata(golden.path().join("kept.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        filetime::set_file_mtime(
+            work.path().join("kept.txt"),
+            filetime::FileTime::from_system_time(mtime),
+        )
+        .unwrap();
+
+        let mut project = Project::new("test", work.path().to_path_buf(), manifest_path);
+        project.settings.manifest_right = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+
+        assert_eq!(app.dialog, Dialog::None);
+        let preview = app.preview.as_ref().unwrap();
+        let action = |name: &str| {
+            preview
+                .actions
+                .iter()
+                .find(|a| a.path() == Path::new(name))
+                .cloned()
+                .unwrap()
+        };
+        assert!(matches!(
+            action("added.txt"),
+            UserAction::Original(SyncAction::CopyToRight { .. })
+        ));
+        assert!(matches!(
+            action("removed.txt"),
+            UserAction::Original(SyncAction::CopyToLeft { .. })
+        ));
+        assert!(matches!(
+            action("kept.txt"),
+            UserAction::Original(SyncAction::Skip { .. })
+        ));
+
+        app.show_sync_confirmation();
+        assert_eq!(app.dialog, Dialog::Error(MANIFEST_READ_ONLY.to_string()));
+    }
+
+    #[test]
+    fn test_nested_exclusions_apply_to_both_sides() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        for root in [left.path(), right.path()] {
+            fs::create_dir(root.join("docs")).unwrap();
+            fs::write(root.join("docs/manual.pdf"), "pdf").unwrap();
+            fs::write(root.join("docs/notes.txt"), "notes").unwrap();
+        }
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        // Only the left learns to ignore PDFs under docs/; the right gets one more
+        fs::write(left.path().join("docs/.rahzomignore"), "*.pdf\n").unwrap();
+        fs::write(right.path().join("docs/guide.pdf"), "pdf").unwrap();
+        app.run_analyze();
+
+        // Neither deleted on the right nor copied to the left
+        let preview = app.preview.as_ref().unwrap();
+        let pending: Vec<_> = preview
+            .actions
+            .iter()
+            .filter(|a| !matches!(a, UserAction::Original(SyncAction::Skip { .. })))
+            .map(|a| a.path())
+            .collect();
+        assert_eq!(pending, [Path::new("docs/.rahzomignore")]);
+        assert!(right.path().join("docs/manual.pdf").exists());
+    }
+
+    #[test]
+    fn test_exclusion_on_one_side_keeps_the_path_out_of_both() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        // Build output is excluded on the source side only
+        fs::write(left.path().join(".rahzomignore"), "build/\n").unwrap();
+        fs::write(left.path().join("main.c"), "int main;").unwrap();
+        fs::create_dir(left.path().join("build")).unwrap();
+        fs::write(left.path().join("build/main.o"), "obj").unwrap();
+        fs::create_dir(right.path().join("build")).unwrap();
+        fs::write(right.path().join("build/app.bin"), "artifact").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        // The left scan never sees build/, the right artifacts are left alone
+        // rather than copied into the source
+        let preview = app.preview.as_ref().unwrap();
+        let left_scan = preview.left_scan.as_ref().unwrap();
+        assert!(left_scan
+            .entries
+            .iter()
+            .all(|e| !e.path.starts_with("build")));
+        let right_scan = preview.right_scan.as_ref().unwrap();
+        assert!(right_scan
+            .entries
+            .iter()
+            .all(|e| !e.path.starts_with("build")));
+        assert!(right_scan
+            .skipped
+            .iter()
+            .any(|s| s.path == right.path().join("build/app.bin")
+                && s.reason == "Excluded on the other side"));
+        let mut paths: Vec<_> = preview.actions.iter().map(|a| a.path()).collect();
+        paths.sort();
+        assert_eq!(paths, [Path::new(".rahzomignore"), Path::new("main.c")]);
+        assert!(right.path().join("build/app.bin").exists());
+
+        app.show_exclusions_dialog();
+        let Dialog::ExclusionsInfo(ref dialog) = app.dialog else {
+            panic!("expected the exclusions dialog");
+        };
+        assert_eq!((dialog.left_count, dialog.right_count), (1, 0));
+        assert_eq!((dialog.left_only, dialog.right_only), (1, 0));
+
+        // Editing a side's missing file creates it from the template first
+        app.handle_key(KeyCode::Char('r'));
+        let right_file = Exclusions::file_path(right.path());
+        assert!(right_file.exists());
+        assert_eq!(app.pending_edit, Some(right_file));
+        assert_eq!(app.dialog, Dialog::None);
+    }
+
+    #[test]
+    fn test_exclusion_added_on_one_side_deletes_nothing() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::create_dir(left.path().join("cache")).unwrap();
+        fs::write(left.path().join("cache/x.dat"), "cached").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        assert!(right.path().join("cache/x.dat").exists());
+
+        // The right side stops seeing cache/; to a per-side diff its synced
+        // files would look deleted there
+        fs::write(right.path().join(".rahzomignore"), "cache/\n").unwrap();
+        fs::write(left.path().join("cache/y.dat"), "new").unwrap();
+        app.run_analyze();
+
+        let pending: Vec<SyncAction> = app
+            .preview
+            .as_ref()
+            .unwrap()
+            .actions
+            .iter()
+            .filter_map(|action| action.to_sync_action())
+            .collect();
+        assert_eq!(
+            pending,
+            vec![SyncAction::CopyToLeft {
+                path: PathBuf::from(".rahzomignore"),
+                size: 7
+            }]
+        );
+    }
+
+    #[test]
+    fn test_other_projects_roots_are_excluded() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        let backup = TempDir::new().unwrap();
+        fs::write(left.path().join("notes.txt"), "notes").unwrap();
+        fs::create_dir(left.path().join("photos")).unwrap();
+        fs::write(left.path().join("photos/a.jpg"), "jpg").unwrap();
+        let pm = app.project_manager.as_ref().unwrap();
+        pm.save_project(&Project::new(
+            "photos",
+            left.path().join("photos"),
+            backup.path().to_path_buf(),
+        ))
+        .unwrap();
+
+        let mut project = Project::new(
+            "home",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        app.current_project = Some(project.clone());
+        app.run_analyze();
+        let paths = |app: &App| {
+            let mut paths: Vec<PathBuf> = app
+                .preview
+                .as_ref()
+                .unwrap()
+                .actions
+                .iter()
+                .map(|a| a.path().clone())
+                .collect();
+            paths.sort();
+            paths
+        };
+        // Off by default: the nested project is synced here as well
+        assert!(paths(&app).contains(&PathBuf::from("photos/a.jpg")));
+
+        project.settings.exclude_other_projects = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+        assert_eq!(paths(&app), [PathBuf::from("notes.txt")]);
+
+        app.show_exclusions_dialog();
+        let Dialog::ExclusionsInfo(ref dialog) = app.dialog else {
+            panic!("expected the exclusions dialog");
+        };
+        assert_eq!(dialog.auto_excluded, [left.path().join("photos")]);
+        // The project's own patterns are counted apart
+        assert_eq!((dialog.left_count, dialog.right_count), (0, 0));
+    }
+
+    #[test]
+    fn test_content_under_another_name_is_skipped_when_enabled() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("report.pdf"), "pdf bytes").unwrap();
+        fs::write(right.path().join("report (1).pdf"), "pdf bytes").unwrap();
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+
+        app.current_project = Some(project.clone());
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_right + summary.copy_to_left, 2);
+
+        project.settings.skip_content_elsewhere = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.copy_to_right + summary.copy_to_left, 0);
+        assert_eq!(summary.content_elsewhere, 2);
+    }
+
+    #[test]
+    fn test_sync_refuses_a_side_locked_by_another_sync() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        let other = SyncLock::acquire(right.path()).unwrap();
+        app.start_sync(true);
+        assert_eq!(app.screen, Screen::Preview);
+        assert!(app.syncing.is_none());
+        assert!(matches!(&app.dialog, Dialog::Error(msg) if msg.contains("is being synced by")));
+
+        // Once the other sync is done, and after this one, the sides are free
+        drop(other);
+        app.dialog = Dialog::None;
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+        assert!(right.path().join("a.txt").exists());
+        assert!(SyncLock::acquire(left.path()).is_ok());
+    }
+
+    #[test]
+    fn test_age_filter_on_one_side_excludes_both() {
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        // Stable on the left, just edited on the right
+        let month_ago = Utc::now().timestamp() - 30 * 86_400;
+        fs::write(left.path().join("doc.txt"), "old").unwrap();
+        filetime::set_file_mtime(
+            left.path().join("doc.txt"),
+            filetime::FileTime::from_unix_time(month_ago, 0),
+        )
+        .unwrap();
+        fs::write(right.path().join("doc.txt"), "editing").unwrap();
+        fs::write(left.path().join("stable.txt"), "x").unwrap();
+        filetime::set_file_mtime(
+            left.path().join("stable.txt"),
+            filetime::FileTime::from_unix_time(month_ago, 0),
+        )
+        .unwrap();
+
+        let mut project = Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        );
+        project.settings.min_age_days = Some(3);
+        app.current_project = Some(project);
+        app.run_analyze();
+
+        let preview = app.preview.as_ref().unwrap();
+        let paths: Vec<_> = preview.actions.iter().map(|a| a.path()).collect();
+        assert_eq!(paths, [Path::new("stable.txt")]);
+    }
+
+    #[test]
+    fn test_age_limits_edited_in_settings() {
+        let (mut app, _temp) = create_test_app();
+        app.current_project = Some(Project::new(
+            "test",
+            PathBuf::from("/tmp/left"),
+            PathBuf::from("/tmp/right"),
+        ));
+        app.screen = Screen::ProjectView;
+
+        app.handle_key(KeyCode::Char('c'));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char('7'));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Char('3'));
+        app.handle_key(KeyCode::Enter);
+        let Dialog::ProjectSettings(dialog) = &app.dialog else {
+            panic!("expected the settings dialog, got {:?}", app.dialog);
+        };
+        assert_eq!(
+            dialog.error.as_deref(),
+            Some("Min age must be below max age")
+        );
+
+        app.handle_key(KeyCode::Char('0'));
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.dialog, Dialog::None);
+        let settings = &app.current_project.as_ref().unwrap().settings;
+        assert_eq!(settings.min_age_days, Some(7));
+        assert_eq!(settings.max_age_days, Some(30));
+
+        // Cleared fields turn the limits off; a huge one doesn't overflow
+        app.handle_key(KeyCode::Char('c'));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::Backspace);
+        app.handle_key(KeyCode::Tab);
+        for _ in 0.."30".len() {
+            app.handle_key(KeyCode::Backspace);
+        }
+        for _ in 0..20 {
+            app.handle_key(KeyCode::Char('9'));
+        }
+        app.handle_key(KeyCode::Enter);
+        let Dialog::ProjectSettings(dialog) = &app.dialog else {
+            panic!("expected the settings dialog, got {:?}", app.dialog);
+        };
+        assert_eq!(dialog.error.as_deref(), Some("Invalid max age"));
+        for _ in 0..2 {
+            app.handle_key(KeyCode::Backspace);
+        }
+        app.handle_key(KeyCode::Enter);
+        let settings = &app.current_project.as_ref().unwrap().settings;
+        assert_eq!(settings.min_age_days, None);
+        assert_eq!(settings.max_age_days, Some(999_999_999_999_999_999));
+        assert_eq!(
+            days(settings.max_age_days),
+            Some(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_other_filesystem_on_one_side_left_alone_on_both() {
+        use crate::sync::scanner::OTHER_FILESYSTEM;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        fs::create_dir(left.path().join("mnt")).unwrap();
+        fs::write(left.path().join("mnt/data.bin"), "data").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        // Later `mnt` is a mount point on the left, left out by one_filesystem
+        let mut left_scan = scan_with_exclusions(left.path(), None).unwrap();
+        left_scan.entries.retain(|e| !e.path.starts_with("mnt"));
+        left_scan.skipped.push(SkippedEntry {
+            path: left_scan.root.join("mnt"),
+            reason: OTHER_FILESYSTEM.to_string(),
+        });
+        let mut right_scan = scan_with_exclusions(right.path(), None).unwrap();
+        drop_other_filesystem(&mut left_scan, &right_scan);
+        drop_other_filesystem(&mut right_scan, &left_scan);
+
+        let result = diff(
+            &left_scan,
+            &right_scan,
+            &SyncMetadata::load(left.path()).unwrap(),
+            &SyncMetadata::load(right.path()).unwrap(),
+        );
+        // Its synced copy on the right is not deleted
+        assert!(result
+            .actions
+            .iter()
+            .all(|a| matches!(a, SyncAction::Skip { .. })));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_unreadable_directory_does_not_block_analyze() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
 
-        if let Dialog::NewProject(ref d) = app.dialog {
-            assert_eq!(d.focused_field, DialogField::Name);
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        fs::create_dir(left.path().join("locked")).unwrap();
+        fs::write(left.path().join("locked/hidden.txt"), "hidden").unwrap();
+        fs::create_dir(right.path().join("locked")).unwrap();
+        fs::write(right.path().join("locked/other.txt"), "other").unwrap();
+
+        let locked = left.path().join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        // Privileged users (e.g. root in CI containers) bypass permission checks
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
         }
 
-        app.handle_key(KeyCode::Tab);
-        if let Dialog::NewProject(ref d) = app.dialog {
-            assert_eq!(d.focused_field, DialogField::LeftPath);
-        }
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(app.screen, Screen::Preview);
+        let preview = app.preview.as_ref().unwrap();
+        assert_eq!(preview.left_stats.unreadable_dirs, 1);
+        // Only the readable part is synced; the right copy of the unreadable
+        // directory is left alone
+        let paths: Vec<_> = preview
+            .actions
+            .iter()
+            .filter(|a| !matches!(a, UserAction::Original(SyncAction::Skip { .. })))
+            .map(|a| a.path())
+            .collect();
+        assert_eq!(paths, [Path::new("a.txt")]);
+    }
 
-        app.handle_key(KeyCode::Tab);
-        if let Dialog::NewProject(ref d) = app.dialog {
-            assert_eq!(d.focused_field, DialogField::RightPath);
-        }
+    #[test]
+    fn test_clock_skew_is_reported_after_analyze() {
+        use std::fs;
 
-        app.handle_key(KeyCode::Tab);
-        if let Dialog::NewProject(ref d) = app.dialog {
-            assert_eq!(d.focused_field, DialogField::Name);
+        let (mut app, _temp) = create_test_app();
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        let base = 1_700_000_000;
+        for i in 0..12 {
+            let name = format!("file{i}.txt");
+            fs::write(left.path().join(&name), "same content").unwrap();
+            fs::write(right.path().join(&name), "same content").unwrap();
+            let set = |root: &Path, secs: i64| {
+                filetime::set_file_mtime(
+                    root.join(&name),
+                    filetime::FileTime::from_unix_time(secs, 0),
+                )
+                .unwrap();
+            };
+            // Written by a machine whose clock runs two hours ahead
+            set(left.path(), base + i * 600);
+            set(right.path(), base + i * 600 + 7_200);
         }
+
+        app.current_project = Some(Project::new(
+            "test",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        assert_eq!(app.screen, Screen::Preview);
+        let Dialog::ClockSkewWarning(skew) = &app.dialog else {
+            panic!("expected a clock skew warning, got {:?}", app.dialog);
+        };
+        assert_eq!(skew.offset.num_seconds(), 7_200);
+        assert_eq!(skew.samples, 12);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.dialog, Dialog::None);
     }
 
     #[test]
-    fn test_create_project() {
+    fn test_files_in_use_are_listed_after_sync() {
+        use crate::sync::executor::SkippedAction;
+        use std::fs;
+
         let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "aaa").unwrap();
 
-        app.dialog = Dialog::NewProject(NewProjectDialog {
-            name: "test-project".to_string(),
-            left_path: "/path/left".to_string(),
-            right_path: "/path/right".to_string(),
-            focused_field: DialogField::Name,
-            error: None,
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
+        let syncing = app.syncing.as_mut().unwrap();
+        syncing.result.skipped.push(SkippedAction {
+            action: SyncAction::CopyToRight {
+                path: PathBuf::from("locked.db"),
+                size: 1,
+            },
+            reason: FILE_IN_USE.to_string(),
         });
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
 
-        app.try_create_project();
-
-        assert!(matches!(app.dialog, Dialog::None));
-        assert_eq!(app.projects.len(), 1);
-        assert_eq!(app.projects[0], "test-project");
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.in_use, vec![PathBuf::from("locked.db")]);
     }
 
+    #[cfg(windows)]
     #[test]
-    fn test_select_next_wraps() {
-        let (mut app, _temp) = create_test_app();
-        app.projects = vec!["a".to_string(), "b".to_string(), "c".to_string()];
-        app.list_state.select(Some(2));
+    fn test_skip_locked_skips_without_asking() {
+        use std::fs;
+        use std::os::windows::fs::OpenOptionsExt;
 
-        app.select_next_project();
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("locked.db"), "data").unwrap();
+        fs::write(temp_left.path().join("free.txt"), "free").unwrap();
+        // No sharing allowed: opening it for the copy fails with a sharing violation
+        let _lock = fs::OpenOptions::new()
+            .read(true)
+            .share_mode(0)
+            .open(temp_left.path().join("locked.db"))
+            .unwrap();
+
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.skip_locked = true;
+        app.current_project = Some(project);
+        app.run_analyze();
+        app.start_sync(true);
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+            assert_eq!(app.dialog, Dialog::None);
+        }
 
-        assert_eq!(app.list_state.selected(), Some(0));
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.in_use, vec![PathBuf::from("locked.db")]);
+        assert!(temp_right.path().join("free.txt").exists());
     }
 
+    #[cfg(feature = "testing")]
     #[test]
-    fn test_select_previous_wraps() {
+    fn test_file_error_dialog_retry_and_skip() {
+        use crate::sync::faults::Fault;
+        use std::fs;
+
         let (mut app, _temp) = create_test_app();
-        app.projects = vec!["a".to_string(), "b".to_string(), "c".to_string()];
-        app.list_state.select(Some(0));
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "a").unwrap();
+        fs::write(temp_left.path().join("b.txt"), "b").unwrap();
+        for path in ["a.txt", "b.txt"] {
+            app.faults
+                .insert(path, Fault::Error(SyncErrorKind::FileLocked));
+        }
 
-        app.select_previous_project();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
 
-        assert_eq!(app.list_state.selected(), Some(2));
+        app.execute_next_sync_action();
+        let Dialog::FileError(ref dialog) = app.dialog else {
+            panic!("expected the file error dialog");
+        };
+        assert_eq!(dialog.kind, SyncErrorKind::FileLocked);
+        let first = dialog.path.clone();
+        let second = if first == Path::new("a.txt") {
+            "b.txt"
+        } else {
+            "a.txt"
+        };
+
+        // Retrying a file that is still locked asks again
+        app.handle_key(KeyCode::Char('r'));
+        app.execute_next_sync_action();
+        assert!(matches!(app.dialog, Dialog::FileError(_)));
+
+        // Once unlocked, a retry copies it
+        app.faults = Faults::default();
+        app.faults
+            .insert(second, Fault::Error(SyncErrorKind::FileLocked));
+        app.handle_key(KeyCode::Char('r'));
+        app.execute_next_sync_action();
+        assert!(temp_right.path().join(&first).exists());
+
+        // Skipping moves past the second one
+        app.execute_next_sync_action();
+        assert!(matches!(app.dialog, Dialog::FileError(_)));
+        app.handle_key(KeyCode::Char('s'));
+        while app.screen == Screen::Syncing {
+            app.execute_next_sync_action();
+        }
+
+        let complete = app.sync_complete.as_ref().unwrap();
+        assert_eq!(complete.completed.len(), 1);
+        assert_eq!(complete.skipped.len(), 1);
+        assert_eq!(complete.skipped[0].reason, "Skipped by user");
+        assert!(!temp_right.path().join(second).exists());
     }
 
     #[test]
-    fn test_open_project() {
+    fn test_analyze_all_lists_each_project() {
+        use std::fs;
+
         let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("new.txt"), "12345").unwrap();
 
         let pm = app.project_manager.as_ref().unwrap();
-        let project = Project::new("test", PathBuf::from("/left"), PathBuf::from("/right"));
-        pm.save_project(&project).unwrap();
+        let broken = Project::new(
+            "broken",
+            temp_left.path().to_path_buf(),
+            temp_right.path().join("missing"),
+        );
+        pm.save_project(&broken).unwrap();
+        let docs = Project::new(
+            "docs",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        pm.save_project(&docs).unwrap();
         app.refresh_projects();
 
-        app.list_state.select(Some(0));
-        app.open_selected_project();
+        app.handle_key(KeyCode::Char('a'));
+        assert_eq!(app.screen, Screen::AnalyzeAll);
+        assert!(app.poll_timeout().is_some());
+        while !app.analyze_all.as_ref().unwrap().is_done() {
+            app.analyze_next_project();
+        }
 
-        assert_eq!(app.screen, Screen::ProjectView);
-        assert!(app.current_project.is_some());
+        let state = app.analyze_all.as_ref().unwrap();
+        assert!(matches!(&state.entries[0], (name, ProjectStatus::Error(e))
+            if name == "broken" && e.contains("Right path missing")));
+        match &state.entries[1].1 {
+            ProjectStatus::Analyzed(summary) => {
+                assert_eq!(summary.copy_to_right, 1);
+                assert_eq!(summary.pending_changes(), 1);
+            }
+            other => panic!("Expected analyzed status, got {:?}", other),
+        }
+        assert_eq!(state.total_bytes(), 5);
+        // Nothing was synced from the overview
+        assert!(!temp_right.path().join("new.txt").exists());
+
+        // Errored projects explain themselves, analyzed ones open their preview
+        app.handle_key(KeyCode::Enter);
+        assert!(matches!(app.dialog, Dialog::Error(_)));
+        app.handle_key(KeyCode::Esc);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.screen, Screen::Preview);
+        assert_eq!(app.current_project.as_ref().unwrap().name, "docs");
     }
 
     #[test]
-    fn test_back_from_project_view() {
+    fn test_full_rescan_refreshes_scan_cache() {
+        use std::fs;
+
         let (mut app, _temp) = create_test_app();
-        app.screen = Screen::ProjectView;
-        app.current_project = Some(Project::new(
-            "test",
-            PathBuf::from("/l"),
-            PathBuf::from("/r"),
-        ));
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("file.txt"), "1").unwrap();
 
-        app.handle_key(KeyCode::Esc);
+        let mut project = Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        );
+        project.settings.scan_cache = true;
+        app.current_project = Some(project);
+        app.screen = Screen::ProjectView;
+        app.run_analyze();
+        assert!(temp_left.path().join(".rahzom/scan_cache.json").exists());
 
-        assert_eq!(app.screen, Screen::ProjectList);
-        assert!(app.current_project.is_none());
+        // An in-place edit isn't seen through the cache, only by a full rescan
+        fs::write(temp_left.path().join("file.txt"), "22").unwrap();
+        app.screen = Screen::ProjectView;
+        app.handle_key(KeyCode::Char('f'));
+        assert_eq!(app.screen, Screen::Preview);
+        let summary = app.preview.as_ref().unwrap().summary();
+        assert_eq!(summary.bytes_to_right, 2);
     }
 
     #[test]
-    fn test_preview_filter_cycle() {
-        let filter = PreviewFilter::All;
-        assert_eq!(filter.next(), PreviewFilter::Changes);
-        assert_eq!(filter.next().next(), PreviewFilter::Conflicts);
-        assert_eq!(filter.next().next().next(), PreviewFilter::All);
+    fn test_help_overlay_toggles_from_any_screen() {
+        let (mut app, _temp) = create_test_app();
+
+        app.handle_key(KeyCode::Char('?'));
+        assert_eq!(app.dialog, Dialog::Help(HelpDialog::default()));
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.dialog, Dialog::Help(HelpDialog { scroll: 1 }));
+        app.handle_key(KeyCode::Char('?'));
+        assert_eq!(app.dialog, Dialog::None);
+
+        // Esc closes the overlay without also leaving the screen
+        app.screen = Screen::SyncComplete;
+        app.handle_key(KeyCode::Char('?'));
+        app.handle_key(KeyCode::Esc);
+        assert_eq!(app.dialog, Dialog::None);
+        assert_eq!(app.screen, Screen::SyncComplete);
+
+        // Every binding the footer can show is described in the overlay
+        for &(_, screen) in keymap::HELP_SECTIONS {
+            for binding in keymap::bindings(screen) {
+                assert!(!binding.description.is_empty());
+                assert!(binding.when == keymap::HintWhen::HelpOnly || !binding.label.is_empty());
+            }
+        }
     }
 
     #[test]
-    fn test_preview_state_creation() {
+    fn test_throughput_sampling() {
         use std::fs;
 
+        let (mut app, _temp) = create_test_app();
         let temp_left = TempDir::new().unwrap();
         let temp_right = TempDir::new().unwrap();
-
         fs::write(temp_left.path().join("file.txt"), "content").unwrap();
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        app.start_sync(true);
 
-        let left_scan = scan_with_exclusions(temp_left.path(), None).unwrap();
-        let right_scan = scan_with_exclusions(temp_right.path(), None).unwrap();
-        let left_meta = SyncMetadata::default();
-        let right_meta = SyncMetadata::default();
+        let syncing = app.syncing.as_mut().unwrap();
+        let start = syncing.last_sample_time;
 
-        let diff_result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
-        let preview = PreviewState::new(diff_result, left_scan, right_scan);
+        syncing.transferred_bytes = 3000;
+        syncing.sample_throughput(start + Duration::from_millis(500));
+        assert!(syncing.throughput.is_empty());
 
-        assert!(!preview.actions.is_empty());
-        assert_eq!(preview.filter, PreviewFilter::All);
-        assert_eq!(preview.selected, 0);
+        syncing.sample_throughput(start + Duration::from_secs(3));
+        assert_eq!(
+            syncing.throughput.iter().copied().collect::<Vec<_>>(),
+            vec![1000, 1000, 1000]
+        );
+
+        // A long stall is recorded as zeros, keeping only the newest samples
+        syncing.sample_throughput(start + Duration::from_secs(200));
+        assert_eq!(syncing.throughput.len(), state::THROUGHPUT_SAMPLES);
+        assert!(syncing.throughput.iter().all(|&rate| rate == 0));
     }
 
     #[test]
@@ -1169,6 +5554,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_test_targets_shows_status_dialog() {
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            PathBuf::from("/nonexistent/right"),
+        ));
+
+        app.handle_key(KeyCode::Char('t'));
+
+        match &app.dialog {
+            Dialog::TargetStatus(dialog) => {
+                assert_eq!(dialog.left, Ok(TargetStatus::Writable));
+                assert_eq!(dialog.right, Ok(TargetStatus::NotFound));
+            }
+            _ => panic!("Expected TargetStatus dialog"),
+        }
+
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.dialog, Dialog::None);
+    }
+
     #[test]
     fn test_analyze_left_missing_shows_create_dialog() {
         let (mut app, _temp) = create_test_app();
@@ -1253,4 +5664,98 @@ mod tests {
 
         assert!(matches!(app.dialog, Dialog::None));
     }
+
+    #[test]
+    fn test_restore_backup_from_preview() {
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        let backup_dir = temp_right.path().join(".rahzom/_backup");
+        std::fs::create_dir_all(&backup_dir).unwrap();
+        std::fs::write(temp_left.path().join("file.txt"), "left").unwrap();
+        std::fs::write(temp_right.path().join("file.txt"), "right").unwrap();
+        std::fs::write(backup_dir.join("file.txt.20240101_120000_000"), "old").unwrap();
+
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+        assert_eq!(app.screen, Screen::Preview);
+
+        app.handle_key(KeyCode::Char('b'));
+        match &app.dialog {
+            Dialog::BackupBrowser(dialog) => {
+                assert_eq!(dialog.path, PathBuf::from("file.txt"));
+                assert_eq!(dialog.versions.len(), 1);
+                assert!(!dialog.versions[0].is_left);
+            }
+            _ => panic!("Expected BackupBrowser dialog"),
+        }
+
+        app.handle_key(KeyCode::Enter);
+        app.handle_key(KeyCode::Char('y'));
+
+        assert_eq!(
+            std::fs::read_to_string(temp_right.path().join("file.txt")).unwrap(),
+            "old"
+        );
+        let backups = list_backups(temp_right.path(), "file.txt").unwrap();
+        assert_eq!(backups.len(), 2);
+    }
+
+    #[test]
+    fn test_verify_reports_content_mismatch() {
+        use crate::sync::verify::VerifyIssueKind;
+        use std::fs;
+
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        fs::write(temp_left.path().join("a.txt"), "abcd").unwrap();
+        fs::write(temp_right.path().join("a.txt"), "abce").unwrap();
+
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.screen = Screen::ProjectView;
+
+        app.handle_key(KeyCode::Char('v'));
+
+        match &app.dialog {
+            Dialog::VerifyReport(dialog) => {
+                assert_eq!(dialog.report.files_compared, 1);
+                assert_eq!(dialog.report.count(&VerifyIssueKind::Mismatch), 1);
+            }
+            _ => panic!("Expected VerifyReport dialog"),
+        }
+        app.handle_key(KeyCode::Esc);
+        assert!(matches!(app.dialog, Dialog::None));
+    }
+
+    #[test]
+    fn test_backup_browser_without_backups_shows_error() {
+        let (mut app, _temp) = create_test_app();
+        let temp_left = TempDir::new().unwrap();
+        let temp_right = TempDir::new().unwrap();
+        std::fs::write(temp_left.path().join("file.txt"), "left").unwrap();
+
+        app.screen = Screen::ProjectView;
+        app.current_project = Some(Project::new(
+            "test",
+            temp_left.path().to_path_buf(),
+            temp_right.path().to_path_buf(),
+        ));
+        app.run_analyze();
+
+        app.handle_key(KeyCode::Char('b'));
+        match &app.dialog {
+            Dialog::Error(msg) => assert!(msg.contains("No backups")),
+            _ => panic!("Expected Error dialog"),
+        }
+    }
 }
@@ -0,0 +1,154 @@
+//! Review decisions kept between sessions, so a large preview can be
+//! reviewed in several sittings
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::state::{FileStamp, PreviewFilter, PreviewState, SideFilter, UserAction};
+use crate::sync::metadata::SyncMetadata;
+
+/// Session file inside the left side's `.rahzom/`
+pub const REVIEW_SESSION_FILE: &str = "review-session.json";
+
+/// A changed action, with both sides as they were when it was decided
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Decision {
+    action: UserAction,
+    left: Option<FileStamp>,
+    right: Option<FileStamp>,
+}
+
+/// The user's part of a preview: per-file decisions, marks and filters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewSession {
+    decisions: Vec<Decision>,
+    /// Paths selected with Space
+    marked: Vec<PathBuf>,
+    filter: PreviewFilter,
+    side_filter: SideFilter,
+    show_skips: bool,
+    grouped: bool,
+}
+
+/// Outcome of applying a saved session to a new preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Restored {
+    pub decisions: usize,
+    /// Decisions dropped because their file is gone or changed since
+    pub stale: usize,
+}
+
+impl ReviewSession {
+    /// Takes the user's decisions and view settings from `preview`
+    pub fn capture(preview: &PreviewState) -> Self {
+        let stamps = preview.stamp_index();
+        let decisions = preview
+            .actions
+            .iter()
+            .filter(|action| action.is_modified())
+            .map(|action| {
+                let (left, right) = stamps.get(action.path());
+                Decision {
+                    action: action.clone(),
+                    left,
+                    right,
+                }
+            })
+            .collect();
+        let mut marked: Vec<PathBuf> = preview
+            .selected_items
+            .iter()
+            .filter_map(|&idx| preview.actions.get(idx))
+            .map(|action| action.path().clone())
+            .collect();
+        marked.sort();
+        Self {
+            decisions,
+            marked,
+            filter: preview.filter,
+            side_filter: preview.side_filter,
+            show_skips: preview.show_skips,
+            grouped: preview.grouped,
+        }
+    }
+
+    /// Whether there is nothing worth resuming
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty() && self.marked.is_empty()
+    }
+
+    /// Path of the session file of the project whose left side is `left_root`
+    pub fn file_path(left_root: &Path) -> PathBuf {
+        SyncMetadata::metadata_dir_path(left_root).join(REVIEW_SESSION_FILE)
+    }
+
+    /// Loads the saved session; a missing or unreadable file is no session
+    pub fn load(left_root: &Path) -> Option<Self> {
+        let content = fs::read(Self::file_path(left_root)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub fn save(&self, left_root: &Path) -> Result<()> {
+        let path = Self::file_path(left_root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let content = serde_json::to_vec_pretty(self)?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write review session: {:?}", path))
+    }
+
+    /// Deletes the saved session, if any
+    pub fn remove(left_root: &Path) {
+        let _ = fs::remove_file(Self::file_path(left_root));
+    }
+
+    /// Applies the session to a freshly analyzed `preview`. A decision is
+    /// kept only if its path is still in the preview and both sides are
+    /// unchanged since it was made.
+    pub fn restore(self, preview: &mut PreviewState) -> Restored {
+        let index: HashMap<PathBuf, usize> = preview
+            .actions
+            .iter()
+            .enumerate()
+            .map(|(idx, action)| (action.path().clone(), idx))
+            .collect();
+
+        // Checked against the scans before any is applied, which needs the
+        // preview mutable
+        let stamps = preview.stamp_index();
+        let mut current = Vec::new();
+        let mut stale = 0;
+        for decision in self.decisions {
+            let path = decision.action.path();
+            match index
+                .get(path)
+                .filter(|_| stamps.get(path) == (decision.left, decision.right))
+            {
+                Some(&idx) => current.push((idx, decision.action)),
+                None => stale += 1,
+            }
+        }
+        let restored = Restored {
+            decisions: current.len(),
+            stale,
+        };
+        for (idx, action) in current {
+            preview.set_action(idx, action);
+        }
+
+        preview
+            .selected_items
+            .extend(self.marked.iter().filter_map(|path| index.get(path)));
+        preview.filter = self.filter;
+        preview.side_filter = self.side_filter;
+        preview.show_skips = self.show_skips;
+        preview.grouped = self.grouped;
+        restored
+    }
+}
@@ -1,15 +1,31 @@
 //! Application state types and enums
 
-use std::collections::{HashMap, HashSet};
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::journal::SyncJournal;
+use crate::config::history::PathHistory;
+pub use crate::config::project::PreviewFilter;
 use crate::config::project::ProjectSettings;
-use crate::sync::differ::{DiffResult, SyncAction};
+use crate::sync::differ::{DiffResult, FileInfo, SyncAction, CONTENT_ELSEWHERE};
 use crate::sync::executor::{
-    CompletedAction, ExecutionResult, FailedAction, FileSnapshot, SkippedAction, SyncErrorKind,
+    BackupEntry, CompletedAction, ExecutionResult, FailedAction, FileSnapshot, SkippedAction,
+    SyncErrorKind, TargetStatus,
 };
-use crate::sync::scanner::ScanResult;
+use crate::sync::lock::SyncLock;
+use crate::sync::metadata::{DeletedFile, FileState, SyncMetadata};
+use crate::sync::scanner::{FileEntry, ScanResult, ScanStats};
+use crate::sync::script::ScriptPlatform;
+use crate::sync::skew::ClockSkew;
+use crate::sync::stats::SyncRun;
+use crate::sync::utils::{path_key, temp_path_for};
+use crate::sync::verify::VerifyReport;
 
 /// Application screens
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +36,8 @@ pub enum Screen {
     Preview,
     Syncing,
     SyncComplete,
+    AnalyzeAll,
+    History,
 }
 
 /// Dialog mode for project list screen
@@ -27,15 +45,91 @@ pub enum Screen {
 pub enum Dialog {
     None,
     NewProject(NewProjectDialog),
+    ProjectFile(ProjectFileDialog),
+    Script(ScriptDialog),
     DeleteConfirm(String),
+    SwapSidesConfirm,
     CreateDirConfirm { path: PathBuf, is_left: bool },
     Error(String),
     SyncConfirm(SyncConfirmDialog),
     CancelSyncConfirm,
+    QuitConfirm { sync_running: bool },
+    ResolveAllConfirm { to_left: bool, count: usize },
     ExclusionsInfo(ExclusionsInfoDialog),
     DiskSpaceWarning(DiskSpaceWarningDialog),
+    ClockSkewWarning(ClockSkew),
+    IncompleteTransfers { left: usize, right: usize },
+    LargePreview(usize),
+    MetadataInspect(Box<MetadataInspectDialog>),
     FileError(FileErrorDialog),
     ProjectSettings(SettingsDialog),
+    TargetStatus(TargetStatusDialog),
+    BackupBrowser(BackupBrowserDialog),
+    VerifyReport(VerifyReportDialog),
+    Help(HelpDialog),
+}
+
+/// Path prompt for exporting a project to a file or importing one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectFileDialog {
+    /// Project being exported; `None` when importing
+    pub export: Option<String>,
+    pub path: String,
+    pub error: Option<String>,
+}
+
+impl ProjectFileDialog {
+    /// Export of `name`, offered next to the working directory
+    pub fn export(name: &str) -> Self {
+        Self {
+            export: Some(name.to_string()),
+            path: format!("{}.rahzom.toml", name),
+            error: None,
+        }
+    }
+
+    pub fn import() -> Self {
+        Self {
+            export: None,
+            path: String::new(),
+            error: None,
+        }
+    }
+}
+
+/// Path prompt for saving the preview as a shell/batch script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptDialog {
+    pub platform: ScriptPlatform,
+    pub path: String,
+    pub error: Option<String>,
+    /// Set once the user was told the file exists; the next Enter replaces it
+    pub overwrite: bool,
+}
+
+impl ScriptDialog {
+    /// Script of `name`'s preview for this platform, offered next to the
+    /// working directory
+    pub fn new(name: &str) -> Self {
+        let platform = ScriptPlatform::current();
+        Self {
+            platform,
+            path: format!("{}-sync.{}", name, platform.extension()),
+            error: None,
+            overwrite: false,
+        }
+    }
+
+    /// Switches to the other platform, and the path to its extension
+    pub fn toggle_platform(&mut self) {
+        let old = format!(".{}", self.platform.extension());
+        self.platform = self.platform.other();
+        if let Some(stem) = self.path.strip_suffix(&old) {
+            self.path = format!("{}.{}", stem, self.platform.extension());
+        }
+        self.error = None;
+        self.overwrite = false;
+    }
 }
 
 /// Disk space warning dialog
@@ -49,6 +143,29 @@ pub struct DiskSpaceWarningDialog {
     pub available: u64,
     /// Required space in bytes
     pub required: u64,
+    /// Free inodes, where the filesystem has a fixed number (Unix)
+    pub available_inodes: Option<u64>,
+    /// Files and directories to be created
+    pub required_inodes: u64,
+    /// First visible line, when the terminal is too short for all of them
+    pub scroll: usize,
+}
+
+impl DiskSpaceWarningDialog {
+    /// Whether the side runs out of inodes, whatever its free bytes
+    pub fn inodes_short(&self) -> bool {
+        self.available_inodes
+            .is_some_and(|available| available < self.required_inodes)
+    }
+
+    /// Lines of text the dialog shows, the key hints included
+    pub fn line_count(&self) -> usize {
+        if self.inodes_short() {
+            11
+        } else {
+            8
+        }
+    }
 }
 
 /// File error dialog (locked file, permission denied)
@@ -64,15 +181,6 @@ pub struct FileErrorDialog {
     pub action: SyncAction,
 }
 
-/// Filter mode for preview
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum PreviewFilter {
-    #[default]
-    All,
-    Changes,
-    Conflicts,
-}
-
 impl PreviewFilter {
     pub fn next(self) -> Self {
         match self {
@@ -91,6 +199,66 @@ impl PreviewFilter {
     }
 }
 
+/// Direction filter for preview, combined with `PreviewFilter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideFilter {
+    #[default]
+    Both,
+    /// Only actions that change the right side
+    ToRight,
+    /// Only actions that change the left side
+    ToLeft,
+}
+
+impl SideFilter {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Both => Self::ToRight,
+            Self::ToRight => Self::ToLeft,
+            Self::ToLeft => Self::Both,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Both => "Both sides",
+            Self::ToRight => "→ Right",
+            Self::ToLeft => "← Left",
+        }
+    }
+
+    /// Checks the action's direction. Skips and conflicts have none,
+    /// so they only pass when both sides are shown.
+    pub fn matches(self, action: &UserAction) -> bool {
+        let to_right = match action {
+            UserAction::Original(SyncAction::CopyToRight { .. })
+            | UserAction::Original(SyncAction::DeleteRight { .. })
+            | UserAction::Original(SyncAction::CreateDirRight { .. })
+            | UserAction::Original(SyncAction::UpdateAttributesRight { .. })
+            | UserAction::CopyToRight { .. }
+            | UserAction::DeleteRight { .. } => Some(true),
+            UserAction::Original(SyncAction::CopyToLeft { .. })
+            | UserAction::Original(SyncAction::DeleteLeft { .. })
+            | UserAction::Original(SyncAction::CreateDirLeft { .. })
+            | UserAction::Original(SyncAction::UpdateAttributesLeft { .. })
+            | UserAction::CopyToLeft { .. }
+            | UserAction::DeleteLeft { .. } => Some(false),
+            UserAction::Original(SyncAction::Skip { .. })
+            | UserAction::Original(SyncAction::Conflict { .. })
+            | UserAction::Original(SyncAction::KeepBoth { .. })
+            | UserAction::Skip { .. }
+            | UserAction::AcceptCurrent { .. }
+            | UserAction::KeepBoth { .. } => None,
+        };
+        match self {
+            Self::Both => true,
+            Self::ToRight => to_right == Some(true),
+            Self::ToLeft => to_right == Some(false),
+        }
+    }
+}
+
 /// Dialog input fields
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DialogField {
@@ -107,16 +275,26 @@ pub struct NewProjectDialog {
     pub right_path: String,
     pub focused_field: DialogField,
     pub error: Option<String>,
+    /// Previously used paths offered for the path fields
+    pub history: PathHistory,
+    /// Highlighted entry of `suggestions()`
+    pub suggestion: Option<usize>,
 }
 
 impl NewProjectDialog {
     pub fn new() -> Self {
+        Self::with_history(PathHistory::default())
+    }
+
+    pub fn with_history(history: PathHistory) -> Self {
         Self {
             name: String::new(),
             left_path: String::new(),
             right_path: String::new(),
             focused_field: DialogField::Name,
             error: None,
+            history,
+            suggestion: None,
         }
     }
 
@@ -128,7 +306,46 @@ impl NewProjectDialog {
         }
     }
 
+    /// Remembered paths extending the focused path field; none for the name
+    pub fn suggestions(&self) -> Vec<&str> {
+        match self.focused_field {
+            DialogField::Name => Vec::new(),
+            DialogField::LeftPath => self.history.suggestions(&self.left_path),
+            DialogField::RightPath => self.history.suggestions(&self.right_path),
+        }
+    }
+
+    /// Highlights the next or previous suggestion, wrapping around
+    pub fn cycle_suggestion(&mut self, forward: bool) {
+        let count = self.suggestions().len();
+        if count == 0 {
+            self.suggestion = None;
+            return;
+        }
+        self.suggestion = Some(match (self.suggestion, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        });
+    }
+
+    /// Fills the focused field with the highlighted suggestion.
+    /// Returns false if nothing is highlighted.
+    pub fn accept_suggestion(&mut self) -> bool {
+        let Some(path) = self
+            .suggestion
+            .and_then(|i| self.suggestions().get(i).map(|s| s.to_string()))
+        else {
+            return false;
+        };
+        *self.focused_value_mut() = path;
+        self.suggestion = None;
+        true
+    }
+
     pub fn next_field(&mut self) {
+        self.suggestion = None;
         self.focused_field = match self.focused_field {
             DialogField::Name => DialogField::LeftPath,
             DialogField::LeftPath => DialogField::RightPath,
@@ -137,6 +354,7 @@ impl NewProjectDialog {
     }
 
     pub fn prev_field(&mut self) {
+        self.suggestion = None;
         self.focused_field = match self.focused_field {
             DialogField::Name => DialogField::RightPath,
             DialogField::LeftPath => DialogField::Name,
@@ -155,9 +373,51 @@ impl Default for NewProjectDialog {
 #[derive(Debug, Clone, PartialEq)]
 pub struct SyncConfirmDialog {
     pub files_to_copy: usize,
+    /// Copies replacing a file that exists at the destination
+    pub files_to_overwrite: usize,
     pub files_to_delete: usize,
     pub bytes_to_transfer: u64,
     pub dirs_to_create: usize,
+    /// Identical files getting only the other side's attributes
+    pub attributes_to_update: usize,
+    pub conflicts_accepted: usize,
+    /// Backup versions kept of overwritten files; 0 means none
+    pub backup_versions: usize,
+    /// Deleted files are moved to the trash instead of removed
+    pub soft_delete: bool,
+    /// Marked items synced and all items, when only the marked are synced
+    pub selection: Option<(usize, usize)>,
+    /// First visible line, when the terminal is too short for all of them
+    pub scroll: usize,
+}
+
+impl SyncConfirmDialog {
+    /// Copies that create a new file
+    pub fn new_files(&self) -> usize {
+        self.files_to_copy - self.files_to_overwrite
+    }
+
+    /// Overwrites and deletes, which discard what's at the destination
+    pub fn destructive(&self) -> usize {
+        self.files_to_overwrite + self.files_to_delete
+    }
+
+    /// Overwrites whose destination content is lost for good, as no backup
+    /// is kept
+    pub fn unbacked_overwrites(&self) -> usize {
+        if self.backup_versions == 0 {
+            self.files_to_overwrite
+        } else {
+            0
+        }
+    }
+
+    /// Lines of text the dialog shows, the key hints included
+    pub fn line_count(&self) -> usize {
+        13 + usize::from(self.conflicts_accepted > 0)
+            + usize::from(self.unbacked_overwrites() > 0)
+            + usize::from(self.selection.is_some())
+    }
 }
 
 /// Exclusions info dialog data
@@ -169,6 +429,58 @@ pub struct ExclusionsInfoDialog {
     pub right_exists: bool,
     pub left_count: usize,
     pub right_count: usize,
+    /// Patterns found in the left file but not the right one
+    pub left_only: usize,
+    /// Patterns found in the right file but not the left one
+    pub right_only: usize,
+    /// Roots of other projects excluded by `exclude_other_projects`
+    pub auto_excluded: Vec<PathBuf>,
+}
+
+/// Target validation result dialog
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetStatusDialog {
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    /// Status of the left side, or error message if the check failed
+    pub left: Result<TargetStatus, String>,
+    /// Status of the right side, or error message if the check failed
+    pub right: Result<TargetStatus, String>,
+}
+
+/// Backup version available for restore
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupVersion {
+    /// Which side the backup belongs to (true = left, false = right)
+    pub is_left: bool,
+    pub entry: BackupEntry,
+}
+
+/// Backup browser dialog for a single file
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupBrowserDialog {
+    /// Relative path of the file whose backups are shown
+    pub path: PathBuf,
+    /// Available versions from both sides, newest first
+    pub versions: Vec<BackupVersion>,
+    pub selected: usize,
+    /// Whether restore of the selected version awaits confirmation
+    pub confirm: bool,
+}
+
+/// Result of comparing both sides by content
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyReportDialog {
+    pub report: VerifyReport,
+    /// First visible issue
+    pub scroll: usize,
+}
+
+/// Keybinding overview opened with `?`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HelpDialog {
+    /// First visible line
+    pub scroll: usize,
 }
 
 /// Settings dialog field selector
@@ -176,8 +488,13 @@ pub struct ExclusionsInfoDialog {
 pub enum SettingsField {
     BackupVersions,
     DeletedRetentionDays,
+    MinAgeDays,
+    MaxAgeDays,
     SoftDelete,
     VerifyHash,
+    SkipEmptyDirs,
+    AppendOnlyRight,
+    QuickSync,
 }
 
 /// Project settings dialog state
@@ -185,10 +502,18 @@ pub enum SettingsField {
 pub struct SettingsDialog {
     pub backup_versions: String,
     pub deleted_retention_days: String,
+    /// Empty when there is no limit
+    pub min_age_days: String,
+    pub max_age_days: String,
     pub soft_delete: bool,
     pub verify_hash: bool,
+    pub skip_empty_dirs: bool,
+    pub append_only_right: bool,
+    pub quick_sync: bool,
     pub focused_field: SettingsField,
     pub error: Option<String>,
+    /// Settings not editable in the dialog, carried through unchanged
+    pub base: ProjectSettings,
 }
 
 impl SettingsDialog {
@@ -196,10 +521,20 @@ impl SettingsDialog {
         Self {
             backup_versions: settings.backup_versions.to_string(),
             deleted_retention_days: settings.deleted_retention_days.to_string(),
+            min_age_days: settings
+                .min_age_days
+                .map_or_else(String::new, |d| d.to_string()),
+            max_age_days: settings
+                .max_age_days
+                .map_or_else(String::new, |d| d.to_string()),
             soft_delete: settings.soft_delete,
             verify_hash: settings.verify_hash,
+            skip_empty_dirs: settings.skip_empty_dirs,
+            append_only_right: settings.append_only_right,
+            quick_sync: settings.quick_sync,
             focused_field: SettingsField::BackupVersions,
             error: None,
+            base: settings.clone(),
         }
     }
 
@@ -220,11 +555,25 @@ impl SettingsDialog {
             return Err("Retention days must be 0-365 (0=off)".to_string());
         }
 
+        let min_age_days = parse_days(&self.min_age_days).map_err(|_| "Invalid min age")?;
+        let max_age_days = parse_days(&self.max_age_days).map_err(|_| "Invalid max age")?;
+        if let (Some(min), Some(max)) = (min_age_days, max_age_days) {
+            if min >= max {
+                return Err("Min age must be below max age".to_string());
+            }
+        }
+
         Ok(ProjectSettings {
             backup_versions,
             deleted_retention_days,
+            min_age_days,
+            max_age_days,
             soft_delete: self.soft_delete,
             verify_hash: self.verify_hash,
+            skip_empty_dirs: self.skip_empty_dirs,
+            append_only_right: self.append_only_right,
+            quick_sync: self.quick_sync,
+            ..self.base.clone()
         })
     }
 
@@ -232,7 +581,13 @@ impl SettingsDialog {
         match self.focused_field {
             SettingsField::BackupVersions => Some(&mut self.backup_versions),
             SettingsField::DeletedRetentionDays => Some(&mut self.deleted_retention_days),
-            SettingsField::SoftDelete | SettingsField::VerifyHash => None,
+            SettingsField::MinAgeDays => Some(&mut self.min_age_days),
+            SettingsField::MaxAgeDays => Some(&mut self.max_age_days),
+            SettingsField::SoftDelete
+            | SettingsField::VerifyHash
+            | SettingsField::SkipEmptyDirs
+            | SettingsField::AppendOnlyRight
+            | SettingsField::QuickSync => None,
         }
     }
 
@@ -240,6 +595,9 @@ impl SettingsDialog {
         match self.focused_field {
             SettingsField::SoftDelete => self.soft_delete = !self.soft_delete,
             SettingsField::VerifyHash => self.verify_hash = !self.verify_hash,
+            SettingsField::SkipEmptyDirs => self.skip_empty_dirs = !self.skip_empty_dirs,
+            SettingsField::AppendOnlyRight => self.append_only_right = !self.append_only_right,
+            SettingsField::QuickSync => self.quick_sync = !self.quick_sync,
             _ => {}
         }
     }
@@ -247,24 +605,43 @@ impl SettingsDialog {
     pub fn next_field(&mut self) {
         self.focused_field = match self.focused_field {
             SettingsField::BackupVersions => SettingsField::DeletedRetentionDays,
-            SettingsField::DeletedRetentionDays => SettingsField::SoftDelete,
+            SettingsField::DeletedRetentionDays => SettingsField::MinAgeDays,
+            SettingsField::MinAgeDays => SettingsField::MaxAgeDays,
+            SettingsField::MaxAgeDays => SettingsField::SoftDelete,
             SettingsField::SoftDelete => SettingsField::VerifyHash,
-            SettingsField::VerifyHash => SettingsField::BackupVersions,
+            SettingsField::VerifyHash => SettingsField::SkipEmptyDirs,
+            SettingsField::SkipEmptyDirs => SettingsField::AppendOnlyRight,
+            SettingsField::AppendOnlyRight => SettingsField::QuickSync,
+            SettingsField::QuickSync => SettingsField::BackupVersions,
         };
     }
 
     pub fn prev_field(&mut self) {
         self.focused_field = match self.focused_field {
-            SettingsField::BackupVersions => SettingsField::VerifyHash,
+            SettingsField::BackupVersions => SettingsField::QuickSync,
             SettingsField::DeletedRetentionDays => SettingsField::BackupVersions,
-            SettingsField::SoftDelete => SettingsField::DeletedRetentionDays,
+            SettingsField::MinAgeDays => SettingsField::DeletedRetentionDays,
+            SettingsField::MaxAgeDays => SettingsField::MinAgeDays,
+            SettingsField::SoftDelete => SettingsField::MaxAgeDays,
             SettingsField::VerifyHash => SettingsField::SoftDelete,
+            SettingsField::SkipEmptyDirs => SettingsField::VerifyHash,
+            SettingsField::AppendOnlyRight => SettingsField::SkipEmptyDirs,
+            SettingsField::QuickSync => SettingsField::AppendOnlyRight,
         };
     }
 }
 
+/// An optional number of days typed into a field; empty is none
+fn parse_days(value: &str) -> Result<Option<u64>, std::num::ParseIntError> {
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        value.parse().map(Some)
+    }
+}
+
 /// Action that user can modify
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UserAction {
     /// Keep the original action from diff
     Original(SyncAction),
@@ -278,6 +655,17 @@ pub enum UserAction {
     DeleteRight { path: PathBuf },
     /// User chose to skip this item
     Skip { path: PathBuf },
+    /// User resolved a conflict outside rahzom; both sides are recorded as
+    /// they are on disk without copying
+    AcceptCurrent { path: PathBuf },
+    /// User resolved a conflict by keeping both files; the right one is
+    /// saved as `copy` on both sides
+    KeepBoth {
+        path: PathBuf,
+        copy: PathBuf,
+        left_size: u64,
+        right_size: u64,
+    },
 }
 
 impl UserAction {
@@ -289,6 +677,8 @@ impl UserAction {
             Self::DeleteLeft { path } => path,
             Self::DeleteRight { path } => path,
             Self::Skip { path } => path,
+            Self::AcceptCurrent { path } => path,
+            Self::KeepBoth { path, .. } => path,
         }
     }
 
@@ -297,7 +687,7 @@ impl UserAction {
     }
 
     /// Converts UserAction to SyncAction for execution.
-    /// Returns None for Skip and Conflict actions.
+    /// Returns None for Skip, Conflict and AcceptCurrent actions.
     pub fn to_sync_action(&self) -> Option<SyncAction> {
         match self {
             UserAction::Original(action) => match action {
@@ -312,13 +702,22 @@ impl UserAction {
                 path: path.clone(),
                 size: *size,
             }),
-            UserAction::DeleteLeft { path } => Some(SyncAction::DeleteLeft {
-                path: path.clone(),
-            }),
-            UserAction::DeleteRight { path } => Some(SyncAction::DeleteRight {
+            UserAction::DeleteLeft { path } => Some(SyncAction::DeleteLeft { path: path.clone() }),
+            UserAction::DeleteRight { path } => {
+                Some(SyncAction::DeleteRight { path: path.clone() })
+            }
+            UserAction::KeepBoth {
+                path,
+                copy,
+                left_size,
+                right_size,
+            } => Some(SyncAction::KeepBoth {
                 path: path.clone(),
+                copy: copy.clone(),
+                left_size: *left_size,
+                right_size: *right_size,
             }),
-            UserAction::Skip { .. } => None,
+            UserAction::Skip { .. } | UserAction::AcceptCurrent { .. } => None,
         }
     }
 }
@@ -333,20 +732,240 @@ pub struct PreviewSummary {
     pub delete_right: usize,
     pub delete_left: usize,
     pub conflicts: usize,
+    /// Conflicts accepted as they are on disk
+    pub accepted: usize,
     pub dirs_to_create: usize,
+    /// Identical files whose attributes are carried over
+    pub attributes_to_update: usize,
     pub skipped: usize,
+    /// Skips whose content is on the other side under another name
+    pub content_elsewhere: usize,
 }
 
+impl PreviewSummary {
+    /// Copies, deletions, directories to create, attribute updates and
+    /// conflicts
+    pub fn pending_changes(&self) -> usize {
+        self.copy_to_right
+            + self.copy_to_left
+            + self.delete_right
+            + self.delete_left
+            + self.dirs_to_create
+            + self.attributes_to_update
+            + self.conflicts
+    }
+
+    /// Bytes to copy in both directions
+    pub fn total_bytes(&self) -> u64 {
+        self.bytes_to_right + self.bytes_to_left
+    }
+}
+
+/// Section of the grouped preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionGroup {
+    CopyToRight,
+    CopyToLeft,
+    Delete,
+    Conflicts,
+    CreateDirs,
+    Attributes,
+    Accepted,
+    KeptBoth,
+    Skipped,
+}
+
+impl ActionGroup {
+    /// Section order of the grouped preview
+    pub const ALL: [Self; 9] = [
+        Self::CopyToRight,
+        Self::CopyToLeft,
+        Self::Delete,
+        Self::Conflicts,
+        Self::CreateDirs,
+        Self::Attributes,
+        Self::Accepted,
+        Self::KeptBoth,
+        Self::Skipped,
+    ];
+
+    pub fn of(action: &UserAction) -> Self {
+        match action {
+            UserAction::Original(SyncAction::CopyToRight { .. })
+            | UserAction::CopyToRight { .. } => Self::CopyToRight,
+            UserAction::Original(SyncAction::CopyToLeft { .. }) | UserAction::CopyToLeft { .. } => {
+                Self::CopyToLeft
+            }
+            UserAction::Original(SyncAction::DeleteRight { .. })
+            | UserAction::Original(SyncAction::DeleteLeft { .. })
+            | UserAction::DeleteRight { .. }
+            | UserAction::DeleteLeft { .. } => Self::Delete,
+            UserAction::Original(SyncAction::Conflict { .. }) => Self::Conflicts,
+            UserAction::Original(SyncAction::CreateDirRight { .. })
+            | UserAction::Original(SyncAction::CreateDirLeft { .. }) => Self::CreateDirs,
+            UserAction::Original(SyncAction::UpdateAttributesRight { .. })
+            | UserAction::Original(SyncAction::UpdateAttributesLeft { .. }) => Self::Attributes,
+            UserAction::AcceptCurrent { .. } => Self::Accepted,
+            UserAction::Original(SyncAction::KeepBoth { .. }) | UserAction::KeepBoth { .. } => {
+                Self::KeptBoth
+            }
+            UserAction::Original(SyncAction::Skip { .. }) | UserAction::Skip { .. } => {
+                Self::Skipped
+            }
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CopyToRight => "Copy →",
+            Self::CopyToLeft => "Copy ←",
+            Self::Delete => "Delete",
+            Self::Conflicts => "Conflicts",
+            Self::CreateDirs => "Create dirs",
+            Self::Attributes => "Attributes",
+            Self::Accepted => "Accepted",
+            Self::KeptBoth => "Keep both",
+            Self::Skipped => "Skipped",
+        }
+    }
+}
+
+/// One line of the preview's action list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewRow {
+    /// Section header with the number of filtered actions in it
+    Header { group: ActionGroup, count: usize },
+    /// Index into `PreviewState::actions`
+    Action(usize),
+}
+
+/// What the cached rows of a preview were built from
+#[derive(Debug, Clone, PartialEq)]
+struct RowsKey {
+    revision: u64,
+    filter: PreviewFilter,
+    show_skips: bool,
+    side_filter: SideFilter,
+    grouped: bool,
+    collapsed: HashSet<ActionGroup>,
+}
+
+#[derive(Debug)]
+struct RowsCache {
+    key: RowsKey,
+    rows: Rc<[PreviewRow]>,
+    /// Number of filtered actions
+    shown: usize,
+}
+
+/// One side of the metadata inspector
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataSide {
+    /// The item as scanned, `None` if it is missing
+    pub current: Option<FileStamp>,
+    /// The file as recorded at the last sync
+    pub recorded: Option<FileState>,
+    /// Tombstone of the file, if its deletion was recorded
+    pub deleted: Option<DeletedFile>,
+}
+
+/// Read-only view of what the sync metadata holds for one path
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataInspectDialog {
+    pub path: PathBuf,
+    pub left: MetadataSide,
+    pub right: MetadataSide,
+    /// Last successful sync, as recorded on the left
+    pub last_sync: Option<DateTime<Utc>>,
+}
+
+/// Size and mtime of one side of an action, for the detail panel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// `None` if the filesystem reported no mtime
+    pub mtime: Option<DateTime<Utc>>,
+    pub is_dir: bool,
+}
+
+impl From<&FileEntry> for FileStamp {
+    fn from(entry: &FileEntry) -> Self {
+        Self {
+            size: entry.size,
+            mtime: entry.mtime_known.then_some(entry.mtime),
+            is_dir: entry.is_dir,
+        }
+    }
+}
+
+impl From<&FileInfo> for FileStamp {
+    fn from(info: &FileInfo) -> Self {
+        Self {
+            size: info.size,
+            mtime: Some(info.mtime),
+            is_dir: false,
+        }
+    }
+}
+
+/// Stamps of every scanned path of a preview, by side
+pub struct StampIndex<'a> {
+    left: HashMap<&'a Path, FileStamp>,
+    right: HashMap<&'a Path, FileStamp>,
+}
+
+impl StampIndex<'_> {
+    /// Left and right side of `path` as scanned
+    pub fn get(&self, path: &Path) -> (Option<FileStamp>, Option<FileStamp>) {
+        (self.left.get(path).copied(), self.right.get(path).copied())
+    }
+}
+
+/// Above this many actions, analysis asks before opening the action list
+pub const LARGE_PREVIEW_ACTIONS: usize = 100_000;
+
 /// Preview state
 #[derive(Debug, Default)]
 pub struct PreviewState {
+    /// Change through [`set_action`](Self::set_action), which keeps the
+    /// cached rows current
     pub actions: Vec<UserAction>,
+    /// Bumped on every change of `actions`
+    revision: u64,
+    /// Rows for the current filters, rebuilt when they or the actions change
+    rows_cache: RefCell<Option<RowsCache>>,
     pub filter: PreviewFilter,
+    /// Whether skipped/identical items are listed (independent of `filter`)
+    pub show_skips: bool,
+    pub side_filter: SideFilter,
+    /// Sections by action type instead of a flat list
+    pub grouped: bool,
+    /// Sections whose actions are folded away
+    pub collapsed: HashSet<ActionGroup>,
+    /// Whether the size/mtime panel of the selected action is shown
+    pub show_details: bool,
+    /// Index into `rows()`
     pub selected: usize,
     pub scroll_offset: usize,
+    /// Rows the list showed when last drawn, the step of PageUp/PageDown
+    pub page_rows: Cell<usize>,
     pub selected_items: HashSet<usize>,
+    /// Action the cursor was on when a range was started; see
+    /// [`range_indices`](Self::range_indices)
+    pub range_anchor: Option<usize>,
     pub left_scan: Option<ScanResult>,
     pub right_scan: Option<ScanResult>,
+    /// Scan totals, computed once when the preview is built
+    pub left_stats: ScanStats,
+    pub right_stats: ScanStats,
+    /// Sync metadata the diff was made against
+    pub left_meta: SyncMetadata,
+    pub right_meta: SyncMetadata,
+    /// Why the diff chose each action, by path
+    pub explanations: HashMap<PathBuf, String>,
+    /// Whether only left changes were looked for (quick sync), making
+    /// this a one-way sync
+    pub one_way: bool,
 }
 
 impl PreviewState {
@@ -357,12 +976,27 @@ impl PreviewState {
                 .into_iter()
                 .map(UserAction::Original)
                 .collect(),
+            revision: 0,
+            rows_cache: RefCell::new(None),
             filter: PreviewFilter::All,
+            show_skips: true,
+            side_filter: SideFilter::Both,
+            grouped: false,
+            collapsed: HashSet::new(),
+            show_details: false,
             selected: 0,
             scroll_offset: 0,
+            page_rows: Cell::new(0),
             selected_items: HashSet::new(),
+            range_anchor: None,
+            left_stats: left_scan.stats(),
+            right_stats: right_scan.stats(),
             left_scan: Some(left_scan),
             right_scan: Some(right_scan),
+            left_meta: SyncMetadata::default(),
+            right_meta: SyncMetadata::default(),
+            explanations: diff_result.explanations,
+            one_way: false,
         }
     }
 
@@ -370,18 +1004,377 @@ impl PreviewState {
         self.actions
             .iter()
             .enumerate()
+            .filter(|(_, action)| self.show_skips || !is_skip_action(action))
             .filter(|(_, action)| match self.filter {
                 PreviewFilter::All => true,
                 PreviewFilter::Changes => !is_skip_action(action),
                 PreviewFilter::Conflicts => is_conflict_action(action),
             })
+            .filter(|(_, action)| self.side_filter.matches(action))
             .map(|(i, _)| i)
             .collect()
     }
 
+    /// Partial copies of an interrupted sync found on the left and right
+    /// that belong to a path of the diff, see [`Self::removable_partials`]
+    pub fn partial_counts(&self) -> (usize, usize) {
+        let (left, right) = self.removable_partials();
+        (left.len(), right.len())
+    }
+
+    /// Partials on the left and right that are the temp file of a path in
+    /// the actions, so surely written by a sync. Other files with the temp
+    /// suffix may be the user's and are never offered for removal.
+    pub fn removable_partials(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let temp_names: HashSet<PathBuf> = self
+            .actions
+            .iter()
+            .map(|action| temp_path_for(action.path()))
+            .collect();
+        let removable = |scan: &Option<ScanResult>| {
+            scan.iter()
+                .flat_map(|s| &s.partials)
+                .filter(|partial| temp_names.contains(*partial))
+                .cloned()
+                .collect()
+        };
+        (removable(&self.left_scan), removable(&self.right_scan))
+    }
+
+    /// Replaces the action at `idx`
+    pub fn set_action(&mut self, idx: usize, action: UserAction) {
+        self.actions[idx] = action;
+        self.revision += 1;
+    }
+
+    /// Lines of the action list: the filtered actions, under a header per
+    /// non-empty section when grouped. Cached until the actions or the
+    /// filters change, as the list asks for them on every frame.
+    pub fn rows(&self) -> Rc<[PreviewRow]> {
+        Rc::clone(&self.cached_rows().rows)
+    }
+
+    /// Number of actions the filters let through
+    pub fn shown_count(&self) -> usize {
+        self.cached_rows().shown
+    }
+
+    fn cached_rows(&self) -> Ref<'_, RowsCache> {
+        let key = RowsKey {
+            revision: self.revision,
+            filter: self.filter,
+            show_skips: self.show_skips,
+            side_filter: self.side_filter,
+            grouped: self.grouped,
+            collapsed: self.collapsed.clone(),
+        };
+        if !matches!(&*self.rows_cache.borrow(), Some(cache) if cache.key == key) {
+            let indices = self.filtered_indices();
+            let shown = indices.len();
+            let rows = self.build_rows(indices).into();
+            *self.rows_cache.borrow_mut() = Some(RowsCache { key, rows, shown });
+        }
+        Ref::map(self.rows_cache.borrow(), |cache| {
+            cache.as_ref().expect("rows were just cached")
+        })
+    }
+
+    fn build_rows(&self, indices: Vec<usize>) -> Vec<PreviewRow> {
+        if !self.grouped {
+            return indices.into_iter().map(PreviewRow::Action).collect();
+        }
+
+        let mut rows = Vec::new();
+        for group in ActionGroup::ALL {
+            let members: Vec<usize> = indices
+                .iter()
+                .copied()
+                .filter(|&i| ActionGroup::of(&self.actions[i]) == group)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            rows.push(PreviewRow::Header {
+                group,
+                count: members.len(),
+            });
+            if !self.collapsed.contains(&group) {
+                rows.extend(members.into_iter().map(PreviewRow::Action));
+            }
+        }
+        rows
+    }
+
+    /// Index into `actions` of the selected row; `None` on a section header
+    pub fn selected_action(&self) -> Option<usize> {
+        match self.rows().get(self.selected) {
+            Some(&PreviewRow::Action(index)) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Actions from the range anchor to the selected row, both included, in
+    /// the order listed (the `filtered_indices`, by section when grouped).
+    /// `None` without a range, or when the filters hide the anchor.
+    pub fn range_indices(&self) -> Option<Vec<usize>> {
+        let anchor = self.range_anchor?;
+        let rows = self.rows();
+        let start = rows
+            .iter()
+            .position(|&row| row == PreviewRow::Action(anchor))?;
+        let end = self.selected.min(rows.len() - 1);
+        Some(
+            rows[start.min(end)..=start.max(end)]
+                .iter()
+                .filter_map(|row| match *row {
+                    PreviewRow::Action(i) => Some(i),
+                    PreviewRow::Header { .. } => None,
+                })
+                .collect(),
+        )
+    }
+
+    /// Actions a direction or skip applies to: the range if one is being
+    /// selected, which ends it, or else the selected action
+    pub fn take_targets(&mut self) -> Vec<usize> {
+        let range = self.range_indices();
+        self.range_anchor = None;
+        range.unwrap_or_else(|| self.selected_action().into_iter().collect())
+    }
+
+    /// Rows of the unresolved conflicts shown
+    fn conflict_rows(&self) -> Vec<usize> {
+        self.rows()
+            .iter()
+            .enumerate()
+            .filter_map(|(row, entry)| match *entry {
+                PreviewRow::Action(i) if is_conflict_action(&self.actions[i]) => Some(row),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Moves the selection to the next (or previous) shown conflict,
+    /// wrapping around. Returns false if no conflict is shown.
+    pub fn select_conflict(&mut self, forward: bool) -> bool {
+        let rows = self.conflict_rows();
+        let next = if forward {
+            rows.iter()
+                .find(|&&row| row > self.selected)
+                .or(rows.first())
+        } else {
+            rows.iter()
+                .rev()
+                .find(|&&row| row < self.selected)
+                .or(rows.last())
+        };
+        match next {
+            Some(&row) => {
+                self.selected = row;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Which shown conflict is selected (1-based) and how many are shown;
+    /// `None` unless a conflict is selected
+    pub fn conflict_position(&self) -> Option<(usize, usize)> {
+        let selected = self.selected_action()?;
+        if !is_conflict_action(&self.actions[selected]) {
+            return None;
+        }
+        let rows = self.conflict_rows();
+        let position = rows.iter().position(|&row| row == self.selected)?;
+        Some((position + 1, rows.len()))
+    }
+
+    /// Selects the action at `index` again after the filters changed, or the
+    /// nearest one still shown if it is filtered out now
+    pub fn reselect(&mut self, index: Option<usize>) {
+        let rows = self.rows();
+        self.selected = index
+            .and_then(|index| {
+                rows.iter()
+                    .enumerate()
+                    .filter_map(|(row, entry)| match entry {
+                        PreviewRow::Action(i) => Some((row, i.abs_diff(index))),
+                        PreviewRow::Header { .. } => None,
+                    })
+                    .min_by_key(|&(_, distance)| distance)
+            })
+            .map_or(0, |(row, _)| row);
+        self.scroll_offset = 0;
+    }
+
+    /// Left and right side of the selected action. Conflicts carry their own
+    /// snapshot of both sides; other actions are looked up in the scans.
+    pub fn selected_details(&self) -> Option<(Option<FileStamp>, Option<FileStamp>)> {
+        let action = &self.actions[self.selected_action()?];
+        if let UserAction::Original(SyncAction::Conflict { left, right, .. }) = action {
+            if left.is_some() || right.is_some() {
+                return Some((
+                    left.as_ref().map(FileStamp::from),
+                    right.as_ref().map(FileStamp::from),
+                ));
+            }
+        }
+        Some(self.stamps(action.path()))
+    }
+
+    /// Why the selected action was chosen, including when it was the user
+    pub fn selected_explanation(&self) -> Option<String> {
+        let action = &self.actions[self.selected_action()?];
+        let why = self.explanations.get(action.path());
+        Some(match (action.is_modified(), why) {
+            (true, Some(why)) => format!("Changed by you (the diff's reason: {})", why),
+            (true, None) => "Changed by you".to_string(),
+            (false, Some(why)) => why.clone(),
+            (false, None) => return None,
+        })
+    }
+
+    /// Left and right side of `path` as scanned
+    pub fn stamps(&self, path: &Path) -> (Option<FileStamp>, Option<FileStamp>) {
+        let stamp = |scan: &Option<ScanResult>| {
+            scan.as_ref()?
+                .entries
+                .iter()
+                .find(|e| e.path == path)
+                .map(FileStamp::from)
+        };
+        (stamp(&self.left_scan), stamp(&self.right_scan))
+    }
+
+    /// Both scans indexed by path, for looking up the stamps of many paths
+    pub fn stamp_index(&self) -> StampIndex<'_> {
+        fn index(scan: &Option<ScanResult>) -> HashMap<&Path, FileStamp> {
+            scan.iter()
+                .flat_map(|s| &s.entries)
+                .map(|e| (e.path.as_path(), FileStamp::from(e)))
+                .collect()
+        }
+        StampIndex {
+            left: index(&self.left_scan),
+            right: index(&self.right_scan),
+        }
+    }
+
+    /// The sync metadata recorded for the selected item, next to its state
+    /// as scanned
+    pub fn inspect_selected(&self) -> Option<MetadataInspectDialog> {
+        let path = self.actions[self.selected_action()?].path().clone();
+        let (left, right) = self.stamps(&path);
+        let meta_path = path_key(&path);
+        let side = |current, meta: &SyncMetadata| MetadataSide {
+            current,
+            recorded: meta.find_file(&meta_path).cloned(),
+            deleted: meta.find_deleted(&meta_path).cloned(),
+        };
+        Some(MetadataInspectDialog {
+            left: side(left, &self.left_meta),
+            right: side(right, &self.right_meta),
+            last_sync: self.left_meta.last_sync,
+            path,
+        })
+    }
+
+    /// Folds or unfolds the section whose header is selected
+    pub fn toggle_selected_group(&mut self) {
+        if let Some(&PreviewRow::Header { group, .. }) = self.rows().get(self.selected) {
+            if !self.collapsed.remove(&group) {
+                self.collapsed.insert(group);
+            }
+        }
+    }
+
+    /// Points the action at `idx` to the left or right: the other side's file
+    /// is copied over, or the file is deleted if the other side has none
+    pub fn direct_action(&mut self, idx: usize, to_left: bool) {
+        let Some(action) = self.actions.get(idx) else {
+            return;
+        };
+        let path = action.path().clone();
+        let action = if to_left {
+            match self.get_file_size_from_right(&path) {
+                Some(size) => UserAction::CopyToLeft { path, size },
+                None => UserAction::DeleteLeft { path },
+            }
+        } else {
+            match self.get_file_size_from_left(&path) {
+                Some(size) => UserAction::CopyToRight { path, size },
+                None => UserAction::DeleteRight { path },
+            }
+        };
+        self.set_action(idx, action);
+    }
+
+    /// Indices of conflicts the user hasn't resolved yet
+    pub fn unresolved_conflicts(&self) -> Vec<usize> {
+        self.actions
+            .iter()
+            .enumerate()
+            .filter(|(_, action)| {
+                matches!(action, UserAction::Original(SyncAction::Conflict { .. }))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
     pub fn summary(&self) -> PreviewSummary {
+        Self::summarize(&self.actions)
+    }
+
+    /// Summary of only the marked items
+    pub fn selected_summary(&self) -> PreviewSummary {
+        Self::summarize(
+            self.actions
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| self.selected_items.contains(idx))
+                .map(|(_, action)| action),
+        )
+    }
+
+    /// Whether the sync is limited to the marked items, i.e. some are marked
+    pub fn syncs_selection(&self) -> bool {
+        !self.selected_items.is_empty()
+    }
+
+    /// Paths on the left and right whose file a sync keeps: all but those
+    /// with a delete on that side the sync runs. A directory needed below one
+    /// must not replace that file.
+    pub fn kept_paths(&self) -> (HashSet<PathBuf>, HashSet<PathBuf>) {
+        let (mut left, mut right) = (HashSet::new(), HashSet::new());
+        for (idx, action) in self.actions.iter().enumerate() {
+            let in_scope = !self.syncs_selection() || self.selected_items.contains(&idx);
+            let (keeps_left, keeps_right) = match action.to_sync_action().filter(|_| in_scope) {
+                Some(SyncAction::DeleteLeft { .. }) => (false, true),
+                Some(SyncAction::DeleteRight { .. }) => (true, false),
+                _ => (true, true),
+            };
+            if keeps_left {
+                left.insert(action.path().clone());
+            }
+            if keeps_right {
+                right.insert(action.path().clone());
+            }
+        }
+        (left, right)
+    }
+
+    /// Actions a sync would run: the marked ones if any are, else all
+    pub fn sync_scope(&self) -> impl Iterator<Item = &UserAction> {
+        self.actions
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !self.syncs_selection() || self.selected_items.contains(idx))
+            .map(|(_, action)| action)
+    }
+
+    fn summarize<'a>(actions: impl IntoIterator<Item = &'a UserAction>) -> PreviewSummary {
         let mut summary = PreviewSummary::default();
-        for action in &self.actions {
+        for action in actions {
             match action {
                 UserAction::Original(SyncAction::CopyToRight { size, .. })
                 | UserAction::CopyToRight { size, .. } => {
@@ -404,13 +1397,43 @@ impl PreviewState {
                 UserAction::Original(SyncAction::Conflict { .. }) => {
                     summary.conflicts += 1;
                 }
+                UserAction::AcceptCurrent { .. } => {
+                    summary.accepted += 1;
+                }
+                // The right file is copied on both sides, the left one
+                // replaces it on the right
+                UserAction::Original(SyncAction::KeepBoth {
+                    left_size,
+                    right_size,
+                    ..
+                })
+                | UserAction::KeepBoth {
+                    left_size,
+                    right_size,
+                    ..
+                } => {
+                    summary.copy_to_left += 1;
+                    summary.bytes_to_left += right_size;
+                    summary.copy_to_right += 1;
+                    summary.bytes_to_right += left_size + right_size;
+                }
                 UserAction::Original(SyncAction::CreateDirRight { .. }) => {
                     summary.dirs_to_create += 1;
                 }
                 UserAction::Original(SyncAction::CreateDirLeft { .. }) => {
                     summary.dirs_to_create += 1;
                 }
-                UserAction::Skip { .. } | UserAction::Original(SyncAction::Skip { .. }) => {
+                UserAction::Original(SyncAction::UpdateAttributesRight { .. })
+                | UserAction::Original(SyncAction::UpdateAttributesLeft { .. }) => {
+                    summary.attributes_to_update += 1;
+                }
+                UserAction::Original(SyncAction::Skip { reason, .. }) => {
+                    summary.skipped += 1;
+                    if reason.starts_with(CONTENT_ELSEWHERE) {
+                        summary.content_elsewhere += 1;
+                    }
+                }
+                UserAction::Skip { .. } => {
                     summary.skipped += 1;
                 }
             }
@@ -418,6 +1441,32 @@ impl PreviewState {
         summary
     }
 
+    /// Copies in the sync whose destination file exists in the scan, i.e.
+    /// overwrites
+    pub fn overwrite_count(&self) -> usize {
+        let files = |scan: &Option<ScanResult>| -> HashSet<PathBuf> {
+            scan.iter()
+                .flat_map(|s| &s.entries)
+                .filter(|e| !e.is_dir)
+                .map(|e| e.path.clone())
+                .collect()
+        };
+        let left = files(&self.left_scan);
+        let right = files(&self.right_scan);
+        self.sync_scope()
+            .filter(|action| match action {
+                UserAction::Original(SyncAction::CopyToRight { path, .. })
+                | UserAction::CopyToRight { path, .. } => right.contains(path),
+                UserAction::Original(SyncAction::CopyToLeft { path, .. })
+                | UserAction::CopyToLeft { path, .. } => left.contains(path),
+                UserAction::Original(SyncAction::KeepBoth { .. }) | UserAction::KeepBoth { .. } => {
+                    true
+                }
+                _ => false,
+            })
+            .count()
+    }
+
     /// Get file size from left scan by path
     pub fn get_file_size_from_left(&self, path: &Path) -> Option<u64> {
         self.left_scan
@@ -439,6 +1488,61 @@ impl PreviewState {
     }
 }
 
+/// Where one project stands on the analyze-all screen
+#[derive(Debug)]
+pub enum ProjectStatus {
+    /// Not analyzed yet
+    Pending,
+    Analyzed(PreviewSummary),
+    /// Missing paths, an unreadable project file or a failed scan
+    Error(String),
+}
+
+/// Overview of every project's pending changes, analyzed one per frame
+#[derive(Debug)]
+pub struct AnalyzeAllState {
+    /// Project names with their status, in project list order
+    pub entries: Vec<(String, ProjectStatus)>,
+    /// Index of the next project to analyze
+    pub next: usize,
+    pub selected: usize,
+}
+
+impl AnalyzeAllState {
+    pub fn new(names: &[String]) -> Self {
+        Self {
+            entries: names
+                .iter()
+                .map(|name| (name.clone(), ProjectStatus::Pending))
+                .collect(),
+            next: 0,
+            selected: 0,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.entries.len()
+    }
+
+    /// Bytes to copy across all analyzed projects
+    pub fn total_bytes(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|(_, status)| match status {
+                ProjectStatus::Analyzed(summary) => summary.total_bytes(),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Past syncs of the current project, newest first
+#[derive(Debug, Clone, Default)]
+pub struct HistoryState {
+    pub runs: Vec<SyncRun>,
+    pub scroll: usize,
+}
+
 /// State during sync execution
 #[derive(Debug)]
 pub struct SyncingState {
@@ -451,21 +1555,74 @@ pub struct SyncingState {
     pub cancel_requested: bool,
     pub current_index: usize,
     pub actions: Vec<SyncAction>,
+    /// Conflicts recorded into metadata as-is once the sync finishes
+    pub accepted: Vec<PathBuf>,
     pub snapshots: HashMap<PathBuf, FileSnapshot>,
+    /// Hardlink groups per side, empty unless `preserve_hardlinks` is set
+    pub left_hardlinks: HashMap<PathBuf, Vec<PathBuf>>,
+    pub right_hardlinks: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Paths per side whose file the sync keeps (see `PreviewState::kept_paths`)
+    pub kept_left: HashSet<PathBuf>,
+    pub kept_right: HashSet<PathBuf>,
     pub result: ExecutionResult,
+    /// Bytes per second, one sample per second, oldest first
+    pub throughput: VecDeque<u64>,
+    /// When the last throughput sample was taken
+    pub last_sample_time: Instant,
+    /// `transferred_bytes` at the last throughput sample
+    pub last_sample_bytes: u64,
+    /// Sync locks of both sides, released when the sync finishes
+    pub locks: Vec<SyncLock>,
+    /// Trash session the sync's soft deletes go to on both sides
+    pub trash_session: String,
+    /// The preview was one-way, so the overwrite policy applies
+    pub one_way: bool,
+    /// Totals of the unfinished sync this one continues, included in the
+    /// counts above
+    pub earlier: SyncJournal,
 }
 
+/// Number of throughput samples kept (one per second)
+pub const THROUGHPUT_SAMPLES: usize = 120;
+
 impl SyncingState {
     pub fn elapsed(&self) -> Duration {
         self.start_time.elapsed()
     }
 
+    /// Records one throughput sample per whole second elapsed since the last
+    /// one. A long gap (e.g. one large file blocking the loop) is filled with
+    /// its average rate so the graph keeps a consistent time axis.
+    pub fn sample_throughput(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_sample_time);
+        let seconds = elapsed.as_secs();
+        if seconds == 0 {
+            return;
+        }
+
+        let delta = self
+            .transferred_bytes
+            .saturating_sub(self.last_sample_bytes);
+        let rate = (delta as f64 / elapsed.as_secs_f64()) as u64;
+        for _ in 0..seconds.min(THROUGHPUT_SAMPLES as u64) {
+            if self.throughput.len() == THROUGHPUT_SAMPLES {
+                self.throughput.pop_front();
+            }
+            self.throughput.push_back(rate);
+        }
+
+        self.last_sample_time = now;
+        self.last_sample_bytes = self.transferred_bytes;
+    }
+
     pub fn estimated_remaining(&self) -> Option<Duration> {
-        if self.completed_actions == 0 {
+        // Only this run's pace counts
+        let done = self.completed_actions - self.earlier.actions();
+        if done == 0 {
             return None;
         }
         let elapsed = self.elapsed();
-        let rate = self.completed_actions as f64 / elapsed.as_secs_f64();
+        let rate = done as f64 / elapsed.as_secs_f64();
         if rate <= 0.0 {
             return None;
         }
@@ -474,16 +1631,104 @@ impl SyncingState {
     }
 }
 
+/// Action list browsed on the sync complete screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompleteList {
+    Completed,
+    Failed,
+    Skipped,
+}
+
+impl CompleteList {
+    pub const ALL: [Self; 3] = [Self::Completed, Self::Failed, Self::Skipped];
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Completed => Self::Failed,
+            Self::Failed => Self::Skipped,
+            Self::Skipped => Self::Completed,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Completed => "Completed",
+            Self::Failed => "Failed",
+            Self::Skipped => "Skipped",
+        }
+    }
+}
+
 /// State after sync completion
 #[derive(Debug)]
 pub struct SyncCompleteState {
+    /// Largest transfer first
     pub completed: Vec<CompletedAction>,
     pub failed: Vec<FailedAction>,
     pub skipped: Vec<SkippedAction>,
     pub duration: Duration,
     pub bytes_transferred: u64,
-    pub scroll_offset: usize,
+    /// List shown; failures first if there are any
+    pub list: CompleteList,
+    /// Scroll position of each list, by `CompleteList` order
+    pub scroll_offsets: [usize; 3],
     pub changed_during_sync: Vec<PathBuf>,
+    /// Locked files skipped automatically, to retry later
+    pub in_use: Vec<PathBuf>,
+    /// Totals of an unfinished earlier run this sync continued; the lists
+    /// above are this run's, `bytes_transferred` covers both
+    pub earlier: SyncJournal,
+}
+
+impl SyncCompleteState {
+    pub fn new(
+        mut completed: Vec<CompletedAction>,
+        failed: Vec<FailedAction>,
+        skipped: Vec<SkippedAction>,
+        duration: Duration,
+        bytes_transferred: u64,
+    ) -> Self {
+        completed.sort_by_key(|c| std::cmp::Reverse(c.bytes_transferred));
+        Self {
+            list: if failed.is_empty() {
+                CompleteList::Completed
+            } else {
+                CompleteList::Failed
+            },
+            completed,
+            failed,
+            skipped,
+            duration,
+            bytes_transferred,
+            scroll_offsets: [0; 3],
+            changed_during_sync: Vec::new(),
+            in_use: Vec::new(),
+            earlier: SyncJournal::default(),
+        }
+    }
+
+    pub fn len_of(&self, list: CompleteList) -> usize {
+        match list {
+            CompleteList::Completed => self.completed.len(),
+            CompleteList::Failed => self.failed.len(),
+            CompleteList::Skipped => self.skipped.len(),
+        }
+    }
+
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offsets[self.list as usize]
+    }
+
+    /// Scrolls the shown list by one line, keeping its last line in reach
+    pub fn scroll(&mut self, down: bool) {
+        let last = self.len_of(self.list).saturating_sub(1);
+        let offset = &mut self.scroll_offsets[self.list as usize];
+        *offset = if down {
+            (*offset + 1).min(last)
+        } else {
+            offset.saturating_sub(1)
+        };
+    }
 }
 
 // Helper functions for action filtering
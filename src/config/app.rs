@@ -0,0 +1,127 @@
+//! Application-wide settings shared by all projects
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Settings file name inside the config directory (~/.rahzom/)
+pub const APP_CONFIG_FILE: &str = "config.toml";
+
+/// Application-wide settings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppConfig {
+    /// Ask before quitting when a sync ran this session and its result was not reviewed
+    #[serde(default = "default_confirm_on_quit")]
+    pub confirm_on_quit: bool,
+    /// Event poll interval outside of sync. `None` blocks until input arrives,
+    /// waking only when something on screen needs to change (e.g. a toast expiring).
+    #[serde(default)]
+    pub idle_poll_ms: Option<u64>,
+    /// Event poll interval while syncing; one action runs per poll
+    #[serde(default = "default_sync_poll_ms")]
+    pub sync_poll_ms: u64,
+    /// Directories no project may use as a side, on top of the built-in
+    /// system directories and the home directory
+    #[serde(default)]
+    pub blocked_paths: Vec<PathBuf>,
+    /// Also block directories containing a blocked one, e.g. `/home`
+    #[serde(default = "default_block_ancestors")]
+    pub block_ancestors: bool,
+    /// What becomes of the project opened last run on startup
+    #[serde(default)]
+    pub resume: ResumeMode,
+}
+
+/// Startup handling of the project opened last run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResumeMode {
+    /// Start at the top of the project list and remember nothing
+    Off,
+    /// Select it in the project list
+    #[default]
+    Select,
+    /// Open its project view
+    Open,
+}
+
+fn default_confirm_on_quit() -> bool {
+    true
+}
+
+fn default_sync_poll_ms() -> u64 {
+    10
+}
+
+fn default_block_ancestors() -> bool {
+    true
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            confirm_on_quit: default_confirm_on_quit(),
+            idle_poll_ms: None,
+            sync_poll_ms: default_sync_poll_ms(),
+            blocked_paths: Vec::new(),
+            block_ancestors: default_block_ancestors(),
+            resume: ResumeMode::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Loads `config.toml` from the config directory.
+    /// Returns default settings if the file doesn't exist.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = config_dir.join(APP_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file: {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file_uses_default() {
+        let temp = TempDir::new().unwrap();
+        let config = AppConfig::load(temp.path()).unwrap();
+        assert!(config.confirm_on_quit);
+        assert_eq!(config.idle_poll_ms, None);
+        assert_eq!(config.sync_poll_ms, 10);
+        assert!(config.blocked_paths.is_empty());
+        assert!(config.block_ancestors);
+        assert_eq!(config.resume, ResumeMode::Select);
+    }
+
+    #[test]
+    fn test_load_config() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join(APP_CONFIG_FILE),
+            "confirm_on_quit = false\n",
+        )
+        .unwrap();
+        assert!(!AppConfig::load(temp.path()).unwrap().confirm_on_quit);
+
+        fs::write(temp.path().join(APP_CONFIG_FILE), "resume = \"open\"\n").unwrap();
+        assert_eq!(
+            AppConfig::load(temp.path()).unwrap().resume,
+            ResumeMode::Open
+        );
+
+        fs::write(temp.path().join(APP_CONFIG_FILE), "unknown = 1\n").unwrap();
+        assert!(AppConfig::load(temp.path()).is_err());
+    }
+}
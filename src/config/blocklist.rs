@@ -0,0 +1,203 @@
+//! Roots a project must never use: filesystem roots, system directories and
+//! the home directory itself. A typo there would turn a mirror into a wipe.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Result};
+
+use crate::config::app::AppConfig;
+use crate::config::expand::expand_path;
+
+/// System directories on Unix-like systems
+#[cfg(not(windows))]
+const UNIX_SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib64",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/sys",
+    "/usr",
+    "/var",
+    "/Applications",
+    "/Library",
+    "/System",
+];
+
+/// Environment variables naming system directories on Windows
+#[cfg(windows)]
+const WINDOWS_SYSTEM_VARS: &[&str] = &[
+    "WINDIR",
+    "SystemRoot",
+    "ProgramFiles",
+    "ProgramFiles(x86)",
+    "ProgramData",
+];
+
+/// A directory whose loss would be a disaster
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Critical {
+    path: PathBuf,
+    /// What the directory is, e.g. "a system directory"
+    kind: &'static str,
+}
+
+/// Fails with an explanation if `path` must not be a sync root
+pub fn check_root(path: &Path, config: &AppConfig) -> Result<()> {
+    if let Some(reason) = blocked_reason(path, &critical_dirs(config), config.block_ancestors) {
+        bail!(
+            "Refusing to sync {}: it is {}. Syncing it could overwrite or delete \
+             files the system or other programs rely on.",
+            path.display(),
+            reason
+        );
+    }
+    Ok(())
+}
+
+/// Built-in system directories, the home directory and `blocked_paths`
+fn critical_dirs(config: &AppConfig) -> Vec<Critical> {
+    let mut dirs = Vec::new();
+
+    #[cfg(not(windows))]
+    dirs.extend(UNIX_SYSTEM_DIRS.iter().map(|dir| Critical {
+        path: PathBuf::from(dir),
+        kind: "a system directory",
+    }));
+    #[cfg(windows)]
+    dirs.extend(
+        WINDOWS_SYSTEM_VARS
+            .iter()
+            .filter_map(|var| std::env::var_os(var))
+            .map(|dir| Critical {
+                path: PathBuf::from(dir),
+                kind: "a system directory",
+            }),
+    );
+
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(Critical {
+            path: home,
+            kind: "your home directory",
+        });
+    }
+    dirs.extend(config.blocked_paths.iter().map(|path| Critical {
+        path: expand_path(path).path,
+        kind: "listed in blocked_paths",
+    }));
+    dirs
+}
+
+/// Why `path` is blocked: a filesystem root, one of `critical`, or with
+/// `block_ancestors` a directory containing one of them
+fn blocked_reason(path: &Path, critical: &[Critical], block_ancestors: bool) -> Option<String> {
+    let path = normalize(path);
+    if path.parent().is_none() {
+        return Some("a filesystem root".to_string());
+    }
+
+    let critical: Vec<(PathBuf, &Critical)> =
+        critical.iter().map(|c| (normalize(&c.path), c)).collect();
+    if let Some((_, c)) = critical.iter().find(|(dir, _)| same_path(dir, &path)) {
+        return Some(c.kind.to_string());
+    }
+    if block_ancestors {
+        let contained = critical.iter().find(|(dir, _)| {
+            dir.ancestors()
+                .skip(1)
+                .any(|ancestor| same_path(ancestor, &path))
+        });
+        if let Some((dir, _)) = contained {
+            return Some(format!("a parent of {}", dir.display()));
+        }
+    }
+    None
+}
+
+/// The canonical form of `path` if it exists, otherwise `path` without
+/// `.`/`..` components and trailing separators
+fn normalize(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Path equality, ignoring case on Windows
+fn same_path(a: &Path, b: &Path) -> bool {
+    if cfg!(windows) {
+        a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_blocked_roots() {
+        let temp = TempDir::new().unwrap();
+        let system = temp.path().join("sys");
+        let home = temp.path().join("home/me");
+        std::fs::create_dir_all(&system).unwrap();
+        let critical = [
+            Critical {
+                path: system.clone(),
+                kind: "a system directory",
+            },
+            Critical {
+                path: home.clone(),
+                kind: "your home directory",
+            },
+        ];
+
+        let reason = |path: &Path, ancestors| blocked_reason(path, &critical, ancestors);
+        let root = temp.path().ancestors().last().unwrap();
+        assert_eq!(reason(root, false).as_deref(), Some("a filesystem root"));
+        assert_eq!(
+            reason(&system, false).as_deref(),
+            Some("a system directory")
+        );
+        // Trailing separators and `..` don't get around it
+        assert_eq!(
+            reason(&home.join("docs/.."), false).as_deref(),
+            Some("your home directory")
+        );
+
+        // Inside a critical directory is fine, containing one only when allowed
+        assert_eq!(reason(&home.join("docs"), true), None);
+        assert_eq!(reason(&temp.path().join("home"), false), None);
+        assert!(reason(&temp.path().join("home"), true)
+            .unwrap()
+            .starts_with("a parent of"));
+    }
+
+    #[test]
+    fn test_check_root_uses_configured_paths() {
+        let temp = TempDir::new().unwrap();
+        let config = AppConfig {
+            blocked_paths: vec![temp.path().join("precious")],
+            ..AppConfig::default()
+        };
+        assert!(check_root(&temp.path().join("other"), &config).is_ok());
+        let error = check_root(&temp.path().join("precious"), &config).unwrap_err();
+        assert!(error.to_string().contains("listed in blocked_paths"));
+    }
+}
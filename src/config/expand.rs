@@ -0,0 +1,220 @@
+//! Expansion of `~` and environment variables in project paths
+
+use std::path::{Path, PathBuf};
+
+/// A path with variables substituted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    pub path: PathBuf,
+    /// Variables that are not set; their references are left in the path
+    pub unknown: Vec<String>,
+}
+
+/// Expands a leading `~` to the home directory, plus `$VAR` and `${VAR}` on
+/// Unix or `%VAR%` on Windows. Unknown variables are kept verbatim and
+/// reported in [`Expansion::unknown`].
+pub fn expand_path(path: &Path) -> Expansion {
+    let Some(raw) = path.to_str() else {
+        // Non-UTF-8 paths can't contain variable references we understand
+        return Expansion {
+            path: path.to_path_buf(),
+            unknown: Vec::new(),
+        };
+    };
+
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().into_owned());
+    let lookup = |name: &str| std::env::var(name).ok();
+    let mut unknown = Vec::new();
+    let expanded = expand_home(raw, home.as_deref());
+
+    #[cfg(windows)]
+    let expanded = expand_percent(&expanded, lookup, &mut unknown);
+    #[cfg(not(windows))]
+    let expanded = expand_dollar(&expanded, lookup, &mut unknown);
+
+    Expansion {
+        path: PathBuf::from(expanded),
+        unknown,
+    }
+}
+
+/// Replaces `~` when it is the whole path or followed by a separator
+fn expand_home(raw: &str, home: Option<&str>) -> String {
+    let Some(home) = home else {
+        return raw.to_string();
+    };
+    match raw.strip_prefix('~') {
+        Some("") => home.to_string(),
+        Some(rest) if rest.starts_with('/') || (cfg!(windows) && rest.starts_with('\\')) => {
+            format!("{}{}", home, rest)
+        }
+        _ => raw.to_string(),
+    }
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Unix syntax: `$VAR` and `${VAR}`; a `$` not starting a name stays literal
+#[cfg(any(not(windows), test))]
+fn expand_dollar(
+    raw: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+    unknown: &mut Vec<String>,
+) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let (name, reference_len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after.find(|c| !is_var_char(c)).unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        if name.is_empty() || !name.chars().all(is_var_char) {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+
+        let reference = &rest[pos..pos + 1 + reference_len];
+        match lookup(name) {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push_str(reference);
+                unknown.push(name.to_string());
+            }
+        }
+        rest = &after[reference_len..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Windows syntax: `%VAR%`; an unpaired `%` stays literal
+#[cfg(any(windows, test))]
+fn expand_percent(
+    raw: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+    unknown: &mut Vec<String>,
+) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let Some(end) = after.find('%') else {
+            out.push('%');
+            rest = after;
+            continue;
+        };
+        let name = &after[..end];
+        if name.is_empty() || !name.chars().all(is_var_char) {
+            // Not a reference; the closing `%` may open the next one
+            out.push('%');
+            rest = after;
+            continue;
+        }
+
+        match lookup(name) {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push_str(&rest[start..start + end + 2]);
+                unknown.push(name.to_string());
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/me".to_string()),
+            "USERPROFILE" => Some(r"C:\Users\me".to_string()),
+            "DATA_DIR" => Some("/srv/data".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(expand_home("~", Some("/home/me")), "/home/me");
+        assert_eq!(expand_home("~/docs", Some("/home/me")), "/home/me/docs");
+        // `~user` and a `~` later in the path are not expanded
+        assert_eq!(expand_home("~other/docs", Some("/home/me")), "~other/docs");
+        assert_eq!(expand_home("/tmp/~", Some("/home/me")), "/tmp/~");
+        assert_eq!(expand_home("~/docs", None), "~/docs");
+    }
+
+    #[test]
+    fn test_expand_dollar() {
+        let mut unknown = Vec::new();
+        assert_eq!(
+            expand_dollar("$HOME/docs", env, &mut unknown),
+            "/home/me/docs"
+        );
+        assert_eq!(
+            expand_dollar("${DATA_DIR}_old/x", env, &mut unknown),
+            "/srv/data_old/x"
+        );
+        assert_eq!(
+            expand_dollar("/cost$/a$-b", env, &mut unknown),
+            "/cost$/a$-b"
+        );
+        assert_eq!(
+            expand_dollar("/a/${unclosed", env, &mut unknown),
+            "/a/${unclosed"
+        );
+        assert!(unknown.is_empty());
+
+        assert_eq!(
+            expand_dollar("$NOPE/${ALSO_NOPE}/x", env, &mut unknown),
+            "$NOPE/${ALSO_NOPE}/x"
+        );
+        assert_eq!(unknown, vec!["NOPE", "ALSO_NOPE"]);
+    }
+
+    #[test]
+    fn test_expand_percent() {
+        let mut unknown = Vec::new();
+        assert_eq!(
+            expand_percent(r"%USERPROFILE%\docs", env, &mut unknown),
+            r"C:\Users\me\docs"
+        );
+        assert_eq!(
+            expand_percent("50% of %DATA_DIR%", env, &mut unknown),
+            "50% of /srv/data"
+        );
+        assert_eq!(expand_percent("100%", env, &mut unknown), "100%");
+        assert!(unknown.is_empty());
+
+        assert_eq!(expand_percent(r"%NOPE%\x", env, &mut unknown), r"%NOPE%\x");
+        assert_eq!(unknown, vec!["NOPE"]);
+    }
+
+    #[test]
+    fn test_expand_path_without_variables_is_unchanged() {
+        let expansion = expand_path(Path::new("/mnt/backup/docs"));
+        assert_eq!(expansion.path, PathBuf::from("/mnt/backup/docs"));
+        assert!(expansion.unknown.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+//! Recently used project paths, offered as suggestions in the new project dialog
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// History file name inside the config directory (~/.rahzom/)
+pub const PATH_HISTORY_FILE: &str = "path_history.toml";
+
+/// Maximum number of remembered paths
+const MAX_ENTRIES: usize = 20;
+
+/// Recently used left/right paths, most recent first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathHistory {
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+impl PathHistory {
+    /// Loads the history from the config directory.
+    /// Returns an empty history if the file doesn't exist.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = config_dir.join(PATH_HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read path history: {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Invalid path history: {:?}", path))
+    }
+
+    /// Saves the history to the config directory
+    pub fn save(&self, config_dir: &Path) -> Result<()> {
+        fs::create_dir_all(config_dir)
+            .with_context(|| format!("Failed to create config dir: {:?}", config_dir))?;
+        let path = config_dir.join(PATH_HISTORY_FILE);
+        let content = toml::to_string_pretty(self).context("Failed to serialize path history")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write path history: {:?}", path))
+    }
+
+    /// Moves `path` to the front, dropping the oldest entries beyond the limit
+    pub fn record(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_string());
+        self.paths.truncate(MAX_ENTRIES);
+    }
+
+    /// Remembered paths that extend `prefix`, most recent first
+    pub fn suggestions(&self, prefix: &str) -> Vec<&str> {
+        self.paths
+            .iter()
+            .filter(|p| p.starts_with(prefix) && p.as_str() != prefix)
+            .map(|p| p.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_dedupes_and_limits() {
+        let mut history = PathHistory::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.record(&format!("/data/{}", i));
+        }
+        history.record("/data/10");
+
+        assert_eq!(history.paths.len(), MAX_ENTRIES);
+        assert_eq!(history.paths[0], "/data/10");
+        assert_eq!(history.paths.iter().filter(|p| *p == "/data/10").count(), 1);
+    }
+
+    #[test]
+    fn test_suggestions_filter_by_prefix() {
+        let mut history = PathHistory::default();
+        history.record("/mnt/backup");
+        history.record("/home/me/photos");
+        history.record("/home/me/docs");
+
+        assert_eq!(
+            history.suggestions("/home"),
+            vec!["/home/me/docs", "/home/me/photos"]
+        );
+        assert_eq!(history.suggestions("").len(), 3);
+        // An exact match has nothing left to complete
+        assert!(history.suggestions("/mnt/backup").is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = TempDir::new().unwrap();
+        assert!(PathHistory::load(temp.path()).unwrap().paths.is_empty());
+
+        let mut history = PathHistory::default();
+        history.record("/a");
+        history.save(temp.path()).unwrap();
+
+        assert_eq!(PathHistory::load(temp.path()).unwrap(), history);
+    }
+}
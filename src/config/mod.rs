@@ -1 +1,6 @@
+pub mod app;
+pub mod blocklist;
+pub mod expand;
+pub mod history;
 pub mod project;
+pub mod ui_state;
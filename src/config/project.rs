@@ -1,10 +1,158 @@
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Write};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
+use globset::Glob;
 use serde::{Deserialize, Serialize};
 
+use crate::config::expand::expand_path;
+
+/// Per-folder metadata directory; also holds `_backup` and `_trash`
+pub const METADATA_DIR_NAME: &str = ".rahzom";
+
+/// Checks whether any component of `path` is a metadata directory
+pub fn is_within_metadata_dir(path: &Path) -> bool {
+    path.components()
+        .any(|c| matches!(c, Component::Normal(name) if name == METADATA_DIR_NAME))
+}
+
+/// Default naming template for the copy a conflict keeps of the right file
+pub const DEFAULT_CONFLICT_RENAME_TEMPLATE: &str = "{name} ({host}-{timestamp}){ext}";
+
+/// Checks that a rename template contains the required `{name}` placeholder
+/// and names a file beside the conflicting one, not a path elsewhere
+pub fn validate_rename_template(template: &str) -> Result<(), String> {
+    if !template.contains("{name}") {
+        return Err(format!(
+            "Rename template '{}' must contain {{name}}",
+            template
+        ));
+    }
+    let sample = ["{name}", "{ext}", "{host}", "{timestamp}", "{counter}"]
+        .iter()
+        .fold(template.to_string(), |s, placeholder| {
+            s.replace(placeholder, "x")
+        });
+    if !is_plain_file_name(&sample) {
+        return Err(format!(
+            "Rename template '{}' must give a file name without directories",
+            template
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `name` is a single normal path component on every platform
+pub fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\\'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+/// Filter mode for preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFilter {
+    #[default]
+    All,
+    Changes,
+    Conflicts,
+}
+
+/// Where soft deletion moves files on a side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoftDeleteTarget {
+    /// The side's own trash, `.rahzom/_trash/`
+    #[default]
+    Internal,
+    /// The OS recycle bin/trash, falling back to the internal trash where
+    /// it isn't available
+    SystemTrash,
+}
+
+/// Whether a copy may replace a destination file newer than its source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwritePolicy {
+    /// Replace the destination whatever its mtime
+    #[default]
+    Always,
+    /// Skip the copy if the destination is newer than the source
+    IfSourceNewer,
+    /// Skip every copy onto an existing destination
+    Never,
+}
+
+/// Line ending written to the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Which files get their line endings rewritten on copy, and to what
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EolNormalization {
+    #[serde(default)]
+    pub target: LineEnding,
+    /// File extensions without the dot, matched case-insensitively
+    #[serde(default = "default_extensions")]
+    pub extensions: Vec<String>,
+}
+
+fn default_extensions() -> Vec<String> {
+    [
+        "txt", "md", "csv", "json", "xml", "yml", "yaml", "toml", "ini",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+impl Default for EolNormalization {
+    fn default() -> Self {
+        Self {
+            target: LineEnding::default(),
+            extensions: default_extensions(),
+        }
+    }
+}
+
+/// How a matching conflict is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Left side wins (copy or delete towards the right)
+    KeepLeft,
+    /// Right side wins (copy or delete towards the left)
+    KeepRight,
+    /// The file with the later mtime wins; needs the file on both sides
+    KeepNewer,
+}
+
+/// A rule as stored in project settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictRule {
+    /// Glob matched against the relative path (`*` also matches `/`)
+    pub pattern: String,
+    pub resolution: ConflictResolution,
+}
+
+impl ConflictRule {
+    /// The pattern as a glob, with `\` taken as `/`
+    pub fn glob(&self) -> Result<Glob> {
+        let pattern = self.pattern.trim().replace('\\', "/");
+        Glob::new(&pattern)
+            .with_context(|| format!("Invalid conflict rule pattern: {}", self.pattern))
+    }
+}
+
 /// Project settings
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ProjectSettings {
@@ -20,6 +168,144 @@ pub struct ProjectSettings {
     /// Whether to use soft delete (move to trash)
     #[serde(default = "default_soft_delete")]
     pub soft_delete: bool,
+    /// Where soft deletion moves files on each side: "internal"
+    /// (`.rahzom/_trash`) or "system_trash", the OS recycle bin (requires a
+    /// build with the `system-trash` feature, else the internal trash is used)
+    #[serde(default)]
+    pub soft_delete_target_left: SoftDeleteTarget,
+    #[serde(default)]
+    pub soft_delete_target_right: SoftDeleteTarget,
+    /// Whether to skip creating directories that contain no files
+    #[serde(default)]
+    pub skip_empty_dirs: bool,
+    /// Whether the right side is append-only (never overwrite or delete there)
+    #[serde(default)]
+    pub append_only_right: bool,
+    /// Filter applied when the preview opens
+    #[serde(default)]
+    pub preview_filter: PreviewFilter,
+    /// Whether the preview hides skipped/identical items by default
+    #[serde(default = "default_hide_skips")]
+    pub hide_skips: bool,
+    /// Name of the right file's copy when a conflict keeps both files, must
+    /// contain `{name}`. Also supports `{ext}`, `{host}`, `{timestamp}` and
+    /// `{counter}`.
+    #[serde(default = "default_conflict_rename_template")]
+    pub conflict_rename_template: String,
+    /// Whether to copy directory mtimes to the destination after sync
+    #[serde(default = "default_preserve_dir_mtime")]
+    pub preserve_dir_mtime: bool,
+    /// Whether to copy NTFS ACLs to the destination (Windows only, needs privileges)
+    #[serde(default)]
+    pub copy_acls: bool,
+    /// Whether to recreate source hardlinks on the destination instead of copying each path
+    #[serde(default)]
+    pub preserve_hardlinks: bool,
+    /// Sync copied data to disk every N bytes (None = let the OS decide).
+    /// Applies to both the fixed and the adaptive copy buffer.
+    #[serde(default)]
+    pub fsync_interval_bytes: Option<u64>,
+    /// Scan Windows reparse points other than junctions/symlinks instead of skipping them
+    #[serde(default)]
+    pub include_reparse_points: bool,
+    /// Rewrite line endings of matching text files on copy (transforming, off by default)
+    #[serde(default)]
+    pub normalize_eol: Option<EolNormalization>,
+    /// Conflict auto-resolution rules, first matching pattern wins
+    #[serde(default)]
+    pub conflict_rules: Vec<ConflictRule>,
+    /// Reuse the previous scan for directories whose mtime is unchanged.
+    /// In-place edits that don't touch the directory need a full rescan.
+    #[serde(default)]
+    pub scan_cache: bool,
+    /// Make read-only destination files writable so they can be overwritten
+    #[serde(default)]
+    pub clear_readonly_on_overwrite: bool,
+    /// Skip files locked by another process ("file in use") instead of
+    /// asking, so unattended syncs run to the end
+    #[serde(default)]
+    pub skip_locked: bool,
+    /// Give copies the Unix owner/group of their source and sync
+    /// ownership-only changes. Needs root; failures are warnings.
+    #[serde(default)]
+    pub preserve_ownership: bool,
+    /// Give copies the Unix permission bits of their source instead of the
+    /// defaults of a new file
+    #[serde(default)]
+    pub preserve_permissions: bool,
+    /// Sync permission, read-only and hidden changes of files whose content
+    /// is the same on both sides, without copying the content
+    #[serde(default)]
+    pub sync_attributes: bool,
+    /// The right path is a snapshot manifest (a saved scan) rather than a
+    /// directory. The left side is compared against it; syncing is disabled.
+    #[serde(default)]
+    pub manifest_right: bool,
+    /// Don't descend into directories on another filesystem (mount points)
+    #[serde(default)]
+    pub one_filesystem: bool,
+    /// Hybrid conflict mode: when both sides changed and their mtimes are
+    /// more than this many seconds apart, the newer one is copied. Closer
+    /// edits stay manual conflicts. `None` leaves every conflict manual.
+    #[serde(default)]
+    pub conflict_age_threshold_secs: Option<u64>,
+    /// Only sync files last modified at least this many days ago
+    #[serde(default)]
+    pub min_age_days: Option<u64>,
+    /// Only sync files last modified within this many days
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    /// Experimental: the right side is a compressed archive rather than a
+    /// mirror, holding each file as `<name>.rahzom.zst` (requires a build
+    /// with the `compression` feature)
+    #[serde(default)]
+    pub compressed_right: bool,
+    /// Quick incremental sync: instead of scanning the right side, copy left
+    /// files modified since the last sync and delete those gone from the
+    /// left, trusting the recorded state. Changes made on the right outside
+    /// of rahzom aren't detected.
+    #[serde(default)]
+    pub quick_sync: bool,
+    /// Leave out directories that are the roots of other projects, so
+    /// nested projects aren't synced twice
+    #[serde(default)]
+    pub exclude_other_projects: bool,
+    /// Don't copy or delete a file whose content is on the other side under
+    /// another name. Files present on one side only are hashed when another
+    /// such file has the same size.
+    #[serde(default)]
+    pub skip_content_elsewhere: bool,
+    /// Note in the preview which copies and deletes are a file renamed on
+    /// one side since the last sync, matched by file id or else by content
+    #[serde(default)]
+    pub detect_renames: bool,
+    /// Size the copy buffer to each file and grow it while copying is fast,
+    /// instead of always using 64 KB. Combines with `fsync_interval_bytes`.
+    #[serde(default)]
+    pub adaptive_buffer: bool,
+    /// Sync FIFOs (named pipes) by recreating them on the other side.
+    /// Otherwise they're skipped, like sockets and device files.
+    #[serde(default)]
+    pub recreate_fifos: bool,
+    /// Clone copies by copy-on-write where both sides share a filesystem
+    /// that supports it (Btrfs, XFS, APFS), instead of copying the bytes
+    #[serde(default)]
+    pub try_reflink: bool,
+    /// Replace a file on one side that stands where the other side has a
+    /// directory, backing the file up first, instead of failing the sync of
+    /// everything under that directory
+    #[serde(default)]
+    pub replace_blocking_files: bool,
+    /// In a one-way (quick) sync, whether a copy may replace a right file
+    /// that is newer than its source: "always", "if_source_newer" or "never".
+    /// A refused copy is skipped, keeping the edit made on the right.
+    #[serde(default)]
+    pub overwrite_policy: OverwritePolicy,
+    /// URL a JSON summary of each finished sync is POSTed to (requires a
+    /// build with the `webhook` feature). Delivery failures are shown but
+    /// don't fail the sync.
+    #[serde(default)]
+    pub on_complete_webhook: Option<String>,
 }
 
 fn default_backup_versions() -> usize {
@@ -34,6 +320,26 @@ fn default_soft_delete() -> bool {
     true
 }
 
+fn default_preserve_dir_mtime() -> bool {
+    true
+}
+
+fn default_hide_skips() -> bool {
+    true
+}
+
+fn default_conflict_rename_template() -> String {
+    DEFAULT_CONFLICT_RENAME_TEMPLATE.to_string()
+}
+
+impl ProjectSettings {
+    /// Whether scans and the sync state keep file ids, which only finding
+    /// hardlinks and renames needs
+    pub fn records_file_ids(&self) -> bool {
+        self.preserve_hardlinks || self.detect_renames
+    }
+}
+
 impl Default for ProjectSettings {
     fn default() -> Self {
         Self {
@@ -41,6 +347,42 @@ impl Default for ProjectSettings {
             backup_versions: default_backup_versions(),
             deleted_retention_days: default_deleted_retention_days(),
             soft_delete: default_soft_delete(),
+            soft_delete_target_left: SoftDeleteTarget::Internal,
+            soft_delete_target_right: SoftDeleteTarget::Internal,
+            skip_empty_dirs: false,
+            append_only_right: false,
+            preview_filter: PreviewFilter::default(),
+            hide_skips: default_hide_skips(),
+            conflict_rename_template: default_conflict_rename_template(),
+            preserve_dir_mtime: default_preserve_dir_mtime(),
+            copy_acls: false,
+            preserve_hardlinks: false,
+            fsync_interval_bytes: None,
+            include_reparse_points: false,
+            normalize_eol: None,
+            conflict_rules: Vec::new(),
+            scan_cache: false,
+            clear_readonly_on_overwrite: false,
+            skip_locked: false,
+            preserve_ownership: false,
+            preserve_permissions: false,
+            sync_attributes: false,
+            manifest_right: false,
+            one_filesystem: false,
+            conflict_age_threshold_secs: None,
+            min_age_days: None,
+            max_age_days: None,
+            compressed_right: false,
+            quick_sync: false,
+            exclude_other_projects: false,
+            skip_content_elsewhere: false,
+            detect_renames: false,
+            adaptive_buffer: false,
+            recreate_fifos: false,
+            try_reflink: false,
+            replace_blocking_files: false,
+            overwrite_policy: OverwritePolicy::Always,
+            on_complete_webhook: None,
         }
     }
 }
@@ -54,6 +396,9 @@ pub struct Project {
     pub left_path: PathBuf,
     /// Right side path
     pub right_path: PathBuf,
+    /// Listed above the other projects
+    #[serde(default)]
+    pub pinned: bool,
     /// Project-specific settings
     #[serde(default)]
     pub settings: ProjectSettings,
@@ -66,6 +411,7 @@ impl Project {
             name: name.into(),
             left_path,
             right_path,
+            pinned: false,
             settings: ProjectSettings::default(),
         }
     }
@@ -91,10 +437,41 @@ impl Project {
             bail!("Right path cannot be empty");
         }
 
+        // Syncing another folder's .rahzom would copy its state, backups and trash
+        for (side, path) in [("Left", &self.left_path), ("Right", &self.right_path)] {
+            if is_within_metadata_dir(path) {
+                bail!(
+                    "{} path is inside a rahzom metadata directory: {}",
+                    side,
+                    path.display()
+                );
+            }
+        }
+
+        if let Err(e) = validate_rename_template(&self.settings.conflict_rename_template) {
+            bail!(e);
+        }
+
+        for rule in &self.settings.conflict_rules {
+            rule.glob()?;
+        }
+
         Ok(())
     }
 }
 
+/// Version of the format [`ProjectManager::export_project`] writes
+pub const PROJECT_EXPORT_VERSION: u32 = 1;
+
+/// A project file as shared between machines: the project with its paths
+/// unexpanded, under a format version
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportedProject {
+    rahzom_project: u32,
+    #[serde(flatten)]
+    project: Project,
+}
+
 /// Checks if a project name is valid (alphanumeric, dashes, underscores)
 fn is_valid_project_name(name: &str) -> bool {
     !name.is_empty()
@@ -167,11 +544,60 @@ impl ProjectManager {
         }
 
         projects.sort();
+        // Pinned first, each group still by name
+        projects.sort_by_key(|name| !self.is_pinned(name));
         Ok(projects)
     }
 
-    /// Loads a project by name
+    /// Whether the project is pinned; false if it can't be read
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.read_project(name).is_ok_and(|project| project.pinned)
+    }
+
+    /// Names of the pinned projects
+    pub fn pinned_projects(&self) -> HashSet<String> {
+        self.list_projects()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|name| self.is_pinned(name))
+            .collect()
+    }
+
+    /// Pins or unpins a project, leaving the rest of its file as stored
+    pub fn set_pinned(&self, name: &str, pinned: bool) -> Result<()> {
+        let mut project = self.read_project(name)?;
+        project.pinned = pinned;
+        self.save_project(&project)
+    }
+
+    /// Loads a project by name, expanding `~` and environment variables in
+    /// its paths. Unknown variables are left in place; see
+    /// [`Self::load_project_with_warnings`] to find out about them.
     pub fn load_project(&self, name: &str) -> Result<Project> {
+        self.load_project_with_warnings(name)
+            .map(|(project, _)| project)
+    }
+
+    /// Like [`Self::load_project`], also returning a warning for every
+    /// variable in the paths that is not set
+    pub fn load_project_with_warnings(&self, name: &str) -> Result<(Project, Vec<String>)> {
+        let mut project = self.read_project(name)?;
+        let mut warnings = Vec::new();
+        for path in [&mut project.left_path, &mut project.right_path] {
+            let expansion = expand_path(path);
+            warnings.extend(
+                expansion
+                    .unknown
+                    .iter()
+                    .map(|var| format!("Unknown variable '{}' in path {}", var, path.display())),
+            );
+            *path = expansion.path;
+        }
+        Ok((project, warnings))
+    }
+
+    /// Reads a project file as stored, without expanding its paths
+    fn read_project(&self, name: &str) -> Result<Project> {
         if !is_valid_project_name(name) {
             bail!("Invalid project name: {}", name);
         }
@@ -194,6 +620,10 @@ impl ProjectManager {
         let project: Project = toml::from_str(&content)
             .with_context(|| format!("Failed to parse project file: {:?}", path))?;
 
+        validate_rename_template(&project.settings.conflict_rename_template)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("Invalid settings in project file: {:?}", path))?;
+
         Ok(project)
     }
 
@@ -204,7 +634,18 @@ impl ProjectManager {
 
         let path = self.project_path(&project.name);
 
-        let content = toml::to_string_pretty(project)
+        // Keep the unexpanded form of paths that still resolve to the same place
+        let mut project = project.clone();
+        if let Ok(stored) = self.read_project(&project.name) {
+            let raw_paths = [stored.left_path, stored.right_path];
+            for path in [&mut project.left_path, &mut project.right_path] {
+                if let Some(raw) = raw_paths.iter().find(|raw| expand_path(raw).path == *path) {
+                    *path = raw.clone();
+                }
+            }
+        }
+
+        let content = toml::to_string_pretty(&project)
             .with_context(|| format!("Failed to serialize project: {}", project.name))?;
 
         let file = File::create(&path)
@@ -218,6 +659,30 @@ impl ProjectManager {
         Ok(())
     }
 
+    /// Swaps the left and right paths of a project and saves it.
+    /// Per-directory `.rahzom` metadata is left untouched.
+    pub fn swap_sides(&self, name: &str) -> Result<Project> {
+        let mut project = self.load_project(name)?;
+        if project.settings.manifest_right {
+            bail!("A snapshot manifest can only be the right side");
+        }
+        if project.settings.compressed_right {
+            bail!("A compressed archive can only be the right side");
+        }
+        // Swapped, the archive would be the side that gets overwritten
+        if project.settings.append_only_right {
+            bail!("The right side is append-only; turn off append_only_right to swap sides");
+        }
+        std::mem::swap(&mut project.left_path, &mut project.right_path);
+        let settings = &mut project.settings;
+        std::mem::swap(
+            &mut settings.soft_delete_target_left,
+            &mut settings.soft_delete_target_right,
+        );
+        self.save_project(&project)?;
+        Ok(project)
+    }
+
     /// Deletes a project
     pub fn delete_project(&self, name: &str) -> Result<()> {
         if !is_valid_project_name(name) {
@@ -236,6 +701,47 @@ impl ProjectManager {
         Ok(())
     }
 
+    /// Exports a project as TOML to share or keep under version control.
+    /// Paths are written as stored, with `~` and variables unexpanded, so
+    /// they resolve on the machine that imports them.
+    pub fn export_project(&self, name: &str) -> Result<String> {
+        let exported = ExportedProject {
+            rahzom_project: PROJECT_EXPORT_VERSION,
+            project: self.read_project(name)?,
+        };
+        toml::to_string_pretty(&exported)
+            .with_context(|| format!("Failed to serialize project: {}", name))
+    }
+
+    /// Parses and validates an exported project, without saving it
+    pub fn parse_exported(data: &str) -> Result<Project> {
+        let exported: ExportedProject =
+            toml::from_str(data).context("Not a rahzom project file")?;
+        if exported.rahzom_project > PROJECT_EXPORT_VERSION {
+            bail!(
+                "Project file version {} needs a newer rahzom (this one reads up to {})",
+                exported.rahzom_project,
+                PROJECT_EXPORT_VERSION
+            );
+        }
+        exported.project.validate()?;
+        Ok(exported.project)
+    }
+
+    /// Imports an exported project. If a project of that name exists, the
+    /// import is saved as `name-2`, `name-3`, ... Returns the name saved under.
+    pub fn import_project(&self, data: &str) -> Result<String> {
+        let mut project = Self::parse_exported(data)?;
+        let base = project.name.clone();
+        let mut counter = 2;
+        while self.project_exists(&project.name) {
+            project.name = format!("{}-{}", base, counter);
+            counter += 1;
+        }
+        self.save_project(&project)?;
+        Ok(project.name)
+    }
+
     /// Checks if a project exists
     pub fn project_exists(&self, name: &str) -> bool {
         if !is_valid_project_name(name) {
@@ -244,6 +750,18 @@ impl ProjectManager {
         self.project_path(name).exists()
     }
 
+    /// Left and right paths of every project except `name`, expanded.
+    /// Projects that fail to load are left out.
+    pub fn other_project_roots(&self, name: &str) -> Vec<PathBuf> {
+        self.list_projects()
+            .unwrap_or_default()
+            .iter()
+            .filter(|other| *other != name)
+            .filter_map(|other| self.load_project(other).ok())
+            .flat_map(|project| [project.left_path, project.right_path])
+            .collect()
+    }
+
     /// Returns the config directory path
     pub fn config_dir(&self) -> &Path {
         &self.config_dir
@@ -299,6 +817,29 @@ mod tests {
         assert_eq!(projects, vec!["alpha", "beta", "gamma"]);
     }
 
+    #[test]
+    fn test_pinned_projects_listed_first() {
+        let (manager, _temp) = create_test_manager();
+        for name in ["alpha", "beta", "gamma", "delta"] {
+            manager.save_project(&sample_project(name)).unwrap();
+        }
+
+        manager.set_pinned("gamma", true).unwrap();
+        manager.set_pinned("beta", true).unwrap();
+        assert_eq!(
+            manager.list_projects().unwrap(),
+            vec!["beta", "gamma", "alpha", "delta"]
+        );
+        assert!(manager.load_project("gamma").unwrap().pinned);
+        assert_eq!(manager.pinned_projects().len(), 2);
+
+        manager.set_pinned("beta", false).unwrap();
+        assert_eq!(
+            manager.list_projects().unwrap(),
+            vec!["gamma", "alpha", "beta", "delta"]
+        );
+    }
+
     #[test]
     fn test_delete_project() {
         let (manager, _temp) = create_test_manager();
@@ -310,6 +851,41 @@ mod tests {
         assert!(!manager.project_exists("to-delete"));
     }
 
+    #[test]
+    fn test_swap_sides() {
+        let (manager, _temp) = create_test_manager();
+
+        let mut project = sample_project("swap");
+        project.settings.soft_delete_target_left = SoftDeleteTarget::SystemTrash;
+        manager.save_project(&project).unwrap();
+        let swapped = manager.swap_sides("swap").unwrap();
+
+        assert_eq!(swapped.left_path, PathBuf::from("/mnt/backup/docs"));
+        assert_eq!(swapped.right_path, PathBuf::from("/home/user/docs"));
+        assert_eq!(
+            swapped.settings.soft_delete_target_right,
+            SoftDeleteTarget::SystemTrash
+        );
+
+        let loaded = manager.load_project("swap").unwrap();
+        assert_eq!(loaded.left_path, PathBuf::from("/mnt/backup/docs"));
+        assert_eq!(loaded.right_path, PathBuf::from("/home/user/docs"));
+    }
+
+    #[test]
+    fn test_swap_sides_refused_for_append_only_right() {
+        let (manager, _temp) = create_test_manager();
+
+        let mut project = sample_project("archive");
+        project.settings.append_only_right = true;
+        manager.save_project(&project).unwrap();
+        assert!(manager.swap_sides("archive").is_err());
+
+        let loaded = manager.load_project("archive").unwrap();
+        assert_eq!(loaded.right_path, PathBuf::from("/mnt/backup/docs"));
+        assert!(loaded.settings.append_only_right);
+    }
+
     #[test]
     fn test_project_exists() {
         let (manager, _temp) = create_test_manager();
@@ -333,10 +909,10 @@ mod tests {
         manager.save_project(&project).unwrap();
         let loaded = manager.load_project("with-settings").unwrap();
 
-        assert_eq!(loaded.settings.verify_hash, true);
+        assert!(loaded.settings.verify_hash);
         assert_eq!(loaded.settings.backup_versions, 10);
         assert_eq!(loaded.settings.deleted_retention_days, 30);
-        assert_eq!(loaded.settings.soft_delete, false);
+        assert!(!loaded.settings.soft_delete);
     }
 
     #[test]
@@ -409,9 +985,210 @@ mod tests {
     fn test_default_settings() {
         let settings = ProjectSettings::default();
 
-        assert_eq!(settings.verify_hash, false);
+        assert!(!settings.verify_hash);
         assert_eq!(settings.backup_versions, 5);
         assert_eq!(settings.deleted_retention_days, 90);
-        assert_eq!(settings.soft_delete, true);
+        assert!(settings.soft_delete);
+        assert!(!settings.skip_empty_dirs);
+        assert!(!settings.append_only_right);
+        assert_eq!(settings.preview_filter, PreviewFilter::All);
+        assert!(settings.hide_skips);
+        assert_eq!(
+            settings.conflict_rename_template,
+            DEFAULT_CONFLICT_RENAME_TEMPLATE
+        );
+        assert!(settings.preserve_dir_mtime);
+        assert!(!settings.copy_acls);
+        assert!(!settings.preserve_hardlinks);
+        assert_eq!(settings.fsync_interval_bytes, None);
+        assert!(!settings.include_reparse_points);
+        assert_eq!(settings.normalize_eol, None);
+        assert!(settings.conflict_rules.is_empty());
+        assert!(!settings.scan_cache);
+        assert!(!settings.clear_readonly_on_overwrite);
+        assert!(!settings.skip_locked);
+        assert!(!settings.preserve_ownership);
+        assert!(!settings.preserve_permissions);
+        assert!(!settings.sync_attributes);
+        assert!(!settings.manifest_right);
+        assert!(!settings.one_filesystem);
+        assert_eq!(settings.conflict_age_threshold_secs, None);
+        assert_eq!(settings.min_age_days, None);
+        assert_eq!(settings.max_age_days, None);
+        assert!(!settings.compressed_right);
+        assert!(!settings.quick_sync);
+        assert!(!settings.exclude_other_projects);
+        assert!(!settings.skip_content_elsewhere);
+        assert!(!settings.detect_renames);
+        assert!(!settings.adaptive_buffer);
+        assert!(!settings.try_reflink);
+        assert!(!settings.replace_blocking_files);
+        assert!(!settings.recreate_fifos);
+        assert_eq!(settings.overwrite_policy, OverwritePolicy::Always);
+        assert_eq!(settings.on_complete_webhook, None);
+    }
+
+    #[test]
+    fn test_validate_rename_template() {
+        assert!(validate_rename_template(DEFAULT_CONFLICT_RENAME_TEMPLATE).is_ok());
+        assert!(validate_rename_template("{name} (conflict){ext}").is_ok());
+        assert!(validate_rename_template("conflict{ext}").is_err());
+        for escaping in [
+            "../{name}{ext}",
+            "sub/{name}{ext}",
+            "sub\\{name}{ext}",
+            "/tmp/{name}",
+            "..{name}/x",
+        ] {
+            assert!(validate_rename_template(escaping).is_err(), "{}", escaping);
+        }
+    }
+
+    #[test]
+    fn test_rename_template_without_name_rejected() {
+        let (manager, temp) = create_test_manager();
+
+        let mut project = sample_project("bad-template");
+        project.settings.conflict_rename_template = "conflict{ext}".to_string();
+        assert!(manager.save_project(&project).is_err());
+
+        // Hand-edited project files are validated on load
+        project.settings.conflict_rename_template = "{name}{ext}".to_string();
+        manager.save_project(&project).unwrap();
+        let path = temp.path().join("projects/bad-template.toml");
+        let content = fs::read_to_string(&path)
+            .unwrap()
+            .replace("{name}{ext}", "conflict{ext}");
+        fs::write(&path, content).unwrap();
+        assert!(manager.load_project("bad-template").is_err());
+    }
+
+    #[test]
+    fn test_path_inside_metadata_dir_rejected() {
+        let (manager, _temp) = create_test_manager();
+
+        let mut project = sample_project("nested");
+        project.left_path = PathBuf::from("/data/other/.rahzom/_backup");
+        let err = manager.save_project(&project).unwrap_err();
+        assert!(err.to_string().contains("Left path"));
+
+        project.left_path = PathBuf::from("/data/left");
+        project.right_path = PathBuf::from("/data/other/.rahzom");
+        assert!(manager.save_project(&project).is_err());
+
+        project.right_path = PathBuf::from("/data/right");
+        assert!(manager.save_project(&project).is_ok());
+    }
+
+    #[test]
+    fn test_conflict_rules_round_trip() {
+        let (manager, _temp) = create_test_manager();
+
+        let mut project = sample_project("rules");
+        project.settings.conflict_rules = vec![
+            ConflictRule {
+                pattern: "*.log".to_string(),
+                resolution: ConflictResolution::KeepRight,
+            },
+            ConflictRule {
+                pattern: "src/**".to_string(),
+                resolution: ConflictResolution::KeepNewer,
+            },
+        ];
+        manager.save_project(&project).unwrap();
+        assert_eq!(manager.load_project("rules").unwrap(), project);
+
+        project.settings.conflict_rules[0].pattern = "src/[".to_string();
+        assert!(manager.save_project(&project).is_err());
+    }
+
+    #[test]
+    fn test_load_expands_variables_and_save_keeps_them() {
+        let (manager, _temp) = create_test_manager();
+        std::env::set_var("RAHZOM_TEST_BASE", "/srv/shared");
+        let var = if cfg!(windows) {
+            "%RAHZOM_TEST_BASE%"
+        } else {
+            "${RAHZOM_TEST_BASE}"
+        };
+
+        let project = Project::new(
+            "portable",
+            PathBuf::from(format!("{}/docs", var)),
+            PathBuf::from("~/backup"),
+        );
+        manager.save_project(&project).unwrap();
+
+        let (mut loaded, warnings) = manager.load_project_with_warnings("portable").unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(loaded.left_path, PathBuf::from("/srv/shared/docs"));
+        assert_eq!(loaded.right_path, dirs::home_dir().unwrap().join("backup"));
+
+        // Saving the expanded project (e.g. after a settings change or a
+        // swap) keeps the portable form on disk
+        loaded.settings.soft_delete = !loaded.settings.soft_delete;
+        manager.save_project(&loaded).unwrap();
+        manager.swap_sides("portable").unwrap();
+        let stored = manager.read_project("portable").unwrap();
+        assert_eq!(stored.left_path, PathBuf::from("~/backup"));
+        assert_eq!(stored.right_path, PathBuf::from(format!("{}/docs", var)));
+    }
+
+    #[test]
+    fn test_export_and_import_project() {
+        let (manager, _temp) = create_test_manager();
+        let mut project = Project::new(
+            "photos",
+            PathBuf::from("$HOME/photos"),
+            PathBuf::from("/mnt/backup/photos"),
+        );
+        project.settings.backup_versions = 3;
+        project.settings.quick_sync = true;
+        manager.save_project(&project).unwrap();
+
+        let exported = manager.export_project("photos").unwrap();
+        assert!(exported.starts_with("rahzom_project = 1\n"), "{}", exported);
+        assert!(exported.contains("$HOME/photos"), "{}", exported);
+
+        // Imported elsewhere as is
+        let (other, _other_temp) = create_test_manager();
+        assert_eq!(other.import_project(&exported).unwrap(), "photos");
+        let imported = other.read_project("photos").unwrap();
+        assert_eq!(imported, project);
+
+        // A name already taken gets a suffix
+        assert_eq!(manager.import_project(&exported).unwrap(), "photos-2");
+        assert_eq!(manager.import_project(&exported).unwrap(), "photos-3");
+        assert_eq!(
+            manager.read_project("photos-3").unwrap().settings,
+            project.settings
+        );
+
+        // Invalid projects, other files and newer formats are refused
+        let invalid = exported.replace("/mnt/backup/photos", "/mnt/backup/.rahzom/x");
+        assert!(manager.import_project(&invalid).is_err());
+        assert!(manager.import_project("name = \"x\"").is_err());
+        let newer = exported.replace("rahzom_project = 1", "rahzom_project = 99");
+        let error = manager.import_project(&newer).unwrap_err().to_string();
+        assert!(error.contains("newer rahzom"), "{}", error);
+        assert_eq!(manager.list_projects().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_load_warns_about_unknown_variables() {
+        let (manager, _temp) = create_test_manager();
+        let var = if cfg!(windows) {
+            "%RAHZOM_TEST_UNSET%"
+        } else {
+            "$RAHZOM_TEST_UNSET"
+        };
+        let left = PathBuf::from(format!("/data/{}", var));
+        let project = Project::new("unset", left.clone(), PathBuf::from("/backup"));
+        manager.save_project(&project).unwrap();
+
+        let (loaded, warnings) = manager.load_project_with_warnings("unset").unwrap();
+        assert_eq!(loaded.left_path, left);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("RAHZOM_TEST_UNSET"));
     }
 }
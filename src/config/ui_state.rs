@@ -0,0 +1,60 @@
+//! UI state kept between runs, so the TUI starts where it was left
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// UI state file name inside the config directory (~/.rahzom/)
+pub const UI_STATE_FILE: &str = "ui_state.toml";
+
+/// What the TUI remembers between runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiState {
+    /// Name of the project opened last
+    #[serde(default)]
+    pub last_project: Option<String>,
+}
+
+impl UiState {
+    /// Loads the state from the config directory.
+    /// Returns an empty state if the file doesn't exist.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = config_dir.join(UI_STATE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read UI state: {:?}", path))?;
+        toml::from_str(&content).with_context(|| format!("Invalid UI state: {:?}", path))
+    }
+
+    /// Saves the state to the config directory
+    pub fn save(&self, config_dir: &Path) -> Result<()> {
+        fs::create_dir_all(config_dir)
+            .with_context(|| format!("Failed to create config dir: {:?}", config_dir))?;
+        let path = config_dir.join(UI_STATE_FILE);
+        let content = toml::to_string_pretty(self).context("Failed to serialize UI state")?;
+        fs::write(&path, content).with_context(|| format!("Failed to write UI state: {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(UiState::load(temp.path()).unwrap(), UiState::default());
+
+        let state = UiState {
+            last_project: Some("photos".to_string()),
+        };
+        state.save(temp.path()).unwrap();
+        assert_eq!(UiState::load(temp.path()).unwrap(), state);
+    }
+}
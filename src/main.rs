@@ -1,25 +1,71 @@
-use anyhow::Result;
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
-use crossterm::execute;
-use std::io;
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::path::Path;
 
 use rahzom::app::App;
+use rahzom::sync::manifest;
+use rahzom::ui::{terminal, ConsoleProgress};
 
 fn main() -> Result<()> {
-    // Initialize terminal with panic hook
-    let mut terminal = ratatui::init();
+    // `rahzom manifest <dir> <file>` saves a snapshot manifest without the TUI
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "manifest") {
+        let [_, dir, file] = args.as_slice() else {
+            bail!("Usage: rahzom manifest <dir> <file>");
+        };
+        let result = manifest::create(Path::new(dir), Path::new(file))?;
+        println!(
+            "Saved {} entries of {} to {}",
+            result.entries.len(),
+            result.root.display(),
+            Path::new(file).display()
+        );
+        return Ok(());
+    }
 
-    // Enable mouse capture
-    execute!(io::stdout(), EnableMouseCapture)?;
+    // `rahzom sync <project>` syncs a project without the TUI, e.g. in CI
+    if args.first().is_some_and(|arg| arg == "sync") {
+        let [_, name] = args.as_slice() else {
+            bail!("Usage: rahzom sync <project>");
+        };
+        let name = name.to_str().context("Project names are UTF-8")?;
+        let mut app = App::new();
+        if !app.start_headless_sync(name)? {
+            eprintln!("Nothing to sync");
+            return Ok(());
+        }
+        let planned = app
+            .syncing
+            .as_ref()
+            .map_or(0, |s| s.total_bytes - s.transferred_bytes);
+        let mut progress = ConsoleProgress::new(planned);
+        let result = app.run_headless_sync(&mut progress);
+        progress.finish(&result)?;
+        for failure in app.wait_for_reports() {
+            eprintln!("{}", failure);
+        }
+        if !result.failed.is_empty() {
+            bail!("{} actions failed", result.failed.len());
+        }
+        return Ok(());
+    }
 
-    // Run application
-    let result = App::new().run(&mut terminal);
+    // Refuses a piped or dumb terminal before writing any escape sequences
+    let mut terminal = terminal::init()?;
+
+    let mut app = App::new();
+    if !args.iter().any(|arg| arg == "--no-resume") {
+        app.resume_last_project();
+    }
 
-    // Disable mouse capture before restoring
-    let _ = execute!(io::stdout(), DisableMouseCapture);
+    // Run application
+    let result = app.run(&mut terminal);
 
-    // Restore terminal
-    ratatui::restore();
+    // Restore terminal, releasing the mouse if it was captured
+    terminal::restore();
 
+    for failure in app.wait_for_reports() {
+        eprintln!("{}", failure);
+    }
     result
 }
@@ -0,0 +1,184 @@
+//! Copy buffer sized to the file and to the throughput it achieves.
+//!
+//! A copy starts with a buffer no larger than the file, so tiny files don't
+//! each allocate the full 64 KB, and the buffer doubles while reads keep
+//! filling it quickly, so a large file on a fast disk needs fewer, larger
+//! reads and writes. Reads that come back short halve it again.
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Smallest buffer used
+pub const MIN_BUFFER: usize = 4 * 1024;
+
+/// Buffer a copy starts with, the fixed size used without adaptation
+pub const START_BUFFER: usize = 64 * 1024;
+
+/// Largest buffer used
+pub const MAX_BUFFER: usize = 4 * 1024 * 1024;
+
+/// Reads each sizing decision is based on
+const WINDOW: usize = 4;
+
+/// Throughput above which the buffer grows, in bytes per second
+const FAST_BYTES_PER_SEC: f64 = 100.0 * 1024.0 * 1024.0;
+
+/// One read and write of a copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadSample {
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+/// Picks the next buffer size from the current one and the last reads made
+/// with it. The result is clamped to `MIN_BUFFER..=MAX_BUFFER`.
+pub type SizeDecision = fn(usize, &[ReadSample]) -> usize;
+
+/// Doubles the buffer when every read filled it at `FAST_BYTES_PER_SEC` or
+/// more, halves it when every read came back half empty or less
+pub fn grow_when_fast(current: usize, recent: &[ReadSample]) -> usize {
+    let bytes: usize = recent.iter().map(|s| s.bytes).sum();
+    let secs: f64 = recent.iter().map(|s| s.elapsed.as_secs_f64()).sum();
+    if recent.iter().all(|s| s.bytes == current) && bytes as f64 >= FAST_BYTES_PER_SEC * secs {
+        current * 2
+    } else if recent.iter().all(|s| s.bytes <= current / 2) {
+        current / 2
+    } else {
+        current
+    }
+}
+
+/// Copy buffer resized every `WINDOW` reads by a [`SizeDecision`]
+#[derive(Debug)]
+pub struct AdaptiveBuffer {
+    buf: Vec<u8>,
+    recent: Vec<ReadSample>,
+    decide: SizeDecision,
+}
+
+impl AdaptiveBuffer {
+    /// Buffer for copying a file of `file_size` bytes
+    pub fn new(file_size: u64) -> Self {
+        Self::with_decision(file_size, grow_when_fast)
+    }
+
+    pub fn with_decision(file_size: u64, decide: SizeDecision) -> Self {
+        let size = usize::try_from(file_size)
+            .unwrap_or(usize::MAX)
+            .clamp(MIN_BUFFER, START_BUFFER);
+        Self {
+            buf: vec![0u8; size],
+            recent: Vec::with_capacity(WINDOW),
+            decide,
+        }
+    }
+
+    /// Current buffer size in bytes
+    pub fn size(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Records a read, resizing the buffer once `WINDOW` reads are in
+    pub fn record(&mut self, sample: ReadSample) {
+        self.recent.push(sample);
+        if self.recent.len() < WINDOW {
+            return;
+        }
+        let next = (self.decide)(self.buf.len(), &self.recent).clamp(MIN_BUFFER, MAX_BUFFER);
+        self.recent.clear();
+        if next != self.buf.len() {
+            self.buf.resize(next, 0);
+            self.buf.shrink_to_fit();
+        }
+    }
+
+    /// Copies `reader` to `writer`. Returns the number of bytes copied.
+    pub fn copy<R: Read, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> io::Result<u64> {
+        let mut total = 0u64;
+        loop {
+            let start = Instant::now();
+            let n = match reader.read(&mut self.buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&self.buf[..n])?;
+            total += n as u64;
+            self.record(ReadSample {
+                bytes: n,
+                elapsed: start.elapsed(),
+            });
+        }
+        writer.flush()?;
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(bytes: usize, micros: u64) -> ReadSample {
+        ReadSample {
+            bytes,
+            elapsed: Duration::from_micros(micros),
+        }
+    }
+
+    #[test]
+    fn test_grows_after_fast_full_reads() {
+        let mut buffer = AdaptiveBuffer::new(1 << 30);
+        assert_eq!(buffer.size(), START_BUFFER);
+
+        // 64 KB per 100 µs is well above the threshold
+        for _ in 0..WINDOW {
+            buffer.record(sample(START_BUFFER, 100));
+        }
+        assert_eq!(buffer.size(), START_BUFFER * 2);
+
+        // Slow reads keep it; many more fast ones stop at the cap
+        for _ in 0..WINDOW {
+            buffer.record(sample(buffer.size(), 1_000_000));
+        }
+        assert_eq!(buffer.size(), START_BUFFER * 2);
+        for _ in 0..100 {
+            buffer.record(sample(buffer.size(), 0));
+        }
+        assert_eq!(buffer.size(), MAX_BUFFER);
+
+        // Short reads shrink it
+        for _ in 0..WINDOW {
+            buffer.record(sample(1024, 0));
+        }
+        assert_eq!(buffer.size(), MAX_BUFFER / 2);
+    }
+
+    #[test]
+    fn test_sized_to_small_files() {
+        assert_eq!(AdaptiveBuffer::new(10).size(), MIN_BUFFER);
+        assert_eq!(AdaptiveBuffer::new(20_000).size(), 20_000);
+    }
+
+    #[test]
+    fn test_decision_is_injectable_and_clamped() {
+        let mut buffer = AdaptiveBuffer::with_decision(1 << 20, |current, _| current * 16);
+        for _ in 0..WINDOW {
+            buffer.record(sample(1, 1));
+        }
+        assert_eq!(buffer.size(), START_BUFFER * 16);
+        for _ in 0..WINDOW {
+            buffer.record(sample(1, 1));
+        }
+        assert_eq!(buffer.size(), MAX_BUFFER);
+
+        // The copy itself is unaffected by resizing
+        let content: Vec<u8> = (0..1_000_000u32).map(|i| i as u8).collect();
+        let mut buffer = AdaptiveBuffer::with_decision(content.len() as u64, |_, _| MIN_BUFFER);
+        let mut copied = Vec::new();
+        let total = buffer.copy(&mut content.as_slice(), &mut copied).unwrap();
+        assert_eq!(total, content.len() as u64);
+        assert_eq!(copied, content);
+        assert_eq!(buffer.size(), MIN_BUFFER);
+    }
+}
@@ -0,0 +1,257 @@
+//! Compressed archive target mode (experimental, requires the `compression` feature).
+//!
+//! In this mode the right side is not a mirror but an archive: each file is
+//! stored zstd-compressed as `<name>.rahzom.zst`. The frame header records
+//! the original size, so [`unarchive_scan`] can map a scan of the archive
+//! back to original names and sizes, and the usual diff compares against it.
+//! Mtimes of archived files are set to those of their originals.
+
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::hash::HashingReader;
+use super::scanner::{ScanResult, SkippedEntry};
+
+/// Suffix of archived files
+pub const ARCHIVE_SUFFIX: &str = ".rahzom.zst";
+
+/// Whether this build can read and write archives
+pub const AVAILABLE: bool = cfg!(feature = "compression");
+
+/// zstd level: fast enough not to be the bottleneck on a slow link
+#[cfg(feature = "compression")]
+const LEVEL: i32 = 3;
+
+/// Skip reason prefix of archives whose original size can't be read
+const UNREADABLE_ARCHIVE: &str = "Unreadable archive";
+
+/// Upper bound on the size of a zstd frame header
+#[cfg(feature = "compression")]
+const FRAME_HEADER_MAX: usize = 18;
+
+/// Archive name of the file at `path`
+pub fn archive_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(ARCHIVE_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Original name of the archived file at `path`, if it is one. Names that
+/// aren't valid UTF-8 are kept byte for byte.
+pub fn original_path(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.as_encoded_bytes();
+    let original = name.strip_suffix(ARCHIVE_SUFFIX.as_bytes())?;
+    if original.is_empty() {
+        return None;
+    }
+    // SAFETY: the bytes come from an `OsStr` and are split right before an
+    // ASCII suffix, which is a valid boundary of the encoding
+    let original = unsafe { OsStr::from_encoded_bytes_unchecked(original) };
+    Some(path.with_file_name(original))
+}
+
+#[cfg(not(feature = "compression"))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "built without the `compression` feature",
+    )
+}
+
+/// Compresses `size` bytes from `reader` into `writer`, recording the size
+/// and a checksum in the frame. Fails if `reader` yields a different size.
+#[cfg(feature = "compression")]
+pub fn compress<R: Read, W: Write>(reader: &mut R, writer: W, size: u64) -> io::Result<W> {
+    let mut encoder = zstd::stream::write::Encoder::new(writer, LEVEL)?;
+    encoder.set_pledged_src_size(Some(size))?;
+    encoder.include_contentsize(true)?;
+    encoder.include_checksum(true)?;
+    io::copy(reader, &mut encoder)?;
+    encoder.finish()
+}
+
+/// Compresses `size` bytes from `reader` into `writer`
+#[cfg(not(feature = "compression"))]
+pub fn compress<R: Read, W: Write>(_reader: &mut R, _writer: W, _size: u64) -> io::Result<W> {
+    Err(unsupported())
+}
+
+/// Reader of the decompressed content of `reader`
+#[cfg(feature = "compression")]
+pub fn decoder<R: Read>(reader: R) -> io::Result<impl Read> {
+    zstd::stream::read::Decoder::new(reader)
+}
+
+/// Reader of the decompressed content of `reader`
+#[cfg(not(feature = "compression"))]
+pub fn decoder<R: Read>(_reader: R) -> io::Result<impl Read> {
+    Err::<io::Empty, _>(unsupported())
+}
+
+/// Original size of the archived file at `path`, read from its frame header
+#[cfg(feature = "compression")]
+pub fn content_size(path: &Path) -> io::Result<u64> {
+    let mut header = Vec::with_capacity(FRAME_HEADER_MAX);
+    File::open(path)?
+        .take(FRAME_HEADER_MAX as u64)
+        .read_to_end(&mut header)?;
+    match zstd::zstd_safe::get_frame_content_size(&header) {
+        Ok(Some(size)) => Ok(size),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a rahzom archive (no content size in the zstd header)",
+        )),
+    }
+}
+
+/// Original size of the archived file at `path`
+#[cfg(not(feature = "compression"))]
+pub fn content_size(_path: &Path) -> io::Result<u64> {
+    Err(unsupported())
+}
+
+/// SHA-256 of the decompressed content of the archive file at `path`
+pub fn hash_archive_file(path: &Path) -> io::Result<String> {
+    let file = File::open(path)?;
+    let mut reader = HashingReader::new(decoder(BufReader::with_capacity(64 * 1024, file))?);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(reader.finish())
+}
+
+/// SHA-256 of the original content of the file archived for `path`, the
+/// right-side counterpart of `hash_file` in verification
+pub fn hash_archived(path: &Path) -> Result<String> {
+    let archive = archive_path(path);
+    hash_archive_file(&archive).with_context(|| format!("Failed to read archive: {:?}", archive))
+}
+
+/// Maps a scan of an archive to original names and sizes. Files that aren't
+/// archives, or whose size can't be read, are recorded as skipped; an
+/// unreadable archive under its original name, see [`unreadable_archives`].
+pub fn unarchive_scan(scan: &mut ScanResult) {
+    let root = scan.root.clone();
+    let mut skipped = Vec::new();
+    scan.entries.retain_mut(|entry| {
+        if entry.is_dir {
+            return true;
+        }
+        let full_path = root.join(&entry.path);
+        let Some(original) = original_path(&entry.path) else {
+            skipped.push(SkippedEntry {
+                path: full_path,
+                reason: "Not a compressed archive file".to_string(),
+            });
+            return false;
+        };
+        match content_size(&full_path) {
+            Ok(size) => {
+                entry.path = original;
+                entry.size = size;
+                true
+            }
+            Err(e) => {
+                skipped.push(SkippedEntry {
+                    path: root.join(original),
+                    reason: format!("{}: {}", UNREADABLE_ARCHIVE, e),
+                });
+                false
+            }
+        }
+    });
+    scan.entries.sort_by(|a, b| a.path.cmp(&b.path));
+    scan.skipped.extend(skipped);
+}
+
+/// Original paths (relative to the root) of the archives [`unarchive_scan`]
+/// couldn't read. Nothing is known about their content, so they must not
+/// look deleted.
+pub fn unreadable_archives(scan: &ScanResult) -> impl Iterator<Item = &Path> + '_ {
+    scan.skipped
+        .iter()
+        .filter(|s| s.reason.starts_with(UNREADABLE_ARCHIVE))
+        .filter_map(|s| s.path.strip_prefix(&scan.root).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_names() {
+        let archived = archive_path(Path::new("docs/report.pdf"));
+        assert_eq!(archived, Path::new("docs/report.pdf.rahzom.zst"));
+        assert_eq!(
+            original_path(&archived),
+            Some(PathBuf::from("docs/report.pdf"))
+        );
+        assert_eq!(original_path(Path::new("docs/report.pdf")), None);
+        assert_eq!(original_path(Path::new(".rahzom.zst")), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_archive_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        let archived = archive_path(&Path::new("docs").join(name));
+        assert_eq!(original_path(&archived), Some(Path::new("docs").join(name)));
+    }
+
+    #[test]
+    fn test_unreadable_archive_is_reported_by_original_name() {
+        use crate::sync::scanner::scan;
+        use std::fs;
+        use tempfile::TempDir;
+
+        let archive = TempDir::new().unwrap();
+        fs::write(archive.path().join("doc.txt.rahzom.zst"), "not zstd").unwrap();
+
+        let mut result = scan(archive.path()).unwrap();
+        unarchive_scan(&mut result);
+        assert!(result.entries.is_empty());
+        let unreadable: Vec<&Path> = unreadable_archives(&result).collect();
+        assert_eq!(unreadable, [Path::new("doc.txt")]);
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_archive_round_trip() {
+        use crate::sync::hash::hash_file;
+        use crate::sync::scanner::scan;
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp = TempDir::new().unwrap();
+        let original = temp.path().join("data.txt");
+        let content = "compressible ".repeat(1000);
+        fs::write(&original, &content).unwrap();
+
+        let archive = TempDir::new().unwrap();
+        let writer =
+            File::create(archive.path().join(archive_path(Path::new("data.txt")))).unwrap();
+        let mut reader = File::open(&original).unwrap();
+        compress(&mut reader, writer, content.len() as u64).unwrap();
+        fs::write(archive.path().join("stray.txt"), "plain").unwrap();
+
+        let mut result = scan(archive.path()).unwrap();
+        unarchive_scan(&mut result);
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].path, Path::new("data.txt"));
+        assert_eq!(result.entries[0].size, content.len() as u64);
+        assert_eq!(result.skipped.len(), 1);
+
+        assert_eq!(
+            hash_archived(&archive.path().join("data.txt")).unwrap(),
+            hash_file(&original).unwrap()
+        );
+
+        // A pledged size that doesn't match the content is an error
+        let mut short = "abc".as_bytes();
+        assert!(compress(&mut short, Vec::new(), 4).is_err());
+    }
+}
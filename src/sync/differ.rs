@@ -1,22 +1,34 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-use chrono::{DateTime, Utc};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 
-use super::metadata::SyncMetadata;
-use super::scanner::ScanResult;
-use super::utils::FAT32_TOLERANCE_SECS;
+use super::hash::hash_file;
+use super::metadata::{FileAttributes, FileId, SyncMetadata};
+use super::rules::{resolve_clear_winner, ConflictRules};
+use super::scanner::{FileEntry as ScanFileEntry, ScanResult};
+use super::utils::{key_path, path_key, FAT32_TOLERANCE_SECS};
 
 /// Information about a file for conflict reporting
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileInfo {
     pub size: u64,
     pub mtime: DateTime<Utc>,
     pub hash: Option<String>,
+    /// False when the mtime couldn't be read and `mtime` is a placeholder
+    #[serde(default = "default_mtime_known")]
+    pub mtime_known: bool,
+}
+
+fn default_mtime_known() -> bool {
+    true
 }
 
 /// Reason for a sync conflict
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ConflictReason {
     /// Both sides were modified since last sync
     BothModified,
@@ -29,7 +41,7 @@ pub enum ConflictReason {
 }
 
 /// Action to perform during synchronization
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SyncAction {
     /// Copy file from left to right
     CopyToRight { path: PathBuf, size: u64 },
@@ -43,6 +55,18 @@ pub enum SyncAction {
     CreateDirRight { path: PathBuf },
     /// Create directory on left side
     CreateDirLeft { path: PathBuf },
+    /// Apply the left file's attributes to the identical right file
+    UpdateAttributesRight { path: PathBuf },
+    /// Apply the right file's attributes to the identical left file
+    UpdateAttributesLeft { path: PathBuf },
+    /// Conflict resolved by keeping both versions: the right file is saved
+    /// as `copy` on both sides, then the left file replaces it at `path`
+    KeepBoth {
+        path: PathBuf,
+        copy: PathBuf,
+        left_size: u64,
+        right_size: u64,
+    },
     /// Conflict that needs user resolution
     Conflict {
         path: PathBuf,
@@ -64,6 +88,9 @@ impl SyncAction {
             Self::DeleteLeft { path } => path,
             Self::CreateDirRight { path } => path,
             Self::CreateDirLeft { path } => path,
+            Self::UpdateAttributesRight { path } => path,
+            Self::UpdateAttributesLeft { path } => path,
+            Self::KeepBoth { path, .. } => path,
             Self::Conflict { path, .. } => path,
             Self::Skip { path, .. } => path,
         }
@@ -83,9 +110,17 @@ pub struct DiffResult {
     pub files_to_delete: usize,
     /// Number of conflicts
     pub conflicts: usize,
+    /// Why each action was chosen, by path, e.g. "Right changed since the
+    /// last sync; left is unchanged"
+    pub explanations: HashMap<PathBuf, String>,
 }
 
 impl DiffResult {
+    fn add_explained(&mut self, action: SyncAction, why: String) {
+        self.explanations.insert(action.path().clone(), why);
+        self.add_action(action);
+    }
+
     fn add_action(&mut self, action: SyncAction) {
         match &action {
             SyncAction::CopyToRight { size, .. } | SyncAction::CopyToLeft { size, .. } => {
@@ -95,23 +130,143 @@ impl DiffResult {
             SyncAction::DeleteRight { .. } | SyncAction::DeleteLeft { .. } => {
                 self.files_to_delete += 1;
             }
+            SyncAction::KeepBoth {
+                left_size,
+                right_size,
+                ..
+            } => {
+                self.total_bytes_to_transfer += left_size + 2 * right_size;
+                self.files_to_copy += 2;
+            }
             SyncAction::Conflict { .. } => {
                 self.conflicts += 1;
             }
             SyncAction::CreateDirRight { .. } | SyncAction::CreateDirLeft { .. } => {}
+            SyncAction::UpdateAttributesRight { .. } | SyncAction::UpdateAttributesLeft { .. } => {}
             SyncAction::Skip { .. } => {}
         }
         self.actions.push(action);
     }
 }
 
+/// Options that tune how actions are generated
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Don't create directories that contain no files (recursively).
+    /// Such directories appear implicitly once a file is copied into them.
+    pub skip_empty_dirs: bool,
+    /// Treat the right side as a write-once archive: only add new files and
+    /// directories there, never overwrite or delete anything.
+    pub append_only_right: bool,
+    /// Per-pattern resolutions turning matching conflicts into copies/deletes
+    pub conflict_rules: ConflictRules,
+    /// Hybrid mode: both-modified conflicts whose mtimes are further apart
+    /// than this go to the newer side. Per-pattern rules take precedence.
+    pub conflict_age_threshold: Option<Duration>,
+    /// Treat a differing Unix owner/group as a change, so ownership-only
+    /// changes are synced
+    pub compare_ownership: bool,
+    /// Carry over permission, read-only and hidden changes of files whose
+    /// content is the same, without copying them
+    pub compare_attributes: bool,
+}
+
+/// A file that appears to have been renamed or moved on one side since last sync
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameCandidate {
+    /// Path recorded in metadata that no longer exists
+    pub from: PathBuf,
+    /// Newly appeared path holding the same file
+    pub to: PathBuf,
+}
+
+/// Detects renames within one side by matching files that disappeared since
+/// the last sync (in metadata, not in scan) to files that appeared (in scan,
+/// not in metadata). Matches by file id when both have one, otherwise falls
+/// back to size + hash. Empty files all share a hash, so they only match by
+/// id. Only unambiguous one-to-one matches are returned.
+pub fn detect_renames(scan: &ScanResult, meta: &SyncMetadata) -> Vec<RenameCandidate> {
+    let new_files: Vec<&ScanFileEntry> = scan
+        .entries
+        .iter()
+        .filter(|e| !e.is_dir && meta.find_file(&path_key(&e.path)).is_none())
+        .collect();
+    if new_files.is_empty() {
+        return Vec::new();
+    }
+
+    let current: HashSet<String> = scan.entries.iter().map(|e| path_key(&e.path)).collect();
+
+    let mut by_id: HashMap<FileId, Vec<&ScanFileEntry>> = HashMap::new();
+    let mut by_content: HashMap<(u64, &str), Vec<&ScanFileEntry>> = HashMap::new();
+    for entry in &new_files {
+        if let Some(id) = entry.file_id {
+            by_id.entry(id).or_default().push(entry);
+        }
+        if let Some(hash) = entry.hash.as_ref().filter(|_| entry.size > 0) {
+            by_content
+                .entry((entry.size, hash.as_str()))
+                .or_default()
+                .push(entry);
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut claims: HashMap<&Path, usize> = HashMap::new();
+    for old in meta.files.values().filter(|f| !current.contains(&f.path)) {
+        let by_identity = old.file_id.and_then(|id| by_id.get(&id));
+        let candidates = by_identity.or_else(|| {
+            old.hash
+                .as_deref()
+                .filter(|_| old.size > 0)
+                .and_then(|hash| by_content.get(&(old.size, hash)))
+        });
+
+        if let Some([only]) = candidates.map(|c| c.as_slice()) {
+            *claims.entry(only.path.as_path()).or_default() += 1;
+            matches.push(RenameCandidate {
+                from: key_path(&old.path),
+                to: only.path.clone(),
+            });
+        }
+    }
+
+    matches.retain(|m| claims.get(m.to.as_path()) == Some(&1));
+    // Metadata isn't ordered; keep the result stable
+    matches.sort_by(|a, b| a.from.cmp(&b.from));
+    matches
+}
+
+/// Notes in the explanations of `result` which copies and deletes are one
+/// of `renames`, made on `side` since the last sync
+pub fn explain_renames(result: &mut DiffResult, renames: &[RenameCandidate], side: &str) {
+    for rename in renames {
+        for (path, note) in [
+            (
+                &rename.to,
+                format!("renamed from {}", rename.from.display()),
+            ),
+            (&rename.from, format!("renamed to {}", rename.to.display())),
+        ] {
+            if let Some(why) = result.explanations.get_mut(path) {
+                why.push_str(&format!("; {} on the {}", note, side));
+            }
+        }
+    }
+}
+
 /// Entry from scan result for easier processing
 #[derive(Debug, Clone)]
 struct FileEntry {
     size: u64,
     mtime: DateTime<Utc>,
+    mtime_known: bool,
     is_dir: bool,
     hash: Option<String>,
+    /// Unix owner and group, only when ownership is compared
+    owner: Option<(u32, u32)>,
+    /// Only when attributes are compared
+    attributes: Option<FileAttributes>,
 }
 
 /// Compares two scan results with their metadata and produces list of actions.
@@ -126,81 +281,102 @@ pub fn diff(
     right_scan: &ScanResult,
     left_meta: &SyncMetadata,
     right_meta: &SyncMetadata,
+) -> DiffResult {
+    diff_with_options(
+        left_scan,
+        right_scan,
+        left_meta,
+        right_meta,
+        &DiffOptions::default(),
+    )
+}
+
+/// Compares two scan results with their metadata using custom options.
+///
+/// # Arguments
+/// * `left_scan` - Scan result from left side
+/// * `right_scan` - Scan result from right side
+/// * `left_meta` - Metadata from left side (previous state)
+/// * `right_meta` - Metadata from right side (previous state)
+/// * `options` - Options controlling action generation
+pub fn diff_with_options(
+    left_scan: &ScanResult,
+    right_scan: &ScanResult,
+    left_meta: &SyncMetadata,
+    right_meta: &SyncMetadata,
+    options: &DiffOptions,
 ) -> DiffResult {
     let mut result = DiffResult::default();
 
     // Build lookup maps
-    let left_files: HashMap<String, FileEntry> = left_scan
-        .entries
-        .iter()
-        .map(|e| {
-            (
-                e.path.to_string_lossy().to_string(),
-                FileEntry {
-                    size: e.size,
-                    mtime: e.mtime,
-                    is_dir: e.is_dir,
-                    hash: e.hash.clone(),
-                },
-            )
-        })
-        .collect();
+    let left_files = file_entries(left_scan, options);
+    let right_files = file_entries(right_scan, options);
 
-    let right_files: HashMap<String, FileEntry> = right_scan
-        .entries
-        .iter()
-        .map(|e| {
-            (
-                e.path.to_string_lossy().to_string(),
-                FileEntry {
-                    size: e.size,
-                    mtime: e.mtime,
-                    is_dir: e.is_dir,
-                    hash: e.hash.clone(),
-                },
-            )
-        })
-        .collect();
+    // Scan entries are already filtered by exclusions, so a directory is
+    // non-empty only if some non-excluded file lives below it
+    let (left_non_empty, right_non_empty) = if options.skip_empty_dirs {
+        (non_empty_dirs(&left_files), non_empty_dirs(&right_files))
+    } else {
+        (HashSet::new(), HashSet::new())
+    };
 
     // Detect case conflicts: paths that differ only in case
     let case_conflicts = detect_case_conflicts(&left_files, &right_files);
+    let conflict_keys: HashSet<OsString> = case_conflicts.iter().map(|p| case_key(p)).collect();
     for path in &case_conflicts {
         // Find file info from both sides
         let left_entry = left_files.get(path);
         let right_entry = right_files
             .iter()
-            .find(|(p, _)| p.to_lowercase() == path.to_lowercase() && p.as_str() != path)
+            .find(|(p, _)| case_key(p) == case_key(path) && *p != path)
             .map(|(_, e)| e)
             .or_else(|| right_files.get(path));
 
-        result.add_action(SyncAction::Conflict {
-            path: PathBuf::from(path),
+        let action = SyncAction::Conflict {
+            path: path.clone(),
             reason: ConflictReason::CaseConflict,
             left: left_entry.map(|e| FileInfo {
                 size: e.size,
                 mtime: e.mtime,
                 hash: e.hash.clone(),
+                mtime_known: e.mtime_known,
             }),
             right: right_entry.map(|e| FileInfo {
                 size: e.size,
                 mtime: e.mtime,
                 hash: e.hash.clone(),
+                mtime_known: e.mtime_known,
             }),
-        });
+        };
+
+        let mut why = "Names differ only in case".to_string();
+        let action = if options.append_only_right {
+            noted(
+                action,
+                &mut why,
+                "the right side is append-only",
+                |action| restrict_to_append_right(action, &right_files),
+            )
+        } else {
+            action
+        };
+        result.add_explained(action, why);
     }
 
     // Process left side entries
     for (path, left_entry) in &left_files {
         // Skip if already handled as case conflict
-        if case_conflicts.iter().any(|p| p.to_lowercase() == path.to_lowercase()) {
+        if conflict_keys.contains(&case_key(path)) {
             continue;
         }
         let right_entry = right_files.get(path);
-        let left_prev = left_meta.find_file(path);
-        let right_prev = right_meta.find_file(path);
-        let right_deleted = right_meta.find_deleted(path);
+        // Metadata stores paths as strings
+        let meta_path = path_key(path);
+        let left_prev = left_meta.find_file(&meta_path);
+        let right_prev = right_meta.find_file(&meta_path);
+        let right_deleted = right_meta.find_deleted(&meta_path);
 
-        let action = determine_action(
+        let (action, why) = determine_action(
             path,
             Some(left_entry),
             right_entry,
@@ -209,8 +385,17 @@ pub fn diff(
             right_deleted.is_some(),
             false, // left_deleted
         );
-
-        result.add_action(action);
+        let mut why = why.to_string();
+        let synced_before = left_prev.is_some() || right_prev.is_some();
+        let action = adjust_action(
+            action,
+            &mut why,
+            options,
+            synced_before,
+            &left_non_empty,
+            &right_files,
+        );
+        result.add_explained(action, why);
     }
 
     // Process right side entries not on left
@@ -219,15 +404,16 @@ pub fn diff(
             continue; // Already processed
         }
         // Skip if already handled as case conflict
-        if case_conflicts.iter().any(|p| p.to_lowercase() == path.to_lowercase()) {
+        if conflict_keys.contains(&case_key(path)) {
             continue;
         }
 
-        let left_prev = left_meta.find_file(path);
-        let right_prev = right_meta.find_file(path);
-        let left_deleted = left_meta.find_deleted(path);
+        let meta_path = path_key(path);
+        let left_prev = left_meta.find_file(&meta_path);
+        let right_prev = right_meta.find_file(&meta_path);
+        let left_deleted = left_meta.find_deleted(&meta_path);
 
-        let action = determine_action(
+        let (action, why) = determine_action(
             path,
             None,
             Some(right_entry),
@@ -236,8 +422,17 @@ pub fn diff(
             false, // right_deleted
             left_deleted.is_some(),
         );
-
-        result.add_action(action);
+        let mut why = why.to_string();
+        let synced_before = left_prev.is_some() || right_prev.is_some();
+        let action = adjust_action(
+            action,
+            &mut why,
+            options,
+            synced_before,
+            &right_non_empty,
+            &right_files,
+        );
+        result.add_explained(action, why);
     }
 
     // Sort actions: directories first, then files
@@ -256,35 +451,289 @@ pub fn diff(
     result
 }
 
-/// Determines what action to take for a specific path
+/// Quick incremental diff that never looks at the right side.
+///
+/// `right_recorded` stands in for a right scan: the right side as recorded
+/// at the last sync (see [`recorded_scan`]). Left files modified after
+/// `since`, or differing from their recorded state, are copied to the right;
+/// recorded files gone from the left are deleted there. Changes made on the
+/// right outside of rahzom go unnoticed.
+pub fn diff_since(
+    left_scan: &ScanResult,
+    right_recorded: &ScanResult,
+    since: DateTime<Utc>,
+    options: &DiffOptions,
+) -> DiffResult {
+    let mut result = DiffResult::default();
+    let left_files = file_entries(left_scan, options);
+    let right_files = file_entries(right_recorded, options);
+    let left_non_empty = if options.skip_empty_dirs {
+        non_empty_dirs(&left_files)
+    } else {
+        HashSet::new()
+    };
+    let modified_since = |entry: &FileEntry| !entry.mtime_known || entry.mtime > since;
+
+    for (path, left_entry) in &left_files {
+        let (action, why) = if left_entry.is_dir {
+            if !modified_since(left_entry) {
+                continue;
+            }
+            (
+                SyncAction::CreateDirRight { path: path.clone() },
+                "Directory on left modified since the last sync",
+            )
+        } else {
+            let unchanged = right_files
+                .get(path)
+                .is_some_and(|recorded| files_equal(left_entry, recorded));
+            if unchanged && !modified_since(left_entry) {
+                continue;
+            }
+            (
+                SyncAction::CopyToRight {
+                    path: path.clone(),
+                    size: left_entry.size,
+                },
+                if unchanged {
+                    "Left modified since the last sync"
+                } else {
+                    "Left differs from the right side as recorded at the last sync"
+                },
+            )
+        };
+
+        let mut why = why.to_string();
+        let action = adjust_action(
+            action,
+            &mut why,
+            options,
+            true,
+            &left_non_empty,
+            &right_files,
+        );
+        result.add_explained(action, why);
+    }
+
+    for path in right_files.keys() {
+        if left_files.contains_key(path) {
+            continue;
+        }
+        let action = SyncAction::DeleteRight { path: path.clone() };
+        let mut why = "Gone from left since the last sync".to_string();
+        let action = adjust_action(
+            action,
+            &mut why,
+            options,
+            true,
+            &left_non_empty,
+            &right_files,
+        );
+        result.add_explained(action, why);
+    }
+
+    // Directories first, then paths in order
+    result.actions.sort_by(|a, b| {
+        let is_dir = |action: &SyncAction| matches!(action, SyncAction::CreateDirRight { .. });
+        is_dir(b)
+            .cmp(&is_dir(a))
+            .then_with(|| a.path().cmp(b.path()))
+    });
+
+    result
+}
+
+/// The files `meta` records as synced, as a scan of `root`
+pub fn recorded_scan(root: &Path, meta: &SyncMetadata) -> ScanResult {
+    let mut entries: Vec<ScanFileEntry> = meta
+        .files
+        .values()
+        .map(|state| ScanFileEntry {
+            path: key_path(&state.path),
+            size: state.size,
+            mtime: state.mtime,
+            mtime_known: true,
+            is_dir: false,
+            hash: state.hash.clone(),
+            attributes: state.attributes.clone(),
+            file_id: state.file_id,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    ScanResult {
+        root: root.to_path_buf(),
+        entries,
+        scan_time: meta.last_sync.unwrap_or_else(Utc::now),
+        skipped: Vec::new(),
+        partials: Vec::new(),
+    }
+}
+
+/// Start of the reason of a copy or delete skipped because the file's
+/// content is on the other side under another name
+pub const CONTENT_ELSEWHERE: &str = "Content exists elsewhere";
+
+/// Turns copies and deletes of files whose content is on the other side
+/// under another name into skips, so neither name is touched. A file is
+/// matched only against files whose name the other side lacks: same size
+/// first, then the same hash. Left files are hashed with `hash_file`,
+/// right ones with `hash_right`; a file that can't be read matches nothing.
+pub fn skip_content_elsewhere(
+    result: &mut DiffResult,
+    left: &ScanResult,
+    right: &ScanResult,
+    hash_right: fn(&Path) -> Result<String>,
+) {
+    let mut left_side = ContentSide::new(left, right, hash_file);
+    let mut right_side = ContentSide::new(right, left, hash_right);
+
+    let actions = std::mem::take(&mut result.actions);
+    *result = DiffResult {
+        explanations: std::mem::take(&mut result.explanations),
+        ..DiffResult::default()
+    };
+    for action in actions {
+        let elsewhere = match &action {
+            SyncAction::CopyToRight { path, .. } | SyncAction::DeleteLeft { path } => {
+                content_elsewhere(&mut left_side, &mut right_side, path)
+            }
+            SyncAction::CopyToLeft { path, .. } | SyncAction::DeleteRight { path } => {
+                content_elsewhere(&mut right_side, &mut left_side, path)
+            }
+            _ => None,
+        };
+        if let Some(other) = elsewhere {
+            if let Some(why) = result.explanations.get_mut(action.path()) {
+                why.push_str(&format!(
+                    "; its content is on the other side as {}",
+                    other.display()
+                ));
+            }
+        }
+        result.add_action(match elsewhere {
+            Some(other) => SyncAction::Skip {
+                path: action.path().clone(),
+                reason: format!("{}: {}", CONTENT_ELSEWHERE, other.display()),
+            },
+            None => action,
+        });
+    }
+}
+
+/// One side's files for [`skip_content_elsewhere`], hashed as needed
+struct ContentSide<'a> {
+    root: &'a Path,
+    sizes: HashMap<&'a Path, u64>,
+    /// Non-empty files whose name the other side doesn't have, by size
+    unmatched: HashMap<u64, Vec<&'a Path>>,
+    hash: fn(&Path) -> Result<String>,
+    hashes: HashMap<&'a Path, Option<String>>,
+}
+
+impl<'a> ContentSide<'a> {
+    fn new(scan: &'a ScanResult, other: &ScanResult, hash: fn(&Path) -> Result<String>) -> Self {
+        let other_paths: HashSet<&Path> = other.entries.iter().map(|e| e.path.as_path()).collect();
+        let mut sizes = HashMap::new();
+        let mut unmatched: HashMap<u64, Vec<&Path>> = HashMap::new();
+        for entry in scan.entries.iter().filter(|e| !e.is_dir) {
+            sizes.insert(entry.path.as_path(), entry.size);
+            if entry.size > 0 && !other_paths.contains(entry.path.as_path()) {
+                unmatched.entry(entry.size).or_default().push(&entry.path);
+            }
+        }
+        Self {
+            root: &scan.root,
+            sizes,
+            unmatched,
+            hash,
+            hashes: HashMap::new(),
+        }
+    }
+
+    fn hash(&mut self, path: &'a Path) -> Option<String> {
+        let (root, hash) = (self.root, self.hash);
+        self.hashes
+            .entry(path)
+            .or_insert_with(|| hash(&root.join(path)).ok())
+            .clone()
+    }
+}
+
+/// A file of `other` holding the content of `this` side's file at `path`
+/// under another name
+fn content_elsewhere<'a>(
+    this: &mut ContentSide<'a>,
+    other: &mut ContentSide<'a>,
+    path: &Path,
+) -> Option<&'a Path> {
+    let (&path, &size) = this.sizes.get_key_value(path)?;
+    let candidates = other.unmatched.get(&size)?.clone();
+    let hash = this.hash(path)?;
+    candidates
+        .into_iter()
+        .find(|&candidate| other.hash(candidate).as_ref() == Some(&hash))
+}
+
+/// Lookup map of a scan's entries, keyed by the raw path so names that
+/// aren't valid UTF-8 are never collapsed into one
+fn file_entries(scan: &ScanResult, options: &DiffOptions) -> HashMap<PathBuf, FileEntry> {
+    scan.entries
+        .iter()
+        .map(|e| {
+            (
+                e.path.clone(),
+                FileEntry {
+                    size: e.size,
+                    mtime: e.mtime,
+                    mtime_known: e.mtime_known,
+                    is_dir: e.is_dir,
+                    hash: e.hash.clone(),
+                    owner: e.attributes.owner().filter(|_| options.compare_ownership),
+                    attributes: Some(e.attributes.clone()).filter(|_| options.compare_attributes),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Determines what action to take for a specific path, and why
 fn determine_action(
-    path: &str,
+    path: &Path,
     left: Option<&FileEntry>,
     right: Option<&FileEntry>,
     left_prev: Option<&super::metadata::FileState>,
     right_prev: Option<&super::metadata::FileState>,
     right_deleted: bool,
     left_deleted: bool,
-) -> SyncAction {
-    let path_buf = PathBuf::from(path);
+) -> (SyncAction, &'static str) {
+    let path_buf = path.to_path_buf();
 
     match (left, right) {
         // File exists on both sides
         (Some(l), Some(r)) => {
             // Handle directories
             if l.is_dir && r.is_dir {
-                return SyncAction::Skip {
-                    path: path_buf,
-                    reason: "Directory exists on both sides".to_string(),
-                };
+                return (
+                    SyncAction::Skip {
+                        path: path_buf,
+                        reason: "Directory exists on both sides".to_string(),
+                    },
+                    "Directory on both sides",
+                );
             }
 
             // Check if files are the same (within FAT32 tolerance)
             if files_equal(l, r) {
-                return SyncAction::Skip {
-                    path: path_buf,
-                    reason: "Files are identical".to_string(),
-                };
+                if let Some(update) = attribute_update(path, l, r, left_prev, right_prev) {
+                    return update;
+                }
+                return (
+                    SyncAction::Skip {
+                        path: path_buf,
+                        reason: "Files are identical".to_string(),
+                    },
+                    "Same size and mtime on both sides",
+                );
             }
 
             // Files differ - check what changed
@@ -292,146 +741,322 @@ fn determine_action(
             let right_changed = right_prev.is_none() || file_changed_since(r, right_prev.unwrap());
 
             match (left_changed, right_changed) {
-                (true, true) => SyncAction::Conflict {
-                    path: path_buf,
-                    reason: ConflictReason::BothModified,
-                    left: Some(FileInfo {
+                (true, true) => (
+                    SyncAction::Conflict {
+                        path: path_buf,
+                        reason: ConflictReason::BothModified,
+                        left: Some(FileInfo {
+                            size: l.size,
+                            mtime: l.mtime,
+                            hash: l.hash.clone(),
+                            mtime_known: l.mtime_known,
+                        }),
+                        right: Some(FileInfo {
+                            size: r.size,
+                            mtime: r.mtime,
+                            hash: r.hash.clone(),
+                            mtime_known: r.mtime_known,
+                        }),
+                    },
+                    if left_prev.is_none() && right_prev.is_none() {
+                        "Files differ and neither side was synced before"
+                    } else {
+                        "Both sides changed since the last sync"
+                    },
+                ),
+                (true, false) => (
+                    SyncAction::CopyToRight {
+                        path: path_buf,
                         size: l.size,
-                        mtime: l.mtime,
-                        hash: l.hash.clone(),
-                    }),
-                    right: Some(FileInfo {
+                    },
+                    if left_prev.is_none() {
+                        "Left was never synced; right is unchanged since the last sync"
+                    } else {
+                        "Left changed since the last sync; right is unchanged"
+                    },
+                ),
+                (false, true) => (
+                    SyncAction::CopyToLeft {
+                        path: path_buf,
                         size: r.size,
-                        mtime: r.mtime,
-                        hash: r.hash.clone(),
-                    }),
-                },
-                (true, false) => SyncAction::CopyToRight {
-                    path: path_buf,
-                    size: l.size,
-                },
-                (false, true) => SyncAction::CopyToLeft {
-                    path: path_buf,
-                    size: r.size,
-                },
-                (false, false) => SyncAction::Skip {
-                    path: path_buf,
-                    reason: "No changes detected".to_string(),
-                },
+                    },
+                    if right_prev.is_none() {
+                        "Right was never synced; left is unchanged since the last sync"
+                    } else {
+                        "Right changed since the last sync; left is unchanged"
+                    },
+                ),
+                (false, false) => (
+                    SyncAction::Skip {
+                        path: path_buf,
+                        reason: "No changes detected".to_string(),
+                    },
+                    "Neither side changed since the last sync",
+                ),
             }
         }
 
         // File only on left side
         (Some(l), None) => {
             if l.is_dir {
-                return SyncAction::CreateDirRight { path: path_buf };
+                return (
+                    SyncAction::CreateDirRight { path: path_buf },
+                    "Directory only on left",
+                );
             }
 
             if right_deleted {
                 // Was deleted on right - conflict
-                SyncAction::Conflict {
-                    path: path_buf,
-                    reason: ConflictReason::ExistsVsDeleted,
-                    left: Some(FileInfo {
-                        size: l.size,
-                        mtime: l.mtime,
-                        hash: l.hash.clone(),
-                    }),
-                    right: None,
-                }
-            } else if right_prev.is_some() {
-                // Existed before on right but now gone - was deleted
-                let left_changed = left_prev.is_none() || file_changed_since(l, left_prev.unwrap());
-                if left_changed {
-                    // Modified on left, deleted on right - conflict
+                (
                     SyncAction::Conflict {
                         path: path_buf,
-                        reason: ConflictReason::ModifiedAndDeleted,
+                        reason: ConflictReason::ExistsVsDeleted,
                         left: Some(FileInfo {
                             size: l.size,
                             mtime: l.mtime,
                             hash: l.hash.clone(),
+                            mtime_known: l.mtime_known,
                         }),
                         right: None,
-                    }
+                    },
+                    "Recorded as deleted on right, but present on left",
+                )
+            } else if right_prev.is_some() {
+                // Existed before on right but now gone - was deleted
+                let left_changed = left_prev.is_none() || file_changed_since(l, left_prev.unwrap());
+                if left_changed {
+                    // Modified on left, deleted on right - conflict
+                    (
+                        SyncAction::Conflict {
+                            path: path_buf,
+                            reason: ConflictReason::ModifiedAndDeleted,
+                            left: Some(FileInfo {
+                                size: l.size,
+                                mtime: l.mtime,
+                                hash: l.hash.clone(),
+                                mtime_known: l.mtime_known,
+                            }),
+                            right: None,
+                        },
+                        "Deleted on right; left changed since the last sync",
+                    )
                 } else {
                     // Not modified on left, deleted on right - delete left
-                    SyncAction::DeleteLeft { path: path_buf }
+                    (
+                        SyncAction::DeleteLeft { path: path_buf },
+                        "Deleted on right since the last sync; left is unchanged",
+                    )
                 }
             } else {
                 // New file on left - copy to right
-                SyncAction::CopyToRight {
-                    path: path_buf,
-                    size: l.size,
-                }
+                (
+                    SyncAction::CopyToRight {
+                        path: path_buf,
+                        size: l.size,
+                    },
+                    "New on left",
+                )
             }
         }
 
         // File only on right side
         (None, Some(r)) => {
             if r.is_dir {
-                return SyncAction::CreateDirLeft { path: path_buf };
+                return (
+                    SyncAction::CreateDirLeft { path: path_buf },
+                    "Directory only on right",
+                );
             }
 
             if left_deleted {
                 // Was deleted on left - conflict
-                SyncAction::Conflict {
-                    path: path_buf,
-                    reason: ConflictReason::ExistsVsDeleted,
-                    left: None,
-                    right: Some(FileInfo {
-                        size: r.size,
-                        mtime: r.mtime,
-                        hash: r.hash.clone(),
-                    }),
-                }
-            } else if left_prev.is_some() {
-                // Existed before on left but now gone - was deleted
-                let right_changed =
-                    right_prev.is_none() || file_changed_since(r, right_prev.unwrap());
-                if right_changed {
-                    // Modified on right, deleted on left - conflict
+                (
                     SyncAction::Conflict {
                         path: path_buf,
-                        reason: ConflictReason::ModifiedAndDeleted,
+                        reason: ConflictReason::ExistsVsDeleted,
                         left: None,
                         right: Some(FileInfo {
                             size: r.size,
                             mtime: r.mtime,
                             hash: r.hash.clone(),
+                            mtime_known: r.mtime_known,
                         }),
-                    }
+                    },
+                    "Recorded as deleted on left, but present on right",
+                )
+            } else if left_prev.is_some() {
+                // Existed before on left but now gone - was deleted
+                let right_changed =
+                    right_prev.is_none() || file_changed_since(r, right_prev.unwrap());
+                if right_changed {
+                    // Modified on right, deleted on left - conflict
+                    (
+                        SyncAction::Conflict {
+                            path: path_buf,
+                            reason: ConflictReason::ModifiedAndDeleted,
+                            left: None,
+                            right: Some(FileInfo {
+                                size: r.size,
+                                mtime: r.mtime,
+                                hash: r.hash.clone(),
+                                mtime_known: r.mtime_known,
+                            }),
+                        },
+                        "Deleted on left; right changed since the last sync",
+                    )
                 } else {
                     // Not modified on right, deleted on left - delete right
-                    SyncAction::DeleteRight { path: path_buf }
+                    (
+                        SyncAction::DeleteRight { path: path_buf },
+                        "Deleted on left since the last sync; right is unchanged",
+                    )
                 }
             } else {
                 // New file on right - copy to left
-                SyncAction::CopyToLeft {
-                    path: path_buf,
-                    size: r.size,
-                }
+                (
+                    SyncAction::CopyToLeft {
+                        path: path_buf,
+                        size: r.size,
+                    },
+                    "New on right",
+                )
             }
         }
 
         // File on neither side (shouldn't happen)
-        (None, None) => SyncAction::Skip {
-            path: path_buf,
-            reason: "File not found on either side".to_string(),
+        (None, None) => (
+            SyncAction::Skip {
+                path: path_buf,
+                reason: "File not found on either side".to_string(),
+            },
+            "Missing on both sides",
+        ),
+    }
+}
+
+/// Collects all directories that contain at least one file (recursively).
+fn non_empty_dirs(files: &HashMap<PathBuf, FileEntry>) -> HashSet<PathBuf> {
+    let mut dirs = HashSet::new();
+    for (path, entry) in files {
+        if entry.is_dir {
+            continue;
+        }
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            if !dirs.insert(ancestor.to_path_buf()) {
+                break; // Remaining ancestors were added by a sibling
+            }
+        }
+    }
+    dirs
+}
+
+/// Applies the option-driven adjustments to a determined action, noting in
+/// `why` each one that changed it. `synced_before` tells whether either side
+/// has a record of the path from a previous sync.
+fn adjust_action(
+    action: SyncAction,
+    why: &mut String,
+    options: &DiffOptions,
+    synced_before: bool,
+    source_non_empty: &HashSet<PathBuf>,
+    right_files: &HashMap<PathBuf, FileEntry>,
+) -> SyncAction {
+    let mut action = action;
+    if options.skip_empty_dirs {
+        action = noted(action, why, "the directory has no files", |action| {
+            skip_if_empty_dir(action, source_non_empty)
+        });
+    }
+    action = noted(action, why, "resolved by a conflict rule", |action| {
+        options.conflict_rules.apply(action)
+    });
+    // Without a previous sync neither side is known to hold an edit
+    if let Some(threshold) = options.conflict_age_threshold.filter(|_| synced_before) {
+        action = noted(
+            action,
+            why,
+            "the edits are far enough apart for the newer to win",
+            |action| resolve_clear_winner(action, threshold),
+        );
+    }
+    if options.append_only_right {
+        action = noted(action, why, "the right side is append-only", |action| {
+            restrict_to_append_right(action, right_files)
+        });
+    }
+    action
+}
+
+/// Runs `step` on `action`, appending `how` to `why` if it changed the kind
+/// of action
+fn noted(
+    action: SyncAction,
+    why: &mut String,
+    how: &str,
+    step: impl FnOnce(SyncAction) -> SyncAction,
+) -> SyncAction {
+    let before = std::mem::discriminant(&action);
+    let action = step(action);
+    if std::mem::discriminant(&action) != before {
+        why.push_str("; ");
+        why.push_str(how);
+    }
+    action
+}
+
+/// Turns a directory creation into a skip if the source directory has no files.
+fn skip_if_empty_dir(action: SyncAction, source_non_empty: &HashSet<PathBuf>) -> SyncAction {
+    match action {
+        SyncAction::CreateDirRight { path } | SyncAction::CreateDirLeft { path }
+            if !source_non_empty.contains(&path) =>
+        {
+            SyncAction::Skip {
+                path,
+                reason: "Empty directory".to_string(),
+            }
+        }
+        other => other,
+    }
+}
+
+/// Keeps only actions that add new content to the right side; everything
+/// that would modify either side's existing data becomes a skip.
+fn restrict_to_append_right(
+    action: SyncAction,
+    right_files: &HashMap<PathBuf, FileEntry>,
+) -> SyncAction {
+    match action {
+        SyncAction::CopyToRight { ref path, .. } if !right_files.contains_key(path) => action,
+        SyncAction::CreateDirRight { .. } | SyncAction::Skip { .. } => action,
+        other => SyncAction::Skip {
+            path: other.path().clone(),
+            reason: "Append-only".to_string(),
         },
     }
 }
 
-/// Checks if two files are equal (considering FAT32 time tolerance)
+/// Checks if two files are equal (considering FAT32 time tolerance).
+/// Entries without a known mtime are compared by size and hash only.
 fn files_equal(a: &FileEntry, b: &FileEntry) -> bool {
     if a.size != b.size {
         return false;
     }
 
     // Check mtime with FAT32 tolerance
-    let time_diff = (a.mtime - b.mtime).num_seconds().abs();
-    if time_diff > FAT32_TOLERANCE_SECS {
-        return false;
+    if a.mtime_known && b.mtime_known {
+        let time_diff = (a.mtime - b.mtime).num_seconds().abs();
+        if time_diff > FAT32_TOLERANCE_SECS {
+            return false;
+        }
+    }
+
+    if let (Some(oa), Some(ob)) = (a.owner, b.owner) {
+        if oa != ob {
+            return false;
+        }
     }
 
     // If hashes are available, compare them
@@ -442,15 +1067,55 @@ fn files_equal(a: &FileEntry, b: &FileEntry) -> bool {
     true
 }
 
+/// An attribute-only update for identical files whose attributes differ,
+/// towards the side that didn't change them since the last sync. `None` if
+/// they match or attributes aren't compared.
+fn attribute_update(
+    path: &Path,
+    l: &FileEntry,
+    r: &FileEntry,
+    left_prev: Option<&super::metadata::FileState>,
+    right_prev: Option<&super::metadata::FileState>,
+) -> Option<(SyncAction, &'static str)> {
+    let (la, ra) = (l.attributes.as_ref()?, r.attributes.as_ref()?);
+    if la.same_permissions(ra) {
+        return None;
+    }
+    // Without a recorded state either side may have changed
+    let changed = |current: &FileAttributes, prev: Option<&super::metadata::FileState>| {
+        prev.is_none_or(|prev| !current.same_permissions(&prev.attributes))
+    };
+    let path = path.to_path_buf();
+    Some(match (changed(la, left_prev), changed(ra, right_prev)) {
+        (true, false) => (
+            SyncAction::UpdateAttributesRight { path },
+            "Same content; left attributes changed since the last sync",
+        ),
+        (false, true) => (
+            SyncAction::UpdateAttributesLeft { path },
+            "Same content; right attributes changed since the last sync",
+        ),
+        _ => (
+            SyncAction::Skip {
+                path,
+                reason: "Only attributes differ; can't tell which side changed".to_string(),
+            },
+            "Same content, attributes differ on both sides since the last sync",
+        ),
+    })
+}
+
 /// Checks if a file has changed since the recorded state
 fn file_changed_since(current: &FileEntry, prev: &super::metadata::FileState) -> bool {
     if current.size != prev.size {
         return true;
     }
 
-    let time_diff = (current.mtime - prev.mtime).num_seconds().abs();
-    if time_diff > FAT32_TOLERANCE_SECS {
-        return true;
+    if current.mtime_known {
+        let time_diff = (current.mtime - prev.mtime).num_seconds().abs();
+        if time_diff > FAT32_TOLERANCE_SECS {
+            return true;
+        }
     }
 
     // If hashes available and differ, file changed
@@ -460,34 +1125,42 @@ fn file_changed_since(current: &FileEntry, prev: &super::metadata::FileState) ->
         }
     }
 
+    // Owner recorded at the last sync differs (only when ownership is compared)
+    if let (Some(oc), Some(op)) = (current.owner, prev.attributes.owner()) {
+        if oc != op {
+            return true;
+        }
+    }
+
     false
 }
 
+/// Case-insensitive comparison key. Names that aren't valid UTF-8 are
+/// compared byte for byte, since case folding them would be lossy.
+fn case_key(path: &Path) -> OsString {
+    match path.to_str() {
+        Some(s) => s.to_lowercase().into(),
+        None => path.as_os_str().to_os_string(),
+    }
+}
+
 /// Detects paths that differ only in case between left and right sides.
 /// Returns list of paths (from left side) that have case conflicts.
 fn detect_case_conflicts(
-    left_files: &HashMap<String, FileEntry>,
-    right_files: &HashMap<String, FileEntry>,
-) -> Vec<String> {
-    use std::collections::HashSet;
-
+    left_files: &HashMap<PathBuf, FileEntry>,
+    right_files: &HashMap<PathBuf, FileEntry>,
+) -> Vec<PathBuf> {
     let mut conflicts = HashSet::new();
 
     // Build case-normalized maps
-    let mut left_by_case: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut left_by_case: HashMap<OsString, Vec<&Path>> = HashMap::new();
     for path in left_files.keys() {
-        left_by_case
-            .entry(path.to_lowercase())
-            .or_default()
-            .push(path);
+        left_by_case.entry(case_key(path)).or_default().push(path);
     }
 
-    let mut right_by_case: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut right_by_case: HashMap<OsString, Vec<&Path>> = HashMap::new();
     for path in right_files.keys() {
-        right_by_case
-            .entry(path.to_lowercase())
-            .or_default()
-            .push(path);
+        right_by_case.entry(case_key(path)).or_default().push(path);
     }
 
     // Check for conflicts within left side (multiple paths with same lowercase)
@@ -495,7 +1168,7 @@ fn detect_case_conflicts(
         if paths.len() > 1 {
             // Multiple files with same case-insensitive name on left
             for path in paths {
-                conflicts.insert((*path).to_string());
+                conflicts.insert(path.to_path_buf());
             }
         }
     }
@@ -504,7 +1177,7 @@ fn detect_case_conflicts(
     for paths in right_by_case.values() {
         if paths.len() > 1 {
             for path in paths {
-                conflicts.insert((*path).to_string());
+                conflicts.insert(path.to_path_buf());
             }
         }
     }
@@ -517,7 +1190,7 @@ fn detect_case_conflicts(
                 for rp in right_paths {
                     if lp != rp {
                         // Case conflict between sides
-                        conflicts.insert((*lp).to_string());
+                        conflicts.insert(lp.to_path_buf());
                     }
                 }
             }
@@ -539,9 +1212,11 @@ mod tests {
             path: PathBuf::from(path),
             size,
             mtime,
+            mtime_known: true,
             is_dir: false,
             hash: None,
             attributes: FileAttributes::default(),
+            file_id: None,
         }
     }
 
@@ -550,9 +1225,11 @@ mod tests {
             path: PathBuf::from(path),
             size: 0,
             mtime: Utc::now(),
+            mtime_known: true,
             is_dir: true,
             hash: None,
             attributes: FileAttributes::default(),
+            file_id: None,
         }
     }
 
@@ -563,6 +1240,7 @@ mod tests {
             mtime,
             hash: None,
             attributes: FileAttributes::default(),
+            file_id: None,
             last_synced: Utc::now(),
         }
     }
@@ -573,6 +1251,7 @@ mod tests {
             entries: vec![],
             scan_time: Utc::now(),
             skipped: vec![],
+            partials: vec![],
         }
     }
 
@@ -598,6 +1277,55 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_diff_since_copies_changes_after_last_sync() {
+        let since = Utc::now() - Duration::hours(1);
+        let old = since - Duration::days(1);
+
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.extend([
+            make_scan_entry("old.txt", 10, old),
+            make_scan_entry("recent.txt", 20, Utc::now()),
+            make_scan_entry("restored.txt", 30, old),
+        ]);
+        let mut right_meta = SyncMetadata::new();
+        for state in [
+            make_file_state("old.txt", 10, old),
+            make_file_state("recent.txt", 20, old),
+            make_file_state("gone.txt", 40, old),
+        ] {
+            right_meta.upsert_file(state);
+        }
+        let recorded = recorded_scan(Path::new("/right"), &right_meta);
+
+        let result = diff_since(&left_scan, &recorded, since, &DiffOptions::default());
+        assert_eq!(
+            result.actions,
+            vec![
+                SyncAction::DeleteRight {
+                    path: PathBuf::from("gone.txt")
+                },
+                SyncAction::CopyToRight {
+                    path: PathBuf::from("recent.txt"),
+                    size: 20
+                },
+                // Old mtime, but never synced
+                SyncAction::CopyToRight {
+                    path: PathBuf::from("restored.txt"),
+                    size: 30
+                },
+            ]
+        );
+
+        let options = DiffOptions {
+            append_only_right: true,
+            ..Default::default()
+        };
+        let result = diff_since(&left_scan, &recorded, since, &options);
+        assert_eq!(result.files_to_copy, 1);
+        assert_eq!(result.files_to_delete, 0);
+    }
+
     #[test]
     fn test_new_file_on_right_copies_to_left() {
         let now = Utc::now();
@@ -659,14 +1387,10 @@ mod tests {
             .push(make_scan_entry("file.txt", 100, old_time));
 
         let mut left_meta = SyncMetadata::new();
-        left_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        left_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let mut right_meta = SyncMetadata::new();
-        right_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        right_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
 
@@ -675,6 +1399,10 @@ mod tests {
             &result.actions[0],
             SyncAction::CopyToRight { path, .. } if path == &PathBuf::from("file.txt")
         ));
+        assert_eq!(
+            result.explanations[&PathBuf::from("file.txt")],
+            "Left changed since the last sync; right is unchanged"
+        );
     }
 
     #[test]
@@ -693,14 +1421,10 @@ mod tests {
             .push(make_scan_entry("file.txt", 200, new_time));
 
         let mut left_meta = SyncMetadata::new();
-        left_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        left_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let mut right_meta = SyncMetadata::new();
-        right_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        right_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
 
@@ -714,6 +1438,92 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_conflict_rules_resolve_matching_conflicts() {
+        use crate::sync::rules::{ConflictResolution, ConflictRule};
+
+        let old_time = Utc::now() - Duration::hours(1);
+        let new_time = Utc::now();
+
+        let mut left_scan = empty_scan("/left");
+        let mut right_scan = empty_scan("/right");
+        let mut left_meta = SyncMetadata::new();
+        let mut right_meta = SyncMetadata::new();
+        for path in ["server.log", "notes.txt"] {
+            left_scan.entries.push(make_scan_entry(path, 150, new_time));
+            right_scan
+                .entries
+                .push(make_scan_entry(path, 200, new_time));
+            left_meta.upsert_file(make_file_state(path, 100, old_time));
+            right_meta.upsert_file(make_file_state(path, 100, old_time));
+        }
+
+        let options = DiffOptions {
+            conflict_rules: ConflictRules::compile(&[ConflictRule {
+                pattern: "*.log".to_string(),
+                resolution: ConflictResolution::KeepRight,
+            }])
+            .unwrap(),
+            ..Default::default()
+        };
+        let result = diff_with_options(&left_scan, &right_scan, &left_meta, &right_meta, &options);
+
+        assert_eq!(result.conflicts, 1);
+        assert_eq!(result.files_to_copy, 1);
+        assert!(result.actions.contains(&SyncAction::CopyToLeft {
+            path: PathBuf::from("server.log"),
+            size: 200
+        }));
+        assert_eq!(
+            result.explanations[&PathBuf::from("server.log")],
+            "Both sides changed since the last sync; resolved by a conflict rule"
+        );
+        assert_eq!(
+            result.explanations[&PathBuf::from("notes.txt")],
+            "Both sides changed since the last sync"
+        );
+    }
+
+    #[test]
+    fn test_conflict_age_threshold_resolves_clear_winners() {
+        let synced = Utc::now() - Duration::hours(3);
+        let now = Utc::now();
+
+        let mut left_scan = empty_scan("/left");
+        let mut right_scan = empty_scan("/right");
+        let mut left_meta = SyncMetadata::new();
+        let mut right_meta = SyncMetadata::new();
+        // (path, left mtime, right mtime)
+        for (path, left, right) in [
+            ("stale.txt", now - Duration::hours(2), now),
+            ("close.txt", now - Duration::seconds(30), now),
+        ] {
+            left_scan.entries.push(make_scan_entry(path, 150, left));
+            right_scan.entries.push(make_scan_entry(path, 200, right));
+            left_meta.upsert_file(make_file_state(path, 100, synced));
+            right_meta.upsert_file(make_file_state(path, 100, synced));
+        }
+        // Never synced: far apart, but neither is known to be an edit
+        left_scan
+            .entries
+            .push(make_scan_entry("new.txt", 150, now - Duration::hours(2)));
+        right_scan
+            .entries
+            .push(make_scan_entry("new.txt", 200, now));
+
+        let options = DiffOptions {
+            conflict_age_threshold: Some(Duration::minutes(5)),
+            ..Default::default()
+        };
+        let result = diff_with_options(&left_scan, &right_scan, &left_meta, &right_meta, &options);
+
+        assert_eq!(result.conflicts, 2);
+        assert!(result.actions.contains(&SyncAction::CopyToLeft {
+            path: PathBuf::from("stale.txt"),
+            size: 200
+        }));
+    }
+
     #[test]
     fn test_deleted_left_unchanged_right_deletes_right() {
         let old_time = Utc::now() - Duration::hours(1);
@@ -726,14 +1536,10 @@ mod tests {
             .push(make_scan_entry("file.txt", 100, old_time));
 
         let mut left_meta = SyncMetadata::new();
-        left_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        left_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let mut right_meta = SyncMetadata::new();
-        right_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        right_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
 
@@ -757,14 +1563,10 @@ mod tests {
             .push(make_scan_entry("file.txt", 200, new_time));
 
         let mut left_meta = SyncMetadata::new();
-        left_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        left_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let mut right_meta = SyncMetadata::new();
-        right_meta
-            .files
-            .push(make_file_state("file.txt", 100, old_time));
+        right_meta.upsert_file(make_file_state("file.txt", 100, old_time));
 
         let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
 
@@ -802,6 +1604,65 @@ mod tests {
         assert!(matches!(&result.actions[0], SyncAction::Skip { .. }));
     }
 
+    #[test]
+    fn test_unknown_mtime_compares_by_size_and_hash() {
+        let now = Utc::now();
+
+        let mut unknown = make_scan_entry("file.txt", 100, DateTime::UNIX_EPOCH);
+        unknown.mtime_known = false;
+
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(unknown.clone());
+
+        let mut right_scan = empty_scan("/right");
+        right_scan
+            .entries
+            .push(make_scan_entry("file.txt", 100, now));
+
+        let left_meta = SyncMetadata::new();
+        let right_meta = SyncMetadata::new();
+
+        let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+        assert!(matches!(&result.actions[0], SyncAction::Skip { .. }));
+
+        // Differing hashes still mark the files as different
+        left_scan.entries[0].hash = Some("aaa".to_string());
+        right_scan.entries[0].hash = Some("bbb".to_string());
+
+        let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+        assert_eq!(result.conflicts, 1);
+    }
+
+    #[test]
+    fn test_unknown_mtime_unchanged_since_last_sync() {
+        let old_time = Utc::now() - Duration::hours(1);
+
+        let mut left_entry = make_scan_entry("file.txt", 150, DateTime::UNIX_EPOCH);
+        left_entry.mtime_known = false;
+
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(left_entry);
+
+        let mut right_scan = empty_scan("/right");
+        right_scan
+            .entries
+            .push(make_scan_entry("file.txt", 200, Utc::now()));
+
+        let mut left_meta = SyncMetadata::new();
+        left_meta.upsert_file(make_file_state("file.txt", 150, old_time));
+
+        let mut right_meta = SyncMetadata::new();
+        right_meta.upsert_file(make_file_state("file.txt", 150, old_time));
+
+        let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+
+        // Left size matches the recorded state, so only the right side changed
+        assert!(matches!(
+            &result.actions[0],
+            SyncAction::CopyToLeft { path, .. } if path == &PathBuf::from("file.txt")
+        ));
+    }
+
     #[test]
     fn test_first_sync_no_metadata() {
         let now = Utc::now();
@@ -866,4 +1727,446 @@ mod tests {
 
         assert!(matches!(&result.actions[0], SyncAction::Skip { .. }));
     }
+
+    #[test]
+    fn test_skip_empty_dirs_suppresses_nested_empty_dirs() {
+        let now = Utc::now();
+
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(make_dir_entry("empty"));
+        left_scan.entries.push(make_dir_entry("empty/nested"));
+        left_scan
+            .entries
+            .push(make_dir_entry("empty/nested/deeper"));
+        left_scan.entries.push(make_dir_entry("full"));
+        left_scan.entries.push(make_dir_entry("full/sub"));
+        left_scan
+            .entries
+            .push(make_scan_entry("full/sub/file.txt", 10, now));
+
+        let right_scan = empty_scan("/right");
+        let left_meta = SyncMetadata::new();
+        let right_meta = SyncMetadata::new();
+
+        let options = DiffOptions {
+            skip_empty_dirs: true,
+            ..Default::default()
+        };
+        let result = diff_with_options(&left_scan, &right_scan, &left_meta, &right_meta, &options);
+
+        let created: HashSet<PathBuf> = result
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                SyncAction::CreateDirRight { path } => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            created,
+            HashSet::from([PathBuf::from("full"), PathBuf::from("full/sub")])
+        );
+
+        for empty in ["empty", "empty/nested", "empty/nested/deeper"] {
+            assert!(result.actions.iter().any(|a| matches!(
+                a,
+                SyncAction::Skip { path, .. } if path == &PathBuf::from(empty)
+            )));
+        }
+        assert_eq!(result.files_to_copy, 1);
+    }
+
+    #[test]
+    fn test_append_only_right_suppresses_overwrites_and_deletes() {
+        let old_time = Utc::now() - Duration::hours(1);
+        let new_time = Utc::now();
+
+        let mut left_scan = empty_scan("/left");
+        // Modified on left since last sync
+        left_scan
+            .entries
+            .push(make_scan_entry("changed.txt", 150, new_time));
+        // New on left
+        left_scan.entries.push(make_dir_entry("newdir"));
+        left_scan
+            .entries
+            .push(make_scan_entry("newdir/new.txt", 10, new_time));
+
+        let mut right_scan = empty_scan("/right");
+        right_scan
+            .entries
+            .push(make_scan_entry("changed.txt", 100, old_time));
+        // Deleted on left since last sync
+        right_scan
+            .entries
+            .push(make_scan_entry("removed.txt", 100, old_time));
+        // New on right only
+        right_scan
+            .entries
+            .push(make_scan_entry("archive_only.txt", 100, new_time));
+
+        let mut left_meta = SyncMetadata::new();
+        let mut right_meta = SyncMetadata::new();
+        for meta in [&mut left_meta, &mut right_meta] {
+            meta.upsert_file(make_file_state("changed.txt", 100, old_time));
+            meta.upsert_file(make_file_state("removed.txt", 100, old_time));
+        }
+
+        let options = DiffOptions {
+            append_only_right: true,
+            ..Default::default()
+        };
+        let result = diff_with_options(&left_scan, &right_scan, &left_meta, &right_meta, &options);
+
+        let action_for = |p: &str| {
+            result
+                .actions
+                .iter()
+                .find(|a| a.path() == Path::new(p))
+                .unwrap()
+        };
+
+        for path in ["changed.txt", "removed.txt", "archive_only.txt"] {
+            match action_for(path) {
+                SyncAction::Skip { reason, .. } => assert_eq!(reason, "Append-only"),
+                other => panic!("expected {} to be skipped, got {:?}", path, other),
+            }
+        }
+        assert!(matches!(
+            action_for("newdir"),
+            SyncAction::CreateDirRight { .. }
+        ));
+        assert!(matches!(
+            action_for("newdir/new.txt"),
+            SyncAction::CopyToRight { .. }
+        ));
+        assert_eq!(result.files_to_copy, 1);
+        assert_eq!(result.files_to_delete, 0);
+    }
+
+    #[test]
+    fn test_ownership_only_change_copies_when_compared() {
+        let time = Utc::now() - Duration::hours(1);
+        let owned_by = |uid: u32| FileAttributes {
+            unix_uid: Some(uid),
+            unix_gid: Some(100),
+            ..Default::default()
+        };
+
+        // Same content, but the left file was chowned since the last sync
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(ScanFileEntry {
+            attributes: owned_by(1001),
+            ..make_scan_entry("file.txt", 100, time)
+        });
+        let mut right_scan = empty_scan("/right");
+        right_scan.entries.push(ScanFileEntry {
+            attributes: owned_by(1000),
+            ..make_scan_entry("file.txt", 100, time)
+        });
+
+        let mut meta = SyncMetadata::new();
+        meta.upsert_file(FileState {
+            attributes: owned_by(1000),
+            ..make_file_state("file.txt", 100, time)
+        });
+
+        let ignored = diff(&left_scan, &right_scan, &meta, &meta);
+        assert!(matches!(&ignored.actions[0], SyncAction::Skip { .. }));
+
+        let options = DiffOptions {
+            compare_ownership: true,
+            ..Default::default()
+        };
+        let compared = diff_with_options(&left_scan, &right_scan, &meta, &meta, &options);
+        assert!(matches!(
+            &compared.actions[0],
+            SyncAction::CopyToRight { path, .. } if path == Path::new("file.txt")
+        ));
+    }
+
+    #[test]
+    fn test_readonly_only_change_updates_attributes() {
+        let time = Utc::now();
+        let readonly = |on: bool| FileAttributes {
+            windows_readonly: Some(on),
+            ..Default::default()
+        };
+
+        // Same content, but the right file was made read-only since the last sync
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(ScanFileEntry {
+            attributes: readonly(false),
+            ..make_scan_entry("file.txt", 100, time)
+        });
+        let mut right_scan = empty_scan("/right");
+        right_scan.entries.push(ScanFileEntry {
+            attributes: readonly(true),
+            ..make_scan_entry("file.txt", 100, time)
+        });
+
+        let mut meta = SyncMetadata::new();
+        meta.upsert_file(FileState {
+            attributes: readonly(false),
+            ..make_file_state("file.txt", 100, time)
+        });
+
+        let ignored = diff(&left_scan, &right_scan, &meta, &meta);
+        assert!(matches!(&ignored.actions[0], SyncAction::Skip { .. }));
+
+        let options = DiffOptions {
+            compare_attributes: true,
+            ..Default::default()
+        };
+        let compared = diff_with_options(&left_scan, &right_scan, &meta, &meta, &options);
+        assert!(matches!(
+            &compared.actions[0],
+            SyncAction::UpdateAttributesLeft { path } if path == Path::new("file.txt")
+        ));
+        assert_eq!(compared.files_to_copy, 0);
+
+        // Nothing recorded: either side may have changed
+        let unknown = SyncMetadata::new();
+        let first = diff_with_options(&left_scan, &right_scan, &unknown, &unknown, &options);
+        assert!(matches!(&first.actions[0], SyncAction::Skip { .. }));
+    }
+
+    #[test]
+    fn test_detect_renames_by_file_id() {
+        let now = Utc::now();
+        let id = FileId {
+            device: 1,
+            index: 42,
+        };
+
+        let mut scan = empty_scan("/left");
+        let mut entry = make_scan_entry("moved/new.txt", 100, now);
+        entry.file_id = Some(id);
+        scan.entries.push(entry);
+        let mut other = make_scan_entry("unrelated.txt", 100, now);
+        other.file_id = Some(FileId {
+            device: 1,
+            index: 7,
+        });
+        scan.entries.push(other);
+
+        let mut meta = SyncMetadata::new();
+        let mut old = make_file_state("old.txt", 100, now);
+        old.file_id = Some(id);
+        meta.upsert_file(old);
+
+        let renames = detect_renames(&scan, &meta);
+        assert_eq!(
+            renames,
+            vec![RenameCandidate {
+                from: PathBuf::from("old.txt"),
+                to: PathBuf::from("moved/new.txt"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_detect_renames_falls_back_to_hash() {
+        let now = Utc::now();
+
+        let mut scan = empty_scan("/left");
+        let mut entry = make_scan_entry("b/a.txt", 10, now);
+        entry.hash = Some("abc".to_string());
+        scan.entries.push(entry);
+
+        let mut meta = SyncMetadata::new();
+        let mut old = make_file_state("a.txt", 10, now);
+        old.hash = Some("abc".to_string());
+        meta.upsert_file(old);
+
+        let renames = detect_renames(&scan, &meta);
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].to, PathBuf::from("b/a.txt"));
+    }
+
+    #[test]
+    fn test_detect_renames_ignores_empty_files() {
+        let now = Utc::now();
+        let empty_hash = "e3b0c442".to_string();
+
+        // A removed empty file and an unrelated new one
+        let mut scan = empty_scan("/left");
+        let mut entry = make_scan_entry("b/__init__.py", 0, now);
+        entry.hash = Some(empty_hash.clone());
+        scan.entries.push(entry);
+
+        let mut meta = SyncMetadata::new();
+        let mut old = make_file_state("a/.keep", 0, now);
+        old.hash = Some(empty_hash);
+        meta.upsert_file(old);
+
+        assert!(detect_renames(&scan, &meta).is_empty());
+    }
+
+    #[test]
+    fn test_detect_renames_ignores_ambiguous_matches() {
+        let now = Utc::now();
+
+        let mut scan = empty_scan("/left");
+        for path in ["copy1.txt", "copy2.txt"] {
+            let mut entry = make_scan_entry(path, 10, now);
+            entry.hash = Some("abc".to_string());
+            scan.entries.push(entry);
+        }
+
+        let mut meta = SyncMetadata::new();
+        let mut old = make_file_state("a.txt", 10, now);
+        old.hash = Some("abc".to_string());
+        meta.upsert_file(old);
+
+        assert!(detect_renames(&scan, &meta).is_empty());
+    }
+
+    #[test]
+    fn test_empty_dirs_created_by_default() {
+        let mut left_scan = empty_scan("/left");
+        left_scan.entries.push(make_dir_entry("empty"));
+
+        let right_scan = empty_scan("/right");
+        let left_meta = SyncMetadata::new();
+        let right_meta = SyncMetadata::new();
+
+        let result = diff(&left_scan, &right_scan, &left_meta, &right_meta);
+
+        assert!(matches!(
+            &result.actions[0],
+            SyncAction::CreateDirRight { path } if path == &PathBuf::from("empty")
+        ));
+    }
+
+    #[test]
+    fn test_skip_empty_dirs_respects_exclusions() {
+        use crate::sync::exclusions::Exclusions;
+        use crate::sync::scanner::scan_with_exclusions;
+        use std::fs;
+        use tempfile::TempDir;
+
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        fs::create_dir_all(left.path().join("cache/nested")).unwrap();
+        fs::write(left.path().join("cache/nested/data.tmp"), "tmp").unwrap();
+
+        let excl = Exclusions::from_patterns(&["*.tmp".to_string()]).unwrap();
+        let left_scan = scan_with_exclusions(left.path(), Some(&excl)).unwrap();
+        let right_scan = scan_with_exclusions(right.path(), Some(&excl)).unwrap();
+
+        let options = DiffOptions {
+            skip_empty_dirs: true,
+            ..Default::default()
+        };
+        let result = diff_with_options(
+            &left_scan,
+            &right_scan,
+            &SyncMetadata::new(),
+            &SyncMetadata::new(),
+            &options,
+        );
+
+        // Directory only holds excluded files, so it is considered empty
+        assert!(!result.actions.iter().any(|a| matches!(
+            a,
+            SyncAction::CreateDirRight { .. } | SyncAction::CopyToRight { .. }
+        )));
+    }
+
+    #[test]
+    fn test_skip_content_elsewhere() {
+        use crate::sync::scanner::scan;
+        use std::fs;
+        use tempfile::TempDir;
+
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        // The same photo under each side's own name
+        fs::write(left.path().join("beach.jpg"), "photo bytes").unwrap();
+        fs::write(right.path().join("IMG_0001.jpg"), "photo bytes").unwrap();
+        // Same size, other content
+        fs::write(left.path().join("new.txt"), "aaaa").unwrap();
+        fs::write(right.path().join("other.txt"), "bbbb").unwrap();
+
+        let left_scan = scan(left.path()).unwrap();
+        let right_scan = scan(right.path()).unwrap();
+        let mut result = diff(
+            &left_scan,
+            &right_scan,
+            &SyncMetadata::new(),
+            &SyncMetadata::new(),
+        );
+        assert_eq!(result.files_to_copy, 4);
+
+        skip_content_elsewhere(&mut result, &left_scan, &right_scan, hash_file);
+        let action = |name: &str| {
+            result
+                .actions
+                .iter()
+                .find(|a| a.path() == Path::new(name))
+                .unwrap()
+        };
+        assert_eq!(
+            action("beach.jpg"),
+            &SyncAction::Skip {
+                path: PathBuf::from("beach.jpg"),
+                reason: format!("{}: IMG_0001.jpg", CONTENT_ELSEWHERE),
+            }
+        );
+        assert!(matches!(action("IMG_0001.jpg"), SyncAction::Skip { .. }));
+        assert!(matches!(action("new.txt"), SyncAction::CopyToRight { .. }));
+        assert!(matches!(action("other.txt"), SyncAction::CopyToLeft { .. }));
+        assert_eq!(result.files_to_copy, 2);
+        assert_eq!(result.total_bytes_to_transfer, 8);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_non_utf8_names_are_kept_distinct() {
+        use crate::sync::executor::{Executor, ExecutorConfig, NoopProgress};
+        use crate::sync::scanner::scan;
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+        use tempfile::TempDir;
+
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        // Both decode lossily to "caf\u{FFFD}.txt"
+        let latin1 = OsStr::from_bytes(b"caf\xe9.txt");
+        let cp437 = OsStr::from_bytes(b"caf\x82.txt");
+        fs::write(left.path().join(latin1), "latin-1").unwrap();
+        fs::write(left.path().join(cp437), "cp437").unwrap();
+
+        let result = diff(
+            &scan(left.path()).unwrap(),
+            &scan(right.path()).unwrap(),
+            &SyncMetadata::new(),
+            &SyncMetadata::new(),
+        );
+        assert_eq!(result.conflicts, 0);
+        let mut copied: Vec<&Path> = result
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                SyncAction::CopyToRight { path, .. } => Some(path.as_path()),
+                _ => None,
+            })
+            .collect();
+        copied.sort();
+        assert_eq!(copied, vec![Path::new(cp437), Path::new(latin1)]);
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        let executed = executor
+            .execute(result.actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+        assert!(executed.failed.is_empty());
+        assert_eq!(fs::read(right.path().join(latin1)).unwrap(), b"latin-1");
+        assert_eq!(fs::read(right.path().join(cp437)).unwrap(), b"cp437");
+    }
 }
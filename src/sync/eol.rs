@@ -0,0 +1,138 @@
+//! Line ending normalization for text file copies
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+pub use crate::config::project::{EolNormalization, LineEnding};
+
+/// How many leading bytes are checked when telling text from binary
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+impl LineEnding {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::Crlf => b"\r\n",
+        }
+    }
+}
+
+impl EolNormalization {
+    /// Checks whether the path's extension is in the configured set
+    pub fn applies_to(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        self.extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    }
+}
+
+/// Treats content with a NUL byte in its first 8 KiB as binary
+pub fn looks_binary(sample: &[u8]) -> bool {
+    sample[..sample.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// Copies `reader` to `writer`, rewriting CRLF, LF and lone CR line breaks
+/// to `target`. Returns the number of bytes written.
+pub fn copy_normalized<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    target: LineEnding,
+) -> io::Result<u64> {
+    let eol = target.as_bytes();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut out = Vec::with_capacity(buf.len() * 2);
+    let mut written = 0u64;
+    // A CR at the end of a chunk may be the first half of a CRLF
+    let mut pending_cr = false;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        out.clear();
+        for &byte in &buf[..n] {
+            match byte {
+                b'\r' => {
+                    if pending_cr {
+                        out.extend_from_slice(eol);
+                    }
+                    pending_cr = true;
+                }
+                b'\n' => {
+                    out.extend_from_slice(eol);
+                    pending_cr = false;
+                }
+                _ => {
+                    if pending_cr {
+                        out.extend_from_slice(eol);
+                        pending_cr = false;
+                    }
+                    out.push(byte);
+                }
+            }
+        }
+        writer.write_all(&out)?;
+        written += out.len() as u64;
+    }
+
+    if pending_cr {
+        writer.write_all(eol)?;
+        written += eol.len() as u64;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(input: &[u8], target: LineEnding) -> Vec<u8> {
+        let mut out = Vec::new();
+        let written = copy_normalized(&mut &input[..], &mut out, target).unwrap();
+        assert_eq!(written, out.len() as u64);
+        out
+    }
+
+    #[test]
+    fn test_normalize_to_lf() {
+        assert_eq!(
+            normalize(b"a\r\nb\nc\rd\r", LineEnding::Lf),
+            b"a\nb\nc\nd\n"
+        );
+        assert_eq!(normalize(b"\r\r\n", LineEnding::Lf), b"\n\n");
+    }
+
+    #[test]
+    fn test_normalize_to_crlf() {
+        assert_eq!(normalize(b"a\nb\r\nc", LineEnding::Crlf), b"a\r\nb\r\nc");
+    }
+
+    #[test]
+    fn test_crlf_split_across_chunks() {
+        // CR as the last byte of one 64 KiB read and LF as the first of the next
+        let mut input = vec![b'x'; 64 * 1024 - 1];
+        input.extend_from_slice(b"\r\ny");
+        let out = normalize(&input, LineEnding::Lf);
+        assert_eq!(out.len(), input.len() - 1);
+        assert!(out.ends_with(b"x\ny"));
+    }
+
+    #[test]
+    fn test_applies_to_and_binary_detection() {
+        let config = EolNormalization::default();
+        assert!(config.applies_to(Path::new("docs/README.MD")));
+        assert!(!config.applies_to(Path::new("image.png")));
+        assert!(!config.applies_to(Path::new("Makefile")));
+
+        assert!(looks_binary(b"PNG\0data"));
+        assert!(!looks_binary(b"plain text\r\n"));
+    }
+}
@@ -1,7 +1,9 @@
 //! File exclusion patterns for synchronization filtering.
 //!
 //! Manages glob patterns stored in `.rahzomignore` file in the root of sync folder.
-//! The file syncs naturally between sides like any other file.
+//! The file syncs naturally between sides like any other file. Further
+//! `.rahzomignore` files in subdirectories apply to their own subtree; the
+//! scanner stacks them on top of the root's.
 
 use std::collections::HashSet;
 use std::fs::File;
@@ -11,7 +13,7 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
-/// Exclusions file name (in root directory)
+/// Exclusions file name (in the root or any directory below it)
 const EXCLUSIONS_FILE: &str = ".rahzomignore";
 
 /// Result of comparing two exclusion sets
@@ -30,12 +32,16 @@ pub struct ExclusionsDiff {
 /// Patterns are stored in `.rahzomignore` with one pattern per line.
 /// Supports glob syntax with `*`, `**`, `?`, `[abc]`, `{a,b}` patterns.
 /// Directory patterns end with `/` and match the directory and all its contents.
+/// A pattern starting with `!` includes what another pattern of the set, or
+/// of an ignore file above, excludes.
 #[derive(Debug, Clone)]
 pub struct Exclusions {
     /// Raw pattern strings (for display)
     patterns: Vec<String>,
     /// Compiled glob matcher for efficient matching
     matcher: GlobSet,
+    /// Compiled `!` patterns, without the `!`
+    negated: GlobSet,
 }
 
 impl Default for Exclusions {
@@ -50,6 +56,7 @@ impl Exclusions {
         Self {
             patterns: Vec::new(),
             matcher: GlobSet::empty(),
+            negated: GlobSet::empty(),
         }
     }
 
@@ -61,11 +68,16 @@ impl Exclusions {
             .filter(|p| !p.is_empty() && !p.starts_with('#'))
             .collect();
 
-        let matcher = Self::compile_patterns(&filtered)?;
+        let (negated, included): (Vec<String>, Vec<String>) =
+            filtered.iter().cloned().partition(|p| p.starts_with('!'));
+        let matcher = Self::compile_patterns(&included)?;
+        let negated: Vec<String> = negated.iter().map(|p| p[1..].to_string()).collect();
+        let negated = Self::compile_patterns(&negated)?;
 
         Ok(Self {
             patterns: filtered,
             matcher,
+            negated,
         })
     }
 
@@ -155,32 +167,49 @@ dist/
     /// The `is_dir` parameter should be true for directories.
     /// Directory patterns (ending with `/`) only match directories.
     pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
-        // Normalize path separators to forward slashes for matching
+        self.verdict(path, is_dir) == Some(true)
+    }
+
+    /// Whether these patterns exclude a relative path (`Some(true)`), include
+    /// it with a `!` pattern (`Some(false)`), or don't mention it (`None`)
+    pub fn verdict(&self, path: &Path, is_dir: bool) -> Option<bool> {
         let path_str = path.to_string_lossy().replace('\\', "/");
+        let included = self.negated.is_match(&path_str)
+            || (is_dir && self.negated.is_match(format!("{}/", path_str)));
+        if included {
+            Some(false)
+        } else if Self::matches(&self.matcher, &path_str, is_dir) {
+            Some(true)
+        } else {
+            None
+        }
+    }
 
+    /// Checks the path, and its parent directories, against `matcher`
+    fn matches(matcher: &GlobSet, path_str: &str, is_dir: bool) -> bool {
         // Check the path itself
-        if self.matcher.is_match(&path_str) {
+        if matcher.is_match(path_str) {
             return true;
         }
 
         // For directories, also check with trailing /
         if is_dir {
             let dir_path = format!("{}/", path_str);
-            if self.matcher.is_match(&dir_path) {
+            if matcher.is_match(&dir_path) {
                 return true;
             }
         }
 
         // Check if any parent directory is excluded
         // This handles cases like "node_modules/" excluding "node_modules/lodash/index.js"
-        let mut current = Path::new(&path_str);
+        let mut current = Path::new(path_str);
         while let Some(parent) = current.parent() {
             if parent.as_os_str().is_empty() {
                 break;
             }
             let parent_str = parent.to_string_lossy();
             let parent_dir = format!("{}/", parent_str);
-            if self.matcher.is_match(parent_dir.as_str()) {
+            if matcher.is_match(parent_dir.as_str()) {
                 return true;
             }
             current = parent;
@@ -189,6 +218,18 @@ dist/
         false
     }
 
+    /// Returns these exclusions plus the given directories, relative to the
+    /// root. Each matches only that exact path; glob characters in it are
+    /// taken literally.
+    pub fn with_dirs(&self, dirs: &[PathBuf]) -> Result<Self> {
+        let mut patterns = self.patterns.clone();
+        patterns.extend(dirs.iter().map(|dir| {
+            let dir = dir.to_string_lossy().replace('\\', "/");
+            format!("{}/", globset::escape(&dir))
+        }));
+        Self::from_patterns(&patterns)
+    }
+
     /// Returns the raw pattern strings.
     pub fn patterns(&self) -> &[String] {
         &self.patterns
@@ -475,6 +516,18 @@ node_modules/
         assert!(excl.is_excluded(Path::new("node_modules"), true));
     }
 
+    #[test]
+    fn test_negated_patterns() {
+        let excl =
+            Exclusions::from_patterns(&["*.log".to_string(), "!keep.log".to_string()]).unwrap();
+
+        assert_eq!(excl.len(), 2);
+        assert!(excl.is_excluded(Path::new("a.log"), false));
+        assert!(!excl.is_excluded(Path::new("keep.log"), false));
+        assert_eq!(excl.verdict(Path::new("keep.log"), false), Some(false));
+        assert_eq!(excl.verdict(Path::new("a.txt"), false), None);
+    }
+
     #[test]
     fn test_windows_path_separators() {
         let excl = Exclusions::from_patterns(&["node_modules/".to_string()]).unwrap();
@@ -484,6 +537,21 @@ node_modules/
         assert!(excl.is_excluded(Path::new("node_modules/lodash/index.js"), false));
     }
 
+    #[test]
+    fn test_with_dirs_matches_exact_paths() {
+        let excl = Exclusions::from_patterns(&["*.tmp".to_string()])
+            .unwrap()
+            .with_dirs(&[PathBuf::from("photos/[2024] trip")])
+            .unwrap();
+
+        assert_eq!(excl.len(), 2);
+        assert!(excl.is_excluded(Path::new("photos/[2024] trip"), true));
+        assert!(excl.is_excluded(Path::new("photos/[2024] trip/a.jpg"), false));
+        assert!(!excl.is_excluded(Path::new("photos/2 trip"), true));
+        assert!(!excl.is_excluded(Path::new("other/photos/[2024] trip"), true));
+        assert!(excl.is_excluded(Path::new("a.tmp"), false));
+    }
+
     #[test]
     fn test_invalid_pattern_error() {
         // An invalid glob pattern should return an error
@@ -1,12 +1,24 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{self, BufReader, BufWriter, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
 
+pub use crate::config::project::{OverwritePolicy, SoftDeleteTarget};
+
+use super::buffer::AdaptiveBuffer;
+use super::compress;
 use super::differ::SyncAction;
+use super::eol::{copy_normalized, looks_binary, EolNormalization};
+#[cfg(feature = "testing")]
+use super::faults::{Fault, Faults};
+use super::hash::HashingReader;
+use super::scanner::{device_id, SpecialFile};
+use super::trash;
+use super::utils::{system_time_to_utc, temp_path_for, times_equal_with_tolerance};
 
 /// Classification of sync errors for specific handling
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -102,29 +114,157 @@ pub struct DiskSpaceInfo {
     pub available: u64,
     /// Required space in bytes
     pub required: u64,
-    /// Whether there is enough space
+    /// Free inodes; `None` where the filesystem has no fixed number or it
+    /// can't be read (always on Windows)
+    pub available_inodes: Option<u64>,
+    /// Files and directories to be created
+    pub required_inodes: u64,
+    /// Whether there is enough space, and enough inodes if they are known
     pub sufficient: bool,
 }
 
+impl DiskSpaceInfo {
+    /// Whether the files would run out of inodes before bytes
+    pub fn inodes_short(&self) -> bool {
+        self.available_inodes
+            .is_some_and(|available| available < self.required_inodes)
+    }
+}
+
 /// Checks available disk space at the given path.
 ///
 /// # Arguments
 /// * `path` - Path to check disk space for (uses the mount point of the path)
 /// * `required_bytes` - Required space in bytes
+/// * `required_inodes` - Files and directories to be created, checked
+///   against the free inodes on Unix
 ///
 /// # Returns
 /// * `DiskSpaceInfo` with available space and whether it's sufficient
-pub fn check_disk_space(path: &Path, required_bytes: u64) -> Result<DiskSpaceInfo> {
+pub fn check_disk_space(
+    path: &Path,
+    required_bytes: u64,
+    required_inodes: u64,
+) -> Result<DiskSpaceInfo> {
     use fs2::available_space;
 
     let available = available_space(path)?;
+    let available_inodes = available_inodes(path);
     Ok(DiskSpaceInfo {
         available,
         required: required_bytes,
-        sufficient: available >= required_bytes,
+        available_inodes,
+        required_inodes,
+        sufficient: available >= required_bytes
+            && available_inodes.is_none_or(|inodes| inodes >= required_inodes),
     })
 }
 
+/// Inodes an unprivileged user can still create on the filesystem of
+/// `path` (`statvfs` f_favail). `None` if unknown, or if the filesystem
+/// allocates inodes dynamically and reports no total (e.g. btrfs).
+#[cfg(unix)]
+fn available_inodes(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is NUL-terminated and `stats` is valid for writes
+    if unsafe { libc::statvfs(c_path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: statvfs succeeded, so it filled in `stats`
+    let stats = unsafe { stats.assume_init() };
+    #[allow(clippy::unnecessary_cast)] // fsfilcnt_t is u32 on some targets
+    (stats.f_files > 0).then_some(stats.f_favail as u64)
+}
+
+#[cfg(not(unix))]
+fn available_inodes(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Reachability/writability of a sync target directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetStatus {
+    /// Path doesn't exist (share not mounted or folder missing)
+    NotFound,
+    /// Path exists but is not a directory
+    NotADirectory,
+    /// Directory exists but its contents can't be listed
+    Unreadable,
+    /// Directory can be read but not written to
+    ReadOnly,
+    /// Directory is readable and writable
+    Writable,
+}
+
+impl TargetStatus {
+    /// Returns user-friendly description for this status
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NotFound => "Not found (not mounted?)",
+            Self::NotADirectory => "Not a directory",
+            Self::Unreadable => "Not readable",
+            Self::ReadOnly => "Read-only",
+            Self::Writable => "Writable",
+        }
+    }
+}
+
+/// Checks that a sync target exists, can be listed, and accepts writes.
+/// Write access is probed by creating and removing a temporary file.
+pub fn validate_target(path: &Path) -> Result<TargetStatus> {
+    let metadata = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TargetStatus::NotFound),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            return Ok(TargetStatus::Unreadable)
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if !metadata.is_dir() {
+        return Ok(TargetStatus::NotADirectory);
+    }
+
+    if fs::read_dir(path).is_err() {
+        return Ok(TargetStatus::Unreadable);
+    }
+
+    let probe = path.join(format!(".rahzom-write-test-{}", std::process::id()));
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(file) => {
+            drop(file);
+            let _ = fs::remove_file(&probe);
+            Ok(TargetStatus::Writable)
+        }
+        Err(_) => Ok(TargetStatus::ReadOnly),
+    }
+}
+
+impl OverwritePolicy {
+    /// Why a copy from `src` must leave `dst` alone; `None` if it may replace it
+    fn refusal(self, src: &Path, dst: &Path) -> Option<&'static str> {
+        let dst_meta = fs::symlink_metadata(dst).ok()?;
+        match self {
+            Self::Always => None,
+            Self::Never => Some("destination exists (overwrite_policy = never)"),
+            Self::IfSourceNewer => {
+                let src_mtime: DateTime<Utc> = fs::metadata(src).ok()?.modified().ok()?.into();
+                let dst_mtime: DateTime<Utc> = dst_meta.modified().ok()?.into();
+                (dst_mtime > src_mtime && !times_equal_with_tolerance(dst_mtime, src_mtime))
+                    .then_some("destination is newer (overwrite_policy = if_source_newer)")
+            }
+        }
+    }
+}
+
 /// Configuration for the executor
 #[derive(Debug, Clone)]
 pub struct ExecutorConfig {
@@ -134,6 +274,55 @@ pub struct ExecutorConfig {
     pub backup_versions: usize,
     /// Whether to move deleted files to trash instead of permanent delete
     pub soft_delete: bool,
+    /// Which trash soft deletion uses on the left and on the right
+    pub soft_delete_target_left: SoftDeleteTarget,
+    pub soft_delete_target_right: SoftDeleteTarget,
+    /// Whether to copy directory mtimes from source to destination after sync
+    pub preserve_dir_mtime: bool,
+    /// Simulate execution (ordering and snapshot checks) without writing
+    pub dry_run: bool,
+    /// Whether to copy the NTFS DACL from source to destination (Windows only)
+    pub copy_acls: bool,
+    /// Whether to hardlink copies whose source is hardlinked to an already synced file
+    pub preserve_hardlinks: bool,
+    /// Sync written data to disk every N bytes during a copy, plus once at the end.
+    /// `None` leaves flushing to the OS.
+    pub fsync_interval_bytes: Option<u64>,
+    /// Rewrite line endings of matching text files on copy. Such copies are
+    /// no longer byte-identical to their source.
+    pub normalize_eol: Option<EolNormalization>,
+    /// Make a read-only destination writable before overwriting it.
+    /// Without this, overwriting a read-only file fails with `PermissionDenied`.
+    pub clear_readonly_on_overwrite: bool,
+    /// Whether to give copies the Unix owner/group of their source. Usually
+    /// requires root; failures are reported as warnings.
+    pub preserve_ownership: bool,
+    /// Whether to give copies the Unix permission bits of their source.
+    /// Read-only and hidden are always copied on Windows.
+    pub preserve_permissions: bool,
+    /// The right side is a compressed archive (see `compress`): copies to the
+    /// right are compressed and read back to check them, copies from the
+    /// right are decompressed. Experimental.
+    pub compress_right: bool,
+    /// Copy files in full path order, so each directory's files are written
+    /// consecutively, instead of grouping copies by depth
+    pub copy_in_path_order: bool,
+    /// Size the copy buffer per file and grow it while throughput is high
+    /// (see `buffer`), instead of a fixed 64 KB
+    pub adaptive_buffer: bool,
+    /// Copy a FIFO by creating a new one at the destination. Without this,
+    /// copies of FIFOs and other special files fail instead of blocking.
+    pub recreate_fifos: bool,
+    /// Clone files by copy-on-write (reflink) when source and destination
+    /// are on the same device, falling back to a normal copy where the
+    /// filesystem can't. Clones record no content hash.
+    pub try_reflink: bool,
+    /// Replace a file standing where a directory has to be created, backing
+    /// it up first. Without this, such a collision fails the action.
+    pub replace_blocking_files: bool,
+    /// Whether copies may replace a destination newer than their source.
+    /// Meant for one-way syncs, where the diff doesn't look at the destination.
+    pub overwrite_policy: OverwritePolicy,
 }
 
 impl Default for ExecutorConfig {
@@ -142,6 +331,24 @@ impl Default for ExecutorConfig {
             backup_enabled: true,
             backup_versions: 5,
             soft_delete: true,
+            soft_delete_target_left: SoftDeleteTarget::Internal,
+            soft_delete_target_right: SoftDeleteTarget::Internal,
+            preserve_dir_mtime: true,
+            dry_run: false,
+            copy_acls: false,
+            preserve_hardlinks: false,
+            fsync_interval_bytes: None,
+            normalize_eol: None,
+            clear_readonly_on_overwrite: false,
+            preserve_ownership: false,
+            preserve_permissions: false,
+            compress_right: false,
+            copy_in_path_order: false,
+            adaptive_buffer: false,
+            recreate_fifos: false,
+            try_reflink: false,
+            replace_blocking_files: false,
+            overwrite_policy: OverwritePolicy::Always,
         }
     }
 }
@@ -151,6 +358,23 @@ impl Default for ExecutorConfig {
 pub struct CompletedAction {
     pub action: SyncAction,
     pub bytes_transferred: u64,
+    /// Non-fatal problems (e.g. ACL copy failed) for an otherwise successful action
+    pub warnings: Vec<String>,
+    /// The copy had its line endings rewritten, so its size and hash differ from the source
+    pub eol_normalized: bool,
+    /// SHA-256 of the copied content, computed while copying; `None` for
+    /// actions that aren't byte-for-byte copies
+    pub content_hash: Option<String>,
+    /// How long the action took, backup and verification included
+    pub duration: Duration,
+}
+
+/// Side information collected while executing one action
+#[derive(Debug, Default)]
+struct ActionNotes {
+    warnings: Vec<String>,
+    eol_normalized: bool,
+    content_hash: Option<String>,
 }
 
 /// A failed action
@@ -182,9 +406,19 @@ impl ExecutionResult {
     }
 }
 
-/// Callback trait for progress reporting
+/// Callback trait for progress reporting.
+///
+/// For each action `execute` calls, in this order and before moving on to
+/// the next action: `on_action_start`, `on_progress`, `on_file_complete`.
+/// Indices are 0-based positions in execution order, which `execute`
+/// derives deterministically from the actions it is given. A headless
+/// runner can thus checkpoint "completed through index K" from
+/// `on_file_complete` and, given the same actions again, resume after K.
 pub trait ProgressCallback {
+    /// An action is about to run; `index` is its position in execution order
+    fn on_action_start(&mut self, index: usize, action: &SyncAction);
     fn on_progress(&mut self, current: usize, total: usize, current_file: &Path);
+    /// The action started last has finished, successfully or not
     fn on_file_complete(&mut self, action: &SyncAction, success: bool);
 }
 
@@ -192,6 +426,7 @@ pub trait ProgressCallback {
 pub struct NoopProgress;
 
 impl ProgressCallback for NoopProgress {
+    fn on_action_start(&mut self, _index: usize, _action: &SyncAction) {}
     fn on_progress(&mut self, _current: usize, _total: usize, _current_file: &Path) {}
     fn on_file_complete(&mut self, _action: &SyncAction, _success: bool) {}
 }
@@ -205,14 +440,300 @@ pub struct FileSnapshot {
 
 /// Metadata directory names
 const METADATA_DIR: &str = ".rahzom";
-const TRASH_DIR: &str = "_trash";
 const BACKUP_DIR: &str = "_backup";
 
+/// Skip reason for a copy whose source no longer exists
+const SOURCE_REMOVED: &str = "source removed";
+
+/// A `.rahzom-tmp` file being written; removed on drop unless persisted
+struct PartialFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl PartialFile {
+    /// Moves the finished copy over `dst`
+    fn persist(mut self, dst: &Path) -> std::result::Result<(), ExecuteError> {
+        fs::rename(&self.path, dst).map_err(|e| {
+            if dst.is_dir() {
+                ExecuteError::failed(
+                    format!(
+                        "Failed to replace destination: {} is a directory, not a file",
+                        dst.display()
+                    ),
+                    SyncErrorKind::InvalidPath,
+                )
+            } else {
+                ExecuteError::from_io(e, "Failed to replace destination")
+            }
+        })?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Opens the source of a copy
+fn open_source(src: &Path) -> std::result::Result<File, ExecuteError> {
+    File::open(src).map_err(|e| {
+        // Deleted since the scan: nothing to copy, and nothing the user can fix
+        if e.kind() == io::ErrorKind::NotFound {
+            ExecuteError::Skipped(SOURCE_REMOVED.to_string())
+        } else {
+            ExecuteError::from_io(e, "Failed to open source")
+        }
+    })
+}
+
+/// Partial file to write a copy to `dst` into, with the parent directories
+/// created. Writing there first means a failed copy never leaves a
+/// truncated destination behind.
+fn create_partial(dst: &Path) -> std::result::Result<PartialFile, ExecuteError> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| ExecuteError::from_io(e, "Failed to create parent dir"))?;
+    }
+    Ok(PartialFile {
+        path: temp_path_for(dst),
+        persisted: false,
+    })
+}
+
+/// The file keeping `dir` from being created: its deepest existing
+/// ancestor, or `dir` itself, when that is not a directory
+fn blocking_file(dir: &Path) -> Option<PathBuf> {
+    let existing = dir.ancestors().find(|p| fs::metadata(p).is_ok())?;
+    (!existing.is_dir()).then(|| existing.to_path_buf())
+}
+
+/// Applies the attributes of `src` to the identical file `dst`, whose
+/// content is left alone
+fn update_attributes(src: &Path, dst: &Path) -> std::result::Result<(), ExecuteError> {
+    if !src.exists() {
+        return Err(ExecuteError::Skipped(SOURCE_REMOVED.to_string()));
+    }
+    copy_attributes(dst, src).map_err(|e| ExecuteError::from_io(e, "Failed to update attributes"))
+}
+
+/// Fails a copy that didn't produce the expected number of bytes
+fn check_copied_size(expected: u64, actual: u64) -> std::result::Result<(), ExecuteError> {
+    if actual != expected {
+        return Err(ExecuteError::failed(
+            format!(
+                "Size mismatch after copy: expected {}, got {}",
+                expected, actual
+            ),
+            SyncErrorKind::IoError,
+        ));
+    }
+    Ok(())
+}
+
+/// Removes `.rahzom-tmp` partials of the copies in `actions`, e.g. after a
+/// cancelled or failed run. Only temp files of those destinations are
+/// touched, never other leftovers. With `compress_right`, copies to the
+/// right were written as archives, and so were their partials. Returns the
+/// number of files removed.
+pub fn remove_partial_copies(
+    left_root: &Path,
+    right_root: &Path,
+    actions: &[SyncAction],
+    compress_right: bool,
+) -> usize {
+    let right_file = |path: &Path| {
+        if compress_right {
+            right_root.join(compress::archive_path(path))
+        } else {
+            right_root.join(path)
+        }
+    };
+    actions
+        .iter()
+        .flat_map(|action| match action {
+            SyncAction::CopyToRight { path, .. } => vec![right_file(path)],
+            SyncAction::CopyToLeft { path, .. } => vec![left_root.join(path)],
+            SyncAction::KeepBoth { path, copy, .. } => vec![
+                left_root.join(copy),
+                right_root.join(copy),
+                right_root.join(path),
+            ],
+            _ => Vec::new(),
+        })
+        .filter(|dst| fs::remove_file(temp_path_for(dst)).is_ok())
+        .count()
+}
+
+/// Whether `src` and the directory of `dst` are known to be on one device
+fn same_device(src: &Path, dst: &Path) -> bool {
+    let dst_dir = dst.parent().unwrap_or(dst);
+    matches!((device_id(src), device_id(dst_dir)), (Some(a), Some(b)) if a == b)
+}
+
+/// Clones `src` to the new file `dst` by copy-on-write, sharing its data
+/// blocks instead of copying them. Fails where the filesystem can't, e.g.
+/// ext4, leaving `dst` empty or absent.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn reflink(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let src = File::open(src)?;
+    let dst = File::create(dst)?;
+    // SAFETY: both descriptors are open for the duration of the call
+    if unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONE, src.as_raw_fd()) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn reflink(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = |path: &Path| {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    };
+    let (src, dst) = (c_path(src)?, c_path(dst)?);
+    // SAFETY: both paths are NUL-terminated
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn reflink(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Removes the partial copies a scan of `root` found
+/// ([`ScanResult::partials`](super::scanner::ScanResult::partials)).
+/// Returns the number of files removed.
+pub fn remove_partials(root: &Path, partials: &[PathBuf]) -> usize {
+    partials
+        .iter()
+        .filter(|partial| fs::remove_file(root.join(partial)).is_ok())
+        .count()
+}
+
+/// Timestamp suffix appended to backed up file names; also names trash sessions
+pub const VERSION_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S_%3f";
+
+/// A stored backup version of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupEntry {
+    /// Full path to the backup file in `.rahzom/_backup`
+    pub path: PathBuf,
+    /// When the backup was taken
+    pub timestamp: DateTime<Utc>,
+    /// Backup file size in bytes
+    pub size: u64,
+}
+
+/// Parses the timestamp from a backup name of the form `{filename}.{timestamp}`.
+/// Returns None if the name belongs to a different file (e.g. `a.txt` vs `a.txt.bak`).
+fn parse_backup_timestamp(backup_name: &str, filename: &str) -> Option<DateTime<Utc>> {
+    let suffix = backup_name.strip_prefix(filename)?.strip_prefix('.')?;
+    NaiveDateTime::parse_from_str(suffix, VERSION_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Lists backup versions of `filename` stored under `root`, newest first.
+/// Backups are keyed by file name only, so same-named files in different
+/// directories share their versions.
+pub fn list_backups(root: &Path, filename: &str) -> Result<Vec<BackupEntry>> {
+    let backup_dir = root.join(METADATA_DIR).join(BACKUP_DIR);
+    let entries = match fs::read_dir(&backup_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", backup_dir.display()))
+        }
+    };
+
+    let mut backups: Vec<BackupEntry> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let timestamp = parse_backup_timestamp(&name, filename)?;
+            let meta = e.metadata().ok().filter(|m| m.is_file())?;
+            Some(BackupEntry {
+                path: e.path(),
+                timestamp,
+                size: meta.len(),
+            })
+        })
+        .collect();
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+    Ok(backups)
+}
+
+/// Restores a backup version over the live file at `root/rel_path`.
+/// The current file, if any, is backed up first so the restore can be undone.
+pub fn restore_backup(root: &Path, rel_path: &Path, backup: &BackupEntry) -> Result<()> {
+    let target = root.join(rel_path);
+
+    if target.is_file() {
+        let filename = rel_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let backup_dir = root.join(METADATA_DIR).join(BACKUP_DIR);
+        fs::create_dir_all(&backup_dir)
+            .with_context(|| format!("Failed to create {}", backup_dir.display()))?;
+        let timestamp = Utc::now().format(VERSION_TIMESTAMP_FORMAT);
+        let current_backup = backup_dir.join(format!("{}.{}", filename, timestamp));
+        fs::copy(&target, &current_backup)
+            .with_context(|| format!("Failed to back up {}", target.display()))?;
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    fs::copy(&backup.path, &target).with_context(|| {
+        format!(
+            "Failed to restore {} from {}",
+            target.display(),
+            backup.path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
 /// Executes sync actions between two directories.
 pub struct Executor {
     left_root: PathBuf,
     right_root: PathBuf,
     config: ExecutorConfig,
+    /// Hardlink siblings of left files (see `ScanResult::hardlink_groups`)
+    left_hardlinks: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Hardlink siblings of right files
+    right_hardlinks: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Paths (relative) on the left whose file the sync keeps, so a
+    /// directory must not replace it under `replace_blocking_files`
+    kept_left: HashSet<PathBuf>,
+    /// Paths on the right whose file the sync keeps
+    kept_right: HashSet<PathBuf>,
+    /// Trash session deleted items are moved into (see `trash`)
+    trash_session: String,
+    /// Clones a file under `try_reflink`; [`reflink`] but in tests
+    clone_file: fn(&Path, &Path) -> io::Result<()>,
+    /// Simulated failures injected into matching actions
+    #[cfg(feature = "testing")]
+    faults: Faults,
 }
 
 impl Executor {
@@ -221,11 +742,61 @@ impl Executor {
             left_root,
             right_root,
             config,
+            left_hardlinks: HashMap::new(),
+            right_hardlinks: HashMap::new(),
+            kept_left: HashSet::new(),
+            kept_right: HashSet::new(),
+            trash_session: trash::new_session_id(),
+            clone_file: reflink,
+            #[cfg(feature = "testing")]
+            faults: Faults::default(),
         }
     }
 
+    /// Replaces the copy-on-write clone, to test the paths around it on any
+    /// filesystem
+    #[cfg(test)]
+    fn with_clone_file(mut self, clone_file: fn(&Path, &Path) -> io::Result<()>) -> Self {
+        self.clone_file = clone_file;
+        self
+    }
+
+    /// Sets the trash session soft deletes go to, so that one sync run by
+    /// several executors trashes into one session
+    pub fn with_trash_session(mut self, session: String) -> Self {
+        self.trash_session = session;
+        self
+    }
+
+    /// Sets simulated failures for actions on matching paths
+    #[cfg(feature = "testing")]
+    pub fn with_faults(mut self, faults: Faults) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Sets hardlink groups of both sides, used when `preserve_hardlinks` is on
+    pub fn with_hardlinks(
+        mut self,
+        left: HashMap<PathBuf, Vec<PathBuf>>,
+        right: HashMap<PathBuf, Vec<PathBuf>>,
+    ) -> Self {
+        self.left_hardlinks = left;
+        self.right_hardlinks = right;
+        self
+    }
+
+    /// Sets the paths of both sides whose file the sync keeps: the files a
+    /// pending action other than a delete on that side refers to
+    pub fn with_kept_paths(mut self, left: HashSet<PathBuf>, right: HashSet<PathBuf>) -> Self {
+        self.kept_left = left;
+        self.kept_right = right;
+        self
+    }
+
     /// Executes all actions with progress callback.
     /// Actions are sorted: directories first, then copies, then deletes.
+    /// Directory mtimes are restored last, after all content writes.
     pub fn execute(
         &self,
         actions: Vec<SyncAction>,
@@ -237,14 +808,21 @@ impl Executor {
         let mut result = ExecutionResult::default();
 
         for (index, action) in sorted_actions.into_iter().enumerate() {
+            progress.on_action_start(index, &action);
             progress.on_progress(index + 1, total, self.action_path(&action));
 
-            match self.execute_action(&action, snapshots) {
+            let mut notes = ActionNotes::default();
+            let started = Instant::now();
+            match self.execute_action(&action, snapshots, &mut notes) {
                 Ok(Some(bytes)) => {
                     progress.on_file_complete(&action, true);
                     result.completed.push(CompletedAction {
                         action,
                         bytes_transferred: bytes,
+                        warnings: notes.warnings,
+                        eol_normalized: notes.eol_normalized,
+                        content_hash: notes.content_hash,
+                        duration: started.elapsed(),
                     });
                 }
                 Ok(None) => {
@@ -262,38 +840,97 @@ impl Executor {
             }
         }
 
+        if self.config.preserve_dir_mtime && !self.config.dry_run {
+            self.sync_dir_mtimes(&result);
+        }
+
         Ok(result)
     }
 
-    /// Sorts actions for proper execution order
-    fn sort_actions(&self, mut actions: Vec<SyncAction>) -> Vec<SyncAction> {
-        actions.sort_by(|a, b| {
-            let order_a = self.action_order(a);
-            let order_b = self.action_order(b);
-            order_a.cmp(&order_b)
-        });
-        actions
-    }
+    /// Sets mtime of destination directories touched by the actions, and of
+    /// every directory above them, to match the source side, deepest first.
+    /// Adding or removing entries bumps a directory's mtime, so this must run
+    /// after all content writes. Failed and skipped actions count too, as they
+    /// may have created parents or removed a partial copy before stopping.
+    /// The sync root itself is left alone since metadata writes into
+    /// `.rahzom/` would change it anyway.
+    fn sync_dir_mtimes(&self, result: &ExecutionResult) {
+        let actions = result
+            .completed
+            .iter()
+            .map(|c| &c.action)
+            .chain(result.failed.iter().map(|f| &f.action))
+            .chain(result.skipped.iter().map(|s| &s.action));
+        let mut dirs: HashSet<(PathBuf, bool)> = HashSet::new();
+
+        for action in actions {
+            let (path, to_right, is_dir) = match action {
+                SyncAction::CopyToRight { path, .. } | SyncAction::DeleteRight { path } => {
+                    (path, true, false)
+                }
+                SyncAction::CopyToLeft { path, .. } | SyncAction::DeleteLeft { path } => {
+                    (path, false, false)
+                }
+                SyncAction::CreateDirRight { path } => (path, true, true),
+                SyncAction::CreateDirLeft { path } => (path, false, true),
+                // Attribute changes leave the directory's mtime alone; keeping
+                // both adds a file on both sides, so neither is the source
+                SyncAction::UpdateAttributesRight { .. }
+                | SyncAction::UpdateAttributesLeft { .. }
+                | SyncAction::KeepBoth { .. }
+                | SyncAction::Skip { .. }
+                | SyncAction::Conflict { .. } => continue,
+            };
 
-    fn action_order(&self, action: &SyncAction) -> (u8, usize, bool) {
-        match action {
-            // Directories first, sorted by depth (shallow first)
-            SyncAction::CreateDirLeft { path } | SyncAction::CreateDirRight { path } => {
-                (0, path.components().count(), false)
+            let start = if is_dir {
+                Some(path.as_path())
+            } else {
+                path.parent()
+            };
+            for dir in start.into_iter().flat_map(Path::ancestors) {
+                if dir.as_os_str().is_empty() {
+                    break;
+                }
+                dirs.insert((dir.to_path_buf(), to_right));
             }
-            // Copies second
-            SyncAction::CopyToLeft { path, .. } | SyncAction::CopyToRight { path, .. } => {
-                (1, path.components().count(), false)
+        }
+
+        let mut dirs: Vec<_> = dirs.into_iter().collect();
+        dirs.sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+        for (dir, to_right) in dirs {
+            let (src_root, dst_root) = if to_right {
+                (&self.left_root, &self.right_root)
+            } else {
+                (&self.right_root, &self.left_root)
+            };
+            let src = src_root.join(&dir);
+            let dst = dst_root.join(&dir);
+            if !src.is_dir() || !dst.is_dir() {
+                continue;
             }
-            // Deletes last, sorted by depth (deep first for directories)
-            SyncAction::DeleteLeft { path } | SyncAction::DeleteRight { path } => {
-                (2, usize::MAX - path.components().count(), true)
+            if let Ok(mtime) = fs::metadata(&src).and_then(|m| m.modified()) {
+                let _ = set_file_mtime(&dst, mtime);
             }
-            // Skip and Conflict at the end
-            SyncAction::Skip { .. } | SyncAction::Conflict { .. } => (3, 0, false),
         }
     }
 
+    /// Sorts actions for proper execution order. Each action's order is
+    /// computed once up front, not per comparison, as counting path
+    /// components adds up over a million actions.
+    fn sort_actions(&self, actions: Vec<SyncAction>) -> Vec<SyncAction> {
+        let mut keyed: Vec<_> = actions
+            .into_iter()
+            .map(|action| (action_order(&action), action))
+            .collect();
+        keyed.sort_by(|(order_a, a), (order_b, b)| {
+            if self.config.copy_in_path_order && order_a.0 == 1 && order_b.0 == 1 {
+                return self.action_path(a).cmp(self.action_path(b));
+            }
+            order_a.cmp(order_b)
+        });
+        keyed.into_iter().map(|(_, action)| action).collect()
+    }
+
     fn action_path<'a>(&self, action: &'a SyncAction) -> &'a Path {
         match action {
             SyncAction::CopyToRight { path, .. }
@@ -302,6 +939,9 @@ impl Executor {
             | SyncAction::DeleteLeft { path }
             | SyncAction::CreateDirRight { path }
             | SyncAction::CreateDirLeft { path }
+            | SyncAction::UpdateAttributesRight { path }
+            | SyncAction::UpdateAttributesLeft { path }
+            | SyncAction::KeepBoth { path, .. }
             | SyncAction::Skip { path, .. }
             | SyncAction::Conflict { path, .. } => path,
         }
@@ -311,26 +951,58 @@ impl Executor {
         &self,
         action: &SyncAction,
         snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        notes: &mut ActionNotes,
     ) -> std::result::Result<Option<u64>, ExecuteError> {
+        if self.config.dry_run {
+            return self.simulate_action(action, snapshots);
+        }
+
+        #[cfg(feature = "testing")]
+        match self.faults.get(action.path()) {
+            Some(Fault::Error(kind)) => {
+                return Err(ExecuteError::failed(
+                    format!("Simulated failure: {}", kind.title()),
+                    kind.clone(),
+                ));
+            }
+            Some(Fault::Delay(delay)) => std::thread::sleep(*delay),
+            None => {}
+        }
+
         match action {
+            SyncAction::CopyToRight { path, size } if self.config.compress_right => {
+                let src = self.left_root.join(path);
+                let dst = self.right_root.join(compress::archive_path(path));
+                self.compress_copy(&src, &dst, path, *size, snapshots, notes)
+            }
+            SyncAction::CopyToLeft { path, size } if self.config.compress_right => {
+                let src = self.right_root.join(compress::archive_path(path));
+                let dst = self.left_root.join(path);
+                self.decompress_copy(&src, &dst, path, *size, snapshots, notes)
+            }
             SyncAction::CopyToRight { path, size } => {
                 let src = self.left_root.join(path);
                 let dst = self.right_root.join(path);
-                self.verify_and_copy(&src, &dst, path, *size, snapshots)
+                self.verify_and_copy(&src, &dst, path, *size, snapshots, notes)
             }
             SyncAction::CopyToLeft { path, size } => {
                 let src = self.right_root.join(path);
                 let dst = self.left_root.join(path);
-                self.verify_and_copy(&src, &dst, path, *size, snapshots)
+                self.verify_and_copy(&src, &dst, path, *size, snapshots, notes)
+            }
+            SyncAction::DeleteRight { path } if self.config.compress_right => {
+                let target = self.right_root.join(compress::archive_path(path));
+                self.delete_file(&target, &self.right_root, notes)?;
+                Ok(Some(0))
             }
             SyncAction::DeleteRight { path } => {
                 let target = self.right_root.join(path);
-                self.delete_file(&target, &self.right_root)?;
+                self.delete_file(&target, &self.right_root, notes)?;
                 Ok(Some(0))
             }
             SyncAction::DeleteLeft { path } => {
                 let target = self.left_root.join(path);
-                self.delete_file(&target, &self.left_root)?;
+                self.delete_file(&target, &self.left_root, notes)?;
                 Ok(Some(0))
             }
             SyncAction::CreateDirRight { path } => {
@@ -343,11 +1015,122 @@ impl Executor {
                 self.create_dir(&target)?;
                 Ok(Some(0))
             }
+            SyncAction::UpdateAttributesRight { path } => {
+                let src = self.left_root.join(path);
+                let dst = self.right_root.join(path);
+                update_attributes(&src, &dst)?;
+                Ok(Some(0))
+            }
+            SyncAction::UpdateAttributesLeft { path } => {
+                let src = self.right_root.join(path);
+                let dst = self.left_root.join(path);
+                update_attributes(&src, &dst)?;
+                Ok(Some(0))
+            }
+            SyncAction::KeepBoth { .. } if self.config.compress_right => Err(ExecuteError::failed(
+                "Keeping both versions isn't supported on a compressed right side".to_string(),
+                SyncErrorKind::IoError,
+            )),
+            SyncAction::KeepBoth {
+                path,
+                copy,
+                left_size,
+                right_size,
+            } => self.keep_both(path, copy, *left_size, *right_size, snapshots, notes),
             SyncAction::Skip { .. } => Ok(None),
             SyncAction::Conflict { .. } => Ok(None),
         }
     }
 
+    /// Saves the right file as `copy` on both sides, then copies the left
+    /// file over it. A file that appeared under the copy name since the
+    /// analysis fails the action rather than being overwritten.
+    fn keep_both(
+        &self,
+        path: &Path,
+        copy: &Path,
+        left_size: u64,
+        right_size: u64,
+        snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<Option<u64>, ExecuteError> {
+        let (left_copy, right_copy) = (self.left_root.join(copy), self.right_root.join(copy));
+        if let Some(taken) = [&left_copy, &right_copy]
+            .into_iter()
+            .find(|p| p.symlink_metadata().is_ok())
+        {
+            return Err(ExecuteError::failed(
+                format!("Name for the kept copy is taken: {}", taken.display()),
+                SyncErrorKind::InvalidPath,
+            ));
+        }
+
+        let right_file = self.right_root.join(path);
+        let to_left =
+            self.verify_and_copy(&right_file, &left_copy, path, right_size, snapshots, notes)?;
+        let kept =
+            self.verify_and_copy(&right_file, &right_copy, path, right_size, snapshots, notes)?;
+        let to_right = self.verify_and_copy(
+            &self.left_root.join(path),
+            &right_file,
+            path,
+            left_size,
+            snapshots,
+            notes,
+        )?;
+        Ok(Some(
+            [to_left, kept, to_right]
+                .into_iter()
+                .map(|bytes| bytes.unwrap_or(0))
+                .sum(),
+        ))
+    }
+
+    /// Predicts the outcome of an action without touching either side.
+    /// Copies run the same snapshot verification as a real sync.
+    fn simulate_action(
+        &self,
+        action: &SyncAction,
+        snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+    ) -> std::result::Result<Option<u64>, ExecuteError> {
+        match action {
+            SyncAction::CopyToRight { path, size } | SyncAction::CopyToLeft { path, size } => {
+                let to_right = matches!(action, SyncAction::CopyToRight { .. });
+                let archived = !to_right && self.config.compress_right;
+                let src = match (to_right, archived) {
+                    (true, _) => self.left_root.join(path),
+                    (false, true) => self.right_root.join(compress::archive_path(path)),
+                    (false, false) => self.right_root.join(path),
+                };
+                if let Some(snapshot) = snapshots.get(path) {
+                    if !self.verify_file(&src, snapshot, archived)? {
+                        return Err(ExecuteError::Skipped(
+                            "File changed during sync".to_string(),
+                        ));
+                    }
+                } else if !src.exists() {
+                    return Err(ExecuteError::failed(
+                        "Source file not found".to_string(),
+                        SyncErrorKind::NotFound,
+                    ));
+                }
+                Ok(Some(*size))
+            }
+            SyncAction::DeleteRight { .. }
+            | SyncAction::DeleteLeft { .. }
+            | SyncAction::CreateDirRight { .. }
+            | SyncAction::CreateDirLeft { .. }
+            | SyncAction::UpdateAttributesRight { .. }
+            | SyncAction::UpdateAttributesLeft { .. } => Ok(Some(0)),
+            SyncAction::KeepBoth {
+                left_size,
+                right_size,
+                ..
+            } => Ok(Some(left_size + 2 * right_size)),
+            SyncAction::Skip { .. } | SyncAction::Conflict { .. } => Ok(None),
+        }
+    }
+
     fn verify_and_copy(
         &self,
         src: &Path,
@@ -355,119 +1138,545 @@ impl Executor {
         rel_path: &Path,
         expected_size: u64,
         snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        notes: &mut ActionNotes,
     ) -> std::result::Result<Option<u64>, ExecuteError> {
-        // Pre-copy verification
-        if let Some(snapshot) = snapshots.get(rel_path) {
-            if !self.verify_file(src, snapshot)? {
-                return Err(ExecuteError::Skipped(
-                    "File changed during sync".to_string(),
-                ));
-            }
+        self.check_source(src, rel_path, snapshots, false)?;
+        self.check_overwrite(src, dst)?;
+        self.prepare_destination(dst)?;
+
+        if SpecialFile::at(src) == Some(SpecialFile::Fifo) {
+            self.recreate_fifo(src, dst)?;
+            return Ok(Some(0));
         }
 
-        // Create backup if file exists at destination
-        if dst.exists() && self.config.backup_enabled {
-            let root = if dst.starts_with(&self.left_root) {
-                &self.left_root
-            } else {
-                &self.right_root
-            };
-            self.create_backup(dst, root)?;
+        if self.config.preserve_hardlinks && self.link_to_synced_sibling(src, dst, rel_path) {
+            return Ok(Some(0));
         }
 
         // Perform copy
-        self.copy_file(src, dst)?;
+        let eol = self
+            .config
+            .normalize_eol
+            .as_ref()
+            .filter(|eol| eol.applies_to(rel_path));
+        self.copy_file(src, dst, eol, notes)?;
+
+        if self.config.copy_acls {
+            if let Err(e) = copy_acl(src, dst) {
+                notes.warnings.push(format!("Failed to copy ACL: {}", e));
+            }
+        }
 
-        // Verify copy (size check)
+        // Verify copy (size check); a normalized copy legitimately differs in size
         let dst_meta =
             fs::metadata(dst).map_err(|e| ExecuteError::from_io(e, "Failed to verify copy"))?;
-        if dst_meta.len() != expected_size {
-            return Err(ExecuteError::failed(
-                format!(
-                    "Size mismatch after copy: expected {}, got {}",
-                    expected_size,
-                    dst_meta.len()
-                ),
-                SyncErrorKind::IoError,
-            ));
+        if notes.eol_normalized {
+            return Ok(Some(dst_meta.len()));
         }
+        check_copied_size(expected_size, dst_meta.len())?;
 
         Ok(Some(expected_size))
     }
 
-    fn verify_file(
+    /// Pre-copy verification: skips a copy whose source changed or vanished
+    /// since the scan
+    fn check_source(
         &self,
-        path: &Path,
-        snapshot: &FileSnapshot,
-    ) -> std::result::Result<bool, ExecuteError> {
-        let metadata = match fs::metadata(path) {
-            Ok(m) => m,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => {
-                return Ok(false); // File was deleted
+        src: &Path,
+        rel_path: &Path,
+        snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        archived: bool,
+    ) -> std::result::Result<(), ExecuteError> {
+        if let Some(snapshot) = snapshots.get(rel_path) {
+            if !self.verify_file(src, snapshot, archived)? {
+                return Err(ExecuteError::Skipped(
+                    "File changed during sync".to_string(),
+                ));
             }
-            Err(e) => return Err(ExecuteError::from_io(e, "Failed to verify file")),
-        };
-
-        // Check size
-        if metadata.len() != snapshot.size {
-            return Ok(false);
+        } else if !src.exists() {
+            // Without a snapshot nothing noticed the deletion; don't back up
+            // the destination for a copy that can't happen
+            return Err(ExecuteError::Skipped(SOURCE_REMOVED.to_string()));
         }
 
-        // Check mtime
-        let mtime = metadata
-            .modified()
-            .map_err(|e| ExecuteError::from_io(e, "Failed to get modification time"))?;
-        let mtime_utc = system_time_to_utc(mtime);
-
-        // Allow FAT32 tolerance for mtime comparison
-        let diff = (mtime_utc - snapshot.mtime).num_seconds().abs();
-        if diff > super::utils::FAT32_TOLERANCE_SECS {
-            return Ok(false);
+        // Reading a special file blocks or never ends; only a FIFO can be
+        // recreated instead, and not in an archive
+        if let Some(special) = SpecialFile::at(src) {
+            let recreate = special == SpecialFile::Fifo
+                && self.config.recreate_fifos
+                && !self.config.compress_right;
+            if !recreate {
+                return Err(ExecuteError::failed(
+                    format!("{} can't be copied", special.name()),
+                    SyncErrorKind::IoError,
+                ));
+            }
         }
+        Ok(())
+    }
 
-        Ok(true)
+    /// Skips a copy the overwrite policy keeps from replacing its destination
+    fn check_overwrite(&self, src: &Path, dst: &Path) -> std::result::Result<(), ExecuteError> {
+        match self.config.overwrite_policy.refusal(src, dst) {
+            Some(reason) => Err(ExecuteError::Skipped(reason.to_string())),
+            None => Ok(()),
+        }
     }
 
-    fn copy_file(&self, src: &Path, dst: &Path) -> std::result::Result<(), ExecuteError> {
-        // Create parent directories
+    /// Makes an existing destination replaceable and backs it up, and
+    /// creates the directory it goes in
+    fn prepare_destination(&self, dst: &Path) -> std::result::Result<(), ExecuteError> {
         if let Some(parent) = dst.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| ExecuteError::from_io(e, "Failed to create parent dir"))?;
+            self.ensure_dir(parent, "Failed to create parent dir")?;
         }
 
-        // Copy file content
-        let src_file =
-            File::open(src).map_err(|e| ExecuteError::from_io(e, "Failed to open source"))?;
-        let dst_file = File::create(dst)
-            .map_err(|e| ExecuteError::from_io(e, "Failed to create destination"))?;
-
-        let mut reader = BufReader::with_capacity(64 * 1024, src_file);
-        let mut writer = BufWriter::with_capacity(64 * 1024, dst_file);
-
-        io::copy(&mut reader, &mut writer).map_err(|e| ExecuteError::from_io(e, "Failed to copy"))?;
-
-        writer
-            .flush()
-            .map_err(|e| ExecuteError::from_io(e, "Failed to flush"))?;
-
-        // Preserve mtime
-        let src_meta =
-            fs::metadata(src).map_err(|e| ExecuteError::from_io(e, "Failed to get metadata"))?;
-        if let Ok(mtime) = src_meta.modified() {
-            let _ = set_file_mtime(dst, mtime);
+        // Read-only marks a file its owner didn't want changed; only replace
+        // it when the project allows that (Windows refuses regardless)
+        if is_readonly_file(dst) {
+            if !self.config.clear_readonly_on_overwrite {
+                return Err(ExecuteError::failed(
+                    format!(
+                        "Destination is read-only: {} (set clear_readonly_on_overwrite \
+                         in project settings to overwrite read-only files)",
+                        dst.display()
+                    ),
+                    SyncErrorKind::PermissionDenied,
+                ));
+            }
+            clear_readonly(dst)
+                .map_err(|e| ExecuteError::from_io(e, "Failed to clear read-only attribute"))?;
         }
 
-        // Preserve file attributes (readonly, hidden on Windows)
-        let _ = set_file_attributes(dst, src);
-
+        // Create backup if file exists at destination. A special file has
+        // no content to keep, and reading it for a backup could block.
+        if dst.exists() && self.config.backup_enabled && SpecialFile::at(dst).is_none() {
+            let root = if dst.starts_with(&self.left_root) {
+                &self.left_root
+            } else {
+                &self.right_root
+            };
+            self.create_backup(dst, root)?;
+        }
+        Ok(())
+    }
+
+    /// Creates `dir` and its parents. A file in the way, e.g. `foo` when
+    /// `foo/bar` is needed, fails with [`SyncErrorKind::InvalidPath`] naming
+    /// it, unless the project allows replacing it and the sync doesn't keep
+    /// it. A replaced file is backed up, or else moved to the trash.
+    fn ensure_dir(&self, dir: &Path, context: &str) -> std::result::Result<(), ExecuteError> {
+        let Err(e) = fs::create_dir_all(dir) else {
+            return Ok(());
+        };
+        let Some(blocker) = blocking_file(dir) else {
+            return Err(ExecuteError::from_io(e, context));
+        };
+        let (root, kept) = if blocker.starts_with(&self.left_root) {
+            (&self.left_root, &self.kept_left)
+        } else {
+            (&self.right_root, &self.kept_right)
+        };
+        // Never anything outside the synced tree, or the root itself
+        let replaceable = blocker.starts_with(root) && blocker != *root;
+        if replaceable
+            && blocker
+                .strip_prefix(root)
+                .is_ok_and(|relative| kept.contains(relative))
+        {
+            return Err(ExecuteError::failed(
+                format!(
+                    "{}: {} is a file, not a directory, and has its own action in this sync",
+                    context,
+                    blocker.display()
+                ),
+                SyncErrorKind::InvalidPath,
+            ));
+        }
+        if !self.config.replace_blocking_files || !replaceable {
+            return Err(ExecuteError::failed(
+                format!(
+                    "{}: {} is a file, not a directory (set replace_blocking_files \
+                     in project settings to replace it)",
+                    context,
+                    blocker.display()
+                ),
+                SyncErrorKind::InvalidPath,
+            ));
+        }
+
+        // Never deleted for good: without a backup it goes to the trash
+        if self.config.backup_enabled && SpecialFile::at(&blocker).is_none() {
+            self.create_backup(&blocker, root)?;
+            fs::remove_file(&blocker)
+                .map_err(|e| ExecuteError::from_io(e, "Failed to remove blocking file"))?;
+        } else {
+            self.soft_delete(&blocker, root)?;
+        }
+        fs::create_dir_all(dir).map_err(|e| ExecuteError::from_io(e, context))
+    }
+
+    /// Copies `src` into the archive file `dst`, then decompresses the
+    /// result to check it against the source before it replaces `dst`
+    fn compress_copy(
+        &self,
+        src: &Path,
+        dst: &Path,
+        rel_path: &Path,
+        expected_size: u64,
+        snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<Option<u64>, ExecuteError> {
+        self.check_source(src, rel_path, snapshots, false)?;
+        self.check_overwrite(src, dst)?;
+        self.prepare_destination(dst)?;
+
+        let src_file = open_source(src)?;
+        let partial = create_partial(dst)?;
+        let dst_file = File::create(&partial.path)
+            .map_err(|e| ExecuteError::from_io(e, "Failed to create destination"))?;
+
+        let mut reader = HashingReader::new(BufReader::with_capacity(64 * 1024, src_file));
+        let writer = BufWriter::with_capacity(64 * 1024, dst_file);
+        // The pledged size makes a source that changed meanwhile fail here
+        compress::compress(&mut reader, writer, expected_size)
+            .and_then(|writer| writer.into_inner().map_err(|e| e.into_error()))
+            .map_err(|e| ExecuteError::from_io(e, "Failed to compress"))?;
+        let hash = reader.finish();
+
+        let archived_hash = compress::hash_archive_file(&partial.path)
+            .map_err(|e| ExecuteError::from_io(e, "Failed to read back archive"))?;
+        if archived_hash != hash {
+            return Err(ExecuteError::failed(
+                "Archive does not decompress to its source".to_string(),
+                SyncErrorKind::IoError,
+            ));
+        }
+        partial.persist(dst)?;
+
+        if let Ok(mtime) = fs::metadata(src).and_then(|m| m.modified()) {
+            let _ = set_file_mtime(dst, mtime);
+        }
+        notes.content_hash = Some(hash);
+        Ok(Some(expected_size))
+    }
+
+    /// Restores the archive file `src` to `dst` by decompressing it
+    fn decompress_copy(
+        &self,
+        src: &Path,
+        dst: &Path,
+        rel_path: &Path,
+        expected_size: u64,
+        snapshots: &std::collections::HashMap<PathBuf, FileSnapshot>,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<Option<u64>, ExecuteError> {
+        self.check_source(src, rel_path, snapshots, true)?;
+        self.check_overwrite(src, dst)?;
+        self.prepare_destination(dst)?;
+
+        let src_file = open_source(src)?;
+        let partial = create_partial(dst)?;
+        let dst_file = File::create(&partial.path)
+            .map_err(|e| ExecuteError::from_io(e, "Failed to create destination"))?;
+
+        let decoder = compress::decoder(BufReader::with_capacity(64 * 1024, src_file))
+            .map_err(|e| ExecuteError::from_io(e, "Failed to decompress"))?;
+        let mut reader = HashingReader::new(decoder);
+        let mut writer = BufWriter::with_capacity(64 * 1024, dst_file);
+        let copied = io::copy(&mut reader, &mut writer)
+            .and_then(|copied| writer.flush().map(|_| copied))
+            .map_err(|e| ExecuteError::from_io(e, "Failed to decompress"))?;
+        drop(writer);
+        check_copied_size(expected_size, copied)?;
+        partial.persist(dst)?;
+
+        if let Ok(mtime) = fs::metadata(src).and_then(|m| m.modified()) {
+            let _ = set_file_mtime(dst, mtime);
+        }
+        notes.content_hash = Some(reader.finish());
+        Ok(Some(expected_size))
+    }
+
+    /// Hardlinks `dst` to the destination copy of a source hardlink sibling,
+    /// if one is already in sync. Returns false when the caller should copy
+    /// instead (no synced sibling, or linking failed e.g. across filesystems).
+    fn link_to_synced_sibling(&self, src: &Path, dst: &Path, rel_path: &Path) -> bool {
+        let (groups, dst_root) = if dst.starts_with(&self.right_root) {
+            (&self.left_hardlinks, &self.right_root)
+        } else {
+            (&self.right_hardlinks, &self.left_root)
+        };
+        let Some(siblings) = groups.get(rel_path) else {
+            return false;
+        };
+        let Ok(src_meta) = fs::metadata(src) else {
+            return false;
+        };
+
+        for sibling in siblings {
+            let sibling_dst = dst_root.join(sibling);
+            let Ok(meta) = fs::metadata(&sibling_dst) else {
+                continue;
+            };
+            // Same inode on the source means same size/mtime; a destination
+            // sibling matching them holds the same content
+            let in_sync = meta.is_file()
+                && meta.len() == src_meta.len()
+                && match (meta.modified(), src_meta.modified()) {
+                    (Ok(a), Ok(b)) => {
+                        let diff = (system_time_to_utc(a) - system_time_to_utc(b))
+                            .num_seconds()
+                            .abs();
+                        diff <= super::utils::FAT32_TOLERANCE_SECS
+                    }
+                    _ => true,
+                };
+            if !in_sync {
+                continue;
+            }
+
+            if let Some(parent) = dst.parent() {
+                if fs::create_dir_all(parent).is_err() {
+                    return false;
+                }
+            }
+            if dst.exists() && fs::remove_file(dst).is_err() {
+                return false;
+            }
+            if fs::hard_link(&sibling_dst, dst).is_ok() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Whether the file at `path` still matches its scan snapshot. An
+    /// `archived` file is compared by its original size.
+    fn verify_file(
+        &self,
+        path: &Path,
+        snapshot: &FileSnapshot,
+        archived: bool,
+    ) -> std::result::Result<bool, ExecuteError> {
+        let metadata = match fs::metadata(path) {
+            Ok(m) => m,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(false); // File was deleted
+            }
+            Err(e) => return Err(ExecuteError::from_io(e, "Failed to verify file")),
+        };
+
+        // Check size
+        let size = if archived {
+            match compress::content_size(path) {
+                Ok(size) => size,
+                Err(_) => return Ok(false),
+            }
+        } else {
+            metadata.len()
+        };
+        if size != snapshot.size {
+            return Ok(false);
+        }
+
+        // Check mtime; filesystems without timestamps are verified by size only
+        if let Ok(mtime) = metadata.modified() {
+            let mtime_utc = system_time_to_utc(mtime);
+
+            // Allow FAT32 tolerance for mtime comparison
+            let diff = (mtime_utc - snapshot.mtime).num_seconds().abs();
+            if diff > super::utils::FAT32_TOLERANCE_SECS {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Copies `src` to `dst`, rewriting line endings if `eol` is given and the
+    /// content isn't binary. Returns whether line endings were rewritten.
+    fn copy_file(
+        &self,
+        src: &Path,
+        dst: &Path,
+        eol: Option<&EolNormalization>,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<(), ExecuteError> {
+        // Copy file content
+        let src_file = open_source(src)?;
+        let src_size = src_file.metadata().map_or(u64::MAX, |m| m.len());
+        let partial = create_partial(dst)?;
+
+        // Instant on Btrfs/XFS/APFS; anything else copies below, over
+        // whatever a failed or short clone left in the partial
+        let cloned = self.config.try_reflink
+            && eol.is_none()
+            && same_device(src, dst)
+            && (self.clone_file)(src, &partial.path).is_ok()
+            && fs::metadata(&partial.path).is_ok_and(|m| m.len() == src_size);
+        if cloned {
+            if self.config.fsync_interval_bytes.is_some() {
+                fs::OpenOptions::new()
+                    .write(true)
+                    .open(&partial.path)
+                    .and_then(|file| file.sync_all())
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to sync"))?;
+            }
+            partial.persist(dst)?;
+            return self.finish_copy(src, dst, notes);
+        }
+
+        let dst_file = File::create(&partial.path)
+            .map_err(|e| ExecuteError::from_io(e, "Failed to create destination"))?;
+
+        let mut reader = BufReader::with_capacity(64 * 1024, src_file);
+
+        let eol = match eol {
+            Some(eol) => {
+                let sample = reader
+                    .fill_buf()
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to read source"))?;
+                (!looks_binary(sample)).then_some(eol)
+            }
+            None => None,
+        };
+
+        if let Some(eol) = eol {
+            let mut writer = BufWriter::with_capacity(64 * 1024, dst_file);
+
+            copy_normalized(&mut reader, &mut writer, eol.target)
+                .map_err(|e| ExecuteError::from_io(e, "Failed to copy"))?;
+
+            writer
+                .flush()
+                .map_err(|e| ExecuteError::from_io(e, "Failed to flush"))?;
+            if self.config.fsync_interval_bytes.is_some() {
+                writer
+                    .get_ref()
+                    .sync_all()
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to sync"))?;
+            }
+        } else {
+            // Hash what passes through the copy loop instead of reading the
+            // file again later
+            let mut reader = HashingReader::new(reader);
+            if let Some(interval) = self.config.fsync_interval_bytes {
+                let mut writer = dst_file;
+                let mut buffer = self
+                    .config
+                    .adaptive_buffer
+                    .then(|| AdaptiveBuffer::new(src_size));
+                copy_with_fsync(&mut reader, &mut writer, interval, buffer.as_mut())
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to copy"))?;
+            } else if self.config.adaptive_buffer {
+                let mut writer = dst_file;
+                AdaptiveBuffer::new(src_size)
+                    .copy(&mut reader, &mut writer)
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to copy"))?;
+            } else {
+                let mut writer = BufWriter::with_capacity(64 * 1024, dst_file);
+
+                io::copy(&mut reader, &mut writer)
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to copy"))?;
+
+                writer
+                    .flush()
+                    .map_err(|e| ExecuteError::from_io(e, "Failed to flush"))?;
+            }
+            notes.content_hash = Some(reader.finish());
+        }
+        partial.persist(dst)?;
+        notes.eol_normalized = eol.is_some();
+        self.finish_copy(src, dst, notes)
+    }
+
+    /// Gives a finished copy the mtime, owner and attributes of its source
+    fn finish_copy(
+        &self,
+        src: &Path,
+        dst: &Path,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<(), ExecuteError> {
+        // Preserve mtime
+        let src_meta =
+            fs::metadata(src).map_err(|e| ExecuteError::from_io(e, "Failed to get metadata"))?;
+        if let Ok(mtime) = src_meta.modified() {
+            let _ = set_file_mtime(dst, mtime);
+        }
+
+        // Owner before mode: chown clears the setuid/setgid bits
+        if self.config.preserve_ownership {
+            if let Err(e) = copy_ownership(&src_meta, dst) {
+                notes
+                    .warnings
+                    .push(format!("Failed to copy owner/group: {}", e));
+            }
+        }
+
+        // Preserve file attributes (readonly, hidden on Windows; the mode
+        // elsewhere only when asked)
+        if cfg!(windows) || self.config.preserve_permissions {
+            let _ = set_file_attributes(dst, src);
+        }
+        Ok(())
+    }
+
+    /// Creates a FIFO at `dst` in place of the FIFO `src`, which has no
+    /// content to copy. `dst` must already be prepared.
+    fn recreate_fifo(&self, src: &Path, dst: &Path) -> std::result::Result<(), ExecuteError> {
+        let src_meta =
+            fs::metadata(src).map_err(|e| ExecuteError::from_io(e, "Failed to get metadata"))?;
+        if fs::symlink_metadata(dst).is_ok() {
+            fs::remove_file(dst)
+                .map_err(|e| ExecuteError::from_io(e, "Failed to replace destination"))?;
+        } else if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| ExecuteError::from_io(e, "Failed to create parent dirs"))?;
+        }
+        make_fifo(dst, &src_meta).map_err(|e| ExecuteError::from_io(e, "Failed to create FIFO"))?;
+
+        // By path: setting them through a handle would open, and block on, the FIFO
+        let _ = filetime::set_symlink_file_times(
+            dst,
+            filetime::FileTime::from_last_access_time(&src_meta),
+            filetime::FileTime::from_last_modification_time(&src_meta),
+        );
+        if self.config.preserve_ownership {
+            let _ = copy_ownership(&src_meta, dst);
+        }
         Ok(())
     }
 
-    fn delete_file(&self, path: &Path, root: &Path) -> std::result::Result<(), ExecuteError> {
+    fn delete_file(
+        &self,
+        path: &Path,
+        root: &Path,
+        notes: &mut ActionNotes,
+    ) -> std::result::Result<(), ExecuteError> {
         if !path.exists() {
             return Ok(()); // Already deleted
         }
 
+        let target = if root == self.right_root {
+            self.config.soft_delete_target_right
+        } else {
+            self.config.soft_delete_target_left
+        };
+        if self.config.soft_delete && target == SoftDeleteTarget::SystemTrash {
+            // A directory's contents were trashed before it
+            if path.is_dir() && fs::remove_dir(path).is_ok() {
+                return Ok(());
+            }
+            match trash::move_to_system_trash(path) {
+                Ok(()) => return Ok(()),
+                Err(e) => notes.warnings.push(format!(
+                    "System trash unavailable ({}); moved to .rahzom/_trash instead",
+                    e
+                )),
+            }
+        }
+
         if self.config.soft_delete {
             self.soft_delete(path, root)
         } else {
@@ -481,20 +1690,9 @@ impl Executor {
     }
 
     fn soft_delete(&self, path: &Path, root: &Path) -> std::result::Result<(), ExecuteError> {
-        let trash_dir = root.join(METADATA_DIR).join(TRASH_DIR);
-        fs::create_dir_all(&trash_dir)
-            .map_err(|e| ExecuteError::from_io(e, "Failed to create trash dir"))?;
-
-        let filename = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f");
-        let trash_name = format!("{}.{}", filename, timestamp);
-        let trash_path = trash_dir.join(trash_name);
-
-        fs::rename(path, &trash_path).map_err(|e| ExecuteError::from_io(e, "Failed to move to trash"))
+        trash::move_to_trash(root, path, &self.trash_session)
+            .map(|_| ())
+            .map_err(|e| ExecuteError::from_io(e, "Failed to move to trash"))
     }
 
     fn create_backup(&self, path: &Path, root: &Path) -> std::result::Result<(), ExecuteError> {
@@ -507,7 +1705,7 @@ impl Executor {
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S_%3f");
+        let timestamp = Utc::now().format(VERSION_TIMESTAMP_FORMAT);
         let backup_name = format!("{}.{}", filename, timestamp);
         let backup_path = backup_dir.join(&backup_name);
 
@@ -525,11 +1723,12 @@ impl Executor {
         backup_dir: &Path,
         filename: &str,
     ) -> std::result::Result<(), ExecuteError> {
-        let prefix = format!("{}.", filename);
         let mut backups: Vec<_> = fs::read_dir(backup_dir)
             .map_err(|e| ExecuteError::from_io(e, "Failed to read backup dir"))?
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            .filter(|e| {
+                parse_backup_timestamp(&e.file_name().to_string_lossy(), filename).is_some()
+            })
             .collect();
 
         // Sort by name (timestamp) descending
@@ -544,7 +1743,7 @@ impl Executor {
     }
 
     fn create_dir(&self, path: &Path) -> std::result::Result<(), ExecuteError> {
-        fs::create_dir_all(path).map_err(|e| ExecuteError::from_io(e, "Failed to create directory"))
+        self.ensure_dir(path, "Failed to create directory")
     }
 }
 
@@ -567,12 +1766,83 @@ impl ExecuteError {
     }
 }
 
-fn system_time_to_utc(time: SystemTime) -> DateTime<Utc> {
-    let duration = time
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    DateTime::from_timestamp(duration.as_secs() as i64, duration.subsec_nanos())
-        .unwrap_or_else(Utc::now)
+/// Copy destination that can be forced to stable storage
+trait SyncWrite: Write {
+    fn sync_data(&mut self) -> io::Result<()>;
+    fn sync_all(&mut self) -> io::Result<()>;
+}
+
+impl SyncWrite for File {
+    fn sync_data(&mut self) -> io::Result<()> {
+        File::sync_data(self)
+    }
+
+    fn sync_all(&mut self) -> io::Result<()> {
+        File::sync_all(self)
+    }
+}
+
+/// Writer that syncs data to disk after every `interval` bytes written
+struct IntervalSync<'a, W: SyncWrite> {
+    inner: &'a mut W,
+    interval: u64,
+    unsynced: u64,
+}
+
+impl<W: SyncWrite> Write for IntervalSync<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.unsynced += n as u64;
+        if self.unsynced >= self.interval {
+            self.inner.sync_data()?;
+            self.unsynced = 0;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copies `reader` to `writer`, syncing data after every `interval` bytes
+/// and syncing everything once at the end. Copies through `buffer` when
+/// given, through a fixed 64 KB buffer otherwise. Returns the number of
+/// bytes copied.
+fn copy_with_fsync<R: Read, W: SyncWrite>(
+    reader: &mut R,
+    writer: &mut W,
+    interval: u64,
+    buffer: Option<&mut AdaptiveBuffer>,
+) -> io::Result<u64> {
+    let mut synced = IntervalSync {
+        inner: writer,
+        interval,
+        unsynced: 0,
+    };
+
+    let total = match buffer {
+        Some(buffer) => buffer.copy(reader, &mut synced)?,
+        None => {
+            let mut buf = vec![0u8; 64 * 1024];
+            let mut total = 0u64;
+            loop {
+                let n = match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                synced.write_all(&buf[..n])?;
+                total += n as u64;
+            }
+            synced.flush()?;
+            total
+        }
+    };
+
+    writer.sync_all()?;
+    Ok(total)
 }
 
 #[cfg(windows)]
@@ -611,10 +1881,8 @@ fn set_file_mtime(path: &Path, mtime: SystemTime) -> io::Result<()> {
 
 #[cfg(not(windows))]
 fn set_file_mtime(path: &Path, mtime: SystemTime) -> io::Result<()> {
-    // On Unix, we'd use filetime crate or libc
-    // For now, just ignore mtime setting on non-Windows
-    let _ = (path, mtime);
-    Ok(())
+    // Leaves atime untouched (UTIME_OMIT)
+    filetime::set_file_mtime(path, filetime::FileTime::from_system_time(mtime))
 }
 
 /// Sets Windows file attributes (readonly, hidden) on the destination file
@@ -659,37 +1927,366 @@ fn set_file_attributes(path: &Path, src_path: &Path) -> io::Result<()> {
     }
 }
 
+/// Makes the read-only and hidden attributes of `path` those of
+/// `src_path`, clearing them as well as setting them
+#[cfg(windows)]
+fn copy_attributes(path: &Path, src_path: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const ATTRS_MASK: u32 = FILE_ATTRIBUTE_READONLY | FILE_ATTRIBUTE_HIDDEN;
+
+    let src_attrs = fs::metadata(src_path)?.file_attributes() & ATTRS_MASK;
+    let dst_attrs = fs::metadata(path)?.file_attributes();
+    let new_attrs = (dst_attrs & !ATTRS_MASK) | src_attrs;
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let result = unsafe {
+        windows_sys::Win32::Storage::FileSystem::SetFileAttributesW(wide_path.as_ptr(), new_attrs)
+    };
+    if result == 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Makes the permission bits of `path` those of `src_path`, which is
+/// what `set_file_attributes` already does outside Windows
 #[cfg(not(windows))]
+fn copy_attributes(path: &Path, src_path: &Path) -> io::Result<()> {
+    set_file_attributes(path, src_path)
+}
+
+/// Copies the permission bits of the source onto the destination file
+#[cfg(unix)]
+fn set_file_attributes(path: &Path, src_path: &Path) -> io::Result<()> {
+    fs::set_permissions(path, fs::metadata(src_path)?.permissions())
+}
+
+#[cfg(not(any(windows, unix)))]
 fn set_file_attributes(_path: &Path, _src_path: &Path) -> io::Result<()> {
-    // On Unix, permissions would be handled differently
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
-    use tempfile::TempDir;
+/// Whether `path` is an existing regular file marked read-only
+fn is_readonly_file(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.is_file() && m.permissions().readonly())
+        .unwrap_or(false)
+}
 
-    fn create_test_dirs() -> (TempDir, TempDir) {
-        (
-            TempDir::new().expect("Failed to create left dir"),
-            TempDir::new().expect("Failed to create right dir"),
+/// Makes a read-only file writable for its owner
+#[cfg(unix)]
+fn clear_readonly(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o200);
+    fs::set_permissions(path, permissions)
+}
+
+/// Clears the read-only attribute of a file
+#[cfg(not(unix))]
+fn clear_readonly(path: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    // Only the attribute bit on Windows; there are no world-write bits to open up
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions)
+}
+
+/// Copies the DACL of `src` onto `dst`. Usually requires ownership of the
+/// destination or SeRestorePrivilege.
+#[cfg(windows)]
+fn copy_acl(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{
+        GetNamedSecurityInfoW, SetNamedSecurityInfoW, SE_FILE_OBJECT,
+    };
+    use windows_sys::Win32::Security::{ACL, DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR};
+
+    let wide_src: Vec<u16> = src
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let wide_dst: Vec<u16> = dst
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide_src.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut dacl,
+            std::ptr::null_mut(),
+            &mut descriptor,
         )
+    };
+    if status != ERROR_SUCCESS {
+        return Err(io::Error::from_raw_os_error(status as i32));
     }
 
-    #[test]
-    fn test_copy_single_file() {
-        let (left, right) = create_test_dirs();
+    // `dacl` points into `descriptor`, which must outlive the set call
+    let status = unsafe {
+        SetNamedSecurityInfoW(
+            wide_dst.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            dacl,
+            std::ptr::null(),
+        )
+    };
 
-        // Create source file
-        let content = "Hello, World!";
-        fs::write(left.path().join("test.txt"), content).unwrap();
+    unsafe {
+        LocalFree(descriptor as _);
+    }
 
-        let executor = Executor::new(
-            left.path().to_path_buf(),
-            right.path().to_path_buf(),
-            ExecutorConfig::default(),
+    if status != ERROR_SUCCESS {
+        Err(io::Error::from_raw_os_error(status as i32))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+fn copy_acl(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Gives `dst` the owner and group of the file `src_meta` was read from.
+/// Fails with `PermissionDenied` (EPERM) unless running as root.
+#[cfg(unix)]
+fn copy_ownership(src_meta: &fs::Metadata, dst: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    std::os::unix::fs::chown(dst, Some(src_meta.uid()), Some(src_meta.gid()))
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_src_meta: &fs::Metadata, _dst: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Creates a FIFO at `path` with the permissions of `src_meta`
+#[cfg(unix)]
+fn make_fifo(path: &Path, src_meta: &fs::Metadata) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mode = src_meta.permissions().mode() & 0o7777;
+    // SAFETY: `c_path` is a valid NUL-terminated string that outlives the call
+    if unsafe { libc::mkfifo(c_path.as_ptr(), mode as libc::mode_t) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn make_fifo(_path: &Path, _src_meta: &fs::Metadata) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "FIFOs are only supported on Unix",
+    ))
+}
+
+/// Execution position of an action: directories first, then copies, then
+/// deletes (deepest first), skips and conflicts last
+pub(crate) fn action_order(action: &SyncAction) -> (u8, usize, bool) {
+    match action {
+        // Directories first, sorted by depth (shallow first)
+        SyncAction::CreateDirLeft { path } | SyncAction::CreateDirRight { path } => {
+            (0, path.components().count(), false)
+        }
+        // Copies second, attribute updates alongside them
+        SyncAction::CopyToLeft { path, .. }
+        | SyncAction::CopyToRight { path, .. }
+        | SyncAction::KeepBoth { path, .. }
+        | SyncAction::UpdateAttributesLeft { path }
+        | SyncAction::UpdateAttributesRight { path } => (1, path.components().count(), false),
+        // Deletes last, sorted by depth (deep first for directories)
+        SyncAction::DeleteLeft { path } | SyncAction::DeleteRight { path } => {
+            (2, usize::MAX - path.components().count(), true)
+        }
+        // Skip and Conflict at the end
+        SyncAction::Skip { .. } | SyncAction::Conflict { .. } => (3, 0, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn create_test_dirs() -> (TempDir, TempDir) {
+        (
+            TempDir::new().expect("Failed to create left dir"),
+            TempDir::new().expect("Failed to create right dir"),
+        )
+    }
+
+    #[test]
+    fn test_validate_target() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("file.txt"), "content").unwrap();
+
+        assert_eq!(
+            validate_target(temp.path()).unwrap(),
+            TargetStatus::Writable
+        );
+        assert_eq!(
+            validate_target(&temp.path().join("missing")).unwrap(),
+            TargetStatus::NotFound
+        );
+        assert_eq!(
+            validate_target(&temp.path().join("file.txt")).unwrap(),
+            TargetStatus::NotADirectory
+        );
+
+        // Probe file must not be left behind
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_progress_reports_action_indices_in_order() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl ProgressCallback for Recorder {
+            fn on_action_start(&mut self, index: usize, action: &SyncAction) {
+                self.0
+                    .push(format!("start {} {}", index, action.path().display()));
+            }
+            fn on_progress(&mut self, current: usize, total: usize, _current_file: &Path) {
+                self.0.push(format!("progress {}/{}", current, total));
+            }
+            fn on_file_complete(&mut self, action: &SyncAction, success: bool) {
+                self.0
+                    .push(format!("complete {} {}", action.path().display(), success));
+            }
+        }
+
+        let (left, right) = create_test_dirs();
+        fs::write(left.path().join("a.txt"), "a").unwrap();
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        // Given out of order: execution creates directories first
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("a.txt"),
+                size: 1,
+            },
+            SyncAction::CreateDirRight {
+                path: PathBuf::from("dir"),
+            },
+        ];
+
+        let mut recorder = Recorder::default();
+        executor
+            .execute(actions, &HashMap::new(), &mut recorder)
+            .unwrap();
+
+        assert_eq!(
+            recorder.0,
+            [
+                "start 0 dir",
+                "progress 1/2",
+                "complete dir true",
+                "start 1 a.txt",
+                "progress 2/2",
+                "complete a.txt true",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_compressed_right_round_trip() {
+        let (left, right) = create_test_dirs();
+        let content = "repetitive ".repeat(1000);
+        fs::write(left.path().join("data.txt"), &content).unwrap();
+        let config = ExecutorConfig {
+            compress_right: true,
+            ..ExecutorConfig::default()
+        };
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            config,
+        );
+        let copy = |action| {
+            executor
+                .execute(vec![action], &HashMap::new(), &mut NoopProgress)
+                .unwrap()
+        };
+
+        let result = copy(SyncAction::CopyToRight {
+            path: PathBuf::from("data.txt"),
+            size: content.len() as u64,
+        });
+        assert_eq!(result.completed.len(), 1);
+        let archived = right.path().join("data.txt.rahzom.zst");
+        assert!(!right.path().join("data.txt").exists());
+        assert!(fs::metadata(&archived).unwrap().len() < content.len() as u64 / 10);
+        assert_eq!(
+            compress::content_size(&archived).unwrap(),
+            content.len() as u64
+        );
+
+        // Restoring decompresses
+        fs::remove_file(left.path().join("data.txt")).unwrap();
+        let result = copy(SyncAction::CopyToLeft {
+            path: PathBuf::from("data.txt"),
+            size: content.len() as u64,
+        });
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(left.path().join("data.txt")).unwrap(),
+            content
+        );
+
+        copy(SyncAction::DeleteRight {
+            path: PathBuf::from("data.txt"),
+        });
+        assert!(!archived.exists());
+    }
+
+    #[test]
+    fn test_copy_single_file() {
+        let (left, right) = create_test_dirs();
+
+        // Create source file
+        let content = "Hello, World!";
+        fs::write(left.path().join("test.txt"), content).unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
         );
 
         let actions = vec![SyncAction::CopyToRight {
@@ -755,6 +2352,47 @@ mod tests {
         assert!(diff <= 2, "mtime difference too large: {} seconds", diff);
     }
 
+    #[test]
+    fn test_overwrite_policy_with_newer_destination() {
+        let (left, right) = create_test_dirs();
+        let hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::write(left.path().join("old.txt"), "source").unwrap();
+        set_file_mtime(&left.path().join("old.txt"), hour_ago).unwrap();
+        fs::write(left.path().join("new.txt"), "source").unwrap();
+
+        let run = |policy: OverwritePolicy| {
+            // Edited on the right since: newer than old.txt, older than new.txt
+            for name in ["old.txt", "new.txt"] {
+                fs::write(right.path().join(name), "edited").unwrap();
+                let edited = SystemTime::now() - std::time::Duration::from_secs(60);
+                set_file_mtime(&right.path().join(name), edited).unwrap();
+            }
+            let executor = Executor::new(
+                left.path().to_path_buf(),
+                right.path().to_path_buf(),
+                ExecutorConfig {
+                    backup_enabled: false,
+                    overwrite_policy: policy,
+                    ..Default::default()
+                },
+            );
+            let actions = ["old.txt", "new.txt"]
+                .map(|name| SyncAction::CopyToRight {
+                    path: PathBuf::from(name),
+                    size: 6,
+                })
+                .to_vec();
+            executor
+                .execute(actions, &HashMap::new(), &mut NoopProgress)
+                .unwrap();
+            ["old.txt", "new.txt"].map(|name| fs::read_to_string(right.path().join(name)).unwrap())
+        };
+
+        assert_eq!(run(OverwritePolicy::Always), ["source", "source"]);
+        assert_eq!(run(OverwritePolicy::IfSourceNewer), ["edited", "source"]);
+        assert_eq!(run(OverwritePolicy::Never), ["edited", "edited"]);
+    }
+
     #[test]
     fn test_soft_delete() {
         let (left, right) = create_test_dirs();
@@ -786,6 +2424,40 @@ mod tests {
         assert!(trash_dir.exists());
         let trash_files: Vec<_> = fs::read_dir(&trash_dir).unwrap().collect();
         assert_eq!(trash_files.len(), 1);
+        let trashed = trash::list_trash(right.path()).unwrap();
+        assert_eq!(trashed[0].path, PathBuf::from("to_delete.txt"));
+        assert!(trashed[0].session.is_some());
+    }
+
+    #[test]
+    #[cfg(not(feature = "system-trash"))]
+    fn test_system_trash_falls_back_to_internal() {
+        let (left, right) = create_test_dirs();
+        fs::write(right.path().join("to_delete.txt"), "delete me").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                soft_delete_target_right: SoftDeleteTarget::SystemTrash,
+                ..Default::default()
+            },
+        );
+        let actions = vec![SyncAction::DeleteRight {
+            path: PathBuf::from("to_delete.txt"),
+        }];
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert!(!right.path().join("to_delete.txt").exists());
+        let trashed = trash::list_trash(right.path()).unwrap();
+        assert_eq!(trashed[0].path, PathBuf::from("to_delete.txt"));
+        assert!(
+            result.completed[0].warnings[0].contains("System trash unavailable"),
+            "{:?}",
+            result.completed[0].warnings
+        );
     }
 
     #[test]
@@ -856,6 +2528,96 @@ mod tests {
         assert_eq!(backup_files.len(), 1);
     }
 
+    #[test]
+    fn test_list_backups_handles_dotted_names() {
+        let temp = TempDir::new().unwrap();
+        let backup_dir = temp.path().join(".rahzom/_backup");
+        fs::create_dir_all(&backup_dir).unwrap();
+
+        fs::write(backup_dir.join("archive.tar.20240101_120000_000"), "old").unwrap();
+        fs::write(backup_dir.join("archive.tar.20240102_120000_500"), "newer").unwrap();
+        // Backup of a different file sharing the prefix
+        fs::write(backup_dir.join("archive.tar.gz.20240103_120000_000"), "gz").unwrap();
+        // Not a backup name at all
+        fs::write(backup_dir.join("archive.tar.notes"), "x").unwrap();
+
+        let backups = list_backups(temp.path(), "archive.tar").unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].size, 5);
+        assert!(backups[0].timestamp > backups[1].timestamp);
+        let newest = backups[0].timestamp.format("%Y-%m-%d %H:%M:%S%.3f");
+        assert_eq!(newest.to_string(), "2024-01-02 12:00:00.500");
+
+        let gz = list_backups(temp.path(), "archive.tar.gz").unwrap();
+        assert_eq!(gz.len(), 1);
+
+        assert!(list_backups(temp.path(), "missing.txt").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_backups_without_backup_dir() {
+        let temp = TempDir::new().unwrap();
+        assert!(list_backups(temp.path(), "file.txt").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_backup_backs_up_current() {
+        let temp = TempDir::new().unwrap();
+        let backup_dir = temp.path().join(".rahzom/_backup");
+        fs::create_dir_all(&backup_dir).unwrap();
+        fs::create_dir_all(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/file.txt"), "current").unwrap();
+        fs::write(backup_dir.join("file.txt.20240101_120000_000"), "old").unwrap();
+
+        let backups = list_backups(temp.path(), "file.txt").unwrap();
+        restore_backup(temp.path(), Path::new("sub/file.txt"), &backups[0]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(temp.path().join("sub/file.txt")).unwrap(),
+            "old"
+        );
+
+        // Previous content is kept as a new backup version
+        let backups = list_backups(temp.path(), "file.txt").unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "current");
+    }
+
+    #[test]
+    fn test_backup_rotation_ignores_other_files() {
+        let (left, right) = create_test_dirs();
+        let backup_dir = right.path().join(".rahzom/_backup");
+        fs::create_dir_all(&backup_dir).unwrap();
+        fs::write(backup_dir.join("file.txt.bak.20240101_120000_000"), "other").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                backup_enabled: true,
+                backup_versions: 1,
+                ..Default::default()
+            },
+        );
+
+        for i in 0..3 {
+            fs::write(left.path().join("file.txt"), format!("v{}", i)).unwrap();
+            fs::write(right.path().join("file.txt"), format!("old{}", i)).unwrap();
+            let actions = vec![SyncAction::CopyToRight {
+                path: PathBuf::from("file.txt"),
+                size: 2,
+            }];
+            executor
+                .execute(actions, &HashMap::new(), &mut NoopProgress)
+                .unwrap();
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(list_backups(right.path(), "file.txt").unwrap().len(), 1);
+        assert!(backup_dir.join("file.txt.bak.20240101_120000_000").exists());
+    }
+
     #[test]
     fn test_backup_rotation() {
         let (left, right) = create_test_dirs();
@@ -918,11 +2680,12 @@ mod tests {
     }
 
     #[test]
-    fn test_execution_order() {
+    fn test_preserve_file_mtime_after_copy() {
         let (left, right) = create_test_dirs();
 
         fs::write(left.path().join("file.txt"), "content").unwrap();
-        fs::write(right.path().join("to_delete.txt"), "delete").unwrap();
+        let old_mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        set_file_mtime(&left.path().join("file.txt"), old_mtime).unwrap();
 
         let executor = Executor::new(
             left.path().to_path_buf(),
@@ -930,40 +2693,36 @@ mod tests {
             ExecutorConfig::default(),
         );
 
-        // Actions in wrong order
-        let actions = vec![
-            SyncAction::DeleteRight {
-                path: PathBuf::from("to_delete.txt"),
-            },
-            SyncAction::CopyToRight {
-                path: PathBuf::from("file.txt"),
-                size: 7,
-            },
-            SyncAction::CreateDirRight {
-                path: PathBuf::from("newdir"),
-            },
-        ];
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("file.txt"),
+            size: 7,
+        }];
 
-        let result = executor
+        executor
             .execute(actions, &HashMap::new(), &mut NoopProgress)
             .unwrap();
 
-        // All should succeed
-        assert_eq!(result.completed.len(), 3);
-        assert_eq!(result.failed.len(), 0);
-
-        // Verify order was: dir, copy, delete
-        // (We can't easily verify order, but we verify all completed)
-        assert!(right.path().join("newdir").is_dir());
-        assert!(right.path().join("file.txt").exists());
-        assert!(!right.path().join("to_delete.txt").exists());
+        let dst_mtime = fs::metadata(right.path().join("file.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        let diff = dst_mtime
+            .duration_since(old_mtime)
+            .unwrap_or_else(|e| e.duration())
+            .as_secs();
+        assert!(diff <= 1, "file mtime not preserved: {} seconds off", diff);
     }
 
     #[test]
-    fn test_file_changed_during_sync() {
+    fn test_preserve_dir_mtime_after_copy() {
         let (left, right) = create_test_dirs();
 
-        fs::write(left.path().join("test.txt"), "content").unwrap();
+        fs::create_dir(left.path().join("sub")).unwrap();
+        fs::create_dir(right.path().join("sub")).unwrap();
+        fs::write(left.path().join("sub/file.txt"), "content").unwrap();
+
+        let old_mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        set_file_mtime(&left.path().join("sub"), old_mtime).unwrap();
 
         let executor = Executor::new(
             left.path().to_path_buf(),
@@ -971,29 +2730,1348 @@ mod tests {
             ExecutorConfig::default(),
         );
 
-        // Create snapshot with different size
-        let mut snapshots = HashMap::new();
-        snapshots.insert(
-            PathBuf::from("test.txt"),
-            FileSnapshot {
-                size: 100, // Different from actual size
-                mtime: Utc::now(),
-            },
-        );
-
         let actions = vec![SyncAction::CopyToRight {
-            path: PathBuf::from("test.txt"),
+            path: PathBuf::from("sub/file.txt"),
             size: 7,
         }];
 
-        let result = executor
-            .execute(actions, &snapshots, &mut NoopProgress)
+        executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
             .unwrap();
 
-        // Should be skipped
-        assert_eq!(result.completed.len(), 0);
-        assert_eq!(result.skipped.len(), 1);
-        assert!(!right.path().join("test.txt").exists());
+        let dst_mtime = fs::metadata(right.path().join("sub"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        let diff = dst_mtime
+            .duration_since(old_mtime)
+            .unwrap_or_else(|e| e.duration())
+            .as_secs();
+        assert!(diff <= 2, "dir mtime not preserved: {} seconds off", diff);
+    }
+
+    #[test]
+    fn test_preserve_dir_mtime_of_all_ancestors() {
+        let (left, right) = create_test_dirs();
+
+        fs::create_dir_all(left.path().join("a/b/c")).unwrap();
+        fs::write(left.path().join("a/b/c/file.txt"), "content").unwrap();
+        fs::create_dir(left.path().join("x")).unwrap();
+        fs::write(left.path().join("x/blocked"), "content").unwrap();
+        // The destination holds a stale file the sync removes, and a
+        // directory the copy of `blocked` fails on after writing its partial
+        fs::create_dir_all(right.path().join("a/b/c")).unwrap();
+        fs::write(right.path().join("a/b/c/old.txt"), "old").unwrap();
+        fs::create_dir_all(right.path().join("x/blocked/inner")).unwrap();
+
+        let times = [
+            ("a", 1_000_000_000),
+            ("a/b", 1_100_000_000),
+            ("a/b/c", 1_200_000_000),
+            ("x", 1_300_000_000),
+        ];
+        for (dir, secs) in times {
+            let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            set_file_mtime(&left.path().join(dir), mtime).unwrap();
+        }
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("a/b/c/file.txt"),
+                size: 7,
+            },
+            SyncAction::DeleteRight {
+                path: PathBuf::from("a/b/c/old.txt"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("x/blocked"),
+                size: 7,
+            },
+        ];
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+        assert_eq!(result.failed.len(), 1);
+
+        for (dir, secs) in times {
+            let mtime = fs::metadata(right.path().join(dir))
+                .unwrap()
+                .modified()
+                .unwrap();
+            let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+            let diff = mtime
+                .duration_since(expected)
+                .unwrap_or_else(|e| e.duration())
+                .as_secs();
+            assert!(diff <= 2, "mtime of {} not preserved: {}s off", dir, diff);
+        }
+    }
+
+    #[test]
+    fn test_execution_order() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("file.txt"), "content").unwrap();
+        fs::write(right.path().join("to_delete.txt"), "delete").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+
+        // Actions in wrong order
+        let actions = vec![
+            SyncAction::DeleteRight {
+                path: PathBuf::from("to_delete.txt"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("file.txt"),
+                size: 7,
+            },
+            SyncAction::CreateDirRight {
+                path: PathBuf::from("newdir"),
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        // All should succeed
+        assert_eq!(result.completed.len(), 3);
+        assert_eq!(result.failed.len(), 0);
+
+        // Verify order was: dir, copy, delete
+        // (We can't easily verify order, but we verify all completed)
+        assert!(right.path().join("newdir").is_dir());
+        assert!(right.path().join("file.txt").exists());
+        assert!(!right.path().join("to_delete.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_in_path_order() {
+        let copy = |path: &str| SyncAction::CopyToRight {
+            path: PathBuf::from(path),
+            size: 1,
+        };
+        let actions = vec![
+            SyncAction::DeleteRight {
+                path: PathBuf::from("old.txt"),
+            },
+            copy("b/x/deep.txt"),
+            copy("a/2.txt"),
+            copy("z.txt"),
+            SyncAction::CreateDirRight {
+                path: PathBuf::from("b/x"),
+            },
+            copy("b/1.txt"),
+            copy("a/1.txt"),
+        ];
+        let paths = |config: ExecutorConfig| {
+            let executor = Executor::new(PathBuf::from("l"), PathBuf::from("r"), config);
+            executor
+                .sort_actions(actions.clone())
+                .iter()
+                .map(|action| action.path().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        // By default copies go by depth, scattering directories
+        assert_eq!(
+            paths(ExecutorConfig::default()),
+            "b/x z.txt a/2.txt b/1.txt a/1.txt b/x/deep.txt old.txt"
+        );
+        let config = ExecutorConfig {
+            copy_in_path_order: true,
+            ..ExecutorConfig::default()
+        };
+        assert_eq!(
+            paths(config),
+            "b/x a/1.txt a/2.txt b/1.txt b/x/deep.txt z.txt old.txt"
+        );
+    }
+
+    #[test]
+    fn test_sort_actions_matches_per_comparison_order() {
+        let path = PathBuf::from;
+        let mut actions = Vec::new();
+        for (i, dir) in ["", "a/", "a/b/", "c/d/e/"].iter().enumerate() {
+            actions.extend([
+                SyncAction::DeleteLeft {
+                    path: path(format!("{}gone{}", dir, i)),
+                },
+                SyncAction::CopyToRight {
+                    path: path(format!("{}new{}.txt", dir, i)),
+                    size: 1,
+                },
+                SyncAction::Skip {
+                    path: path(format!("{}same{}.txt", dir, i)),
+                    reason: "identical".to_string(),
+                },
+                SyncAction::CreateDirLeft {
+                    path: path(format!("{}dir{}", dir, i)),
+                },
+                SyncAction::CopyToLeft {
+                    path: path(format!("{}back{}.txt", dir, i)),
+                    size: 1,
+                },
+                SyncAction::DeleteRight {
+                    path: path(format!("{}old{}", dir, i)),
+                },
+                SyncAction::CreateDirRight {
+                    path: path(format!("{}made{}", dir, i)),
+                },
+            ]);
+        }
+        actions.reverse();
+
+        for copy_in_path_order in [false, true] {
+            // The order as computed per comparison
+            let mut expected = actions.clone();
+            expected.sort_by(|a, b| {
+                let (order_a, order_b) = (action_order(a), action_order(b));
+                if copy_in_path_order && order_a.0 == 1 && order_b.0 == 1 {
+                    return a.path().cmp(b.path());
+                }
+                order_a.cmp(&order_b)
+            });
+            let config = ExecutorConfig {
+                copy_in_path_order,
+                ..ExecutorConfig::default()
+            };
+            let executor = Executor::new(PathBuf::from("l"), PathBuf::from("r"), config);
+            assert_eq!(executor.sort_actions(actions.clone()), expected);
+        }
+    }
+
+    #[test]
+    fn test_file_changed_during_sync() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("test.txt"), "content").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+
+        // Create snapshot with different size
+        let mut snapshots = HashMap::new();
+        snapshots.insert(
+            PathBuf::from("test.txt"),
+            FileSnapshot {
+                size: 100, // Different from actual size
+                mtime: Utc::now(),
+            },
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("test.txt"),
+            size: 7,
+        }];
+
+        let result = executor
+            .execute(actions, &snapshots, &mut NoopProgress)
+            .unwrap();
+
+        // Should be skipped
+        assert_eq!(result.completed.len(), 0);
+        assert_eq!(result.skipped.len(), 1);
+        assert!(!right.path().join("test.txt").exists());
+    }
+
+    #[test]
+    fn test_source_removed_before_copy_is_skipped() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("gone.txt"), "content").unwrap();
+        fs::write(right.path().join("gone.txt"), "old").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("gone.txt"),
+            size: 7,
+        }];
+
+        // Deleted after the action was planned; there is no snapshot to catch it
+        fs::remove_file(left.path().join("gone.txt")).unwrap();
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert!(result.failed.is_empty());
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].reason, "source removed");
+        // Destination untouched and not backed up
+        assert_eq!(
+            fs::read_to_string(right.path().join("gone.txt")).unwrap(),
+            "old"
+        );
+        assert!(!right.path().join(METADATA_DIR).join(BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn test_copy_records_content_hash() {
+        let (left, right) = create_test_dirs();
+        fs::write(left.path().join("plain.txt"), "Hello, World!").unwrap();
+        fs::write(left.path().join("synced.txt"), "Hello, World!").unwrap();
+
+        for (path, fsync_interval_bytes) in [("plain.txt", None), ("synced.txt", Some(4))] {
+            let executor = Executor::new(
+                left.path().to_path_buf(),
+                right.path().to_path_buf(),
+                ExecutorConfig {
+                    fsync_interval_bytes,
+                    ..Default::default()
+                },
+            );
+            let result = executor
+                .execute(
+                    vec![SyncAction::CopyToRight {
+                        path: PathBuf::from(path),
+                        size: 13,
+                    }],
+                    &HashMap::new(),
+                    &mut NoopProgress,
+                )
+                .unwrap();
+
+            assert_eq!(
+                result.completed[0].content_hash.as_deref(),
+                Some("dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f")
+            );
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_simulated_fault_fails_action() {
+        use crate::sync::faults::{Fault, Faults};
+
+        let (left, right) = create_test_dirs();
+        fs::write(left.path().join("locked.txt"), "locked").unwrap();
+        fs::write(left.path().join("slow.txt"), "slow").unwrap();
+
+        let mut faults = Faults::default();
+        faults.insert("locked.txt", Fault::Error(SyncErrorKind::FileLocked));
+        faults.insert(
+            "slow.txt",
+            Fault::Delay(std::time::Duration::from_millis(1)),
+        );
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        )
+        .with_faults(faults);
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("locked.txt"),
+                size: 6,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("slow.txt"),
+                size: 4,
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].kind, SyncErrorKind::FileLocked);
+        assert!(!right.path().join("locked.txt").exists());
+        assert!(right.path().join("slow.txt").exists());
+    }
+
+    #[test]
+    fn test_copy_goes_through_partial_file() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("ok.txt"), "new").unwrap();
+        fs::write(right.path().join("ok.txt"), "old").unwrap();
+        fs::write(left.path().join("blocked"), "file").unwrap();
+        // A non-empty directory can't be replaced by the finished copy
+        fs::create_dir_all(right.path().join("blocked/inner")).unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                backup_enabled: false,
+                ..Default::default()
+            },
+        );
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("ok.txt"),
+                size: 3,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("blocked"),
+                size: 4,
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0]
+            .error
+            .contains("Failed to replace destination"));
+        assert_eq!(result.failed[0].kind, SyncErrorKind::InvalidPath);
+        assert_eq!(
+            fs::read_to_string(right.path().join("ok.txt")).unwrap(),
+            "new"
+        );
+        // Neither the finished nor the failed copy leaves its partial behind
+        assert!(!temp_path_for(&right.path().join("ok.txt")).exists());
+        assert!(!temp_path_for(&right.path().join("blocked")).exists());
+        assert!(right.path().join("blocked/inner").is_dir());
+    }
+
+    #[test]
+    fn test_remove_partial_copies_only_touches_planned_copies() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(temp_path_for(&right.path().join("a.txt")), "part").unwrap();
+        fs::write(temp_path_for(&left.path().join("b.txt")), "part").unwrap();
+        fs::write(temp_path_for(&right.path().join("c.txt")), "part").unwrap();
+
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("a.txt"),
+                size: 4,
+            },
+            SyncAction::CopyToLeft {
+                path: PathBuf::from("b.txt"),
+                size: 4,
+            },
+            SyncAction::DeleteRight {
+                path: PathBuf::from("c.txt"),
+            },
+        ];
+
+        assert_eq!(
+            remove_partial_copies(left.path(), right.path(), &actions, false),
+            2
+        );
+        assert!(!temp_path_for(&right.path().join("a.txt")).exists());
+        assert!(!temp_path_for(&left.path().join("b.txt")).exists());
+        assert!(temp_path_for(&right.path().join("c.txt")).exists());
+    }
+
+    #[test]
+    fn test_remove_partial_copies_of_archived_copies() {
+        let (left, right) = create_test_dirs();
+        let archived = right
+            .path()
+            .join(compress::archive_path(Path::new("a.txt")));
+        fs::write(temp_path_for(&archived), "part").unwrap();
+        fs::write(temp_path_for(&right.path().join("a.txt")), "not ours").unwrap();
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("a.txt"),
+            size: 4,
+        }];
+
+        assert_eq!(
+            remove_partial_copies(left.path(), right.path(), &actions, true),
+            1
+        );
+        assert!(!temp_path_for(&archived).exists());
+        assert!(temp_path_for(&right.path().join("a.txt")).exists());
+    }
+
+    #[test]
+    fn test_file_in_place_of_directory_is_classified() {
+        let (left, right) = create_test_dirs();
+
+        fs::create_dir_all(left.path().join("foo/sub")).unwrap();
+        fs::write(left.path().join("foo/bar"), "bar").unwrap();
+        fs::write(right.path().join("foo"), "a file").unwrap();
+
+        let actions = || {
+            vec![
+                SyncAction::CreateDirRight {
+                    path: PathBuf::from("foo/sub"),
+                },
+                SyncAction::CopyToRight {
+                    path: PathBuf::from("foo/bar"),
+                    size: 3,
+                },
+            ]
+        };
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        let result = executor
+            .execute(actions(), &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.failed.len(), 2);
+        for failed in &result.failed {
+            assert_eq!(failed.kind, SyncErrorKind::InvalidPath);
+            assert!(failed.error.contains("is a file, not a directory"));
+            assert!(failed
+                .error
+                .contains(&right.path().join("foo").display().to_string()));
+        }
+        assert_eq!(
+            fs::read_to_string(right.path().join("foo")).unwrap(),
+            "a file"
+        );
+
+        // Allowed, the file is backed up and replaced by the directory
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                replace_blocking_files: true,
+                ..Default::default()
+            },
+        );
+        let result = executor
+            .execute(actions(), &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert!(result.failed.is_empty());
+        assert_eq!(result.completed.len(), 2);
+        assert!(right.path().join("foo/sub").is_dir());
+        assert_eq!(
+            fs::read_to_string(right.path().join("foo/bar")).unwrap(),
+            "bar"
+        );
+        let backups = list_backups(right.path(), "foo").unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "a file");
+
+        // Without backups it goes to the trash instead
+        let (left, right) = create_test_dirs();
+        fs::create_dir_all(left.path().join("foo/sub")).unwrap();
+        fs::write(left.path().join("foo/bar"), "bar").unwrap();
+        fs::write(right.path().join("foo"), "a file").unwrap();
+        let config = || ExecutorConfig {
+            replace_blocking_files: true,
+            backup_enabled: false,
+            ..Default::default()
+        };
+        let result = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            config(),
+        )
+        .execute(actions(), &HashMap::new(), &mut NoopProgress)
+        .unwrap();
+
+        assert!(result.failed.is_empty());
+        assert!(right.path().join("foo/sub").is_dir());
+        let trashed = trash::list_trash(right.path()).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].path, PathBuf::from("foo"));
+        assert_eq!(fs::read_to_string(&trashed[0].trashed).unwrap(), "a file");
+
+        // A file the sync keeps, e.g. an unresolved conflict, stays put
+        let (left, right) = create_test_dirs();
+        fs::create_dir_all(left.path().join("foo/sub")).unwrap();
+        fs::write(left.path().join("foo/bar"), "bar").unwrap();
+        fs::write(right.path().join("foo"), "a file").unwrap();
+        let result = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            config(),
+        )
+        .with_kept_paths(HashSet::new(), HashSet::from([PathBuf::from("foo")]))
+        .execute(actions(), &HashMap::new(), &mut NoopProgress)
+        .unwrap();
+
+        assert_eq!(result.failed.len(), 2);
+        assert!(result.failed[0].error.contains("has its own action"));
+        assert_eq!(
+            fs::read_to_string(right.path().join("foo")).unwrap(),
+            "a file"
+        );
+        assert!(trash::list_trash(right.path()).unwrap().is_empty());
+    }
+
+    fn make_readonly(path: &Path) {
+        let mut permissions = fs::metadata(path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(path, permissions).unwrap();
+    }
+
+    #[test]
+    fn test_readonly_destination_needs_opt_in() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("locked.txt"), "new").unwrap();
+        fs::write(right.path().join("locked.txt"), "old").unwrap();
+        make_readonly(&right.path().join("locked.txt"));
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("locked.txt"),
+            size: 3,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].kind, SyncErrorKind::PermissionDenied);
+        assert!(result.failed[0]
+            .error
+            .contains("clear_readonly_on_overwrite"));
+        assert_eq!(
+            fs::read_to_string(right.path().join("locked.txt")).unwrap(),
+            "old"
+        );
+        assert!(!right.path().join(METADATA_DIR).join(BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn test_clear_readonly_on_overwrite() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("locked.txt"), "new").unwrap();
+        fs::write(right.path().join("locked.txt"), "old").unwrap();
+        make_readonly(&right.path().join("locked.txt"));
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                clear_readonly_on_overwrite: true,
+                ..Default::default()
+            },
+        );
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("locked.txt"),
+            size: 3,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        let dst = right.path().join("locked.txt");
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "new");
+        // The copy takes the source's (writable) attributes
+        assert!(!fs::metadata(&dst).unwrap().permissions().readonly());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions_copied_only_when_asked() {
+        use std::os::unix::fs::PermissionsExt;
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("run.sh"), "#!/bin/sh").unwrap();
+        fs::set_permissions(
+            left.path().join("run.sh"),
+            fs::Permissions::from_mode(0o777),
+        )
+        .unwrap();
+        let mode = |config: ExecutorConfig| {
+            let _ = fs::remove_file(right.path().join("run.sh"));
+            let actions = vec![SyncAction::CopyToRight {
+                path: PathBuf::from("run.sh"),
+                size: 9,
+            }];
+            Executor::new(
+                left.path().to_path_buf(),
+                right.path().to_path_buf(),
+                config,
+            )
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+            fs::metadata(right.path().join("run.sh"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+
+        // A new file's defaults, never world-writable
+        assert_ne!(mode(ExecutorConfig::default()), 0o777);
+        let preserved = mode(ExecutorConfig {
+            preserve_permissions: true,
+            ..Default::default()
+        });
+        assert_eq!(preserved, 0o777);
+    }
+
+    #[test]
+    fn test_copy_with_acls_enabled() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("test.txt"), "content").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                copy_acls: true,
+                ..Default::default()
+            },
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("test.txt"),
+            size: 7,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert!(result.completed[0].warnings.is_empty());
+        assert_eq!(
+            fs::read_to_string(right.path().join("test.txt")).unwrap(),
+            "content"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_copy_preserves_ownership() {
+        use std::os::unix::fs::MetadataExt;
+
+        let (left, right) = create_test_dirs();
+        let src = left.path().join("owned.txt");
+        fs::write(&src, "content").unwrap();
+        // Handing the file to another owner needs root; otherwise the source
+        // keeps ours and chown-ing the copy to it is still allowed
+        let privileged = std::os::unix::fs::chown(&src, Some(4242), Some(4242)).is_ok();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                preserve_ownership: true,
+                ..Default::default()
+            },
+        );
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("owned.txt"),
+            size: 7,
+        }];
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert!(result.completed[0].warnings.is_empty());
+        let src_meta = fs::metadata(&src).unwrap();
+        let dst_meta = fs::metadata(right.path().join("owned.txt")).unwrap();
+        assert_eq!(
+            (dst_meta.uid(), dst_meta.gid()),
+            (src_meta.uid(), src_meta.gid())
+        );
+        if privileged {
+            assert_eq!(dst_meta.uid(), 4242);
+        }
+    }
+
+    /// Writer that records how often it was synced
+    #[derive(Default)]
+    struct CountingWriter {
+        data: Vec<u8>,
+        data_syncs: usize,
+        full_syncs: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SyncWrite for CountingWriter {
+        fn sync_data(&mut self) -> io::Result<()> {
+            self.data_syncs += 1;
+            Ok(())
+        }
+
+        fn sync_all(&mut self) -> io::Result<()> {
+            self.full_syncs += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_copy_with_fsync_interval() {
+        let content = vec![7u8; 200 * 1024];
+        let mut writer = CountingWriter::default();
+
+        let copied =
+            copy_with_fsync(&mut content.as_slice(), &mut writer, 64 * 1024, None).unwrap();
+
+        assert_eq!(copied, content.len() as u64);
+        assert_eq!(writer.data, content);
+        // 200 KiB in 64 KiB reads: synced after 64, 128 and 192 KiB
+        assert_eq!(writer.data_syncs, 3);
+        assert_eq!(writer.full_syncs, 1);
+    }
+
+    #[test]
+    fn test_copy_with_fsync_interval_through_adaptive_buffer() {
+        let content = vec![7u8; 200 * 1024];
+        let mut writer = CountingWriter::default();
+        let mut buffer = AdaptiveBuffer::with_decision(content.len() as u64, |_, _| {
+            crate::sync::buffer::MIN_BUFFER
+        });
+
+        let copied = copy_with_fsync(
+            &mut content.as_slice(),
+            &mut writer,
+            64 * 1024,
+            Some(&mut buffer),
+        )
+        .unwrap();
+
+        assert_eq!(copied, content.len() as u64);
+        assert_eq!(writer.data, content);
+        // Resizing the buffer doesn't change how often data is synced
+        assert_eq!(buffer.size(), crate::sync::buffer::MIN_BUFFER);
+        assert_eq!(writer.data_syncs, 3);
+        assert_eq!(writer.full_syncs, 1);
+    }
+
+    #[test]
+    fn test_copy_normalizes_line_endings() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("notes.txt"), "a\r\nb\r\n").unwrap();
+        fs::write(left.path().join("data.txt"), b"a\r\n\0b\r\n").unwrap();
+        fs::write(left.path().join("script.bat"), "a\r\nb\r\n").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                normalize_eol: Some(EolNormalization::default()),
+                ..Default::default()
+            },
+        );
+
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("notes.txt"),
+                size: 6,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("data.txt"),
+                size: 7,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("script.bat"),
+                size: 6,
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 3);
+        let notes = result
+            .completed
+            .iter()
+            .find(|c| c.action.path() == Path::new("notes.txt"))
+            .unwrap();
+        assert!(notes.eol_normalized);
+        // The copy isn't byte-for-byte, so no hash of the source content
+        assert_eq!(notes.content_hash, None);
+        assert_eq!(notes.bytes_transferred, 4);
+        assert_eq!(fs::read(right.path().join("notes.txt")).unwrap(), b"a\nb\n");
+
+        // Binary content and other extensions are copied verbatim
+        assert_eq!(
+            fs::read(right.path().join("data.txt")).unwrap(),
+            b"a\r\n\0b\r\n"
+        );
+        assert_eq!(
+            fs::read(right.path().join("script.bat")).unwrap(),
+            b"a\r\nb\r\n"
+        );
+        assert_eq!(
+            result.completed.iter().filter(|c| c.eol_normalized).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_copy_with_fsync_enabled() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("test.txt"), "content").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                fsync_interval_bytes: Some(4),
+                ..Default::default()
+            },
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("test.txt"),
+            size: 7,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(right.path().join("test.txt")).unwrap(),
+            "content"
+        );
+    }
+
+    #[test]
+    fn test_reflink_copy_falls_back() {
+        let (left, right) = create_test_dirs();
+        let content: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(left.path().join("data.bin"), &content).unwrap();
+        fs::write(left.path().join("old.txt"), "old").unwrap();
+        let old = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        set_file_mtime(&left.path().join("old.txt"), old).unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                try_reflink: true,
+                ..Default::default()
+            },
+        )
+        .with_clone_file(|_, _| Err(io::ErrorKind::Unsupported.into()));
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("data.bin"),
+                size: content.len() as u64,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("old.txt"),
+                size: 3,
+            },
+        ];
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 2);
+        assert_eq!(fs::read(right.path().join("data.bin")).unwrap(), content);
+        // Cloned or copied, the source's mtime is kept and no partial is left
+        let mtime = fs::metadata(right.path().join("old.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(mtime, old);
+        assert!(!temp_path_for(&right.path().join("data.bin")).exists());
+    }
+
+    #[test]
+    fn test_short_clone_is_copied_over() {
+        let (left, right) = create_test_dirs();
+        fs::write(left.path().join("data.bin"), "full content").unwrap();
+        let copy = |config: ExecutorConfig, clone_file: fn(&Path, &Path) -> io::Result<()>| {
+            let actions = vec![SyncAction::CopyToRight {
+                path: PathBuf::from("data.bin"),
+                size: 12,
+            }];
+            Executor::new(
+                left.path().to_path_buf(),
+                right.path().to_path_buf(),
+                config,
+            )
+            .with_clone_file(clone_file)
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap()
+        };
+        let reflink_config = || ExecutorConfig {
+            try_reflink: true,
+            fsync_interval_bytes: Some(1024),
+            ..Default::default()
+        };
+
+        // A clone that "succeeds" with part of the data
+        let result = copy(reflink_config(), |_, dst| fs::write(dst, "full"));
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(right.path().join("data.bin")).unwrap(),
+            "full content"
+        );
+
+        // A complete clone is kept as is, synced before it replaces anything
+        fs::remove_file(right.path().join("data.bin")).unwrap();
+        let result = copy(reflink_config(), |src, dst| fs::copy(src, dst).map(|_| ()));
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(right.path().join("data.bin")).unwrap(),
+            "full content"
+        );
+        assert!(!temp_path_for(&right.path().join("data.bin")).exists());
+    }
+
+    #[test]
+    fn test_reflink_failure_leaves_no_content() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("src.txt"), "content").unwrap();
+        let dst = dir.path().join("dst.txt");
+        match reflink(&dir.path().join("src.txt"), &dst) {
+            Ok(()) => assert_eq!(fs::read_to_string(&dst).unwrap(), "content"),
+            // Nothing half-written for the fallback copy to trip over
+            Err(_) => assert!(fs::metadata(&dst).map_or(true, |m| m.len() == 0)),
+        }
+        assert!(reflink(&dir.path().join("missing.txt"), &dst).is_err());
+    }
+
+    #[test]
+    fn test_copy_with_adaptive_buffer() {
+        let (left, right) = create_test_dirs();
+
+        let content: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(left.path().join("data.bin"), &content).unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                adaptive_buffer: true,
+                ..Default::default()
+            },
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("data.bin"),
+            size: content.len() as u64,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(fs::read(right.path().join("data.bin")).unwrap(), content);
+        let hash = crate::sync::hash::hash_file(&left.path().join("data.bin")).unwrap();
+        assert_eq!(result.completed[0].content_hash, Some(hash));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_fifo_is_recreated_not_read() {
+        use std::os::unix::fs::FileTypeExt;
+
+        let (left, right) = create_test_dirs();
+        let meta = fs::metadata(left.path()).unwrap();
+        make_fifo(&left.path().join("pipe"), &meta).unwrap();
+        let actions = || {
+            vec![SyncAction::CopyToRight {
+                path: PathBuf::from("pipe"),
+                size: 0,
+            }]
+        };
+
+        // Without the option the copy fails rather than blocking
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig::default(),
+        );
+        let result = executor
+            .execute(actions(), &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+        assert_eq!(result.failed.len(), 1);
+        assert!(result.failed[0].error.contains("Named pipe (FIFO)"));
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                recreate_fifos: true,
+                ..Default::default()
+            },
+        );
+        // A second run replaces the FIFO without trying to back it up
+        for _ in 0..2 {
+            let result = executor
+                .execute(actions(), &HashMap::new(), &mut NoopProgress)
+                .unwrap();
+            assert_eq!(result.completed.len(), 1);
+        }
+        let file_type = fs::symlink_metadata(right.path().join("pipe"))
+            .unwrap()
+            .file_type();
+        assert!(file_type.is_fifo());
+    }
+
+    #[test]
+    fn test_preserve_hardlinks() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("a.txt"), "shared").unwrap();
+        fs::hard_link(left.path().join("a.txt"), left.path().join("b.txt")).unwrap();
+
+        let left_groups = HashMap::from([
+            (PathBuf::from("a.txt"), vec![PathBuf::from("b.txt")]),
+            (PathBuf::from("b.txt"), vec![PathBuf::from("a.txt")]),
+        ]);
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                preserve_hardlinks: true,
+                ..Default::default()
+            },
+        )
+        .with_hardlinks(left_groups, HashMap::new());
+
+        let actions = vec![
+            SyncAction::CopyToRight {
+                path: PathBuf::from("a.txt"),
+                size: 6,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("b.txt"),
+                size: 6,
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 2);
+        // Only the first file's data was transferred
+        assert_eq!(result.total_bytes_transferred(), 6);
+        assert_eq!(
+            fs::read_to_string(right.path().join("b.txt")).unwrap(),
+            "shared"
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let a = fs::metadata(right.path().join("a.txt")).unwrap();
+            let b = fs::metadata(right.path().join("b.txt")).unwrap();
+            assert_eq!(a.ino(), b.ino());
+        }
+    }
+
+    #[test]
+    fn test_hardlinks_fall_back_to_copy_without_synced_sibling() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("a.txt"), "shared").unwrap();
+        fs::hard_link(left.path().join("a.txt"), left.path().join("b.txt")).unwrap();
+        // Destination sibling exists but holds different content
+        fs::write(right.path().join("a.txt"), "other content").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                preserve_hardlinks: true,
+                ..Default::default()
+            },
+        )
+        .with_hardlinks(
+            HashMap::from([(PathBuf::from("b.txt"), vec![PathBuf::from("a.txt")])]),
+            HashMap::new(),
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("b.txt"),
+            size: 6,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.total_bytes_transferred(), 6);
+        assert_eq!(
+            fs::read_to_string(right.path().join("a.txt")).unwrap(),
+            "other content"
+        );
+        assert_eq!(
+            fs::read_to_string(right.path().join("b.txt")).unwrap(),
+            "shared"
+        );
+    }
+
+    #[test]
+    fn test_check_disk_space_inodes() {
+        let dir = TempDir::new().unwrap();
+        let info = check_disk_space(dir.path(), 0, 1).unwrap();
+        assert!(info.sufficient);
+        assert!(!info.inodes_short());
+
+        let info = check_disk_space(dir.path(), 0, u64::MAX).unwrap();
+        // Only known inode counts can fall short
+        assert_eq!(info.inodes_short(), info.available_inodes.is_some());
+        assert_eq!(info.sufficient, !info.inodes_short());
+        #[cfg(not(unix))]
+        assert_eq!(info.available_inodes, None);
+    }
+
+    #[test]
+    fn test_dry_run_does_not_touch_disk() {
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("file.txt"), "content").unwrap();
+        fs::write(left.path().join("changed.txt"), "content").unwrap();
+        fs::write(right.path().join("to_delete.txt"), "delete me").unwrap();
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                dry_run: true,
+                ..Default::default()
+            },
+        );
+
+        let mut snapshots = HashMap::new();
+        snapshots.insert(
+            PathBuf::from("changed.txt"),
+            FileSnapshot {
+                size: 100,
+                mtime: Utc::now(),
+            },
+        );
+
+        let actions = vec![
+            SyncAction::DeleteRight {
+                path: PathBuf::from("to_delete.txt"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("file.txt"),
+                size: 7,
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("changed.txt"),
+                size: 7,
+            },
+            SyncAction::CreateDirRight {
+                path: PathBuf::from("newdir"),
+            },
+        ];
+
+        let result = executor
+            .execute(actions, &snapshots, &mut NoopProgress)
+            .unwrap();
+
+        // Completed actions follow execution order: dir, copy, delete
+        let order: Vec<_> = result
+            .completed
+            .iter()
+            .map(|c| c.action.path().clone())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                PathBuf::from("newdir"),
+                PathBuf::from("file.txt"),
+                PathBuf::from("to_delete.txt"),
+            ]
+        );
+        assert_eq!(result.total_bytes_transferred(), 7);
+
+        // Snapshot mismatch is predicted as a skip
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(
+            result.skipped[0].action.path(),
+            &PathBuf::from("changed.txt")
+        );
+
+        assert!(!right.path().join("newdir").exists());
+        assert!(!right.path().join("file.txt").exists());
+        assert!(right.path().join("to_delete.txt").exists());
+        assert!(!right.path().join(".rahzom").exists());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_overwrite_readonly_destination_on_windows() {
+        use std::os::windows::fs::MetadataExt;
+        use std::process::Command;
+
+        let (left, right) = create_test_dirs();
+
+        fs::write(left.path().join("test.txt"), "new content").unwrap();
+        let dst_path = right.path().join("test.txt");
+        fs::write(&dst_path, "old").unwrap();
+
+        let status = Command::new("attrib")
+            .args(["+R", dst_path.to_str().unwrap()])
+            .status()
+            .expect("Failed to run attrib");
+        assert!(status.success(), "attrib command failed");
+
+        let executor = Executor::new(
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+            ExecutorConfig {
+                clear_readonly_on_overwrite: true,
+                ..Default::default()
+            },
+        );
+
+        let actions = vec![SyncAction::CopyToRight {
+            path: PathBuf::from("test.txt"),
+            size: 11,
+        }];
+
+        let result = executor
+            .execute(actions, &HashMap::new(), &mut NoopProgress)
+            .unwrap();
+
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(fs::read_to_string(&dst_path).unwrap(), "new content");
+        let dst_attrs = fs::metadata(&dst_path).unwrap().file_attributes();
+        assert!(
+            (dst_attrs & 0x1) == 0,
+            "Destination should take the source's attributes (attrs: {:#x})",
+            dst_attrs
+        );
     }
 
     #[test]
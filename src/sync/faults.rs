@@ -0,0 +1,94 @@
+//! Simulated executor failures for tests and demos (requires the `testing` feature).
+//!
+//! Faults map paths (relative to the sync root) to an error or a delay. The
+//! app reads them from `RAHZOM_FAULTS`, a comma-separated list of
+//! `path=fault` pairs where `fault` is `locked`, `denied`, `full`, `changed`,
+//! `io` or `delay:<ms>`, e.g. `RAHZOM_FAULTS="a.txt=locked,big.iso=delay:500"`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::executor::SyncErrorKind;
+
+/// Environment variable holding the fault specification
+pub const FAULTS_ENV: &str = "RAHZOM_FAULTS";
+
+/// What happens when an action touches a faulty path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The action fails with this error
+    Error(SyncErrorKind),
+    /// The action runs normally after sleeping
+    Delay(Duration),
+}
+
+impl Fault {
+    fn parse(spec: &str) -> Option<Self> {
+        let kind = match spec {
+            "locked" => SyncErrorKind::FileLocked,
+            "denied" => SyncErrorKind::PermissionDenied,
+            "full" => SyncErrorKind::DiskFull,
+            "changed" => SyncErrorKind::FileChanged,
+            "io" => SyncErrorKind::IoError,
+            _ => {
+                let ms = spec.strip_prefix("delay:")?.parse().ok()?;
+                return Some(Self::Delay(Duration::from_millis(ms)));
+            }
+        };
+        Some(Self::Error(kind))
+    }
+}
+
+/// Faults by relative path
+#[derive(Debug, Clone, Default)]
+pub struct Faults(HashMap<PathBuf, Fault>);
+
+impl Faults {
+    /// Faults from `RAHZOM_FAULTS`; none if it is unset
+    pub fn from_env() -> Self {
+        std::env::var(FAULTS_ENV)
+            .map(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+
+    /// Parses `path=fault` pairs; malformed pairs are ignored
+    pub fn parse(spec: &str) -> Self {
+        let faults = spec
+            .split(',')
+            .filter_map(|pair| {
+                let (path, fault) = pair.trim().rsplit_once('=')?;
+                Some((PathBuf::from(path), Fault::parse(fault)?))
+            })
+            .collect();
+        Self(faults)
+    }
+
+    pub fn insert(&mut self, path: impl Into<PathBuf>, fault: Fault) {
+        self.0.insert(path.into(), fault);
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Fault> {
+        self.0.get(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_faults() {
+        let faults = Faults::parse("a.txt=locked, dir/b.bin=delay:250,bad=nope,=io");
+
+        assert_eq!(
+            faults.get(Path::new("a.txt")),
+            Some(&Fault::Error(SyncErrorKind::FileLocked))
+        );
+        assert_eq!(
+            faults.get(Path::new("dir/b.bin")),
+            Some(&Fault::Delay(Duration::from_millis(250)))
+        );
+        assert_eq!(faults.get(Path::new("bad")), None);
+    }
+}
@@ -0,0 +1,116 @@
+//! Streaming SHA-256 hashing of file content.
+//!
+//! Files are fed through the hasher in fixed-size chunks, so memory use stays
+//! constant however large the file is. [`HashingReader`] lets a copy loop
+//! produce the hash of what it copied without a second pass over the file.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+
+/// Bytes read from a file per chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// SHA-256 of a file's content as lowercase hex, read in fixed-size chunks
+pub fn hash_file(path: &Path) -> Result<String> {
+    // Opening a FIFO would block until something writes to it
+    let metadata =
+        fs::metadata(path).with_context(|| format!("Failed to get metadata for: {:?}", path))?;
+    if !metadata.is_file() {
+        bail!("Not a regular file: {:?}", path);
+    }
+    let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+    let mut reader = HashingReader::new(file);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read file: {:?}", path)),
+        };
+        if bytes_read == 0 {
+            break;
+        }
+    }
+
+    Ok(reader.finish())
+}
+
+/// Reader adapter hashing every byte read through it
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// SHA-256 of everything read so far as lowercase hex
+    pub fn finish(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hash_file_known_value() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("test.txt");
+        fs::write(&path, "Hello, World!").unwrap();
+
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+        );
+    }
+
+    #[test]
+    fn test_hash_file_spanning_many_chunks() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("large.bin");
+        // 8 MiB of a repeating byte pattern, not a multiple of the chunk size
+        let content: Vec<u8> = (0..8 * 1024 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+        fs::write(&path, &content).unwrap();
+
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "f6cf8a2d65ec45a1419e922838efb5da28d94f54bb69276f9c68688cdbe33510"
+        );
+    }
+
+    #[test]
+    fn test_hashing_reader_matches_hash_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file.bin");
+        fs::write(&path, vec![7u8; 3 * CHUNK_SIZE + 1]).unwrap();
+
+        let mut reader = HashingReader::new(File::open(&path).unwrap());
+        let mut copy = Vec::new();
+        io::copy(&mut reader, &mut copy).unwrap();
+
+        assert_eq!(copy.len(), 3 * CHUNK_SIZE + 1);
+        assert_eq!(reader.finish(), hash_file(&path).unwrap());
+    }
+}
@@ -0,0 +1,158 @@
+//! Lock against two syncs of the same directory at once.
+//!
+//! A sync holds an exclusive OS lock on `.rahzom/sync.lock` of each side and
+//! writes its owner into the file. The OS drops the lock when the process
+//! exits, however it exits, so a file with an owner but no lock is left
+//! over from a crash and is taken over. Releasing empties the file rather
+//! than deleting it: a waiting process could otherwise lock the deleted
+//! file while a third creates a new one.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+
+use super::metadata::SyncMetadata;
+use super::utils::local_hostname;
+
+/// Lock file inside each side's `.rahzom/`
+pub const SYNC_LOCK_FILE: &str = "sync.lock";
+
+/// Process that holds, or held, a sync lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub host: String,
+    pub started: DateTime<Utc>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: local_hostname(),
+            started: Utc::now(),
+        }
+    }
+
+    /// E.g. "process 4242 on laptop since 2024-01-15 14:30"
+    pub fn describe(&self) -> String {
+        format!(
+            "process {} on {} since {}",
+            self.pid,
+            self.host,
+            self.started.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        )
+    }
+}
+
+/// A held sync lock; released when dropped
+#[derive(Debug)]
+pub struct SyncLock {
+    file: File,
+    /// Owner of a lock left behind by a process that is gone
+    pub taken_over: Option<LockOwner>,
+}
+
+impl SyncLock {
+    /// Path of the lock file of the side at `root`
+    pub fn file_path(root: &Path) -> PathBuf {
+        SyncMetadata::metadata_dir_path(root).join(SYNC_LOCK_FILE)
+    }
+
+    /// Locks the side at `root`. Fails, naming the holder, if another
+    /// process syncs it.
+    pub fn acquire(root: &Path) -> Result<Self> {
+        let path = Self::file_path(root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open sync lock: {:?}", path))?;
+
+        if file.try_lock_exclusive().is_err() {
+            // Windows doesn't let a locked file be read, so the owner may be unknown
+            let holder = read_owner(&mut file)
+                .map(|owner| owner.describe())
+                .unwrap_or_else(|| "another rahzom instance".to_string());
+            bail!("{} is being synced by {}", root.display(), holder);
+        }
+
+        let taken_over = read_owner(&mut file);
+        let owner = serde_json::to_vec(&LockOwner::current())?;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&owner)
+            .with_context(|| format!("Failed to write sync lock: {:?}", path))?;
+        Ok(Self { file, taken_over })
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+/// Owner recorded in the lock file; `None` if empty or unreadable
+fn read_owner(file: &mut File) -> Option<LockOwner> {
+    let mut content = Vec::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_end(&mut content).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let temp = TempDir::new().unwrap();
+        let lock = SyncLock::acquire(temp.path()).unwrap();
+        assert_eq!(lock.taken_over, None);
+
+        let error = SyncLock::acquire(temp.path()).unwrap_err().to_string();
+        assert!(error.contains("is being synced by"), "{}", error);
+        #[cfg(not(windows))]
+        assert!(
+            error.contains(&format!("process {}", std::process::id())),
+            "{}",
+            error
+        );
+
+        drop(lock);
+        let lock = SyncLock::acquire(temp.path()).unwrap();
+        assert_eq!(lock.taken_over, None);
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let temp = TempDir::new().unwrap();
+        // What a crashed sync leaves: an owner, but no OS lock
+        let stale = LockOwner {
+            pid: 999_999,
+            host: "laptop".to_string(),
+            started: Utc::now(),
+        };
+        let path = SyncLock::file_path(temp.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serde_json::to_vec(&stale).unwrap()).unwrap();
+
+        let lock = SyncLock::acquire(temp.path()).unwrap();
+        assert_eq!(lock.taken_over, Some(stale));
+        assert!(SyncLock::acquire(temp.path()).is_err());
+    }
+}
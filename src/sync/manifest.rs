@@ -0,0 +1,88 @@
+//! Snapshot manifests: a scan saved to a file.
+//!
+//! A project's right side can be a manifest instead of a directory, so the
+//! left side is compared against a recorded "golden" state to find drift.
+//! Nothing can be written to a manifest, so such projects can't be synced.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::exclusions::Exclusions;
+use super::scanner::{scan_with_exclusions, ScanResult};
+
+/// Scans `root` (honoring its exclusions) and saves the result as a manifest
+pub fn create(root: &Path, manifest: &Path) -> Result<ScanResult> {
+    let exclusions = Exclusions::load(root).ok();
+    let result = scan_with_exclusions(root, exclusions.as_ref())?;
+    save(&result, manifest)?;
+    Ok(result)
+}
+
+/// Writes `result` to `path` as JSON
+pub fn save(result: &ScanResult, path: &Path) -> Result<()> {
+    let data = serde_json::to_vec_pretty(result).context("Failed to serialize manifest")?;
+    fs::write(path, data).with_context(|| format!("Failed to write manifest: {:?}", path))
+}
+
+/// Reads a manifest written by [`save`]
+pub fn load(path: &Path) -> Result<ScanResult> {
+    let data = fs::read(path).with_context(|| format!("Failed to read manifest: {:?}", path))?;
+    serde_json::from_slice(&data).with_context(|| format!("Invalid manifest: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let golden = TempDir::new().unwrap();
+        fs::create_dir(golden.path().join("sub")).unwrap();
+        fs::write(golden.path().join("sub/file.txt"), "content").unwrap();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("golden.json");
+
+        let scanned = create(golden.path(), &path).unwrap();
+        let loaded = load(&path).unwrap();
+
+        assert_eq!(loaded.root, scanned.root);
+        assert_eq!(loaded.scan_time, scanned.scan_time);
+        assert_eq!(loaded.stats(), scanned.stats());
+        let file = loaded
+            .entries
+            .iter()
+            .find(|e| e.path == Path::new("sub/file.txt"))
+            .unwrap();
+        assert_eq!(file.size, 7);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_manifest_keeps_non_utf8_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let golden = TempDir::new().unwrap();
+        let name = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+        fs::write(golden.path().join(name), "latin-1").unwrap();
+        let out = TempDir::new().unwrap();
+        let path = out.path().join("golden.json");
+
+        create(golden.path(), &path).unwrap();
+        let loaded = load(&path).unwrap();
+
+        assert!(loaded.entries.iter().any(|e| e.path == name));
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_manifest() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("broken.json");
+        fs::write(&path, "not a manifest").unwrap();
+
+        assert!(load(&path).is_err());
+    }
+}
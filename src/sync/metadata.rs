@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
@@ -10,6 +11,10 @@ use serde::{Deserialize, Serialize};
 const METADATA_DIR: &str = ".rahzom";
 /// State file name
 const STATE_FILE: &str = "state.json";
+/// Temporary file written by `save` before being renamed over the state file
+const STATE_TMP_FILE: &str = "state.json.tmp";
+/// Name a corrupt state file is moved to so it isn't lost on the next save
+const STATE_CORRUPT_FILE: &str = "state.json.corrupt";
 /// Default retention period for deleted files (days)
 const DEFAULT_DELETED_RETENTION_DAYS: i64 = 90;
 
@@ -25,6 +30,15 @@ pub struct FileAttributes {
     /// Windows hidden attribute
     #[serde(skip_serializing_if = "Option::is_none")]
     pub windows_hidden: Option<bool>,
+    /// Windows reparse point attribute (junctions, symlinks, placeholders)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows_reparse_point: Option<bool>,
+    /// Unix owner user id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_uid: Option<u32>,
+    /// Unix owner group id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_gid: Option<u32>,
 }
 
 impl FileAttributes {
@@ -38,11 +52,15 @@ impl FileAttributes {
                 let attrs = metadata.file_attributes();
                 const FILE_ATTRIBUTE_READONLY: u32 = 0x1;
                 const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+                const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
 
                 FileAttributes {
                     unix_mode: None,
                     windows_readonly: Some((attrs & FILE_ATTRIBUTE_READONLY) != 0),
                     windows_hidden: Some((attrs & FILE_ATTRIBUTE_HIDDEN) != 0),
+                    windows_reparse_point: Some((attrs & FILE_ATTRIBUTE_REPARSE_POINT) != 0),
+                    unix_uid: None,
+                    unix_gid: None,
                 }
             }
             Err(_) => FileAttributes::default(),
@@ -52,13 +70,16 @@ impl FileAttributes {
     /// Reads file attributes from a path
     #[cfg(unix)]
     pub fn read_from_path(path: &Path) -> Self {
-        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
 
         match fs::metadata(path) {
             Ok(metadata) => FileAttributes {
                 unix_mode: Some(metadata.permissions().mode()),
                 windows_readonly: None,
                 windows_hidden: None,
+                windows_reparse_point: None,
+                unix_uid: Some(metadata.uid()),
+                unix_gid: Some(metadata.gid()),
             },
             Err(_) => FileAttributes::default(),
         }
@@ -69,6 +90,79 @@ impl FileAttributes {
     pub fn read_from_path(_path: &Path) -> Self {
         FileAttributes::default()
     }
+
+    /// Unix owner and group, if both are known
+    pub fn owner(&self) -> Option<(u32, u32)> {
+        self.unix_uid.zip(self.unix_gid)
+    }
+
+    /// Whether the attributes an attribute-only update carries over match:
+    /// permission bits, read-only and hidden. One unknown is taken as the
+    /// same.
+    pub fn same_permissions(&self, other: &Self) -> bool {
+        fn same<T: PartialEq>(a: Option<T>, b: Option<T>) -> bool {
+            a.zip(b).is_none_or(|(a, b)| a == b)
+        }
+        let mode = |attrs: &Self| attrs.unix_mode.map(|m| m & 0o7777);
+        same(mode(self), mode(other))
+            && same(self.windows_readonly, other.windows_readonly)
+            && same(self.windows_hidden, other.windows_hidden)
+    }
+}
+
+/// Filesystem identity of a file: device/volume plus inode/file index.
+/// Stays the same when a file is renamed or moved within one filesystem.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FileId {
+    /// Device id (Unix) or volume serial number (Windows)
+    pub device: u64,
+    /// Inode number (Unix) or file index (Windows)
+    pub index: u64,
+}
+
+impl FileId {
+    /// Reads file identity from a path
+    #[cfg(windows)]
+    pub fn read_from_path(path: &Path) -> Option<Self> {
+        use std::os::windows::fs::OpenOptionsExt;
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+        };
+
+        let file = fs::OpenOptions::new()
+            .access_mode(0)
+            .custom_flags(0x02000000) // FILE_FLAG_BACKUP_SEMANTICS for directories
+            .open(path)
+            .ok()?;
+
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+        if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+            return None;
+        }
+
+        Some(FileId {
+            device: info.dwVolumeSerialNumber as u64,
+            index: ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+        })
+    }
+
+    /// Reads file identity from a path
+    #[cfg(unix)]
+    pub fn read_from_path(path: &Path) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        fs::metadata(path).ok().map(|m| FileId {
+            device: m.dev(),
+            index: m.ino(),
+        })
+    }
+
+    /// Reads file identity from a path
+    #[cfg(not(any(windows, unix)))]
+    pub fn read_from_path(_path: &Path) -> Option<Self> {
+        None
+    }
 }
 
 /// State of a single file as recorded during last sync
@@ -86,6 +180,9 @@ pub struct FileState {
     /// Platform-specific attributes
     #[serde(default)]
     pub attributes: FileAttributes,
+    /// Filesystem identity on this side, used for rename detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<FileId>,
     /// When this file was last synced
     pub last_synced: DateTime<Utc>,
 }
@@ -106,16 +203,71 @@ pub struct DeletedFile {
     pub deleted_at: DateTime<Utc>,
 }
 
+/// A record identified by its relative path
+trait PathKeyed {
+    fn path(&self) -> &str;
+}
+
+impl PathKeyed for FileState {
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl PathKeyed for DeletedFile {
+    fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Stores a map keyed by path as the plain list of its records, sorted by
+/// path, which is how `state.json` has always held them
+mod records_by_path {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::PathKeyed;
+
+    pub fn serialize<T, S>(map: &HashMap<String, T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        let mut records: Vec<_> = map.iter().collect();
+        records.sort_unstable_by_key(|&(path, _)| path);
+        serializer.collect_seq(records.into_iter().map(|(_, record)| record))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<HashMap<String, T>, D::Error>
+    where
+        T: Deserialize<'de> + PathKeyed,
+        D: Deserializer<'de>,
+    {
+        let records = Vec::<T>::deserialize(deserializer)?;
+        Ok(records
+            .into_iter()
+            .map(|record| (record.path().to_string(), record))
+            .collect())
+    }
+}
+
 /// Complete sync metadata for one side of synchronization
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SyncMetadata {
-    /// Known file states
-    pub files: Vec<FileState>,
-    /// Recently deleted files (for conflict detection)
-    pub deleted: Vec<DeletedFile>,
+    /// Known file states by path
+    #[serde(with = "records_by_path")]
+    pub files: HashMap<String, FileState>,
+    /// Recently deleted files by path (for conflict detection)
+    #[serde(with = "records_by_path")]
+    pub deleted: HashMap<String, DeletedFile>,
     /// Timestamp of last successful sync
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_sync: Option<DateTime<Utc>>,
+    /// Set when loading found the state file corrupt and moved it aside:
+    /// why it couldn't be read. The metadata is then empty, as on a first sync.
+    #[serde(skip)]
+    pub recovered_from: Option<String>,
 }
 
 impl SyncMetadata {
@@ -131,6 +283,9 @@ impl SyncMetadata {
     }
 
     /// Loads metadata with custom retention period for deleted files.
+    /// A corrupt or truncated state file is moved aside to `state.json.corrupt`
+    /// and treated as empty metadata (first sync) instead of failing; the
+    /// returned metadata then says so in `recovered_from`.
     pub fn load_with_retention(root: &Path, retention_days: i64) -> Result<Self> {
         let state_path = Self::state_file_path(root);
 
@@ -143,8 +298,17 @@ impl SyncMetadata {
 
         let reader = BufReader::new(file);
 
-        let mut metadata: SyncMetadata = serde_json::from_reader(reader)
-            .with_context(|| format!("Failed to parse state file: {:?}", state_path))?;
+        let mut metadata: SyncMetadata = match serde_json::from_reader(reader) {
+            Ok(m) => m,
+            Err(e) => {
+                let corrupt_path = root.join(METADATA_DIR).join(STATE_CORRUPT_FILE);
+                let _ = fs::rename(&state_path, &corrupt_path);
+                return Ok(Self {
+                    recovered_from: Some(e.to_string()),
+                    ..Self::new()
+                });
+            }
+        };
 
         // Cleanup old deleted entries
         metadata.cleanup_deleted(retention_days);
@@ -154,6 +318,8 @@ impl SyncMetadata {
 
     /// Saves metadata to `.rahzom/state.json` in the given directory.
     /// Creates `.rahzom/` directory if it doesn't exist.
+    /// Writes to a temp file first and renames it over the state file, so an
+    /// interrupted save never leaves a partially written state behind.
     pub fn save(&self, root: &Path) -> Result<()> {
         let rahzom_dir = root.join(METADATA_DIR);
 
@@ -163,13 +329,25 @@ impl SyncMetadata {
         }
 
         let state_path = Self::state_file_path(root);
-        let file = File::create(&state_path)
-            .with_context(|| format!("Failed to create state file: {:?}", state_path))?;
+        let tmp_path = rahzom_dir.join(STATE_TMP_FILE);
+        let file = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create state file: {:?}", tmp_path))?;
+
+        let mut writer = BufWriter::new(file);
+
+        serde_json::to_writer_pretty(&mut writer, self)
+            .with_context(|| format!("Failed to write state file: {:?}", tmp_path))?;
 
-        let writer = BufWriter::new(file);
+        let file = writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .with_context(|| format!("Failed to write state file: {:?}", tmp_path))?;
+        file.sync_all()
+            .with_context(|| format!("Failed to sync state file: {:?}", tmp_path))?;
+        drop(file);
 
-        serde_json::to_writer_pretty(writer, self)
-            .with_context(|| format!("Failed to write state file: {:?}", state_path))?;
+        fs::rename(&tmp_path, &state_path)
+            .with_context(|| format!("Failed to replace state file: {:?}", state_path))?;
 
         Ok(())
     }
@@ -186,12 +364,9 @@ impl SyncMetadata {
 
     /// Adds a file to the deleted registry
     pub fn mark_deleted(&mut self, file: DeletedFile) {
-        // Remove from files list if present
-        self.files.retain(|f| f.path != file.path);
-        // Remove old deleted entry for same path if exists
-        self.deleted.retain(|d| d.path != file.path);
-        // Add to deleted list
-        self.deleted.push(file);
+        self.files.remove(&file.path);
+        // Replaces an older deleted entry for the same path
+        self.deleted.insert(file.path.clone(), file);
     }
 
     /// Removes entries from deleted list older than retention period.
@@ -201,30 +376,24 @@ impl SyncMetadata {
             return; // 0 means keep forever
         }
         let cutoff = Utc::now() - Duration::days(retention_days);
-        self.deleted.retain(|d| d.deleted_at > cutoff);
+        self.deleted.retain(|_, d| d.deleted_at > cutoff);
     }
 
     /// Finds a file state by path
     pub fn find_file(&self, path: &str) -> Option<&FileState> {
-        self.files.iter().find(|f| f.path == path)
+        self.files.get(path)
     }
 
     /// Finds a deleted file by path
     pub fn find_deleted(&self, path: &str) -> Option<&DeletedFile> {
-        self.deleted.iter().find(|d| d.path == path)
+        self.deleted.get(path)
     }
 
     /// Updates or adds a file state
     pub fn upsert_file(&mut self, file: FileState) {
         // Remove from deleted if was there
-        self.deleted.retain(|d| d.path != file.path);
-
-        // Update or add
-        if let Some(existing) = self.files.iter_mut().find(|f| f.path == file.path) {
-            *existing = file;
-        } else {
-            self.files.push(file);
-        }
+        self.deleted.remove(&file.path);
+        self.files.insert(file.path.clone(), file);
     }
 }
 
@@ -244,6 +413,7 @@ mod tests {
             mtime: Utc::now(),
             hash: Some("abc123".to_string()),
             attributes: FileAttributes::default(),
+            file_id: None,
             last_synced: Utc::now(),
         }
     }
@@ -263,11 +433,9 @@ mod tests {
         let temp = create_test_dir();
 
         let mut metadata = SyncMetadata::new();
-        metadata.files.push(sample_file_state("docs/readme.txt"));
-        metadata.files.push(sample_file_state("src/main.rs"));
-        metadata
-            .deleted
-            .push(sample_deleted_file("old/removed.txt"));
+        metadata.upsert_file(sample_file_state("docs/readme.txt"));
+        metadata.upsert_file(sample_file_state("src/main.rs"));
+        metadata.mark_deleted(sample_deleted_file("old/removed.txt"));
         metadata.last_sync = Some(Utc::now());
 
         metadata.save(temp.path()).unwrap();
@@ -277,7 +445,7 @@ mod tests {
         assert_eq!(loaded.files.len(), 2);
         assert_eq!(loaded.deleted.len(), 1);
         assert!(loaded.last_sync.is_some());
-        assert_eq!(loaded.files[0].path, "docs/readme.txt");
+        assert!(loaded.find_file("docs/readme.txt").is_some());
     }
 
     #[test]
@@ -309,12 +477,12 @@ mod tests {
         let mut metadata = SyncMetadata::new();
 
         // Add recent deleted file
-        metadata.deleted.push(sample_deleted_file("recent.txt"));
+        metadata.mark_deleted(sample_deleted_file("recent.txt"));
 
         // Add old deleted file (100 days ago)
         let mut old_deleted = sample_deleted_file("old.txt");
         old_deleted.deleted_at = Utc::now() - Duration::days(100);
-        metadata.deleted.push(old_deleted);
+        metadata.mark_deleted(old_deleted);
 
         metadata.save(temp.path()).unwrap();
 
@@ -323,20 +491,20 @@ mod tests {
 
         // Old file should be cleaned up
         assert_eq!(loaded.deleted.len(), 1);
-        assert_eq!(loaded.deleted[0].path, "recent.txt");
+        assert!(loaded.find_deleted("recent.txt").is_some());
     }
 
     #[test]
     fn test_mark_deleted() {
         let mut metadata = SyncMetadata::new();
-        metadata.files.push(sample_file_state("file.txt"));
+        metadata.upsert_file(sample_file_state("file.txt"));
 
         let deleted = sample_deleted_file("file.txt");
         metadata.mark_deleted(deleted);
 
         assert!(metadata.files.is_empty());
         assert_eq!(metadata.deleted.len(), 1);
-        assert_eq!(metadata.deleted[0].path, "file.txt");
+        assert!(metadata.find_deleted("file.txt").is_some());
     }
 
     #[test]
@@ -353,13 +521,13 @@ mod tests {
         metadata.upsert_file(updated);
 
         assert_eq!(metadata.files.len(), 1);
-        assert_eq!(metadata.files[0].size, 2048);
+        assert_eq!(metadata.find_file("file.txt").unwrap().size, 2048);
     }
 
     #[test]
     fn test_upsert_removes_from_deleted() {
         let mut metadata = SyncMetadata::new();
-        metadata.deleted.push(sample_deleted_file("file.txt"));
+        metadata.mark_deleted(sample_deleted_file("file.txt"));
 
         metadata.upsert_file(sample_file_state("file.txt"));
 
@@ -370,7 +538,7 @@ mod tests {
     #[test]
     fn test_find_file() {
         let mut metadata = SyncMetadata::new();
-        metadata.files.push(sample_file_state("file.txt"));
+        metadata.upsert_file(sample_file_state("file.txt"));
 
         assert!(metadata.find_file("file.txt").is_some());
         assert!(metadata.find_file("other.txt").is_none());
@@ -385,8 +553,43 @@ mod tests {
         fs::create_dir_all(&rahzom_dir).unwrap();
         fs::write(rahzom_dir.join("state.json"), "{ invalid json }").unwrap();
 
-        let result = SyncMetadata::load(temp.path());
-        assert!(result.is_err());
+        let metadata = SyncMetadata::load(temp.path()).unwrap();
+        assert!(metadata.files.is_empty());
+        assert!(metadata.recovered_from.is_some());
+
+        // Corrupt file is preserved for inspection
+        assert!(rahzom_dir.join("state.json.corrupt").exists());
+        assert!(!rahzom_dir.join("state.json").exists());
+    }
+
+    #[test]
+    fn test_truncated_file_loads_as_default() {
+        let temp = create_test_dir();
+
+        let mut metadata = SyncMetadata::new();
+        metadata.upsert_file(sample_file_state("file.txt"));
+        metadata.save(temp.path()).unwrap();
+
+        let state_path = SyncMetadata::state_file_path(temp.path());
+        let content = fs::read(&state_path).unwrap();
+        fs::write(&state_path, &content[..content.len() / 2]).unwrap();
+
+        let loaded = SyncMetadata::load(temp.path()).unwrap();
+        assert!(loaded.files.is_empty());
+        assert!(loaded.recovered_from.is_some());
+    }
+
+    #[test]
+    fn test_save_leaves_no_temp_file() {
+        let temp = create_test_dir();
+
+        let metadata = SyncMetadata::new();
+        metadata.save(temp.path()).unwrap();
+        metadata.save(temp.path()).unwrap();
+
+        let rahzom_dir = temp.path().join(".rahzom");
+        assert!(rahzom_dir.join("state.json").exists());
+        assert!(!rahzom_dir.join("state.json.tmp").exists());
     }
 
     #[test]
@@ -398,7 +601,7 @@ mod tests {
         // Add file deleted 10 days ago
         let mut deleted = sample_deleted_file("file.txt");
         deleted.deleted_at = Utc::now() - Duration::days(10);
-        metadata.deleted.push(deleted);
+        metadata.mark_deleted(deleted);
 
         metadata.save(temp.path()).unwrap();
 
@@ -410,4 +613,59 @@ mod tests {
         let loaded = SyncMetadata::load_with_retention(temp.path(), 15).unwrap();
         assert_eq!(loaded.deleted.len(), 1);
     }
+
+    #[test]
+    fn test_upsert_many_files_is_fast() {
+        use std::time::{Duration as StdDuration, Instant};
+
+        let path = |i: usize| format!("dir{}/file{}.txt", i % 100, i);
+        let mut metadata = SyncMetadata::new();
+        let start = Instant::now();
+        for i in 0..10_000 {
+            metadata.upsert_file(sample_file_state(&path(i)));
+        }
+        // A second sync updating every file
+        for i in 0..10_000 {
+            metadata.upsert_file(FileState {
+                size: i as u64,
+                ..sample_file_state(&path(i))
+            });
+        }
+        // Generous bound: a linear lookup per upsert would make this quadratic
+        assert!(start.elapsed() < StdDuration::from_secs(2));
+
+        assert_eq!(metadata.files.len(), 10_000);
+        assert_eq!(metadata.find_file(&path(9_999)).unwrap().size, 9_999);
+        assert!(metadata.find_file("dir0/missing.txt").is_none());
+
+        metadata.mark_deleted(sample_deleted_file(&path(42)));
+        assert!(metadata.find_file(&path(42)).is_none());
+        assert_eq!(metadata.find_deleted(&path(42)).unwrap().size, 512);
+        metadata.upsert_file(sample_file_state(&path(42)));
+        assert!(metadata.find_deleted(&path(42)).is_none());
+        assert!(metadata.find_file(&path(42)).is_some());
+    }
+
+    #[test]
+    fn test_state_file_keeps_record_lists() {
+        let temp = create_test_dir();
+        let mut metadata = SyncMetadata::new();
+        metadata.upsert_file(sample_file_state("b/c.txt"));
+        metadata.upsert_file(sample_file_state("a.txt"));
+        metadata.mark_deleted(sample_deleted_file("gone.txt"));
+        metadata.save(temp.path()).unwrap();
+
+        let state = fs::read_to_string(SyncMetadata::state_file_path(temp.path())).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&state).unwrap();
+        let paths = |key: &str| -> Vec<String> {
+            json[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["path"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(paths("files"), ["a.txt", "b/c.txt"]);
+        assert_eq!(paths("deleted"), ["gone.txt"]);
+    }
 }
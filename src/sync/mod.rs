@@ -1,6 +1,22 @@
+pub mod buffer;
+pub mod compress;
 pub mod differ;
+pub mod eol;
 pub mod exclusions;
 pub mod executor;
+#[cfg(feature = "testing")]
+pub mod faults;
+pub mod hash;
+pub mod lock;
+pub mod manifest;
 pub mod metadata;
+pub mod rules;
+pub mod scan_cache;
 pub mod scanner;
+pub mod script;
+pub mod skew;
+pub mod stats;
+pub mod trash;
 pub mod utils;
+pub mod verify;
+pub mod webhook;
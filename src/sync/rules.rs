@@ -0,0 +1,294 @@
+//! Per-pattern conflict auto-resolution rules
+
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::Duration;
+use globset::GlobMatcher;
+
+pub use crate::config::project::{ConflictResolution, ConflictRule};
+
+use super::differ::{ConflictReason, SyncAction};
+use super::utils::times_equal_with_tolerance;
+
+/// Compiled rules, consulted in order; the first matching pattern wins
+#[derive(Debug, Clone, Default)]
+pub struct ConflictRules {
+    rules: Vec<(GlobMatcher, ConflictResolution)>,
+}
+
+impl ConflictRules {
+    pub fn compile(rules: &[ConflictRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| Ok((rule.glob()?.compile_matcher(), rule.resolution)))
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Resolution of the first rule matching `path`
+    pub fn resolution_for(&self, path: &Path) -> Option<ConflictResolution> {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(&path_str))
+            .map(|(_, resolution)| *resolution)
+    }
+
+    /// Turns a matching conflict into a concrete action. Anything else, and
+    /// conflicts the matching rule can't decide, are returned unchanged.
+    pub fn apply(&self, action: SyncAction) -> SyncAction {
+        let SyncAction::Conflict {
+            path,
+            reason,
+            left,
+            right,
+        } = &action
+        else {
+            return action;
+        };
+        // Case conflicts are about two different names, not a direction
+        if *reason == ConflictReason::CaseConflict {
+            return action;
+        }
+        let Some(resolution) = self.resolution_for(path) else {
+            return action;
+        };
+
+        let keep_left = match resolution {
+            ConflictResolution::KeepLeft => true,
+            ConflictResolution::KeepRight => false,
+            ConflictResolution::KeepNewer => match (left, right) {
+                (Some(l), Some(r))
+                    if l.mtime_known
+                        && r.mtime_known
+                        && !times_equal_with_tolerance(l.mtime, r.mtime) =>
+                {
+                    l.mtime > r.mtime
+                }
+                _ => return action,
+            },
+        };
+
+        let path = path.clone();
+        match (keep_left, left, right) {
+            (true, Some(l), _) => SyncAction::CopyToRight { path, size: l.size },
+            (true, None, _) => SyncAction::DeleteRight { path },
+            (false, _, Some(r)) => SyncAction::CopyToLeft { path, size: r.size },
+            (false, _, None) => SyncAction::DeleteLeft { path },
+        }
+    }
+}
+
+/// Hybrid mode: a both-modified conflict whose mtimes are more than
+/// `threshold` apart is resolved in favor of the newer side. Edits closer in
+/// time, or with an mtime that couldn't be read, stay manual conflicts; other
+/// actions are returned unchanged.
+pub fn resolve_clear_winner(action: SyncAction, threshold: Duration) -> SyncAction {
+    let SyncAction::Conflict {
+        path,
+        reason: ConflictReason::BothModified,
+        left: Some(left),
+        right: Some(right),
+    } = &action
+    else {
+        return action;
+    };
+    if !left.mtime_known || !right.mtime_known {
+        return action;
+    }
+    let left_newer_by = left.mtime - right.mtime;
+    if left_newer_by.abs() <= threshold {
+        return action;
+    }
+
+    let path = path.clone();
+    if left_newer_by > Duration::zero() {
+        SyncAction::CopyToRight {
+            path,
+            size: left.size,
+        }
+    } else {
+        SyncAction::CopyToLeft {
+            path,
+            size: right.size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::differ::FileInfo;
+    use chrono::{Duration, Utc};
+    use std::path::PathBuf;
+
+    fn rule(pattern: &str, resolution: ConflictResolution) -> ConflictRule {
+        ConflictRule {
+            pattern: pattern.to_string(),
+            resolution,
+        }
+    }
+
+    fn conflict(path: &str, left: Option<FileInfo>, right: Option<FileInfo>) -> SyncAction {
+        SyncAction::Conflict {
+            path: PathBuf::from(path),
+            reason: ConflictReason::BothModified,
+            left,
+            right,
+        }
+    }
+
+    fn info(size: u64, age_secs: i64) -> Option<FileInfo> {
+        Some(FileInfo {
+            size,
+            mtime: Utc::now() - Duration::seconds(age_secs),
+            hash: None,
+            mtime_known: true,
+        })
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let rules = ConflictRules::compile(&[
+            rule("logs/keep/*.log", ConflictResolution::KeepLeft),
+            rule("*.log", ConflictResolution::KeepRight),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            rules.resolution_for(Path::new("logs/keep/a.log")),
+            Some(ConflictResolution::KeepLeft)
+        );
+        assert_eq!(
+            rules.resolution_for(Path::new("deep/dir/b.log")),
+            Some(ConflictResolution::KeepRight)
+        );
+        assert_eq!(rules.resolution_for(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_keep_side_resolves_to_copy_or_delete() {
+        let rules = ConflictRules::compile(&[
+            rule("*.log", ConflictResolution::KeepRight),
+            rule("*.cfg", ConflictResolution::KeepLeft),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            rules.apply(conflict("a.log", info(1, 0), info(2, 0))),
+            SyncAction::CopyToLeft {
+                path: PathBuf::from("a.log"),
+                size: 2
+            }
+        );
+        // Modified left, deleted right: keeping the right means deleting left
+        assert_eq!(
+            rules.apply(conflict("b.log", info(1, 0), None)),
+            SyncAction::DeleteLeft {
+                path: PathBuf::from("b.log")
+            }
+        );
+        assert_eq!(
+            rules.apply(conflict("c.cfg", info(3, 0), info(4, 0))),
+            SyncAction::CopyToRight {
+                path: PathBuf::from("c.cfg"),
+                size: 3
+            }
+        );
+
+        // Unmatched conflicts stay manual
+        let unmatched = conflict("d.txt", info(1, 0), info(2, 0));
+        assert_eq!(rules.apply(unmatched.clone()), unmatched);
+    }
+
+    #[test]
+    fn test_keep_newer() {
+        let rules =
+            ConflictRules::compile(&[rule("src/**", ConflictResolution::KeepNewer)]).unwrap();
+
+        assert_eq!(
+            rules.apply(conflict("src/main.rs", info(1, 3600), info(2, 0))),
+            SyncAction::CopyToLeft {
+                path: PathBuf::from("src/main.rs"),
+                size: 2
+            }
+        );
+
+        // Same mtime or a missing side can't be decided
+        let tie = conflict("src/lib.rs", info(1, 0), info(2, 0));
+        assert_eq!(rules.apply(tie.clone()), tie);
+        let deleted = conflict("src/old.rs", info(1, 0), None);
+        assert_eq!(rules.apply(deleted.clone()), deleted);
+    }
+
+    #[test]
+    fn test_invalid_pattern_rejected() {
+        assert!(ConflictRules::compile(&[rule("src/[", ConflictResolution::KeepLeft)]).is_err());
+    }
+
+    #[test]
+    fn test_clear_winner_threshold_boundary() {
+        let threshold = Duration::minutes(10);
+        let at = |left_age: i64, right_age: i64| {
+            let now = Utc::now();
+            let info = |size, age| {
+                Some(FileInfo {
+                    size,
+                    mtime: now - Duration::seconds(age),
+                    hash: None,
+                    mtime_known: true,
+                })
+            };
+            conflict("notes.txt", info(1, left_age), info(2, right_age))
+        };
+
+        // Right is newer by more than the threshold: it wins
+        assert_eq!(
+            resolve_clear_winner(at(601, 0), threshold),
+            SyncAction::CopyToLeft {
+                path: PathBuf::from("notes.txt"),
+                size: 2
+            }
+        );
+        assert_eq!(
+            resolve_clear_winner(at(0, 601), threshold),
+            SyncAction::CopyToRight {
+                path: PathBuf::from("notes.txt"),
+                size: 1
+            }
+        );
+
+        // An mtime that couldn't be read is the epoch, not an old edit
+        let mut unknown = at(0, 601);
+        if let SyncAction::Conflict {
+            right: Some(ref mut right),
+            ..
+        } = unknown
+        {
+            right.mtime_known = false;
+        }
+        assert_eq!(resolve_clear_winner(unknown.clone(), threshold), unknown);
+
+        // Exactly at or within the threshold stays a conflict
+        let at_threshold = at(600, 0);
+        assert_eq!(
+            resolve_clear_winner(at_threshold.clone(), threshold),
+            at_threshold
+        );
+        let close = at(0, 5);
+        assert_eq!(resolve_clear_winner(close.clone(), threshold), close);
+
+        // Only both-modified conflicts with both sides are decided
+        let deleted = conflict("notes.txt", info(1, 3600), None);
+        assert_eq!(resolve_clear_winner(deleted.clone(), threshold), deleted);
+        let mut exists_vs_deleted = at(3600, 0);
+        if let SyncAction::Conflict { reason, .. } = &mut exists_vs_deleted {
+            *reason = ConflictReason::ExistsVsDeleted;
+        }
+        assert_eq!(
+            resolve_clear_winner(exists_vs_deleted.clone(), threshold),
+            exists_vs_deleted
+        );
+    }
+}
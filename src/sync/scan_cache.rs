@@ -0,0 +1,218 @@
+//! On-disk cache of the previous scan, stored in `.rahzom/scan_cache.json`.
+//!
+//! A directory's mtime changes when entries are added, removed or renamed in
+//! it, so the files of a directory whose mtime is unchanged can be taken from
+//! the cache instead of being `stat`-ed again. Rewriting a file in place does
+//! not touch its directory, so such edits are only noticed by a full rescan.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::scanner::{FileEntry, ScanResult};
+use super::utils::METADATA_DIR_NAME;
+
+/// Cache file name inside `.rahzom/`
+const CACHE_FILE: &str = "scan_cache.json";
+
+/// Entries of the previous scan of one root
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    /// Whether the cached entries carry file ids
+    record_file_ids: bool,
+    /// Mtime of the root itself, which covers its direct children
+    root_mtime: Option<DateTime<Utc>>,
+    #[serde(with = "entry_list")]
+    entries: HashMap<PathBuf, FileEntry>,
+    /// Directories (relative, "" for the root) whose mtime matches the cache
+    #[serde(skip)]
+    unchanged_dirs: HashSet<PathBuf>,
+}
+
+impl ScanCache {
+    fn path(root: &Path) -> PathBuf {
+        root.join(METADATA_DIR_NAME).join(CACHE_FILE)
+    }
+
+    /// Loads the cache of `root`. A missing, unreadable or incompatible cache
+    /// (scanned with different file id recording) yields an empty one.
+    pub fn load(root: &Path, record_file_ids: bool) -> Self {
+        let cache = fs::read(Self::path(root))
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Self>(&data).ok())
+            .filter(|cache| cache.record_file_ids == record_file_ids)
+            .unwrap_or_default();
+        Self {
+            record_file_ids,
+            ..cache
+        }
+    }
+
+    /// Stores `result` as the cache of its root. `root_mtime` must be read
+    /// before the scan started, so changes made during the scan are caught.
+    pub fn save(
+        result: &ScanResult,
+        root_mtime: Option<DateTime<Utc>>,
+        record_file_ids: bool,
+    ) -> Result<()> {
+        let cache = Self {
+            record_file_ids,
+            root_mtime,
+            entries: result
+                .entries
+                .iter()
+                .map(|e| (e.path.clone(), e.clone()))
+                .collect(),
+            unchanged_dirs: HashSet::new(),
+        };
+        let path = Self::path(&result.root);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let data = serde_json::to_vec(&cache).context("Failed to serialize scan cache")?;
+        fs::write(&path, data).with_context(|| format!("Failed to write scan cache: {:?}", path))
+    }
+
+    /// Records the current mtime of the root; its direct children can be
+    /// reused if it matches the cached one
+    pub fn check_root(&mut self, mtime: Option<DateTime<Utc>>) {
+        if mtime.is_some() && mtime == self.root_mtime {
+            self.unchanged_dirs.insert(PathBuf::new());
+        }
+    }
+
+    /// Records a freshly scanned directory; its children can be reused if its
+    /// mtime matches the cached entry
+    pub fn check_dir(&mut self, dir: &FileEntry) {
+        let unchanged = self.entries.get(&dir.path).is_some_and(|cached| {
+            cached.is_dir && dir.mtime_known && cached.mtime_known && cached.mtime == dir.mtime
+        });
+        if unchanged {
+            self.unchanged_dirs.insert(dir.path.clone());
+        }
+    }
+
+    /// Cached entry for the file at `path` (relative) if its directory is unchanged
+    pub fn reusable_file(&self, path: &Path) -> Option<FileEntry> {
+        let parent = path.parent()?;
+        if !self.unchanged_dirs.contains(parent) {
+            return None;
+        }
+        self.entries.get(path).filter(|e| !e.is_dir).cloned()
+    }
+}
+
+/// Serde for the cached entries as a list: each entry carries its path,
+/// which as a map key would have to be UTF-8
+mod entry_list {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::sync::scanner::FileEntry;
+
+    pub fn serialize<S: Serializer>(
+        entries: &HashMap<PathBuf, FileEntry>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(entries.values())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<PathBuf, FileEntry>, D::Error> {
+        let entries = Vec::<FileEntry>::deserialize(deserializer)?;
+        Ok(entries.into_iter().map(|e| (e.path.clone(), e)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::scanner::{scan_with_options, ScanCacheMode, ScanOptions};
+    use filetime::{set_file_mtime, FileTime};
+    use tempfile::TempDir;
+
+    fn options(cache: ScanCacheMode) -> ScanOptions {
+        ScanOptions {
+            cache,
+            ..Default::default()
+        }
+    }
+
+    fn size_of(result: &ScanResult, path: &str) -> Option<u64> {
+        result
+            .entries
+            .iter()
+            .find(|e| e.path == Path::new(path))
+            .map(|e| e.size)
+    }
+
+    #[test]
+    fn test_unchanged_directories_are_reused() {
+        let temp = TempDir::new().unwrap();
+        let sub = temp.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("edited.txt"), "1").unwrap();
+        fs::write(sub.join("saved.txt"), "1").unwrap();
+        // Directory mtimes well in the past, so any change below is visible
+        let past = FileTime::from_unix_time(1_600_000_000, 0);
+        set_file_mtime(&sub, past).unwrap();
+        set_file_mtime(temp.path(), past).unwrap();
+
+        scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        assert!(ScanCache::path(temp.path()).exists());
+
+        // Rewritten in place: the directory doesn't notice, the cache is used
+        fs::write(sub.join("edited.txt"), "22").unwrap();
+        let cached = scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        assert_eq!(size_of(&cached, "sub/edited.txt"), Some(1));
+
+        // Saved via rename and added files change the directory's mtime
+        fs::write(sub.join("saved.txt.new"), "333").unwrap();
+        fs::rename(sub.join("saved.txt.new"), sub.join("saved.txt")).unwrap();
+        fs::write(temp.path().join("added.txt"), "4444").unwrap();
+        let rescanned =
+            scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        assert_eq!(size_of(&rescanned, "sub/saved.txt"), Some(3));
+        assert_eq!(size_of(&rescanned, "sub/edited.txt"), Some(2));
+        assert_eq!(size_of(&rescanned, "added.txt"), Some(4));
+    }
+
+    #[test]
+    fn test_rebuild_ignores_cache() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("file.txt"), "1").unwrap();
+        set_file_mtime(temp.path(), FileTime::from_unix_time(1_600_000_000, 0)).unwrap();
+
+        scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        fs::write(temp.path().join("file.txt"), "22").unwrap();
+
+        let rebuilt =
+            scan_with_options(temp.path(), None, &options(ScanCacheMode::Rebuild)).unwrap();
+        assert_eq!(size_of(&rebuilt, "file.txt"), Some(2));
+        // The rebuilt cache holds the new state
+        let reused = scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        assert_eq!(size_of(&reused, "file.txt"), Some(2));
+    }
+
+    #[test]
+    fn test_removed_files_are_not_resurrected() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("gone.txt"), "1").unwrap();
+        fs::write(temp.path().join("kept.txt"), "1").unwrap();
+
+        scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        fs::remove_file(temp.path().join("gone.txt")).unwrap();
+
+        let result = scan_with_options(temp.path(), None, &options(ScanCacheMode::Reuse)).unwrap();
+        assert_eq!(size_of(&result, "gone.txt"), None);
+        assert_eq!(size_of(&result, "kept.txt"), Some(1));
+    }
+}
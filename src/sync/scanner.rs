@@ -1,36 +1,48 @@
-use std::fs::{self, File};
-use std::io::{BufReader, Read};
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Context, Result};
-use chrono::{DateTime, TimeZone, Utc};
-use sha2::{Digest, Sha256};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use super::exclusions::Exclusions;
-use super::metadata::FileAttributes;
+use super::metadata::{FileAttributes, FileId};
+use super::scan_cache::ScanCache;
+use super::utils::{
+    is_temp_file, is_within_metadata_dir, serde_path, serde_paths, system_time_to_utc,
+};
 
 /// Represents a single file or directory entry in the scan result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     /// Path relative to sync root
+    #[serde(with = "serde_path")]
     pub path: PathBuf,
     /// File size in bytes (0 for directories)
     pub size: u64,
-    /// Last modification time
+    /// Last modification time (Unix epoch when `mtime_known` is false)
     pub mtime: DateTime<Utc>,
+    /// Whether the filesystem reported a modification time
+    pub mtime_known: bool,
     /// Whether this entry is a directory
     pub is_dir: bool,
     /// SHA-256 hash, computed on demand
+    #[serde(skip)]
     pub hash: Option<String>,
     /// Platform-specific file attributes
     pub attributes: FileAttributes,
+    /// Filesystem identity (inode/file index), recorded only when requested
+    pub file_id: Option<FileId>,
 }
 
 /// Result of scanning a directory
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScanResult {
     /// Absolute path to the scanned root directory
+    #[serde(with = "serde_path")]
     pub root: PathBuf,
     /// All entries found during scan
     pub entries: Vec<FileEntry>,
@@ -38,17 +50,228 @@ pub struct ScanResult {
     pub scan_time: DateTime<Utc>,
     /// Paths that were skipped due to errors
     pub skipped: Vec<SkippedEntry>,
+    /// Partial copies (`*.rahzom-tmp`) an interrupted sync left behind,
+    /// relative to `root`. Never synced; reported so they can be cleaned up.
+    #[serde(default, with = "serde_paths")]
+    pub partials: Vec<PathBuf>,
+}
+
+/// Aggregate counts over a scan result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    pub total_files: usize,
+    pub total_dirs: usize,
+    /// Sum of file sizes in bytes
+    pub total_bytes: u64,
+    /// Directories whose contents couldn't be listed
+    pub unreadable_dirs: usize,
+}
+
+impl ScanResult {
+    /// Computes file/dir counts and total size in a single pass over entries
+    pub fn stats(&self) -> ScanStats {
+        let mut stats = ScanStats::default();
+        for entry in &self.entries {
+            if entry.is_dir {
+                stats.total_dirs += 1;
+            } else {
+                stats.total_files += 1;
+                stats.total_bytes += entry.size;
+            }
+        }
+        stats.unreadable_dirs = self.unreadable_dirs().count();
+        stats
+    }
+
+    /// Paths (relative to the root) of directories that couldn't be listed.
+    /// Nothing is known about their contents.
+    pub fn unreadable_dirs(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.skipped
+            .iter()
+            .filter(|s| s.reason.starts_with(UNREADABLE_DIR))
+            .filter_map(|s| s.path.strip_prefix(&self.root).ok())
+    }
+
+    /// Paths (relative to the root) of directories on another filesystem,
+    /// left out with their contents by `one_filesystem`
+    pub fn other_filesystem_dirs(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.skipped
+            .iter()
+            .filter(|s| s.reason == OTHER_FILESYSTEM)
+            .filter_map(|s| s.path.strip_prefix(&self.root).ok())
+    }
+
+    /// Ignore files found below the root, loaded again from disk, as
+    /// (depth, dir, patterns) outermost first, the way [`is_excluded`] takes
+    /// them. Files that fail to load or hold no patterns are left out.
+    pub fn nested_exclusions(&self) -> Vec<(usize, PathBuf, Exclusions)> {
+        let mut nested: Vec<(usize, PathBuf, Exclusions)> = self
+            .entries
+            .iter()
+            .filter(|e| !e.is_dir)
+            .filter_map(|e| {
+                let dir = e.path.parent().filter(|d| !d.as_os_str().is_empty())?;
+                if Exclusions::file_path(dir) != e.path {
+                    return None;
+                }
+                let found = Exclusions::load(&self.root.join(dir)).ok()?;
+                (!found.is_empty()).then(|| (dir.components().count(), dir.to_path_buf(), found))
+            })
+            .collect();
+        nested.sort_by_key(|(depth, ..)| *depth);
+        nested
+    }
+
+    /// Paths (relative to the root) skipped by the `min_age`/`max_age` filters
+    pub fn skipped_by_age(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.skipped
+            .iter()
+            .filter(|s| s.reason == TOO_RECENT || s.reason == TOO_OLD)
+            .filter_map(|s| s.path.strip_prefix(&self.root).ok())
+    }
+
+    /// Maps each hardlinked file to the other paths sharing its file id.
+    /// Only entries scanned with `record_file_ids` take part.
+    pub fn hardlink_groups(&self) -> HashMap<PathBuf, Vec<PathBuf>> {
+        let mut by_id: HashMap<FileId, Vec<&PathBuf>> = HashMap::new();
+        for entry in &self.entries {
+            if let (false, Some(id)) = (entry.is_dir, entry.file_id) {
+                by_id.entry(id).or_default().push(&entry.path);
+            }
+        }
+
+        let mut groups = HashMap::new();
+        for paths in by_id.values().filter(|p| p.len() > 1) {
+            for path in paths {
+                let siblings = paths
+                    .iter()
+                    .filter(|p| *p != path)
+                    .map(|p| (*p).clone())
+                    .collect();
+                groups.insert((*path).clone(), siblings);
+            }
+        }
+        groups
+    }
+}
+
+/// Options that tune what the scanner records
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Record inode (Unix) / file index (Windows) for each entry.
+    /// On Windows this opens every file, so it's off by default.
+    pub record_file_ids: bool,
+    /// Don't skip Windows reparse points outright; apply the symlink rule instead.
+    /// Junctions and symlinks are still skipped, other reparse points
+    /// (e.g. cloud placeholders, deduplicated files) are scanned as regular entries.
+    pub include_reparse_points: bool,
+    /// Whether the on-disk scan cache is read and updated
+    pub cache: ScanCacheMode,
+    /// Stay on the filesystem of the root (like `rsync -x`): directories on
+    /// another device, i.e. mount points, are skipped. On Windows, where no
+    /// device id is available, directory reparse points (mounted volumes,
+    /// junctions) are skipped instead.
+    pub one_filesystem: bool,
+    /// Files modified more recently than this are skipped
+    pub min_age: Option<Duration>,
+    /// Files not modified within this long are skipped
+    pub max_age: Option<Duration>,
+    /// Scan FIFOs (named pipes) as empty files, for the executor to recreate
+    /// instead of copying. Otherwise they're skipped like other special files.
+    pub recreate_fifos: bool,
+}
+
+/// Use of the scan cache in `.rahzom/scan_cache.json` (see `scan_cache`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanCacheMode {
+    /// Neither read nor written
+    #[default]
+    Off,
+    /// Files of directories with an unchanged mtime are taken from the cache
+    Reuse,
+    /// Every entry is read from disk and the cache is rewritten
+    Rebuild,
+}
+
+/// A file that is neither a regular file, a directory nor a symlink (Unix).
+/// Reading one blocks (FIFO), fails (socket) or never ends (device).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialFile {
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+}
+
+impl SpecialFile {
+    /// The kind of special file `file_type` is, if it is one
+    #[cfg(unix)]
+    pub fn of(file_type: &fs::FileType) -> Option<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_fifo() {
+            Some(Self::Fifo)
+        } else if file_type.is_socket() {
+            Some(Self::Socket)
+        } else if file_type.is_block_device() {
+            Some(Self::BlockDevice)
+        } else if file_type.is_char_device() {
+            Some(Self::CharDevice)
+        } else {
+            None
+        }
+    }
+
+    /// The kind of special file `file_type` is, if it is one
+    #[cfg(not(unix))]
+    pub fn of(_file_type: &fs::FileType) -> Option<Self> {
+        None
+    }
+
+    /// The kind of special file at `path`, not following symlinks
+    pub fn at(path: &Path) -> Option<Self> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        Self::of(&metadata.file_type())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fifo => "Named pipe (FIFO)",
+            Self::Socket => "Socket",
+            Self::BlockDevice => "Block device",
+            Self::CharDevice => "Character device",
+        }
+    }
 }
 
 /// Entry that was skipped during scan
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SkippedEntry {
+    #[serde(with = "serde_path")]
     pub path: PathBuf,
     pub reason: String,
 }
 
-/// Directory to skip during scanning
-const SKIP_DIR: &str = ".rahzom";
+/// Skip reason of files younger than `min_age`
+const TOO_RECENT: &str = "Modified too recently (min age)";
+
+/// Skip reason of files older than `max_age`
+const TOO_OLD: &str = "Not modified recently (max age)";
+
+/// Skip reason prefix of directories whose contents couldn't be listed
+const UNREADABLE_DIR: &str = "Unreadable directory";
+
+/// Skip reason of directories left out by `one_filesystem`
+pub const OTHER_FILESYSTEM: &str = "On another filesystem";
+
+/// Windows FILE_ATTRIBUTE_REPARSE_POINT
+#[cfg(windows)]
+const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+/// Upper bound on directory handles held open during traversal.
+/// Beyond this depth walkdir buffers remaining entries of the oldest
+/// directory in memory and closes its handle.
+const MAX_OPEN_DIR_HANDLES: usize = 16;
 
 /// Scans a directory and returns structured representation of all files.
 ///
@@ -70,41 +293,140 @@ pub fn scan(root: &Path) -> Result<ScanResult> {
 /// # Returns
 /// * `ScanResult` containing all found entries (excluding filtered files)
 pub fn scan_with_exclusions(root: &Path, exclusions: Option<&Exclusions>) -> Result<ScanResult> {
+    scan_with_options(root, exclusions, &ScanOptions::default())
+}
+
+/// Scans a directory with optional exclusion patterns and scan options.
+///
+/// # Arguments
+/// * `root` - Path to the directory to scan
+/// * `exclusions` - Optional exclusion patterns to filter out matching files
+/// * `options` - Options controlling what is recorded per entry
+///
+/// # Returns
+/// * `ScanResult` containing all found entries (excluding filtered files)
+pub fn scan_with_options(
+    root: &Path,
+    exclusions: Option<&Exclusions>,
+    options: &ScanOptions,
+) -> Result<ScanResult> {
     let root = normalize_path(root)?;
+    // Another folder's state, backups and trash must never become sync content
+    if is_within_metadata_dir(&root) {
+        bail!("Refusing to scan inside a metadata directory: {:?}", root);
+    }
     let mut entries = Vec::new();
     let mut skipped = Vec::new();
+    let mut partials = Vec::new();
+
+    // Taken before walking, so anything added meanwhile invalidates the cache
+    let root_mtime = fs::metadata(&root)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(system_time_to_utc);
+    let mut cache = match options.cache {
+        ScanCacheMode::Off => None,
+        ScanCacheMode::Reuse => Some(ScanCache::load(&root, options.record_file_ids)),
+        ScanCacheMode::Rebuild => Some(ScanCache::default()),
+    };
+    if let Some(ref mut cache) = cache {
+        cache.check_root(root_mtime);
+    }
+
+    let now = Utc::now();
+    let root_dev = if options.one_filesystem {
+        device_id(&root)
+    } else {
+        None
+    };
+
+    // Ignore files found below the root, innermost last, as (depth, dir, patterns)
+    let mut nested: Vec<(usize, PathBuf, Exclusions)> = Vec::new();
 
-    for entry in WalkDir::new(&root).follow_links(false) {
+    // walkdir traverses iteratively; bounding open handles keeps wide/deep trees
+    // from exhausting file descriptors on systems with a low ulimit
+    let mut walker = WalkDir::new(&root)
+        .follow_links(false)
+        .max_open(MAX_OPEN_DIR_HANDLES)
+        .into_iter();
+
+    while let Some(entry) = walker.next() {
         match entry {
             Ok(entry) => {
                 let path = entry.path();
+                let is_dir = entry.file_type().is_dir();
 
                 // Skip the root itself
                 if path == root {
                     continue;
                 }
 
-                // Skip .rahzom directory and its contents
+                // Skip .rahzom directories (at any depth) without descending into them
                 if should_skip(path, &root) {
+                    if is_dir {
+                        walker.skip_current_dir();
+                    }
+                    continue;
+                }
+
+                // Partial copies of an interrupted sync are never synced
+                if !is_dir && is_temp_file(path) {
+                    if let Ok(relative) = path.strip_prefix(&root) {
+                        partials.push(relative.to_path_buf());
+                    }
                     continue;
                 }
 
                 // Apply exclusion patterns
                 if let Some(excl) = exclusions {
+                    // Walking is depth-first: a shallower entry left their subtrees
+                    while nested
+                        .last()
+                        .is_some_and(|(depth, ..)| *depth >= entry.depth())
+                    {
+                        nested.pop();
+                    }
                     if let Ok(relative) = path.strip_prefix(&root) {
-                        let is_dir = path.is_dir();
-                        if excl.is_excluded(relative, is_dir) {
+                        if is_excluded(excl, &nested, relative, is_dir) {
                             skipped.push(SkippedEntry {
                                 path: path.to_path_buf(),
                                 reason: "Excluded by pattern".to_string(),
                             });
+                            if is_dir {
+                                walker.skip_current_dir();
+                            }
                             continue;
                         }
+                        if is_dir {
+                            match Exclusions::load(path) {
+                                Ok(found) if !found.is_empty() => {
+                                    nested.push((entry.depth(), relative.to_path_buf(), found));
+                                }
+                                Ok(_) => {}
+                                Err(e) => skipped.push(SkippedEntry {
+                                    path: Exclusions::file_path(path),
+                                    reason: format!("Ignore file not applied: {:#}", e),
+                                }),
+                            }
+                        }
+                    }
+                }
+
+                // Skip reparse points; a junction to an ancestor would otherwise
+                // make the tree cyclic
+                if !options.include_reparse_points && is_reparse_point(&entry) {
+                    skipped.push(SkippedEntry {
+                        path: path.to_path_buf(),
+                        reason: "Reparse point (not supported)".to_string(),
+                    });
+                    if is_dir {
+                        walker.skip_current_dir();
                     }
+                    continue;
                 }
 
                 // Skip symlinks (not supported)
-                if path.is_symlink() {
+                if entry.path_is_symlink() {
                     skipped.push(SkippedEntry {
                         path: path.to_path_buf(),
                         reason: "Symlink (not supported)".to_string(),
@@ -112,21 +434,79 @@ pub fn scan_with_exclusions(root: &Path, exclusions: Option<&Exclusions>) -> Res
                     continue;
                 }
 
-                match process_entry(path, &root) {
-                    Ok(file_entry) => entries.push(file_entry),
+                // Copying a FIFO would block until something writes to it
+                if let Some(special) = SpecialFile::of(&entry.file_type()) {
+                    if !(special == SpecialFile::Fifo && options.recreate_fifos) {
+                        skipped.push(SkippedEntry {
+                            path: path.to_path_buf(),
+                            reason: format!("{} (special file, not synced)", special.name()),
+                        });
+                        continue;
+                    }
+                }
+
+                if is_dir && options.one_filesystem && is_other_filesystem(&entry, root_dev) {
+                    skipped.push(SkippedEntry {
+                        path: path.to_path_buf(),
+                        reason: OTHER_FILESYSTEM.to_string(),
+                    });
+                    walker.skip_current_dir();
+                    continue;
+                }
+
+                if let Some(ref cache) = cache {
+                    let cached = path
+                        .strip_prefix(&root)
+                        .ok()
+                        .filter(|_| !is_dir)
+                        .and_then(|relative| cache.reusable_file(relative));
+                    if let Some(file_entry) = cached {
+                        match age_filter(&file_entry, options, now) {
+                            Some(reason) => skipped.push(SkippedEntry {
+                                path: path.to_path_buf(),
+                                reason: reason.to_string(),
+                            }),
+                            None => entries.push(file_entry),
+                        }
+                        continue;
+                    }
+                }
+
+                match process_entry(path, &root, options) {
+                    Ok(file_entry) => {
+                        if let (true, Some(ref mut cache)) = (file_entry.is_dir, &mut cache) {
+                            cache.check_dir(&file_entry);
+                        }
+                        match age_filter(&file_entry, options, now) {
+                            Some(reason) => skipped.push(SkippedEntry {
+                                path: path.to_path_buf(),
+                                reason: reason.to_string(),
+                            }),
+                            None => entries.push(file_entry),
+                        }
+                    }
                     Err(e) => {
                         skipped.push(SkippedEntry {
                             path: path.to_path_buf(),
                             reason: e.to_string(),
                         });
+                        if is_dir {
+                            walker.skip_current_dir();
+                        }
                     }
                 }
             }
             Err(e) => {
+                // Without the root there is nothing to compare against
+                if e.depth() == 0 {
+                    return Err(e).with_context(|| format!("Cannot read directory: {:?}", root));
+                }
+                // Unreadable directories (permission denied, too many open files)
+                // are recorded and skipped; the rest of the tree is still scanned
                 let path = e.path().map(|p| p.to_path_buf()).unwrap_or_default();
                 skipped.push(SkippedEntry {
                     path,
-                    reason: e.to_string(),
+                    reason: format!("{}: {}", UNREADABLE_DIR, e),
                 });
             }
         }
@@ -135,33 +515,37 @@ pub fn scan_with_exclusions(root: &Path, exclusions: Option<&Exclusions>) -> Res
     // Sort entries by path for consistent ordering
     entries.sort_by(|a, b| a.path.cmp(&b.path));
 
-    Ok(ScanResult {
+    let result = ScanResult {
         root,
         entries,
         scan_time: Utc::now(),
         skipped,
-    })
-}
-
-/// Computes SHA-256 hash of a file using streaming to avoid loading entire file into memory.
-pub fn compute_hash(path: &Path) -> Result<String> {
-    let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
-    let mut reader = BufReader::with_capacity(64 * 1024, file);
-    let mut hasher = Sha256::new();
-    let mut buffer = [0u8; 64 * 1024];
-
-    loop {
-        let bytes_read = reader
-            .read(&mut buffer)
-            .with_context(|| format!("Failed to read file: {:?}", path))?;
-        if bytes_read == 0 {
-            break;
-        }
-        hasher.update(&buffer[..bytes_read]);
+        partials,
+    };
+    if cache.is_some() {
+        // The cache only saves time; a read-only root simply goes without
+        let _ = ScanCache::save(&result, root_mtime, options.record_file_ids);
     }
+    Ok(result)
+}
 
-    let result = hasher.finalize();
-    Ok(format!("{:x}", result))
+/// Whether `relative` is excluded. The innermost ignore file with a pattern
+/// for it decides, with paths relative to its directory; the root
+/// exclusions decide if none has one.
+pub fn is_excluded(
+    root_exclusions: &Exclusions,
+    nested: &[(usize, PathBuf, Exclusions)],
+    relative: &Path,
+    is_dir: bool,
+) -> bool {
+    nested
+        .iter()
+        .rev()
+        .find_map(|(_, dir, exclusions)| {
+            exclusions.verdict(relative.strip_prefix(dir).ok()?, is_dir)
+        })
+        .or_else(|| root_exclusions.verdict(relative, is_dir))
+        .unwrap_or(false)
 }
 
 /// Normalizes path for cross-platform compatibility.
@@ -185,20 +569,86 @@ fn normalize_path(path: &Path) -> Result<PathBuf> {
 
 /// Checks if a path should be skipped during scanning.
 fn should_skip(path: &Path, root: &Path) -> bool {
-    // Get the relative path from root
-    if let Ok(relative) = path.strip_prefix(root) {
-        // Check if any component is .rahzom
-        for component in relative.components() {
-            if let std::path::Component::Normal(name) = component {
-                if name == SKIP_DIR {
-                    return true;
-                }
-            }
-        }
-    }
+    path.strip_prefix(root)
+        .map(is_within_metadata_dir)
+        .unwrap_or(false)
+}
+
+/// Checks the entry's own (not followed) attributes for a reparse point.
+#[cfg(windows)]
+fn is_reparse_point(entry: &walkdir::DirEntry) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    entry
+        .metadata()
+        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
+        .unwrap_or(false)
+}
+
+/// Reparse points only exist on Windows.
+#[cfg(not(windows))]
+fn is_reparse_point(_entry: &walkdir::DirEntry) -> bool {
+    false
+}
+
+/// Device (filesystem) `path` is on; `None` where unknown (Windows)
+#[cfg(unix)]
+pub(crate) fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Whether `dev` differs from the root's device; unknown devices never do
+fn on_other_device(root_dev: Option<u64>, dev: u64) -> bool {
+    root_dev.is_some_and(|root_dev| root_dev != dev)
+}
+
+/// Whether a directory entry lies on another filesystem than the scan root
+#[cfg(unix)]
+fn is_other_filesystem(entry: &walkdir::DirEntry, root_dev: Option<u64>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    entry
+        .metadata()
+        .is_ok_and(|m| on_other_device(root_dev, m.dev()))
+}
+
+/// Without a device id, a directory reparse point (a volume mounted into a
+/// folder, or a junction) stands in for a filesystem boundary
+#[cfg(windows)]
+fn is_other_filesystem(entry: &walkdir::DirEntry, _root_dev: Option<u64>) -> bool {
+    is_reparse_point(entry)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_other_filesystem(_entry: &walkdir::DirEntry, _root_dev: Option<u64>) -> bool {
     false
 }
 
+/// Why `entry` falls outside the `min_age`/`max_age` window, if it does.
+/// Directories and files with an unknown mtime are always kept; a future
+/// mtime (clock skew) counts as age zero.
+fn age_filter(
+    entry: &FileEntry,
+    options: &ScanOptions,
+    now: DateTime<Utc>,
+) -> Option<&'static str> {
+    if entry.is_dir || !entry.mtime_known {
+        return None;
+    }
+    let age = (now - entry.mtime).to_std().unwrap_or_default();
+    if options.min_age.is_some_and(|min| age < min) {
+        Some(TOO_RECENT)
+    } else if options.max_age.is_some_and(|max| age > max) {
+        Some(TOO_OLD)
+    } else {
+        None
+    }
+}
+
 /// Gets platform-specific file attributes from metadata.
 #[cfg(windows)]
 fn get_file_attributes(metadata: &fs::Metadata) -> FileAttributes {
@@ -206,19 +656,25 @@ fn get_file_attributes(metadata: &fs::Metadata) -> FileAttributes {
     let attrs = metadata.file_attributes();
     FileAttributes {
         unix_mode: None,
-        windows_readonly: Some((attrs & 0x1) != 0),  // FILE_ATTRIBUTE_READONLY
-        windows_hidden: Some((attrs & 0x2) != 0),    // FILE_ATTRIBUTE_HIDDEN
+        windows_readonly: Some((attrs & 0x1) != 0), // FILE_ATTRIBUTE_READONLY
+        windows_hidden: Some((attrs & 0x2) != 0),   // FILE_ATTRIBUTE_HIDDEN
+        windows_reparse_point: Some((attrs & FILE_ATTRIBUTE_REPARSE_POINT) != 0),
+        unix_uid: None,
+        unix_gid: None,
     }
 }
 
 /// Gets platform-specific file attributes from metadata.
 #[cfg(unix)]
 fn get_file_attributes(metadata: &fs::Metadata) -> FileAttributes {
-    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
     FileAttributes {
         unix_mode: Some(metadata.permissions().mode()),
         windows_readonly: None,
         windows_hidden: None,
+        windows_reparse_point: None,
+        unix_uid: Some(metadata.uid()),
+        unix_gid: Some(metadata.gid()),
     }
 }
 
@@ -229,7 +685,7 @@ fn get_file_attributes(_metadata: &fs::Metadata) -> FileAttributes {
 }
 
 /// Processes a single directory entry into FileEntry.
-fn process_entry(path: &Path, root: &Path) -> Result<FileEntry> {
+fn process_entry(path: &Path, root: &Path, options: &ScanOptions) -> Result<FileEntry> {
     let metadata =
         fs::metadata(path).with_context(|| format!("Failed to get metadata for: {:?}", path))?;
 
@@ -238,37 +694,36 @@ fn process_entry(path: &Path, root: &Path) -> Result<FileEntry> {
         .with_context(|| format!("Path {:?} is not under root {:?}", path, root))?
         .to_path_buf();
 
-    let mtime = metadata
-        .modified()
-        .with_context(|| format!("Failed to get mtime for: {:?}", path))?;
-
-    let mtime_utc = system_time_to_utc(mtime);
+    // Some filesystems (e.g. FUSE mounts) don't expose timestamps; the differ
+    // falls back to size/hash comparison for such entries
+    let (mtime_utc, mtime_known) = match metadata.modified() {
+        Ok(mtime) => (system_time_to_utc(mtime), true),
+        Err(_) => (DateTime::UNIX_EPOCH, false),
+    };
     let attributes = get_file_attributes(&metadata);
+    let file_id = if options.record_file_ids {
+        FileId::read_from_path(path)
+    } else {
+        None
+    };
 
     Ok(FileEntry {
         path: relative_path,
         size: if metadata.is_dir() { 0 } else { metadata.len() },
         mtime: mtime_utc,
+        mtime_known,
         is_dir: metadata.is_dir(),
         hash: None,
         attributes,
+        file_id,
     })
 }
 
-/// Converts SystemTime to DateTime<Utc>
-fn system_time_to_utc(time: std::time::SystemTime) -> DateTime<Utc> {
-    let duration = time
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    Utc.timestamp_opt(duration.as_secs() as i64, duration.subsec_nanos())
-        .single()
-        .unwrap_or_else(Utc::now)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::sync::exclusions::Exclusions;
+    use crate::sync::utils::temp_path_for;
     use std::fs;
     use tempfile::TempDir;
 
@@ -298,11 +753,11 @@ mod tests {
         assert!(result
             .entries
             .iter()
-            .any(|e| e.path == PathBuf::from("file1.txt")));
+            .any(|e| e.path == Path::new("file1.txt")));
         assert!(result
             .entries
             .iter()
-            .any(|e| e.path == PathBuf::from("file2.txt")));
+            .any(|e| e.path == Path::new("file2.txt")));
     }
 
     #[test]
@@ -326,6 +781,70 @@ mod tests {
         assert_eq!(files.len(), 3);
     }
 
+    #[test]
+    fn test_scan_records_file_ids_when_requested() {
+        let temp = create_test_dir();
+        fs::write(temp.path().join("file.txt"), "content").unwrap();
+
+        let plain = scan(temp.path()).unwrap();
+        assert!(plain.entries[0].file_id.is_none());
+
+        let options = ScanOptions {
+            record_file_ids: true,
+            ..Default::default()
+        };
+        let before = scan_with_options(temp.path(), None, &options).unwrap();
+        let id = before.entries[0].file_id;
+        assert!(id.is_some());
+
+        // Identity survives a rename within the same filesystem
+        fs::rename(
+            temp.path().join("file.txt"),
+            temp.path().join("renamed.txt"),
+        )
+        .unwrap();
+        let after = scan_with_options(temp.path(), None, &options).unwrap();
+        assert_eq!(after.entries[0].path, Path::new("renamed.txt"));
+        assert_eq!(after.entries[0].file_id, id);
+    }
+
+    #[test]
+    fn test_hardlink_groups() {
+        let temp = create_test_dir();
+        fs::write(temp.path().join("a.txt"), "shared").unwrap();
+        fs::hard_link(temp.path().join("a.txt"), temp.path().join("b.txt")).unwrap();
+        fs::write(temp.path().join("c.txt"), "shared").unwrap();
+
+        let options = ScanOptions {
+            record_file_ids: true,
+            ..Default::default()
+        };
+        let result = scan_with_options(temp.path(), None, &options).unwrap();
+        let groups = result.hardlink_groups();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[Path::new("a.txt")], vec![PathBuf::from("b.txt")]);
+        assert_eq!(groups[Path::new("b.txt")], vec![PathBuf::from("a.txt")]);
+
+        // Without file ids nothing is grouped
+        assert!(scan(temp.path()).unwrap().hardlink_groups().is_empty());
+    }
+
+    #[test]
+    fn test_scan_stats() {
+        let temp = create_test_dir();
+
+        fs::create_dir_all(temp.path().join("subdir/empty")).unwrap();
+        fs::write(temp.path().join("root.txt"), "root").unwrap();
+        fs::write(temp.path().join("subdir/sub.txt"), "sub").unwrap();
+
+        let stats = scan(temp.path()).unwrap().stats();
+
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.total_dirs, 2);
+        assert_eq!(stats.total_bytes, 7);
+    }
+
     #[test]
     fn test_scan_skips_rahzom_directory() {
         let temp = create_test_dir();
@@ -341,35 +860,74 @@ mod tests {
     }
 
     #[test]
-    fn test_file_entry_has_correct_size() {
+    fn test_scan_skips_partial_copies() {
         let temp = create_test_dir();
 
-        let content = "Hello, World!";
-        fs::write(temp.path().join("test.txt"), content).unwrap();
+        fs::write(temp.path().join("video.mp4"), "complete").unwrap();
+        let partial = temp_path_for(Path::new("video.mp4"));
+        fs::write(temp.path().join(&partial), "part").unwrap();
+        // The user's own, only named like one
+        fs::write(temp.path().join("notes.rahzom-tmp"), "mine").unwrap();
 
         let result = scan(temp.path()).unwrap();
 
-        assert_eq!(result.entries.len(), 1);
-        assert_eq!(result.entries[0].size, content.len() as u64);
+        let mut paths: Vec<&PathBuf> = result.entries.iter().map(|e| &e.path).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                &PathBuf::from("notes.rahzom-tmp"),
+                &PathBuf::from("video.mp4")
+            ]
+        );
+        // Reported on their own instead
+        assert_eq!(result.partials, [partial]);
     }
 
     #[test]
-    fn test_compute_hash() {
+    fn test_scan_skips_nested_metadata_directories() {
         let temp = create_test_dir();
 
-        let content = "Hello, World!";
-        let file_path = temp.path().join("test.txt");
-        fs::write(&file_path, content).unwrap();
+        // Another synced folder placed inside this one
+        let nested = temp.path().join("other/.rahzom");
+        fs::create_dir_all(nested.join("_backup")).unwrap();
+        fs::create_dir_all(nested.join("_trash")).unwrap();
+        fs::write(nested.join("state.json"), "{}").unwrap();
+        fs::write(nested.join("_backup/a.txt.20240101_000000_000"), "old").unwrap();
+        fs::write(nested.join("_trash/b.txt"), "gone").unwrap();
+        fs::write(temp.path().join("other/data.txt"), "data").unwrap();
 
-        let hash = compute_hash(&file_path).unwrap();
+        let result = scan(temp.path()).unwrap();
+        let paths: Vec<_> = result.entries.iter().map(|e| e.path.clone()).collect();
 
-        // SHA-256 of "Hello, World!" is known
         assert_eq!(
-            hash,
-            "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+            paths,
+            vec![PathBuf::from("other"), PathBuf::from("other/data.txt")]
         );
     }
 
+    #[test]
+    fn test_scan_refuses_root_inside_metadata_directory() {
+        let temp = create_test_dir();
+        let backup = temp.path().join(".rahzom/_backup");
+        fs::create_dir_all(&backup).unwrap();
+
+        assert!(scan(&backup).is_err());
+    }
+
+    #[test]
+    fn test_file_entry_has_correct_size() {
+        let temp = create_test_dir();
+
+        let content = "Hello, World!";
+        fs::write(temp.path().join("test.txt"), content).unwrap();
+
+        let result = scan(temp.path()).unwrap();
+
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(result.entries[0].size, content.len() as u64);
+    }
+
     #[test]
     fn test_directories_have_zero_size() {
         let temp = create_test_dir();
@@ -416,9 +974,9 @@ mod tests {
         let result = scan_with_exclusions(temp.path(), Some(&excl)).unwrap();
 
         assert_eq!(result.entries.len(), 2);
-        assert!(result.entries.iter().any(|e| e.path == PathBuf::from("keep.txt")));
-        assert!(result.entries.iter().any(|e| e.path == PathBuf::from("also_keep.rs")));
-        assert!(!result.entries.iter().any(|e| e.path == PathBuf::from("exclude.tmp")));
+        assert!(result.entries.iter().any(|e| e.path == Path::new("keep.txt")));
+        assert!(result.entries.iter().any(|e| e.path == Path::new("also_keep.rs")));
+        assert!(!result.entries.iter().any(|e| e.path == Path::new("exclude.tmp")));
 
         // Excluded file should be in skipped list
         assert!(result.skipped.iter().any(|s| s.reason.contains("Excluded")));
@@ -438,8 +996,8 @@ mod tests {
 
         // Should only have src and src/main.rs
         assert_eq!(result.entries.len(), 2);
-        assert!(result.entries.iter().any(|e| e.path == PathBuf::from("src")));
-        assert!(result.entries.iter().any(|e| e.path == PathBuf::from("src/main.rs") || e.path == PathBuf::from("src\\main.rs")));
+        assert!(result.entries.iter().any(|e| e.path == Path::new("src")));
+        assert!(result.entries.iter().any(|e| e.path == Path::new("src/main.rs") || e.path == Path::new("src\\main.rs")));
 
         // node_modules directory and its contents should not be in entries
         assert!(!result.entries.iter().any(|e| e.path.to_string_lossy().contains("node_modules")));
@@ -479,6 +1037,53 @@ mod tests {
         assert_eq!(result.entries[0].path, PathBuf::from("file.txt"));
     }
 
+    #[test]
+    fn test_scan_with_nested_ignore_files() {
+        let temp = create_test_dir();
+        let root = temp.path();
+        fs::create_dir_all(root.join("docs/sub")).unwrap();
+        fs::create_dir(root.join("other")).unwrap();
+        for file in [
+            "a.log",
+            "a.txt",
+            "docs/keep.log",
+            "docs/x.log",
+            "docs/y.pdf",
+            "docs/sub/z.pdf",
+            "other/y.pdf",
+        ] {
+            fs::write(root.join(file), "x").unwrap();
+        }
+        fs::write(root.join("docs/.rahzomignore"), "*.pdf\n!keep.log\n").unwrap();
+        fs::write(root.join("docs/sub/.rahzomignore"), "!z.pdf\n").unwrap();
+
+        let excl = Exclusions::from_patterns(&["*.log".to_string()]).unwrap();
+        let result = scan_with_exclusions(root, Some(&excl)).unwrap();
+        let files: Vec<&Path> = result
+            .entries
+            .iter()
+            .filter(|e| !e.is_dir)
+            .map(|e| e.path.as_path())
+            .collect();
+
+        // docs/ adds *.pdf to the root's *.log and takes keep.log back;
+        // docs/sub/ takes z.pdf back; other/ only sees the root's rules
+        assert_eq!(
+            files,
+            vec![
+                Path::new("a.txt"),
+                Path::new("docs/.rahzomignore"),
+                Path::new("docs/keep.log"),
+                Path::new("docs/sub/.rahzomignore"),
+                Path::new("docs/sub/z.pdf"),
+                Path::new("other/y.pdf"),
+            ]
+        );
+
+        // Without exclusions nothing is filtered, nested files included
+        assert_eq!(scan(root).unwrap().entries.len(), 12);
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_scan_skips_symlinks() {
@@ -503,6 +1108,50 @@ mod tests {
         assert!(result.skipped[0].reason.contains("Symlink"));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_scan_does_not_follow_link_to_ancestor() {
+        use std::os::unix::fs::symlink;
+
+        let temp = create_test_dir();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/file.txt"), "content").unwrap();
+        symlink(temp.path(), temp.path().join("sub/loop")).unwrap();
+
+        let options = ScanOptions {
+            include_reparse_points: true,
+            ..Default::default()
+        };
+        let result = scan_with_options(temp.path(), None, &options).unwrap();
+
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.skipped.len(), 1);
+        assert!(result.skipped[0].path.ends_with("sub/loop"));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_scan_skips_junctions() {
+        let temp = create_test_dir();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/file.txt"), "content").unwrap();
+
+        // Junction pointing back at the root
+        let status = std::process::Command::new("cmd")
+            .args(["/C", "mklink", "/J"])
+            .arg(temp.path().join("sub").join("loop"))
+            .arg(temp.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let result = scan(temp.path()).unwrap();
+
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.skipped.len(), 1);
+        assert!(result.skipped[0].reason.contains("Reparse point"));
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_scan_skips_broken_symlinks() {
@@ -527,6 +1176,93 @@ mod tests {
         assert!(result.skipped[0].reason.contains("Symlink"));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_scan_skips_special_files() {
+        use std::ffi::CString;
+        use std::os::unix::net::UnixListener;
+
+        let temp = create_test_dir();
+        fs::write(temp.path().join("regular.txt"), "content").unwrap();
+        let fifo = CString::new(temp.path().join("pipe").to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        let _socket = UnixListener::bind(temp.path().join("socket")).unwrap();
+
+        // Returning at all shows nothing tried to read the FIFO
+        let result = scan(temp.path()).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        let mut reasons: Vec<&str> = result.skipped.iter().map(|s| s.reason.as_str()).collect();
+        reasons.sort();
+        assert_eq!(
+            reasons,
+            [
+                "Named pipe (FIFO) (special file, not synced)",
+                "Socket (special file, not synced)"
+            ]
+        );
+
+        let options = ScanOptions {
+            recreate_fifos: true,
+            ..Default::default()
+        };
+        let result = scan_with_options(temp.path(), None, &options).unwrap();
+        let paths: Vec<&Path> = result.entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("pipe"), Path::new("regular.txt")]);
+        assert_eq!(result.skipped.len(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_scan_records_unreadable_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = create_test_dir();
+
+        fs::write(temp.path().join("visible.txt"), "visible").unwrap();
+        fs::create_dir(temp.path().join("locked")).unwrap();
+        fs::write(temp.path().join("locked/hidden.txt"), "hidden").unwrap();
+        fs::create_dir(temp.path().join("open")).unwrap();
+        fs::write(temp.path().join("open/file.txt"), "file").unwrap();
+
+        let locked = temp.path().join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Privileged users (e.g. root in CI containers) bypass permission checks
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let result = scan(temp.path());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        let result = result.unwrap();
+
+        assert!(result
+            .entries
+            .iter()
+            .any(|e| e.path == Path::new("visible.txt")));
+        assert!(result
+            .entries
+            .iter()
+            .any(|e| e.path == Path::new("open/file.txt")));
+        assert!(!result
+            .entries
+            .iter()
+            .any(|e| e.path == Path::new("locked/hidden.txt")));
+        assert!(result.skipped.iter().any(|s| s.path.ends_with("locked")));
+        assert_eq!(
+            result.unreadable_dirs().collect::<Vec<_>>(),
+            [Path::new("locked")]
+        );
+        assert_eq!(result.stats().unreadable_dirs, 1);
+
+        // An unreadable root fails the scan
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let result = scan(&locked);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(result.is_err());
+    }
+
     #[test]
     #[cfg(windows)]
     fn test_scan_handles_long_paths() {
@@ -564,4 +1300,79 @@ mod tests {
             .any(|e| e.path.to_string_lossy().contains("test.txt"));
         assert!(has_test_file, "Should find test.txt in deeply nested path");
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_one_filesystem_skips_other_devices() {
+        let temp = create_test_dir();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/file.txt"), "x").unwrap();
+
+        let root_dev = device_id(temp.path());
+        let dev = root_dev.unwrap();
+        // A directory on the root's device is kept, one on another device
+        // (a mount point) is skipped
+        assert!(!on_other_device(root_dev, dev));
+        assert!(on_other_device(root_dev, dev + 1));
+        assert!(!on_other_device(None, dev + 1));
+
+        // Everything on the root's filesystem is still scanned
+        let options = ScanOptions {
+            one_filesystem: true,
+            ..Default::default()
+        };
+        let result = scan_with_options(temp.path(), None, &options).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        assert!(result.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_age_filters_use_mtime() {
+        let temp = create_test_dir();
+        let day = 86_400;
+        let now = Utc::now().timestamp();
+        for (name, mtime) in [
+            ("old.txt", now - 30 * day),
+            ("week.txt", now - 7 * day),
+            ("new.txt", now - 60),
+            ("future.txt", now + 10 * day),
+        ] {
+            let path = temp.path().join(name);
+            fs::write(&path, name).unwrap();
+            filetime::set_file_mtime(&path, filetime::FileTime::from_unix_time(mtime, 0)).unwrap();
+        }
+        let scan_names = |min_days: Option<u64>, max_days: Option<u64>| {
+            let options = ScanOptions {
+                min_age: min_days.map(|d| Duration::from_secs(d * day as u64)),
+                max_age: max_days.map(|d| Duration::from_secs(d * day as u64)),
+                ..Default::default()
+            };
+            let result = scan_with_options(temp.path(), None, &options).unwrap();
+            let mut kept: Vec<_> = result
+                .entries
+                .iter()
+                .map(|e| e.path.to_string_lossy().into_owned())
+                .collect();
+            kept.sort();
+            let mut skipped: Vec<_> = result
+                .skipped_by_age()
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect();
+            skipped.sort();
+            (kept, skipped)
+        };
+
+        // Stable files only: the future mtime counts as brand new
+        let (kept, skipped) = scan_names(Some(3), None);
+        assert_eq!(kept, ["old.txt", "week.txt"]);
+        assert_eq!(skipped, ["future.txt", "new.txt"]);
+
+        // Recently changed files only
+        let (kept, skipped) = scan_names(None, Some(10));
+        assert_eq!(kept, ["future.txt", "new.txt", "week.txt"]);
+        assert_eq!(skipped, ["old.txt"]);
+
+        let (kept, _) = scan_names(Some(3), Some(10));
+        assert_eq!(kept, ["week.txt"]);
+    }
 }
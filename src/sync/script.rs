@@ -0,0 +1,362 @@
+//! Shell/batch script equivalent of a sync, for review or other tools
+//!
+//! [`DiffResult::to_script`] turns the actions into plain `cp`/`rm` (Unix)
+//! or `copy`/`del` (Windows) commands in the order the executor would run
+//! them. The script is lossy: it keeps no backups, uses no trash, doesn't
+//! verify copies and leaves the sync metadata alone, so running it is not
+//! the same as syncing. Unresolved conflicts and attribute-only updates are
+//! listed as comments.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::differ::{DiffResult, SyncAction};
+use super::executor::action_order;
+
+/// Which shell a script is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptPlatform {
+    /// POSIX `sh`
+    Unix,
+    /// `cmd.exe` batch file
+    Windows,
+}
+
+impl ScriptPlatform {
+    /// The platform rahzom runs on
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Self::Unix => Self::Windows,
+            Self::Windows => Self::Unix,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unix => "Unix shell",
+            Self::Windows => "Windows batch",
+        }
+    }
+
+    /// File extension of a script, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Unix => "sh",
+            Self::Windows => "bat",
+        }
+    }
+
+    fn comment(self) -> &'static str {
+        match self {
+            Self::Unix => "#",
+            Self::Windows => "REM",
+        }
+    }
+
+    /// `path` as one shell word, or `None` if it can't be written as one
+    fn quote(self, path: &str) -> Option<String> {
+        match self {
+            Self::Unix => Some(format!("'{}'", path.replace('\'', r"'\''"))),
+            // Windows paths can't contain quotes; `%` would start a variable;
+            // a batch command can't span lines
+            Self::Windows if path.contains('"') || path.chars().any(char::is_control) => None,
+            Self::Windows => Some(format!(
+                "\"{}\"",
+                path.replace('/', "\\").replace('%', "%%")
+            )),
+        }
+    }
+}
+
+impl DiffResult {
+    /// Writes the actions as a script for `platform`, with `left` and `right`
+    /// as the roots the relative action paths are joined to
+    pub fn to_script(&self, platform: ScriptPlatform, left: &Path, right: &Path) -> String {
+        let rem = platform.comment();
+        let mut lines = match platform {
+            ScriptPlatform::Unix => vec!["#!/bin/sh".to_string()],
+            ScriptPlatform::Windows => vec!["@echo off".to_string()],
+        };
+        lines.push(format!(
+            "{} Generated by rahzom: {} <-> {}",
+            rem,
+            printable(&left.display().to_string()),
+            printable(&right.display().to_string())
+        ));
+        for note in [
+            "LOSSY: this is not a sync. These commands only copy and delete:",
+            "no backups, no trash, no verification of copies, and the sync state",
+            "is not updated. Review before running.",
+        ] {
+            lines.push(format!("{} {}", rem, note));
+        }
+        if platform == ScriptPlatform::Unix {
+            lines.push("set -e".to_string());
+        }
+        lines.push(String::new());
+
+        let mut actions: Vec<&SyncAction> = self.actions.iter().collect();
+        actions.sort_by_cached_key(|a| action_order(a));
+
+        // Directories already created, so each is made only once
+        let mut made: HashSet<String> = HashSet::new();
+        for action in actions {
+            // Side the action writes to
+            let target = match action {
+                SyncAction::CopyToRight { .. }
+                | SyncAction::CreateDirRight { .. }
+                | SyncAction::DeleteRight { .. } => right,
+                _ => left,
+            };
+            let relative = action.path();
+            let commands = match action {
+                SyncAction::CopyToRight { .. } | SyncAction::CopyToLeft { .. } => {
+                    let from = match action {
+                        SyncAction::CopyToRight { .. } => left,
+                        _ => right,
+                    };
+                    let src = from.join(relative).to_string_lossy().to_string();
+                    let dst = target.join(relative).to_string_lossy().to_string();
+                    let parent = relative
+                        .parent()
+                        .filter(|p| !p.as_os_str().is_empty())
+                        .map(|p| target.join(p).to_string_lossy().to_string());
+                    let mut commands = Vec::new();
+                    if let Some(parent) = parent {
+                        if made.insert(parent.clone()) {
+                            commands.push(mkdir(platform, &parent));
+                        }
+                    }
+                    commands.push(copy(platform, &src, &dst));
+                    commands.into_iter().collect::<Option<Vec<_>>>()
+                }
+                // The right file is saved under the copy name on both sides
+                // before the left one replaces it
+                SyncAction::KeepBoth { copy: saved, .. } => {
+                    let file = |root: &Path, relative: &Path| {
+                        root.join(relative).to_string_lossy().to_string()
+                    };
+                    [
+                        copy(platform, &file(right, relative), &file(left, saved)),
+                        copy(platform, &file(right, relative), &file(right, saved)),
+                        copy(platform, &file(left, relative), &file(right, relative)),
+                    ]
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                }
+                SyncAction::CreateDirRight { .. } | SyncAction::CreateDirLeft { .. } => {
+                    let dir = target.join(relative).to_string_lossy().to_string();
+                    made.insert(dir.clone());
+                    mkdir(platform, &dir).map(|c| vec![c])
+                }
+                SyncAction::DeleteRight { .. } | SyncAction::DeleteLeft { .. } => {
+                    let path = target.join(relative).to_string_lossy().to_string();
+                    delete(platform, &path).map(|c| vec![c])
+                }
+                // No portable command copies another file's attributes
+                SyncAction::UpdateAttributesRight { .. }
+                | SyncAction::UpdateAttributesLeft { .. } => Some(vec![format!(
+                    "{} Attributes only, not synced: {}",
+                    rem,
+                    printable(&relative.display().to_string())
+                )]),
+                SyncAction::Conflict { reason, .. } => Some(vec![format!(
+                    "{} Conflict, not synced: {} ({:?})",
+                    rem,
+                    printable(&relative.display().to_string()),
+                    reason
+                )]),
+                SyncAction::Skip { .. } => Some(Vec::new()),
+            };
+            match commands {
+                Some(commands) => lines.extend(commands),
+                None => lines.push(format!(
+                    "{} Can't be written for this shell, not synced: {}",
+                    rem,
+                    printable(&relative.display().to_string())
+                )),
+            }
+        }
+
+        match platform {
+            ScriptPlatform::Unix => lines.join("\n") + "\n",
+            ScriptPlatform::Windows => lines.join("\r\n") + "\r\n",
+        }
+    }
+}
+
+/// `text` with control characters escaped (`\n`, `\u{1b}`), so a name
+/// can't end a comment line and start a command
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn mkdir(platform: ScriptPlatform, dir: &str) -> Option<String> {
+    let dir = platform.quote(dir)?;
+    Some(match platform {
+        ScriptPlatform::Unix => format!("mkdir -p -- {}", dir),
+        ScriptPlatform::Windows => format!("if not exist {0} mkdir {0}", dir),
+    })
+}
+
+fn copy(platform: ScriptPlatform, src: &str, dst: &str) -> Option<String> {
+    let (src, dst) = (platform.quote(src)?, platform.quote(dst)?);
+    Some(match platform {
+        ScriptPlatform::Unix => format!("cp -p -- {} {}", src, dst),
+        ScriptPlatform::Windows => format!("copy /y {} {}", src, dst),
+    })
+}
+
+/// Deletes a file, or a directory the deletes before it emptied
+fn delete(platform: ScriptPlatform, path: &str) -> Option<String> {
+    Some(match platform {
+        ScriptPlatform::Unix => {
+            let path = platform.quote(path)?;
+            format!(
+                "if [ -d {0} ]; then rmdir -- {0}; else rm -f -- {0}; fi",
+                path
+            )
+        }
+        ScriptPlatform::Windows => {
+            let contents = platform.quote(&format!("{}\\*", path))?;
+            let path = platform.quote(path)?;
+            format!(
+                "if exist {} (rmdir {}) else (del /f /q {})",
+                contents, path, path
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::differ::ConflictReason;
+    use std::path::PathBuf;
+
+    fn diff(actions: Vec<SyncAction>) -> DiffResult {
+        DiffResult {
+            actions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unix_script_quotes_and_orders() {
+        let result = diff(vec![
+            SyncAction::DeleteLeft {
+                path: PathBuf::from("old"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("my docs/it's.txt"),
+                size: 3,
+            },
+            SyncAction::CopyToLeft {
+                path: PathBuf::from("b.txt"),
+                size: 1,
+            },
+            SyncAction::Skip {
+                path: PathBuf::from("same.txt"),
+                reason: "identical".to_string(),
+            },
+        ]);
+        let script = result.to_script(ScriptPlatform::Unix, Path::new("/l"), Path::new("/r b"));
+        let body: Vec<&str> = script.lines().skip_while(|l| *l != "set -e").collect();
+        assert_eq!(
+            body,
+            [
+                "set -e",
+                "",
+                "cp -p -- '/r b/b.txt' '/l/b.txt'",
+                "mkdir -p -- '/r b/my docs'",
+                r"cp -p -- '/l/my docs/it'\''s.txt' '/r b/my docs/it'\''s.txt'",
+                "if [ -d '/l/old' ]; then rmdir -- '/l/old'; else rm -f -- '/l/old'; fi",
+            ]
+        );
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("LOSSY"));
+    }
+
+    #[test]
+    fn test_windows_script() {
+        let result = diff(vec![
+            SyncAction::CreateDirRight {
+                path: PathBuf::from("new dir"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("new dir/100%.txt"),
+                size: 3,
+            },
+            SyncAction::DeleteRight {
+                path: PathBuf::from("gone.txt"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("bad\"name"),
+                size: 3,
+            },
+        ]);
+        let script = result.to_script(
+            ScriptPlatform::Windows,
+            Path::new("C:/left"),
+            Path::new("D:/right"),
+        );
+        let lines: Vec<&str> = script.split("\r\n").collect();
+        assert_eq!(lines[0], "@echo off");
+        assert!(lines.contains(&r#"if not exist "D:\right\new dir" mkdir "D:\right\new dir""#));
+        assert!(
+            lines.contains(&r#"copy /y "C:\left\new dir\100%%.txt" "D:\right\new dir\100%%.txt""#)
+        );
+        assert!(lines.contains(
+            &r#"if exist "D:\right\gone.txt\*" (rmdir "D:\right\gone.txt") else (del /f /q "D:\right\gone.txt")"#
+        ));
+        assert!(lines.contains(&"REM Can't be written for this shell, not synced: bad\"name"));
+        // The directory is not made twice for the copy into it
+        assert_eq!(lines.iter().filter(|l| l.contains("mkdir")).count(), 1);
+    }
+
+    #[test]
+    fn test_names_cannot_break_out_of_comments() {
+        let result = diff(vec![
+            SyncAction::Conflict {
+                path: PathBuf::from("a\nrm -rf ~"),
+                reason: ConflictReason::BothModified,
+                left: None,
+                right: None,
+            },
+            SyncAction::UpdateAttributesRight {
+                path: PathBuf::from("b\nrm -rf ~"),
+            },
+            SyncAction::CopyToRight {
+                path: PathBuf::from("c\r\nrm -rf ~"),
+                size: 1,
+            },
+        ]);
+
+        let script = result.to_script(ScriptPlatform::Unix, Path::new("/l"), Path::new("/r"));
+        assert!(script.contains(r"# Conflict, not synced: a\nrm -rf ~"));
+        assert!(script.contains(r"# Attributes only, not synced: b\nrm -rf ~"));
+
+        // A batch command can't hold the line break at all
+        let script = result.to_script(ScriptPlatform::Windows, Path::new("/l"), Path::new("/r"));
+        assert!(script.contains(r"REM Conflict, not synced: a\nrm -rf ~"));
+        assert!(script.contains(r"REM Attributes only, not synced: b\nrm -rf ~"));
+        assert!(script.contains(r"REM Can't be written for this shell, not synced: c\r\nrm -rf ~"));
+        assert!(!script.lines().any(|l| l.starts_with("rm ")));
+    }
+}
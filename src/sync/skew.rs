@@ -0,0 +1,194 @@
+//! Clock skew between the two sides.
+//!
+//! When the sides are written by machines whose clocks disagree, or by a
+//! filesystem with a wrong time zone, files that are the same on both sides
+//! show mtimes a fixed amount apart. Every edit then looks newer on one side
+//! than it is, and conflicts resolve in the wrong direction. This estimates
+//! such an offset from files that are present and equal-sized on both sides.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::Duration;
+
+use super::scanner::{FileEntry, ScanResult};
+
+/// Fewest file pairs an estimate is made from
+pub const MIN_SAMPLES: usize = 10;
+
+/// Share of the pairs that must agree on the offset for it to be systematic
+const AGREEMENT: f64 = 0.75;
+
+/// How far apart two pairs' offsets may be and still agree, in seconds.
+/// Covers FAT's 2-second mtime resolution.
+const AGREEMENT_SECS: i64 = 2;
+
+/// Offsets below this are within what filesystems and copies introduce
+pub const THRESHOLD_SECS: i64 = 60;
+
+/// A systematic difference between the mtimes of the two sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkew {
+    /// Right mtime minus left mtime of the same file
+    pub offset: Duration,
+    /// File pairs the offset was estimated from
+    pub samples: usize,
+}
+
+impl ClockSkew {
+    /// Whether the offset is large enough to mislead the diff
+    pub fn is_significant(&self) -> bool {
+        self.offset.num_seconds().abs() >= THRESHOLD_SECS
+    }
+
+    /// E.g. "the right side is 1h 5m ahead of the left"
+    pub fn describe(&self) -> String {
+        let secs = self.offset.num_seconds();
+        let (side, other) = if secs >= 0 {
+            ("right", "left")
+        } else {
+            ("left", "right")
+        };
+        format!(
+            "the {} side is {} ahead of the {}",
+            side,
+            format_offset(secs.unsigned_abs()),
+            other
+        )
+    }
+}
+
+/// Estimates the systematic offset between the mtimes of files present with
+/// the same size on both sides. `None` if there are too few such files or
+/// they don't agree on one offset, e.g. because most were simply edited.
+pub fn estimate_clock_skew(left: &ScanResult, right: &ScanResult) -> Option<ClockSkew> {
+    let right_files: HashMap<&Path, &FileEntry> = right
+        .entries
+        .iter()
+        .filter(|e| comparable(e))
+        .map(|e| (e.path.as_path(), e))
+        .collect();
+
+    let mut offsets: Vec<i64> = left
+        .entries
+        .iter()
+        .filter(|e| comparable(e))
+        .filter_map(|l| {
+            let r = right_files.get(l.path.as_path())?;
+            (r.size == l.size).then(|| (r.mtime - l.mtime).num_seconds())
+        })
+        .collect();
+    if offsets.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    offsets.sort_unstable();
+    let median = offsets[offsets.len() / 2];
+    let agreeing = offsets
+        .iter()
+        .filter(|&&offset| (offset - median).abs() <= AGREEMENT_SECS)
+        .count();
+    if (agreeing as f64) < offsets.len() as f64 * AGREEMENT {
+        return None;
+    }
+    Some(ClockSkew {
+        offset: Duration::seconds(median),
+        samples: offsets.len(),
+    })
+}
+
+/// Non-empty files with a known mtime; empty files carry no evidence of
+/// being the same file
+fn comparable(entry: &FileEntry) -> bool {
+    !entry.is_dir && entry.mtime_known && entry.size > 0
+}
+
+/// "45s", "5m 3s", "2h 10m" or "3d 4h"
+fn format_offset(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::metadata::FileAttributes;
+    use chrono::{DateTime, Utc};
+    use std::path::PathBuf;
+
+    fn file(path: &str, size: u64, mtime: DateTime<Utc>) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            size,
+            mtime,
+            mtime_known: true,
+            is_dir: false,
+            hash: None,
+            attributes: FileAttributes::default(),
+            file_id: None,
+        }
+    }
+
+    fn scan(entries: Vec<FileEntry>) -> ScanResult {
+        ScanResult {
+            root: PathBuf::from("/root"),
+            entries,
+            scan_time: Utc::now(),
+            skipped: Vec::new(),
+            partials: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_clock_skew() {
+        let base = Utc::now();
+        let hour = Duration::hours(1);
+        let left: Vec<FileEntry> = (0..20)
+            .map(|i| file(&format!("f{i}"), 100, base - Duration::days(i)))
+            .collect();
+        // Right is an hour ahead, give or take FAT rounding; two files were edited
+        let mut right: Vec<FileEntry> = left
+            .iter()
+            .map(|e| file(&e.path.to_string_lossy(), 100, e.mtime + hour))
+            .collect();
+        right[0].mtime += Duration::seconds(1);
+        right[1].mtime = base;
+        right[2].mtime = base - Duration::days(30);
+
+        let skew = estimate_clock_skew(&scan(left.clone()), &scan(right.clone())).unwrap();
+        assert_eq!(skew.offset, hour);
+        assert_eq!(skew.samples, 20);
+        assert!(skew.is_significant());
+        assert_eq!(skew.describe(), "the right side is 1h 0m ahead of the left");
+
+        // Synced files agree on no offset at all
+        let skew = estimate_clock_skew(&scan(left.clone()), &scan(left.clone())).unwrap();
+        assert!(!skew.is_significant());
+
+        // Offsets all over the place are edits, not a clock
+        for (i, entry) in right.iter_mut().enumerate() {
+            entry.mtime = base + Duration::minutes(i as i64 * 7);
+        }
+        assert_eq!(estimate_clock_skew(&scan(left.clone()), &scan(right)), None);
+
+        // Too few pairs to tell
+        let few = scan(left[..MIN_SAMPLES - 1].to_vec());
+        assert_eq!(estimate_clock_skew(&few, &few), None);
+    }
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!(format_offset(45), "45s");
+        assert_eq!(format_offset(303), "5m 3s");
+        assert_eq!(format_offset(7_800), "2h 10m");
+        assert_eq!(format_offset(273_600), "3d 4h");
+    }
+}
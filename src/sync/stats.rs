@@ -0,0 +1,203 @@
+//! History of a project's syncs, `.rahzom/history.jsonl` on the left side.
+//!
+//! Each finished sync appends one JSON line with its counts, bytes and
+//! duration, so trends (a backup growing, failures creeping in) can be seen
+//! over time. Appending is cheap and reading only parses the tail of the
+//! file. Once the file passes [`MAX_HISTORY_BYTES`] it is moved aside to
+//! `history.1.jsonl`, replacing the previous one, and a new file is started.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::metadata::SyncMetadata;
+
+/// History file inside the left side's `.rahzom/`
+pub const HISTORY_FILE: &str = "history.jsonl";
+/// Previous history file, moved aside by rotation
+pub const ROTATED_HISTORY_FILE: &str = "history.1.jsonl";
+
+/// Size past which the history is rotated
+pub const MAX_HISTORY_BYTES: u64 = 1024 * 1024;
+
+/// Bytes read from the end of a history file, enough for a few hundred runs
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// One finished sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncRun {
+    pub finished: DateTime<Utc>,
+    pub cancelled: bool,
+    /// Actions completed, failed and skipped
+    pub completed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub bytes_transferred: u64,
+    pub duration_secs: f64,
+}
+
+impl SyncRun {
+    /// A run finishing now
+    pub fn new(
+        cancelled: bool,
+        completed: usize,
+        failed: usize,
+        skipped: usize,
+        bytes_transferred: u64,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            finished: Utc::now(),
+            cancelled,
+            completed,
+            failed,
+            skipped,
+            bytes_transferred,
+            duration_secs: duration.as_secs_f64(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration_secs.max(0.0))
+    }
+}
+
+fn history_path(root: &Path, file: &str) -> PathBuf {
+    SyncMetadata::metadata_dir_path(root).join(file)
+}
+
+/// Appends `run` to the history of the project whose left side is `root`,
+/// rotating the file first if it grew too large
+pub fn append(root: &Path, run: &SyncRun) -> Result<()> {
+    let path = history_path(root, HISTORY_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_HISTORY_BYTES) {
+        fs::rename(&path, history_path(root, ROTATED_HISTORY_FILE))
+            .with_context(|| format!("Failed to rotate {:?}", path))?;
+    }
+
+    let mut line = serde_json::to_string(run)?;
+    line.push('\n');
+    append_line(&path, &line).with_context(|| format!("Failed to write {:?}", path))
+}
+
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .create(true)
+        .append(true)
+        .open(path)?;
+    // Don't run on from a line a crash cut off
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+    file.write_all(line.as_bytes())
+}
+
+/// The last `limit` runs of the project whose left side is `root`, newest
+/// first. Lines that don't parse, e.g. cut off by a crash, are skipped.
+pub fn load_recent(root: &Path, limit: usize) -> Result<Vec<SyncRun>> {
+    let mut runs = read_tail(&history_path(root, HISTORY_FILE))?;
+    if runs.len() < limit {
+        let mut older = read_tail(&history_path(root, ROTATED_HISTORY_FILE))?;
+        older.append(&mut runs);
+        runs = older;
+    }
+    runs.reverse();
+    runs.truncate(limit);
+    Ok(runs)
+}
+
+/// Runs in the last [`TAIL_BYTES`] of `path`, oldest first; none if the
+/// file doesn't exist
+fn read_tail(path: &Path) -> Result<Vec<SyncRun>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {:?}", path)),
+    };
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = text.lines();
+    if start > 0 {
+        // Starts mid-line
+        lines.next();
+    }
+    Ok(lines
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn run(completed: usize) -> SyncRun {
+        SyncRun::new(false, completed, 0, 0, 100, Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_append_and_load_recent() {
+        let root = TempDir::new().unwrap();
+        assert!(load_recent(root.path(), 10).unwrap().is_empty());
+
+        for completed in 1..=3 {
+            append(root.path(), &run(completed)).unwrap();
+        }
+        // A torn line doesn't hide the others
+        let path = history_path(root.path(), HISTORY_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"finished\":").unwrap();
+        drop(file);
+        append(root.path(), &run(4)).unwrap();
+
+        let recent = load_recent(root.path(), 2).unwrap();
+        let counts: Vec<usize> = recent.iter().map(|r| r.completed).collect();
+        assert_eq!(counts, [4, 3]);
+        assert_eq!(recent[0].duration(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_rotation_keeps_recent_runs() {
+        let root = TempDir::new().unwrap();
+        append(root.path(), &run(1)).unwrap();
+        let path = history_path(root.path(), HISTORY_FILE);
+        // Pad the file past the limit
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&vec![b' '; MAX_HISTORY_BYTES as usize])
+            .unwrap();
+        file.write_all(b"\n").unwrap();
+        drop(file);
+        append(root.path(), &run(1)).unwrap();
+        append(root.path(), &run(2)).unwrap();
+
+        assert!(fs::metadata(&path).unwrap().len() < 1024);
+        assert!(history_path(root.path(), ROTATED_HISTORY_FILE).exists());
+        let counts: Vec<usize> = load_recent(root.path(), 3)
+            .unwrap()
+            .iter()
+            .map(|r| r.completed)
+            .collect();
+        // The rotated file's first run is beyond its tail
+        assert_eq!(counts, [2, 1]);
+    }
+}
@@ -0,0 +1,259 @@
+//! Trash of soft-deleted files.
+//!
+//! Each sync moves what it deletes into a session of its own,
+//! `.rahzom/_trash/<session>/`, under the path the item had below the root,
+//! so everything one sync deleted can be put back in one go. The session id
+//! is the time the sync started. Older versions trashed items flat, as
+//! `_trash/<name>.<timestamp>`; those are still listed, but only by name.
+//!
+//! A side can send deletions to the OS recycle bin instead (see
+//! [`move_to_system_trash`]). Those are restored with the OS's tools, and
+//! not always to their exact original path.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use super::executor::VERSION_TIMESTAMP_FORMAT;
+use super::metadata::SyncMetadata;
+
+/// Trash directory inside each side's `.rahzom/`
+pub const TRASH_DIR: &str = "_trash";
+
+/// An item in the trash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashItem {
+    /// Path the item had relative to the root; only its name for flat entries
+    pub path: PathBuf,
+    /// Where the item is in the trash
+    pub trashed: PathBuf,
+    /// When it was deleted: the start of its sync, or the flat entry's suffix
+    pub deleted: DateTime<Utc>,
+    /// Session the item belongs to; `None` for flat entries
+    pub session: Option<String>,
+}
+
+/// Path of the trash directory of the side at `root`
+pub fn trash_dir(root: &Path) -> PathBuf {
+    SyncMetadata::metadata_dir_path(root).join(TRASH_DIR)
+}
+
+/// Id of a session starting now
+pub fn new_session_id() -> String {
+    Utc::now().format(VERSION_TIMESTAMP_FORMAT).to_string()
+}
+
+/// Moves `path`, below `root`, into `session`. Returns where it ended up.
+///
+/// A directory whose path is already in the session had its contents
+/// trashed before it, so it is only removed. A file whose path is already
+/// there gets a timestamp suffix rather than replacing it.
+pub fn move_to_trash(root: &Path, path: &Path, session: &str) -> io::Result<PathBuf> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let mut target = trash_dir(root).join(session).join(relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if target.exists() {
+        if path.is_dir() && target.is_dir() && fs::remove_dir(path).is_ok() {
+            return Ok(target);
+        }
+        let mut name = target.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}", Utc::now().format(VERSION_TIMESTAMP_FORMAT)));
+        target.set_file_name(name);
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Moves `path` to the OS recycle bin/trash. Fails on filesystems without
+/// one (e.g. many network drives), and in builds without the
+/// `system-trash` feature.
+pub fn move_to_system_trash(path: &Path) -> io::Result<()> {
+    #[cfg(feature = "system-trash")]
+    {
+        ::trash::delete(path).map_err(|e| io::Error::other(e.to_string()))
+    }
+    #[cfg(not(feature = "system-trash"))]
+    {
+        let _ = path;
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "built without the system-trash feature",
+        ))
+    }
+}
+
+/// Lists the trash of the side at `root`, oldest first. An empty list if
+/// nothing was trashed yet.
+pub fn list_trash(root: &Path) -> Result<Vec<TrashItem>> {
+    let dir = trash_dir(root);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut items = Vec::new();
+    for entry in entries {
+        let entry = entry.with_context(|| format!("Failed to read trash: {:?}", dir))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(deleted) = parse_timestamp(&name).filter(|_| entry.path().is_dir()) {
+            for path in session_items(&entry.path())? {
+                items.push(TrashItem {
+                    trashed: entry.path().join(&path),
+                    path,
+                    deleted,
+                    session: Some(name.clone()),
+                });
+            }
+        } else if let Some((original, deleted)) = name
+            .rsplit_once('.')
+            .and_then(|(original, suffix)| Some((original, parse_timestamp(suffix)?)))
+        {
+            items.push(TrashItem {
+                path: PathBuf::from(original),
+                trashed: entry.path(),
+                deleted,
+                session: None,
+            });
+        }
+    }
+    items.sort_by(|a, b| a.deleted.cmp(&b.deleted).then_with(|| a.path.cmp(&b.path)));
+    Ok(items)
+}
+
+/// Moves everything in `session` back to where it was below `root` and
+/// removes the session. Fails without moving anything if one of its paths
+/// has been taken since. Returns the number of items restored.
+pub fn restore_trash_session(root: &Path, session_id: &str) -> Result<usize> {
+    let session_dir = trash_dir(root).join(session_id);
+    if parse_timestamp(session_id).is_none() || !session_dir.is_dir() {
+        bail!("No trash session {}", session_id);
+    }
+
+    let items = session_items(&session_dir)?;
+    if let Some(taken) = items
+        .iter()
+        .find(|path| !session_dir.join(path).is_dir() && root.join(path).exists())
+    {
+        bail!(
+            "Cannot restore trash session {}: {} exists again",
+            session_id,
+            taken.display()
+        );
+    }
+
+    for path in &items {
+        let (from, to) = (session_dir.join(path), root.join(path));
+        if from.is_dir() {
+            fs::create_dir_all(&to)
+                .with_context(|| format!("Failed to create directory: {:?}", to))?;
+            continue;
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        fs::rename(&from, &to).with_context(|| format!("Failed to restore {:?}", to))?;
+    }
+    fs::remove_dir_all(&session_dir)
+        .with_context(|| format!("Failed to remove trash session: {:?}", session_dir))?;
+    Ok(items.len())
+}
+
+/// Files and empty directories of a session, relative to it
+fn session_items(session_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut items = Vec::new();
+    for entry in walkdir::WalkDir::new(session_dir)
+        .min_depth(1)
+        .sort_by_file_name()
+    {
+        let entry =
+            entry.with_context(|| format!("Failed to read trash session: {:?}", session_dir))?;
+        let is_empty_dir = entry.file_type().is_dir()
+            && fs::read_dir(entry.path()).is_ok_and(|mut d| d.next().is_none());
+        if !entry.file_type().is_dir() || is_empty_dir {
+            let path = entry
+                .path()
+                .strip_prefix(session_dir)
+                .unwrap_or(entry.path());
+            items.push(path.to_path_buf());
+        }
+    }
+    Ok(items)
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, VERSION_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_session_keeps_structure_and_restores() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("photos/2024")).unwrap();
+        fs::write(root.join("photos/2024/a.jpg"), "a").unwrap();
+        fs::write(root.join("photos/b.jpg"), "b").unwrap();
+        fs::write(root.join("top.txt"), "top").unwrap();
+        // A file from before sessions
+        fs::create_dir_all(trash_dir(root)).unwrap();
+        fs::write(trash_dir(root).join("old.txt.20230101_120000_000"), "old").unwrap();
+
+        // Deleted the way a sync does: contents first, then their directories
+        let session = new_session_id();
+        for path in [
+            "photos/2024/a.jpg",
+            "photos/b.jpg",
+            "photos/2024",
+            "photos",
+            "top.txt",
+        ] {
+            move_to_trash(root, &root.join(path), &session).unwrap();
+        }
+        assert!(!root.join("photos").exists());
+        assert_eq!(
+            fs::read_to_string(trash_dir(root).join(&session).join("photos/2024/a.jpg")).unwrap(),
+            "a"
+        );
+
+        let items = list_trash(root).unwrap();
+        let paths: Vec<(&Path, Option<&str>)> = items
+            .iter()
+            .map(|item| (item.path.as_path(), item.session.as_deref()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (Path::new("old.txt"), None),
+                (Path::new("photos/2024/a.jpg"), Some(session.as_str())),
+                (Path::new("photos/b.jpg"), Some(session.as_str())),
+                (Path::new("top.txt"), Some(session.as_str())),
+            ]
+        );
+
+        // Restore refuses to overwrite a path taken since
+        fs::write(root.join("top.txt"), "new").unwrap();
+        assert!(restore_trash_session(root, &session).is_err());
+        assert!(!root.join("photos").exists());
+        fs::remove_file(root.join("top.txt")).unwrap();
+
+        assert_eq!(restore_trash_session(root, &session).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(root.join("photos/2024/a.jpg")).unwrap(),
+            "a"
+        );
+        assert_eq!(fs::read_to_string(root.join("photos/b.jpg")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(root.join("top.txt")).unwrap(), "top");
+        assert_eq!(list_trash(root).unwrap().len(), 1);
+        assert!(restore_trash_session(root, &session).is_err());
+    }
+}
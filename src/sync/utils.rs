@@ -1,6 +1,13 @@
 //! Shared utilities for sync module
 
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+pub use crate::config::project::{is_within_metadata_dir, METADATA_DIR_NAME};
 
 /// FAT32 filesystem has 2-second mtime precision.
 /// We use this tolerance when comparing file modification times.
@@ -11,10 +18,271 @@ pub fn times_equal_with_tolerance(t1: DateTime<Utc>, t2: DateTime<Utc>) -> bool
     (t1 - t2).num_seconds().abs() <= FAT32_TOLERANCE_SECS
 }
 
+/// Converts a file time to UTC. The only conversion the scanner, executor
+/// and metadata use, so all mtimes compare alike: it works on offsets from
+/// the epoch and never consults the local timezone. Times before 1970 are
+/// kept; ones chrono can't represent become now.
+pub fn system_time_to_utc(time: SystemTime) -> DateTime<Utc> {
+    let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+        Err(e) => {
+            let before = e.duration();
+            let secs = -(before.as_secs() as i64);
+            match before.subsec_nanos() {
+                0 => (secs, 0),
+                nanos => (secs - 1, 1_000_000_000 - nanos),
+            }
+        }
+    };
+    DateTime::from_timestamp(secs, nanos).unwrap_or_else(Utc::now)
+}
+
+/// Converts a UTC time back to a file time, the inverse of
+/// [`system_time_to_utc`]
+pub fn utc_to_system_time(time: DateTime<Utc>) -> SystemTime {
+    time.into()
+}
+
+/// Suffix of the partial file a copy is written to before replacing the destination
+pub const TEMP_FILE_SUFFIX: &str = ".rahzom-tmp";
+
+/// Longest file name, in bytes, most filesystems take
+const NAME_MAX: usize = 255;
+
+/// Partial file used while copying to `path`, next to it in the same
+/// directory: `{name}.{tag}.rahzom-tmp`, the tag being the first 8 hex digits
+/// of the name's hash. When that doesn't fit in [`NAME_MAX`], it is the first
+/// 16 hex digits alone: `{hash}.rahzom-tmp`.
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default();
+    let hash = name_hash(name.as_encoded_bytes());
+    let mut temp = name.to_os_string();
+    temp.push(format!(".{}{}", &hash[..8], TEMP_FILE_SUFFIX));
+    if temp.len() > NAME_MAX {
+        temp = format!("{}{}", &hash[..16], TEMP_FILE_SUFFIX).into();
+    }
+    path.with_file_name(temp)
+}
+
+/// Checks whether `path` is a partial copy left by the executor, i.e. has
+/// one of the names [`temp_path_for`] gives. A user's own file that merely
+/// ends in the suffix is not one.
+pub fn is_temp_file(path: &Path) -> bool {
+    let Some(stem) = path.file_name().and_then(|n| {
+        n.as_encoded_bytes()
+            .strip_suffix(TEMP_FILE_SUFFIX.as_bytes())
+    }) else {
+        return false;
+    };
+    if stem.len() == 16 && stem.iter().all(is_hex_digit) {
+        return true;
+    }
+    match stem.len().checked_sub(9).map(|at| stem.split_at(at)) {
+        Some((name, tag)) if !name.is_empty() && tag[0] == b'.' => {
+            tag[1..] == name_hash(name).as_bytes()[..8]
+        }
+        _ => false,
+    }
+}
+
+/// Lowercase hex SHA-256 of a file name
+fn name_hash(name: &[u8]) -> String {
+    Sha256::digest(name)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn is_hex_digit(byte: &u8) -> bool {
+    byte.is_ascii_digit() || (b'a'..=b'f').contains(byte)
+}
+
+/// Starts the key of a path that isn't valid UTF-8. No file name contains
+/// NUL, so no UTF-8 path's key starts with it.
+const RAW_KEY_PREFIX: char = '\0';
+
+/// The string `path` is recorded under in metadata, manifests and the scan
+/// cache: the path itself when it is valid UTF-8, otherwise
+/// [`RAW_KEY_PREFIX`] and the hex of its raw name. Unlike `to_string_lossy`,
+/// two paths never share a key. [`key_path`] turns it back.
+pub fn path_key(path: &Path) -> String {
+    match path.to_str() {
+        Some(key) => key.to_string(),
+        None => format!("{}{}", RAW_KEY_PREFIX, encode_raw(path.as_os_str())),
+    }
+}
+
+/// The path recorded under `key` (see [`path_key`])
+pub fn key_path(key: &str) -> PathBuf {
+    key.strip_prefix(RAW_KEY_PREFIX)
+        .and_then(decode_raw)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(key))
+}
+
+#[cfg(unix)]
+fn encode_raw(name: &OsStr) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    name.as_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(unix)]
+fn decode_raw(hex: &str) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(OsString::from_vec(bytes))
+}
+
+/// Windows names are UTF-16, possibly with unpaired surrogates
+#[cfg(windows)]
+fn encode_raw(name: &OsStr) -> String {
+    use std::os::windows::ffi::OsStrExt;
+    name.encode_wide()
+        .map(|unit| format!("{:04x}", unit))
+        .collect()
+}
+
+#[cfg(windows)]
+fn decode_raw(hex: &str) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    let units = (0..hex.len())
+        .step_by(4)
+        .map(|i| u16::from_str_radix(hex.get(i..i + 4)?, 16).ok())
+        .collect::<Option<Vec<u16>>>()?;
+    Some(OsString::from_wide(&units))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn encode_raw(name: &OsStr) -> String {
+    name.to_string_lossy().into_owned()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn decode_raw(key: &str) -> Option<OsString> {
+    Some(OsString::from(key))
+}
+
+/// Serde for a path stored as its [`path_key`], for `#[serde(with)]`. Reads
+/// what serde's own `PathBuf` support writes, which fails on names that
+/// aren't UTF-8.
+pub mod serde_path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::path_key(path))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        String::deserialize(deserializer).map(|key| super::key_path(&key))
+    }
+}
+
+/// [`serde_path`] for a list of paths
+pub mod serde_paths {
+    use std::path::PathBuf;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(paths: &[PathBuf], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(paths.iter().map(|path| super::path_key(path)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<PathBuf>, D::Error> {
+        let keys = Vec::<String>::deserialize(deserializer)?;
+        Ok(keys.iter().map(|key| super::key_path(key)).collect())
+    }
+}
+
+/// Returns this machine's hostname, or "unknown" if it can't be determined
+pub fn local_hostname() -> String {
+    hostname::get()
+        .map(|h| h.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Builds the file name for the copy a conflict keeps of `file_name` when
+/// both files are kept. Placeholders: `{name}` (stem), `{ext}` (extension with
+/// dot, or empty), `{host}`, `{timestamp}` and `{counter}`.
+/// `exists` is consulted to avoid collisions; if the template has no
+/// `{counter}`, a ` N` suffix is inserted before the extension instead.
+pub fn conflict_file_name(
+    file_name: &str,
+    template: &str,
+    host: &str,
+    now: DateTime<Utc>,
+    exists: impl Fn(&str) -> bool,
+) -> String {
+    let path = Path::new(file_name);
+    let (name, ext) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => (
+            stem.to_string_lossy().to_string(),
+            format!(".{}", ext.to_string_lossy()),
+        ),
+        _ => (file_name.to_string(), String::new()),
+    };
+    let timestamp = now.format("%Y%m%d_%H%M%S").to_string();
+
+    let render = |template: &str, counter: u32| {
+        template
+            .replace("{name}", &name)
+            .replace("{ext}", &ext)
+            .replace("{host}", host)
+            .replace("{timestamp}", &timestamp)
+            .replace("{counter}", &counter.to_string())
+    };
+
+    let fallback = if template.contains("{ext}") {
+        template.replacen("{ext}", " {counter}{ext}", 1)
+    } else {
+        format!("{} {{counter}}", template)
+    };
+
+    let mut counter = 1;
+    loop {
+        let candidate = if counter == 1 || template.contains("{counter}") {
+            render(template, counter)
+        } else {
+            render(&fallback, counter)
+        };
+        if !exists(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Duration;
+    use crate::config::project::DEFAULT_CONFLICT_RENAME_TEMPLATE;
+    use chrono::{Duration, FixedOffset, TimeZone};
+
+    #[test]
+    fn test_path_keys_round_trip() {
+        assert_eq!(path_key(Path::new("docs/a.txt")), "docs/a.txt");
+        assert_eq!(key_path("docs/a.txt"), PathBuf::from("docs/a.txt"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            // Both display as "caf\u{FFFD}" but are different files
+            let latin1 = Path::new(OsStr::from_bytes(b"caf\xe9"));
+            let other = Path::new(OsStr::from_bytes(b"caf\xff"));
+            assert_ne!(path_key(latin1), path_key(other));
+            assert_eq!(key_path(&path_key(latin1)), latin1);
+            assert_eq!(key_path(&path_key(other)), other);
+        }
+    }
 
     #[test]
     fn test_times_equal_exact() {
@@ -35,4 +303,133 @@ mod tests {
         let later = now + Duration::seconds(3);
         assert!(!times_equal_with_tolerance(now, later));
     }
+
+    #[test]
+    fn test_system_time_round_trip() {
+        let after = UNIX_EPOCH + std::time::Duration::new(1_700_000_000, 123_456_789);
+        let utc = system_time_to_utc(after);
+        assert_eq!(utc.timestamp(), 1_700_000_000);
+        assert_eq!(utc.timestamp_subsec_nanos(), 123_456_789);
+        assert_eq!(utc_to_system_time(utc), after);
+
+        // Before the epoch, e.g. files restored from old archives
+        let before = UNIX_EPOCH - std::time::Duration::from_millis(1_500);
+        let utc = system_time_to_utc(before);
+        assert_eq!(utc.timestamp(), -2);
+        assert_eq!(utc.timestamp_subsec_nanos(), 500_000_000);
+        assert_eq!(utc_to_system_time(utc), before);
+    }
+
+    #[test]
+    fn test_times_compare_across_dst() {
+        // Central Europe springs forward at 02:00 CET to 03:00 CEST: one
+        // second apart despite the wall clock jumping an hour
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        let cest = FixedOffset::east_opt(7200).unwrap();
+        let before = cet.with_ymd_and_hms(2024, 3, 31, 1, 59, 59).unwrap();
+        let after = cest.with_ymd_and_hms(2024, 3, 31, 3, 0, 0).unwrap();
+        let utc = |t: DateTime<FixedOffset>| system_time_to_utc(t.into());
+        assert!(times_equal_with_tolerance(utc(before), utc(after)));
+
+        // Falling back repeats 02:30, an hour apart in UTC
+        let first = cest.with_ymd_and_hms(2024, 10, 27, 2, 30, 0).unwrap();
+        let second = cet.with_ymd_and_hms(2024, 10, 27, 2, 30, 0).unwrap();
+        assert!(!times_equal_with_tolerance(utc(first), utc(second)));
+    }
+
+    #[test]
+    fn test_file_mtime_independent_of_offset() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("file.txt");
+        std::fs::write(&path, "content").unwrap();
+
+        // The same instant written down in a non-UTC zone and in UTC
+        let local = FixedOffset::west_opt(5 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 10, 1, 30, 0)
+            .unwrap();
+        let expected = Utc.with_ymd_and_hms(2024, 3, 10, 6, 30, 0).unwrap();
+        filetime::set_file_mtime(&path, filetime::FileTime::from_system_time(local.into()))
+            .unwrap();
+
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(system_time_to_utc(mtime), expected);
+    }
+
+    #[test]
+    fn test_conflict_file_name_placeholders() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 14, 30, 0).unwrap();
+
+        let name = conflict_file_name(
+            "report.final.pdf",
+            DEFAULT_CONFLICT_RENAME_TEMPLATE,
+            "laptop",
+            now,
+            |_| false,
+        );
+        assert_eq!(name, "report.final (laptop-20240305_143000).pdf");
+
+        let name = conflict_file_name("Makefile", "{name} (conflict){ext}", "h", now, |_| false);
+        assert_eq!(name, "Makefile (conflict)");
+    }
+
+    #[test]
+    fn test_conflict_file_name_avoids_collisions() {
+        let now = Utc::now();
+        let taken = ["a (conflict).txt", "a (conflict) 2.txt"];
+        let name = conflict_file_name("a.txt", "{name} (conflict){ext}", "h", now, |n| {
+            taken.contains(&n)
+        });
+        assert_eq!(name, "a (conflict) 3.txt");
+
+        let taken = ["a-1.txt"];
+        let name = conflict_file_name("a.txt", "{name}-{counter}{ext}", "h", now, |n| {
+            taken.contains(&n)
+        });
+        assert_eq!(name, "a-2.txt");
+    }
+
+    #[test]
+    fn test_is_within_metadata_dir() {
+        assert!(is_within_metadata_dir(Path::new("/data/.rahzom")));
+        assert!(is_within_metadata_dir(Path::new("/data/.rahzom/_backup/x")));
+        assert!(is_within_metadata_dir(Path::new("sub/.rahzom/_trash")));
+        assert!(!is_within_metadata_dir(Path::new("/data/_backup")));
+        assert!(!is_within_metadata_dir(Path::new("/data/.rahzomignore")));
+    }
+
+    #[test]
+    fn test_temp_path_for() {
+        let temp = temp_path_for(Path::new("sub/report.pdf"));
+        let name = temp.file_name().unwrap().to_str().unwrap();
+        assert_eq!(temp.parent(), Some(Path::new("sub")));
+        assert!(name.starts_with("report.pdf.") && name.ends_with(".rahzom-tmp"));
+        assert!(is_temp_file(&temp));
+        assert!(!is_temp_file(Path::new("sub/report.pdf")));
+        assert!(!is_temp_file(Path::new("sub/report.pdf.rahzom-tmp")));
+        assert!(!is_temp_file(Path::new(
+            "sub/report.pdf.0badc0de.rahzom-tmp"
+        )));
+    }
+
+    #[test]
+    fn test_temp_path_for_long_name() {
+        let longest = "n".repeat(NAME_MAX);
+        let temp = temp_path_for(&Path::new("sub").join(&longest));
+        let name = temp.file_name().unwrap();
+        assert!(name.len() <= NAME_MAX);
+        assert_eq!(name.len(), 16 + TEMP_FILE_SUFFIX.len());
+        assert!(is_temp_file(&temp));
+        assert_ne!(
+            temp,
+            temp_path_for(&Path::new("sub").join("m".repeat(NAME_MAX)))
+        );
+
+        // Just short enough for the tagged form
+        let fits = "n".repeat(NAME_MAX - 9 - TEMP_FILE_SUFFIX.len());
+        let temp = temp_path_for(Path::new(&fits));
+        assert_eq!(temp.as_os_str().len(), NAME_MAX);
+        assert!(temp.to_str().unwrap().starts_with(&fits));
+        assert!(is_temp_file(&temp));
+    }
 }
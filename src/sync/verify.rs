@@ -0,0 +1,177 @@
+//! Content verification of an existing sync, independent of metadata history
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::hash::hash_file;
+use super::scanner::{FileEntry, ScanResult};
+
+/// How a path differs between the two sides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyIssueKind {
+    /// Present on both sides with different content (or file vs directory)
+    Mismatch,
+    /// Only on the left side
+    Missing,
+    /// Only on the right side
+    Extra,
+    /// Content could not be read on either side
+    Error(String),
+}
+
+/// A path that failed verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyIssue {
+    pub path: PathBuf,
+    pub kind: VerifyIssueKind,
+}
+
+/// Outcome of comparing both sides byte by byte
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Files present on both sides whose content was compared
+    pub files_compared: usize,
+    /// Issues sorted by path
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn count(&self, kind: &VerifyIssueKind) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| std::mem::discriminant(&i.kind) == std::mem::discriminant(kind))
+            .count()
+    }
+
+    fn push(&mut self, path: &Path, kind: VerifyIssueKind) {
+        self.issues.push(VerifyIssue {
+            path: path.to_path_buf(),
+            kind,
+        });
+    }
+}
+
+/// Compares two scans by content: files on both sides must have the same
+/// size and SHA-256 hash. Sync metadata is not consulted, so this catches
+/// differences the size/mtime heuristics of a normal diff would miss.
+pub fn verify(left: &ScanResult, right: &ScanResult) -> VerifyReport {
+    verify_with(left, right, hash_file)
+}
+
+/// Like [`verify`], hashing right-side content with `hash_right`, which is
+/// given the path the file would have in a plain mirror
+pub fn verify_with(
+    left: &ScanResult,
+    right: &ScanResult,
+    hash_right: fn(&Path) -> Result<String>,
+) -> VerifyReport {
+    let by_path = |scan: &ScanResult| -> BTreeMap<PathBuf, FileEntry> {
+        scan.entries
+            .iter()
+            .map(|e| (e.path.clone(), e.clone()))
+            .collect()
+    };
+    let left_entries = by_path(left);
+    let right_entries = by_path(right);
+
+    let mut report = VerifyReport::default();
+    for (path, l) in &left_entries {
+        let Some(r) = right_entries.get(path) else {
+            report.push(path, VerifyIssueKind::Missing);
+            continue;
+        };
+        if l.is_dir || r.is_dir {
+            if l.is_dir != r.is_dir {
+                report.push(path, VerifyIssueKind::Mismatch);
+            }
+            continue;
+        }
+
+        report.files_compared += 1;
+        if l.size != r.size {
+            report.push(path, VerifyIssueKind::Mismatch);
+            continue;
+        }
+        match (
+            hash_file(&left.root.join(path)),
+            hash_right(&right.root.join(path)),
+        ) {
+            (Ok(lh), Ok(rh)) if lh != rh => report.push(path, VerifyIssueKind::Mismatch),
+            (Ok(_), Ok(_)) => {}
+            (Err(e), _) | (_, Err(e)) => {
+                report.push(path, VerifyIssueKind::Error(format!("{:#}", e)));
+            }
+        }
+    }
+    for path in right_entries.keys() {
+        if !left_entries.contains_key(path) {
+            report.push(path, VerifyIssueKind::Extra);
+        }
+    }
+
+    report.issues.sort_by(|a, b| a.path.cmp(&b.path));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::scanner::scan;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_verify_reports_each_kind() {
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+
+        fs::write(left.path().join("same.txt"), "same").unwrap();
+        fs::write(right.path().join("same.txt"), "same").unwrap();
+        // Same size, different bytes: only a hash comparison notices
+        fs::write(left.path().join("flipped.bin"), "abcd").unwrap();
+        fs::write(right.path().join("flipped.bin"), "abce").unwrap();
+        fs::write(left.path().join("only_left.txt"), "x").unwrap();
+        fs::write(right.path().join("only_right.txt"), "y").unwrap();
+        fs::create_dir(left.path().join("kind")).unwrap();
+        fs::write(right.path().join("kind"), "file").unwrap();
+
+        let report = verify(&scan(left.path()).unwrap(), &scan(right.path()).unwrap());
+
+        assert!(!report.is_clean());
+        assert_eq!(report.files_compared, 2);
+        let issue = |path: &str| {
+            report
+                .issues
+                .iter()
+                .find(|i| i.path == Path::new(path))
+                .map(|i| i.kind.clone())
+        };
+        assert_eq!(issue("same.txt"), None);
+        assert_eq!(issue("flipped.bin"), Some(VerifyIssueKind::Mismatch));
+        assert_eq!(issue("only_left.txt"), Some(VerifyIssueKind::Missing));
+        assert_eq!(issue("only_right.txt"), Some(VerifyIssueKind::Extra));
+        assert_eq!(issue("kind"), Some(VerifyIssueKind::Mismatch));
+        assert_eq!(report.count(&VerifyIssueKind::Mismatch), 2);
+    }
+
+    #[test]
+    fn test_verify_identical_trees_is_clean() {
+        let left = TempDir::new().unwrap();
+        let right = TempDir::new().unwrap();
+        for root in [left.path(), right.path()] {
+            fs::create_dir(root.join("sub")).unwrap();
+            fs::write(root.join("sub/a.txt"), "content").unwrap();
+        }
+
+        let report = verify(&scan(left.path()).unwrap(), &scan(right.path()).unwrap());
+
+        assert!(report.is_clean());
+        assert_eq!(report.files_compared, 1);
+    }
+}
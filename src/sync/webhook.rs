@@ -0,0 +1,238 @@
+//! Sync report posted to a webhook (requires the `webhook` feature).
+//!
+//! After a sync, a project with `on_complete_webhook` set POSTs a JSON
+//! summary of it to that URL, e.g. for a dashboard that alerts when a backup
+//! fails. Delivery is best effort: a failure is reported to the user but
+//! never changes the outcome of the sync. A network error is retried once;
+//! an error status from the server is not.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::executor::{FailedAction, SkippedAction};
+
+/// Whether this build can post reports
+pub const AVAILABLE: bool = cfg!(feature = "webhook");
+
+/// Longest a delivery attempt may take
+#[cfg(feature = "webhook")]
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Summary of a finished sync
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncReport {
+    pub project: String,
+    /// Ran to the end without failures
+    pub ok: bool,
+    pub cancelled: bool,
+    pub completed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub bytes_transferred: u64,
+    pub duration_secs: f64,
+    pub finished: DateTime<Utc>,
+    pub failures: Vec<ReportedFailure>,
+}
+
+/// A failed action in a [`SyncReport`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportedFailure {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+impl SyncReport {
+    pub fn new(
+        project: &str,
+        cancelled: bool,
+        completed: usize,
+        failed: &[FailedAction],
+        skipped: &[SkippedAction],
+        bytes_transferred: u64,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            project: project.to_string(),
+            ok: !cancelled && failed.is_empty(),
+            cancelled,
+            completed,
+            failed: failed.len(),
+            skipped: skipped.len(),
+            bytes_transferred,
+            duration_secs: duration.as_secs_f64(),
+            finished: Utc::now(),
+            failures: failed
+                .iter()
+                .map(|f| ReportedFailure {
+                    path: f.action.path().clone(),
+                    error: f.error.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Why a delivery attempt failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// The server couldn't be reached or didn't answer in time
+    Network(String),
+    /// The server answered with an error status
+    Status(u16),
+}
+
+/// Posts `report` to `url`
+pub fn post(url: &str, report: &SyncReport) -> Result<()> {
+    let body = serde_json::to_vec(report)?;
+    deliver(|| send(url, &body))
+}
+
+/// Runs `attempt`, once more if it fails on the network
+fn deliver(mut attempt: impl FnMut() -> std::result::Result<(), SendError>) -> Result<()> {
+    let result = match attempt() {
+        Err(SendError::Network(_)) => attempt(),
+        result => result,
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(SendError::Network(e)) => bail!("{}", e),
+        Err(SendError::Status(status)) => bail!("server answered {}", status),
+    }
+}
+
+#[cfg(feature = "webhook")]
+fn send(url: &str, body: &[u8]) -> std::result::Result<(), SendError> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into();
+    match agent
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(body)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::StatusCode(status)) => Err(SendError::Status(status)),
+        Err(e) => Err(SendError::Network(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "webhook"))]
+fn send(_url: &str, _body: &[u8]) -> std::result::Result<(), SendError> {
+    Err(SendError::Network(
+        "built without the `webhook` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::differ::SyncAction;
+    use crate::sync::executor::SyncErrorKind;
+
+    #[test]
+    fn test_report_payload() {
+        let failed = vec![FailedAction {
+            action: SyncAction::CopyToRight {
+                path: PathBuf::from("photos/a.jpg"),
+                size: 10,
+            },
+            error: "Disk full".to_string(),
+            kind: SyncErrorKind::DiskFull,
+        }];
+        let report = SyncReport::new(
+            "photos",
+            false,
+            7,
+            &failed,
+            &[],
+            2048,
+            Duration::from_secs(3),
+        );
+        assert!(!report.ok);
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["project"], "photos");
+        assert_eq!(json["completed"], 7);
+        assert_eq!(json["failed"], 1);
+        assert_eq!(json["bytes_transferred"], 2048);
+        assert_eq!(json["duration_secs"], 3.0);
+        assert_eq!(json["failures"][0]["path"], "photos/a.jpg");
+        assert_eq!(json["failures"][0]["error"], "Disk full");
+    }
+
+    #[test]
+    fn test_network_errors_are_retried_once() {
+        let mut attempts = 0;
+        let result = deliver(|| {
+            attempts += 1;
+            match attempts {
+                1 => Err(SendError::Network("connection refused".to_string())),
+                _ => Ok(()),
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(attempts, 2);
+
+        attempts = 0;
+        let result = deliver(|| {
+            attempts += 1;
+            Err(SendError::Network("connection refused".to_string()))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "connection refused");
+        assert_eq!(attempts, 2);
+
+        // The server got the report; sending it again won't help
+        attempts = 0;
+        let result = deliver(|| {
+            attempts += 1;
+            Err(SendError::Status(500))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "server answered 500");
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "webhook")]
+    fn test_post_to_a_server() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            // The first connection drops without an answer, so the post is retried
+            drop(listener.accept().unwrap());
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            body
+        });
+
+        let report = SyncReport::new("docs", false, 3, &[], &[], 10, Duration::from_secs(1));
+        post(&url, &report).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&server.join().unwrap()).unwrap();
+        assert_eq!(body["project"], "docs");
+        assert_eq!(body["ok"], true);
+    }
+}
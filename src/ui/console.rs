@@ -0,0 +1,179 @@
+//! Single-line console progress for headless runs
+
+use std::io::{self, IsTerminal, Stderr, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::sync::differ::SyncAction;
+use crate::sync::executor::{ExecutionResult, ProgressCallback};
+
+use super::format_bytes;
+
+/// Least time between two status lines when the output is not a terminal
+pub const LINE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Writes sync progress as one `\r`-updated status line, e.g.
+/// `123/1000 files, 4.2 GB/10.0 GB`, followed by a summary line at the end.
+///
+/// Bytes count every processed copy (including skipped ones), so the line
+/// tracks progress through the plan; the summary reports what was transferred.
+/// When the output is not a terminal, the status goes on a line of its own
+/// at most every [`LINE_INTERVAL`], keeping captured logs readable.
+pub struct ConsoleProgress<W: Write = Stderr> {
+    out: W,
+    interactive: bool,
+    /// When the last status line was written, outside a terminal
+    last_line: Option<Instant>,
+    current: usize,
+    total: usize,
+    processed_bytes: u64,
+    total_bytes: u64,
+    /// Length of the last status line, to blank out leftovers of a longer one
+    last_len: usize,
+}
+
+impl ConsoleProgress<Stderr> {
+    /// Progress on stderr; `total_bytes` is the sum of all planned copies
+    pub fn new(total_bytes: u64) -> Self {
+        let interactive = io::stderr().is_terminal();
+        Self::with_writer(io::stderr(), total_bytes, interactive)
+    }
+}
+
+impl<W: Write> ConsoleProgress<W> {
+    pub fn with_writer(out: W, total_bytes: u64, interactive: bool) -> Self {
+        Self {
+            out,
+            interactive,
+            last_line: None,
+            current: 0,
+            total: 0,
+            processed_bytes: 0,
+            total_bytes,
+            last_len: 0,
+        }
+    }
+
+    /// Current status, e.g. "123/1000 files, 4.2 GB/10.0 GB"
+    pub fn status_line(&self) -> String {
+        format!(
+            "{}/{} files, {}/{}",
+            self.current,
+            self.total,
+            format_bytes(self.processed_bytes),
+            format_bytes(self.total_bytes)
+        )
+    }
+
+    /// Ends the status line and writes the summary of `result`
+    pub fn finish(&mut self, result: &ExecutionResult) -> io::Result<()> {
+        if self.interactive {
+            self.redraw()?;
+            writeln!(self.out)?;
+        } else {
+            writeln!(self.out, "{}", self.status_line())?;
+        }
+        writeln!(
+            self.out,
+            "Done: {} completed, {} skipped, {} failed, {} transferred",
+            result.completed.len(),
+            result.skipped.len(),
+            result.failed.len(),
+            format_bytes(result.total_bytes_transferred())
+        )?;
+        self.out.flush()
+    }
+
+    fn redraw(&mut self) -> io::Result<()> {
+        let line = self.status_line();
+        let padding = self.last_len.saturating_sub(line.len());
+        write!(self.out, "\r{}{}", line, " ".repeat(padding))?;
+        self.last_len = line.len();
+        self.out.flush()
+    }
+
+    /// Progress output is best-effort; a closed stderr must not abort the sync
+    fn update(&mut self) {
+        if self.interactive {
+            let _ = self.redraw();
+        } else if self
+            .last_line
+            .is_none_or(|last| last.elapsed() >= LINE_INTERVAL)
+        {
+            self.last_line = Some(Instant::now());
+            let _ = writeln!(self.out, "{}", self.status_line());
+        }
+    }
+}
+
+impl<W: Write> ProgressCallback for ConsoleProgress<W> {
+    fn on_action_start(&mut self, _index: usize, _action: &SyncAction) {}
+
+    fn on_progress(&mut self, current: usize, total: usize, _current_file: &Path) {
+        self.current = current;
+        self.total = total;
+        self.update();
+    }
+
+    fn on_file_complete(&mut self, action: &SyncAction, _success: bool) {
+        if let SyncAction::CopyToRight { size, .. } | SyncAction::CopyToLeft { size, .. } = action {
+            self.processed_bytes += size;
+        }
+        self.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn copy(size: u64) -> SyncAction {
+        SyncAction::CopyToRight {
+            path: PathBuf::from("a.bin"),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_interactive_updates_single_line() {
+        let mut out = Vec::new();
+        let mut progress = ConsoleProgress::with_writer(&mut out, 3 * 1024, true);
+
+        progress.on_progress(1, 2, Path::new("a.bin"));
+        progress.on_file_complete(&copy(1024), true);
+        progress.on_progress(2, 2, Path::new("b.bin"));
+        progress.on_file_complete(&copy(2048), true);
+        assert_eq!(progress.status_line(), "2/2 files, 3.0 KB/3.0 KB");
+        progress.finish(&ExecutionResult::default()).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        // Every update rewrites the same line; only the end adds newlines
+        assert!(text.starts_with("\r1/2 files, 0 B/3.0 KB"));
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.ends_with("Done: 0 completed, 0 skipped, 0 failed, 0 B transferred\n"));
+    }
+
+    #[test]
+    fn test_non_interactive_writes_throttled_lines() {
+        let mut out = Vec::new();
+        let mut progress = ConsoleProgress::with_writer(&mut out, 1024, false);
+
+        // The first update is written, the next ones within the interval not
+        progress.on_progress(1, 1, Path::new("a.bin"));
+        progress.on_file_complete(&copy(1024), true);
+        progress.finish(&ExecutionResult::default()).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains('\r'));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "1/1 files, 0 B/1.0 KB",
+                "1/1 files, 1.0 KB/1.0 KB",
+                "Done: 0 completed, 0 skipped, 0 failed, 0 B transferred",
+            ]
+        );
+    }
+}
@@ -2,54 +2,67 @@
 
 use std::path::Path;
 
-use ratatui::layout::{Alignment, Constraint, Layout, Margin};
-use ratatui::style::{Color, Style};
+use ratatui::layout::{Alignment, Constraint, Layout, Margin, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
+use crate::app::keymap::{self, KeyBinding};
 use crate::app::{
-    DialogField, DiskSpaceWarningDialog, ExclusionsInfoDialog, FileErrorDialog, NewProjectDialog,
-    SettingsDialog, SettingsField, SyncConfirmDialog,
+    BackupBrowserDialog, DialogField, DiskSpaceWarningDialog, ExclusionsInfoDialog,
+    FileErrorDialog, HelpDialog, MetadataInspectDialog, MetadataSide, NewProjectDialog,
+    ProjectFileDialog, ScriptDialog, SettingsDialog, SettingsField, SyncConfirmDialog,
+    TargetStatusDialog, VerifyReportDialog,
 };
-use crate::sync::executor::SyncErrorKind;
-use crate::ui::{centered_rect, format_bytes};
+use crate::sync::executor::{SyncErrorKind, TargetStatus};
+use crate::sync::skew::ClockSkew;
+use crate::sync::utils::times_equal_with_tolerance;
+use crate::sync::verify::VerifyIssueKind;
+use crate::ui::{centered_rect, format_bytes, format_count, Theme};
+
+/// Most auto-excluded project roots listed in the exclusions dialog
+const MAX_AUTO_EXCLUDED_SHOWN: usize = 4;
 
 /// Renders new project dialog
-pub fn render_new_project_dialog(frame: &mut Frame, dialog: &NewProjectDialog) {
+pub fn render_new_project_dialog(frame: &mut Frame, dialog: &NewProjectDialog, theme: &Theme) {
     let area = centered_rect(60, 14, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" New Project ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.title));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    // A terminal too short for the full dialog drops the spacing and the
+    // suggestion rows, so every field and the hint stay visible
+    let compact = inner.height < 12;
+    let (gap, field) = if compact { (0, 1) } else { (1, 2) };
     let chunks = Layout::vertical([
-        Constraint::Length(1),
-        Constraint::Length(2),
-        Constraint::Length(1),
-        Constraint::Length(2),
-        Constraint::Length(1),
-        Constraint::Length(2),
-        Constraint::Length(1),
+        Constraint::Length(gap),
+        Constraint::Length(field),
+        Constraint::Length(gap),
+        Constraint::Length(field),
+        Constraint::Length(gap),
+        Constraint::Length(field),
+        Constraint::Length(gap),
         Constraint::Min(1),
     ])
     .split(inner.inner(Margin::new(2, 0)));
 
     let name_style = if dialog.focused_field == DialogField::Name {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let name_label = Line::from(vec![
         Span::styled("Name: ", name_style),
         Span::raw(&dialog.name),
         if dialog.focused_field == DialogField::Name {
-            Span::styled("▌", Style::default().fg(Color::White))
+            Span::styled("▌", Style::default().fg(theme.text))
         } else {
             Span::raw("")
         },
@@ -57,15 +70,15 @@ pub fn render_new_project_dialog(frame: &mut Frame, dialog: &NewProjectDialog) {
     frame.render_widget(Paragraph::new(name_label), chunks[1]);
 
     let left_style = if dialog.focused_field == DialogField::LeftPath {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let left_label = Line::from(vec![
         Span::styled("Left path: ", left_style),
         Span::raw(&dialog.left_path),
         if dialog.focused_field == DialogField::LeftPath {
-            Span::styled("▌", Style::default().fg(Color::White))
+            Span::styled("▌", Style::default().fg(theme.text))
         } else {
             Span::raw("")
         },
@@ -73,45 +86,181 @@ pub fn render_new_project_dialog(frame: &mut Frame, dialog: &NewProjectDialog) {
     frame.render_widget(Paragraph::new(left_label), chunks[3]);
 
     let right_style = if dialog.focused_field == DialogField::RightPath {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let right_label = Line::from(vec![
         Span::styled("Right path: ", right_style),
         Span::raw(&dialog.right_path),
         if dialog.focused_field == DialogField::RightPath {
-            Span::styled("▌", Style::default().fg(Color::White))
+            Span::styled("▌", Style::default().fg(theme.text))
         } else {
             Span::raw("")
         },
     ]);
     frame.render_widget(Paragraph::new(right_label), chunks[5]);
 
+    // Suggestion line below the focused path field
+    let suggestions = dialog.suggestions();
+    let suggestion_area = match dialog.focused_field {
+        DialogField::Name => None,
+        DialogField::LeftPath => Some(chunks[3]),
+        DialogField::RightPath => Some(chunks[5]),
+    };
+    if let Some(area) = suggestion_area.filter(|a| a.height > 1 && !suggestions.is_empty()) {
+        let selected = dialog
+            .suggestion
+            .and_then(|i| Some((i, *suggestions.get(i)?)));
+        let line = match selected {
+            Some((i, path)) => Line::from(vec![
+                Span::styled("  ↳ ", Style::default().fg(theme.muted)),
+                Span::styled(path, Style::default().fg(theme.info)),
+                Span::styled(
+                    format!("  ({}/{}, Tab to use)", i + 1, suggestions.len()),
+                    Style::default().fg(theme.muted),
+                ),
+            ]),
+            None => Line::from(Span::styled(
+                format!("  ↑↓ {} recent paths", suggestions.len()),
+                Style::default().fg(theme.muted),
+            )),
+        };
+        let row = Rect {
+            y: area.y + 1,
+            height: 1,
+            ..area
+        };
+        frame.render_widget(Paragraph::new(line), row);
+    }
+
     let hint = if let Some(ref error) = dialog.error {
-        Line::from(Span::styled(error, Style::default().fg(Color::Red)))
+        Line::from(Span::styled(error, Style::default().fg(theme.error)))
     } else {
         Line::from(vec![
-            Span::styled(" Tab ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Tab ", theme.key_style()),
             Span::raw(" Next  "),
-            Span::styled(" Enter ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Enter ", theme.key_style()),
             Span::raw(" Create  "),
-            Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Esc ", theme.key_style()),
+            Span::raw(" Cancel"),
+        ])
+    };
+    // Errors such as a blocked path explain themselves at length
+    frame.render_widget(Paragraph::new(hint).wrap(Wrap { trim: true }), chunks[7]);
+}
+
+/// Renders the path prompt of a project export or import
+pub fn render_project_file_dialog(frame: &mut Frame, dialog: &ProjectFileDialog, theme: &Theme) {
+    let area = centered_rect(60, 8, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (title, action) = match dialog.export {
+        Some(ref name) => (format!(" Export '{}' ", name), " Export  "),
+        None => (" Import Project ".to_string(), " Import  "),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(1),
+    ])
+    .split(inner.inner(Margin::new(2, 0)));
+
+    let path_line = Line::from(vec![
+        Span::styled("File: ", Style::default().fg(theme.focus)),
+        Span::raw(&dialog.path),
+        Span::styled("▌", Style::default().fg(theme.text)),
+    ]);
+    frame.render_widget(Paragraph::new(path_line), chunks[1]);
+
+    let hint = if let Some(ref error) = dialog.error {
+        Line::from(Span::styled(error, Style::default().fg(theme.error)))
+    } else {
+        Line::from(vec![
+            Span::styled(" Enter ", theme.key_style()),
+            Span::raw(action),
+            Span::styled(" Esc ", theme.key_style()),
             Span::raw(" Cancel"),
         ])
     };
-    frame.render_widget(Paragraph::new(hint), chunks[7]);
+    frame.render_widget(Paragraph::new(hint).wrap(Wrap { trim: true }), chunks[3]);
+}
+
+/// Renders the path prompt for saving the preview as a script
+pub fn render_script_dialog(frame: &mut Frame, dialog: &ScriptDialog, theme: &Theme) {
+    let area = centered_rect(64, 10, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Save as Script ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(1),
+    ])
+    .split(inner.inner(Margin::new(2, 0)));
+
+    let path_line = Line::from(vec![
+        Span::styled("File: ", Style::default().fg(theme.focus)),
+        Span::raw(&dialog.path),
+        Span::styled("▌", Style::default().fg(theme.text)),
+    ]);
+    frame.render_widget(Paragraph::new(path_line), chunks[1]);
+    let shell_line = Line::from(vec![
+        Span::styled("For:  ", Style::default().fg(theme.focus)),
+        Span::raw(dialog.platform.label()),
+    ]);
+    frame.render_widget(Paragraph::new(shell_line), chunks[2]);
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            "Plain copies and deletes: no backups, trash or verification",
+            Style::default().fg(theme.warning),
+        )),
+        chunks[3],
+    );
+
+    let hint = if let Some(ref error) = dialog.error {
+        Line::from(Span::styled(error, Style::default().fg(theme.error)))
+    } else {
+        Line::from(vec![
+            Span::styled(" Enter ", theme.key_style()),
+            Span::raw(" Save  "),
+            Span::styled(" Tab ", theme.key_style()),
+            Span::raw(" Shell  "),
+            Span::styled(" Esc ", theme.key_style()),
+            Span::raw(" Cancel"),
+        ])
+    };
+    frame.render_widget(Paragraph::new(hint).wrap(Wrap { trim: true }), chunks[5]);
 }
 
 /// Renders delete confirmation dialog
-pub fn render_delete_confirm_dialog(frame: &mut Frame, name: &str) {
+pub fn render_delete_confirm_dialog(frame: &mut Frame, name: &str, theme: &Theme) {
     let area = centered_rect(50, 7, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Confirm Delete ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Red));
+        .border_style(Style::default().fg(theme.error));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -121,9 +270,38 @@ pub fn render_delete_confirm_dialog(frame: &mut Frame, name: &str) {
         Line::from(format!("Delete project '{}'?", name)),
         Line::from(""),
         Line::from(vec![
-            Span::styled(" Y ", Style::default().fg(Color::Black).bg(Color::Red)),
+            Span::styled(" Y ", theme.badge(theme.error)),
             Span::raw(" Yes  "),
-            Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" N ", theme.key_style()),
+            Span::raw(" No"),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders swap left/right confirmation dialog
+pub fn render_swap_sides_confirm_dialog(frame: &mut Frame, theme: &Theme) {
+    let area = centered_rect(60, 8, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Swap Sides ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let text = vec![
+        Line::from(""),
+        Line::from("Swap left and right paths?"),
+        Line::from("This reverses the direction of all sync actions."),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Y ", theme.badge(theme.warning)),
+            Span::raw(" Yes  "),
+            Span::styled(" N ", theme.key_style()),
             Span::raw(" No"),
         ]),
     ];
@@ -132,14 +310,19 @@ pub fn render_delete_confirm_dialog(frame: &mut Frame, name: &str) {
 }
 
 /// Renders create directory confirmation dialog
-pub fn render_create_dir_confirm_dialog(frame: &mut Frame, path: &Path, is_left: bool) {
+pub fn render_create_dir_confirm_dialog(
+    frame: &mut Frame,
+    path: &Path,
+    is_left: bool,
+    theme: &Theme,
+) {
     let area = centered_rect(70, 9, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Create Directory ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.warning));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -150,15 +333,15 @@ pub fn render_create_dir_confirm_dialog(frame: &mut Frame, path: &Path, is_left:
         Line::from(format!("{} directory doesn't exist:", side)),
         Line::from(Span::styled(
             path.display().to_string(),
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.title),
         )),
         Line::from(""),
         Line::from("Create it?"),
         Line::from(""),
         Line::from(vec![
-            Span::styled(" Y ", Style::default().fg(Color::Black).bg(Color::Green)),
+            Span::styled(" Y ", theme.badge(theme.success)),
             Span::raw(" Yes  "),
-            Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" N ", theme.key_style()),
             Span::raw(" No"),
         ]),
     ];
@@ -167,94 +350,200 @@ pub fn render_create_dir_confirm_dialog(frame: &mut Frame, path: &Path, is_left:
 }
 
 /// Renders error dialog
-pub fn render_error_dialog(frame: &mut Frame, message: &str) {
-    let area = centered_rect(60, 7, frame.area());
+pub fn render_error_dialog(frame: &mut Frame, message: &str, theme: &Theme) {
+    // Long messages wrap and the dialog grows to fit them; the estimate
+    // leaves room for words carried over to the next line
+    let width = 60;
+    let message_lines = message.chars().count().div_ceil(width - 12).max(1) as u16;
+    let area = centered_rect(width as u16, 6 + message_lines, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Error ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Red));
+        .border_style(Style::default().fg(theme.error));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
     let text = vec![
         Line::from(""),
-        Line::from(Span::styled(message, Style::default().fg(Color::Red))),
+        Line::from(Span::styled(message, Style::default().fg(theme.error))),
         Line::from(""),
         Line::from(vec![
-            Span::styled(" Enter ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Enter ", theme.key_style()),
             Span::raw(" OK"),
         ]),
     ];
 
-    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        inner,
+    );
 }
 
-/// Renders sync confirmation dialog
-pub fn render_sync_confirm_dialog(frame: &mut Frame, dialog: &SyncConfirmDialog) {
-    let area = centered_rect(60, 11, frame.area());
+/// Renders sync confirmation dialog, with safe and destructive operations
+/// apart and whether the destructive ones can be undone
+pub fn render_sync_confirm_dialog(frame: &mut Frame, dialog: &SyncConfirmDialog, theme: &Theme) {
+    let unbacked = dialog.unbacked_overwrites();
+    let height = dialog.line_count() as u16 + 2;
+    let area = centered_rect(60, height, frame.area());
     frame.render_widget(Clear, area);
 
+    let border = if dialog.destructive() > 0 {
+        theme.warning
+    } else {
+        theme.success
+    };
     let block = Block::default()
         .title(" Confirm Sync ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Green));
+        .border_style(Style::default().fg(border));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let text = vec![
-        Line::from(""),
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    // Destructive counts are red unless there are none
+    let destructive = |count: usize| {
+        let color = if count > 0 { theme.error } else { theme.muted };
+        Span::styled(format!("{} files", count), Style::default().fg(color))
+    };
+    // Whether destroyed data can be recovered
+    let reversibility = |label: &'static str, safe: bool, text: String| {
+        let color = if safe { theme.success } else { theme.error };
         Line::from(vec![
-            Span::styled("Copy: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(label, Style::default().fg(theme.muted)),
+            Span::styled(text, Style::default().fg(color)),
+        ])
+    };
+
+    // Starting is red when it loses data for good
+    let start = if unbacked > 0 {
+        theme.error
+    } else {
+        theme.success
+    };
+
+    let mut text = vec![Line::from("")];
+    // Only the marked items are synced, so the counts below are theirs
+    if let Some((marked, total)) = dialog.selection {
+        text.push(Line::from(Span::styled(
+            format!("Syncing {} of {} items", marked, total),
+            Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
+        )));
+    }
+    text.extend([
+        Line::from(Span::styled("Safe", heading)),
+        Line::from(vec![
+            Span::styled("New files: ", Style::default().fg(theme.muted)),
             Span::styled(
-                format!("{} files", dialog.files_to_copy),
-                Style::default().fg(Color::Green),
+                format!("{}", dialog.new_files()),
+                Style::default().fg(theme.success),
             ),
-        ]),
-        Line::from(vec![
-            Span::styled("Delete: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("  Create dirs: ", Style::default().fg(theme.muted)),
             Span::styled(
-                format!("{} files", dialog.files_to_delete),
-                Style::default().fg(Color::Red),
+                format!("{}", dialog.dirs_to_create),
+                Style::default().fg(theme.success),
+            ),
+            Span::styled("  Attributes: ", Style::default().fg(theme.muted)),
+            Span::styled(
+                format!("{}", dialog.attributes_to_update),
+                Style::default().fg(theme.success),
             ),
         ]),
         Line::from(vec![
-            Span::styled("Transfer: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Transfer: ", Style::default().fg(theme.muted)),
             Span::raw(format_bytes(dialog.bytes_to_transfer)),
         ]),
+        Line::from(""),
+        Line::from(Span::styled("Destructive", heading)),
         Line::from(vec![
-            Span::styled("Create dirs: ", Style::default().fg(Color::DarkGray)),
-            Span::raw(format!("{}", dialog.dirs_to_create)),
+            Span::styled("Overwrite: ", Style::default().fg(theme.muted)),
+            destructive(dialog.files_to_overwrite),
+            Span::styled("  Delete: ", Style::default().fg(theme.muted)),
+            destructive(dialog.files_to_delete),
         ]),
+    ]);
+    if unbacked > 0 {
+        text.push(Line::from(Span::styled(
+            format!("{} files will be overwritten without backup", unbacked),
+            Style::default()
+                .fg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    if dialog.conflicts_accepted > 0 {
+        text.push(Line::from(vec![
+            Span::styled("Accept as-is: ", Style::default().fg(theme.muted)),
+            Span::raw(format!("{} conflicts", dialog.conflicts_accepted)),
+        ]));
+    }
+    text.extend([
+        reversibility(
+            "Backups: ",
+            dialog.backup_versions > 0,
+            if dialog.backup_versions > 0 {
+                format!("on, {} versions kept", dialog.backup_versions)
+            } else {
+                "off, overwritten files are lost".to_string()
+            },
+        ),
+        reversibility(
+            "Deleted files: ",
+            dialog.soft_delete,
+            if dialog.soft_delete {
+                "moved to trash".to_string()
+            } else {
+                "removed permanently".to_string()
+            },
+        ),
         Line::from(""),
         Line::from("Start synchronization?"),
         Line::from(""),
         Line::from(vec![
-            Span::styled(
-                " Enter ",
-                Style::default().fg(Color::Black).bg(Color::Green),
-            ),
+            Span::styled(" Enter ", theme.badge(start)),
             Span::raw(" Start  "),
-            Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Esc ", theme.key_style()),
             Span::raw(" Cancel"),
         ]),
-    ];
+    ]);
 
-    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+    render_lines_with_hint(frame, text, dialog.scroll, inner);
+}
+
+/// Renders centered `text` into `area`. When it doesn't fit, its last line,
+/// the key hints, stays at the bottom and the lines above it scroll, starting
+/// `scroll` lines down.
+fn render_lines_with_hint(frame: &mut Frame, mut text: Vec<Line>, scroll: usize, area: Rect) {
+    if text.len() <= usize::from(area.height) {
+        frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), area);
+        return;
+    }
+
+    let hint = text.pop().unwrap_or_default();
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(area);
+    let scroll = scroll.min(text.len().saturating_sub(usize::from(chunks[0].height)));
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .scroll((scroll as u16, 0)),
+        chunks[0],
+    );
+    frame.render_widget(Paragraph::new(hint).alignment(Alignment::Center), chunks[1]);
 }
 
 /// Renders cancel sync confirmation dialog
-pub fn render_cancel_sync_confirm_dialog(frame: &mut Frame) {
+pub fn render_cancel_sync_confirm_dialog(frame: &mut Frame, theme: &Theme) {
     let area = centered_rect(50, 7, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Cancel Sync? ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.warning));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -264,9 +553,54 @@ pub fn render_cancel_sync_confirm_dialog(frame: &mut Frame) {
         Line::from("Cancel synchronization?"),
         Line::from(""),
         Line::from(vec![
-            Span::styled(" Y ", Style::default().fg(Color::Black).bg(Color::Red)),
+            Span::styled(" Y ", theme.badge(theme.error)),
+            Span::raw(" Cancel sync  "),
+            Span::styled(" N ", theme.key_style()),
+            Span::raw(" Continue"),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders the confirmation of resolving all conflicts in one direction
+pub fn render_resolve_all_confirm_dialog(
+    frame: &mut Frame,
+    to_left: bool,
+    count: usize,
+    theme: &Theme,
+) {
+    let area = centered_rect(64, 8, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Resolve All Conflicts ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let (winner, loser) = if to_left {
+        ("right", "left")
+    } else {
+        ("left", "right")
+    };
+    let text = vec![
+        Line::from(""),
+        Line::from(format!(
+            "Resolve {} conflicts with the {} side's version?",
+            count, winner
+        )),
+        Line::from(format!(
+            "Files on the {} side are overwritten or deleted.",
+            loser
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Y ", theme.badge(theme.warning)),
             Span::raw(" Yes  "),
-            Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" N ", theme.key_style()),
             Span::raw(" No"),
         ]),
     ];
@@ -274,133 +608,775 @@ pub fn render_cancel_sync_confirm_dialog(frame: &mut Frame) {
     frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
 }
 
-/// Renders exclusions info dialog
-pub fn render_exclusions_info_dialog(frame: &mut Frame, dialog: &ExclusionsInfoDialog) {
-    let area = centered_rect(70, 14, frame.area());
+/// Renders the offer to remove partial copies an interrupted sync left
+pub fn render_incomplete_transfers_dialog(
+    frame: &mut Frame,
+    left: usize,
+    right: usize,
+    theme: &Theme,
+) {
+    let area = centered_rect(64, 10, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Exclusion Patterns (.rahzomignore) ")
+        .title(" Incomplete Transfers ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.warning));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let left_status = if dialog.left_exists {
-        Span::styled(
-            format!("{} patterns", dialog.left_count),
-            Style::default().fg(Color::Green),
-        )
+    let text = vec![
+        Line::from(""),
+        Line::from(format!(
+            "{} partial copies from an interrupted sync were found",
+            left + right
+        )),
+        Line::from(format!("(left: {}, right: {}).", left, right)),
+        Line::from("They are never synced; syncing copies those files anew."),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Y ", theme.badge(theme.warning)),
+            Span::raw(" Remove them  "),
+            Span::styled(" Enter/N ", theme.key_style()),
+            Span::raw(" Keep"),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders quit confirmation dialog
+pub fn render_quit_confirm_dialog(frame: &mut Frame, sync_running: bool, theme: &Theme) {
+    let area = centered_rect(60, 8, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Quit ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let detail = if sync_running {
+        "A sync is in progress and will be stopped."
     } else {
-        Span::styled("not created", Style::default().fg(Color::DarkGray))
+        "The last sync result has not been reviewed."
     };
 
-    let right_status = if dialog.right_exists {
-        Span::styled(
-            format!("{} patterns", dialog.right_count),
-            Style::default().fg(Color::Green),
-        )
-    } else {
-        Span::styled("not created", Style::default().fg(Color::DarkGray))
+    let text = vec![
+        Line::from(""),
+        Line::from("Quit rahzom?"),
+        Line::from(Span::styled(detail, Style::default().fg(theme.muted))),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Y ", theme.badge(theme.error)),
+            Span::raw(" Quit app  "),
+            Span::styled(" N ", theme.key_style()),
+            Span::raw(" Stay"),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders exclusions info dialog
+pub fn render_exclusions_info_dialog(
+    frame: &mut Frame,
+    dialog: &ExclusionsInfoDialog,
+    theme: &Theme,
+) {
+    // Other projects' roots are listed below the files, a few at most
+    let auto_shown = dialog.auto_excluded.len().min(MAX_AUTO_EXCLUDED_SHOWN);
+    let auto_lines = match dialog.auto_excluded.len() {
+        0 => 0,
+        n if n > auto_shown => auto_shown + 3,
+        _ => auto_shown + 2,
     };
+    let area = centered_rect(70, 14 + auto_lines as u16, frame.area());
+    frame.render_widget(Clear, area);
 
-    let can_create = !dialog.left_exists || !dialog.right_exists;
+    let block = Block::default()
+        .title(" Exclusion Patterns (.rahzomignore) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let status = |exists: bool, count: usize, only_here: usize| {
+        if !exists {
+            return vec![Span::styled(
+                "not created",
+                Style::default().fg(theme.muted),
+            )];
+        }
+        let mut spans = vec![Span::styled(
+            format!("{} patterns", count),
+            Style::default().fg(theme.success),
+        )];
+        if only_here > 0 {
+            spans.push(Span::styled(
+                format!(", {} only on this side", only_here),
+                Style::default().fg(theme.warning),
+            ));
+        }
+        spans
+    };
+
+    let mut left_line = vec![Span::styled("Left:  ", Style::default().fg(theme.muted))];
+    left_line.extend(status(
+        dialog.left_exists,
+        dialog.left_count,
+        dialog.left_only,
+    ));
+    let mut right_line = vec![Span::styled("Right: ", Style::default().fg(theme.muted))];
+    right_line.extend(status(
+        dialog.right_exists,
+        dialog.right_count,
+        dialog.right_only,
+    ));
 
     let mut text = vec![
+        Line::from(""),
+        Line::from(left_line),
+        Line::from(Span::styled(
+            format!("  {}", dialog.left_path.display()),
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(""),
+        Line::from(right_line),
+        Line::from(Span::styled(
+            format!("  {}", dialog.right_path.display()),
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(""),
+    ];
+
+    if !dialog.auto_excluded.is_empty() {
+        text.push(Line::from("Other projects, excluded automatically:"));
+        text.extend(dialog.auto_excluded[..auto_shown].iter().map(|path| {
+            Line::from(Span::styled(
+                format!("  {}", path.display()),
+                Style::default().fg(theme.muted),
+            ))
+        }));
+        if dialog.auto_excluded.len() > auto_shown {
+            text.push(Line::from(Span::styled(
+                format!("  …and {} more", dialog.auto_excluded.len() - auto_shown),
+                Style::default().fg(theme.muted),
+            )));
+        }
+        text.push(Line::from(""));
+    }
+
+    // Each side applies its own file, but the files themselves sync like any other
+    if dialog.left_only + dialog.right_only > 0 {
+        text.push(Line::from(Span::styled(
+            "Sides differ; exclude .rahzomignore in both files to keep them apart",
+            Style::default().fg(theme.warning),
+        )));
+    } else {
+        text.push(Line::from(""));
+    }
+
+    let mut keys = Vec::new();
+    if !dialog.left_exists || !dialog.right_exists {
+        keys.push(Span::styled(" T ", theme.badge(theme.success)));
+        keys.push(Span::raw(" Create template  "));
+    }
+    keys.extend([
+        Span::styled(" L ", theme.key_style()),
+        Span::raw(" Edit left  "),
+        Span::styled(" R ", theme.key_style()),
+        Span::raw(" Edit right  "),
+        Span::styled(" Esc ", theme.key_style()),
+        Span::raw(" Close"),
+    ]);
+    text.push(Line::from(""));
+    text.push(Line::from(keys));
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders target validation result dialog
+pub fn render_target_status_dialog(frame: &mut Frame, dialog: &TargetStatusDialog, theme: &Theme) {
+    let area = centered_rect(70, 12, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Test Targets ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let status_span = |status: &Result<TargetStatus, String>| match status {
+        Ok(status) => {
+            let color = match status {
+                TargetStatus::Writable => theme.success,
+                TargetStatus::ReadOnly => theme.warning,
+                _ => theme.error,
+            };
+            Span::styled(status.label(), Style::default().fg(color))
+        }
+        Err(e) => Span::styled(format!("Error: {}", e), Style::default().fg(theme.error)),
+    };
+
+    let text = vec![
         Line::from(""),
         Line::from(vec![
-            Span::styled("Left:  ", Style::default().fg(Color::DarkGray)),
-            left_status,
+            Span::styled("Left:  ", Style::default().fg(theme.muted)),
+            status_span(&dialog.left),
         ]),
         Line::from(Span::styled(
             format!("  {}", dialog.left_path.display()),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         )),
         Line::from(""),
         Line::from(vec![
-            Span::styled("Right: ", Style::default().fg(Color::DarkGray)),
-            right_status,
+            Span::styled("Right: ", Style::default().fg(theme.muted)),
+            status_span(&dialog.right),
         ]),
         Line::from(Span::styled(
             format!("  {}", dialog.right_path.display()),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Enter ", theme.key_style()),
+            Span::raw(" OK"),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders backup browser dialog
+pub fn render_backup_browser_dialog(
+    frame: &mut Frame,
+    dialog: &BackupBrowserDialog,
+    theme: &Theme,
+) {
+    let visible = dialog.versions.len().min(10);
+    let area = centered_rect(65, visible as u16 + 8, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Backups ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            dialog.path.display().to_string(),
+            Style::default().fg(theme.title),
         )),
         Line::from(""),
     ];
 
-    if can_create {
+    // Keep the selected version in view
+    let start = dialog.selected.saturating_sub(visible.saturating_sub(1));
+    for (i, version) in dialog.versions.iter().enumerate().skip(start).take(visible) {
+        let style = if i == dialog.selected {
+            theme.badge(theme.title)
+        } else {
+            Style::default()
+        };
+        text.push(Line::from(Span::styled(
+            format!(
+                " {}  {}  {:>10} ",
+                if version.is_left { "Left " } else { "Right" },
+                version
+                    .entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                format_bytes(version.entry.size)
+            ),
+            style,
+        )));
+    }
+
+    text.push(Line::from(""));
+    if dialog.confirm {
+        text.push(Line::from(Span::styled(
+            "Restore this version? Current file will be backed up.",
+            Style::default().fg(theme.warning),
+        )));
+        text.push(Line::from(vec![
+            Span::styled(" Y ", theme.badge(theme.success)),
+            Span::raw(" Restore  "),
+            Span::styled(" N ", theme.key_style()),
+            Span::raw(" Back"),
+        ]));
+    } else {
+        text.push(Line::from(""));
         text.push(Line::from(vec![
-            Span::styled(" T ", Style::default().fg(Color::Black).bg(Color::Green)),
-            Span::raw(" Create template  "),
-            Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" ↑↓ ", theme.key_style()),
+            Span::raw(" Nav  "),
+            Span::styled(" Enter ", theme.badge(theme.success)),
+            Span::raw(" Restore  "),
+            Span::styled(" Esc ", theme.key_style()),
             Span::raw(" Close"),
         ]));
+    }
+
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+}
+
+/// Renders the verify report: counts per kind and a scrollable issue list
+pub fn render_verify_report_dialog(frame: &mut Frame, dialog: &VerifyReportDialog, theme: &Theme) {
+    let report = &dialog.report;
+    let visible = report.issues.len().min(12);
+    let area = centered_rect(70, visible as u16 + 9, frame.area());
+    frame.render_widget(Clear, area);
+
+    let border = if report.is_clean() {
+        theme.success
     } else {
+        theme.warning
+    };
+    let block = Block::default()
+        .title(" Verify ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut text = vec![Line::from("")];
+    if report.is_clean() {
         text.push(Line::from(Span::styled(
-            "Edit .rahzomignore files manually",
-            Style::default().fg(Color::DarkGray),
+            format!(
+                "Both sides match ({} files compared)",
+                report.files_compared
+            ),
+            Style::default().fg(theme.success),
         )));
+    } else {
         text.push(Line::from(vec![
-            Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
-            Span::raw(" Close"),
+            Span::styled("≠ ", Style::default().fg(theme.warning)),
+            Span::raw(format!(
+                "{} mismatched  ",
+                report.count(&VerifyIssueKind::Mismatch)
+            )),
+            Span::styled("→ ", Style::default().fg(theme.error)),
+            Span::raw(format!(
+                "{} missing on right  ",
+                report.count(&VerifyIssueKind::Missing)
+            )),
+            Span::styled("← ", Style::default().fg(theme.info)),
+            Span::raw(format!(
+                "{} only on right",
+                report.count(&VerifyIssueKind::Extra)
+            )),
+        ]));
+        text.push(Line::from(Span::styled(
+            format!(
+                "{} files compared, {} unreadable",
+                report.files_compared,
+                report.count(&VerifyIssueKind::Error(String::new()))
+            ),
+            Style::default().fg(theme.muted),
+        )));
+    }
+    text.push(Line::from(""));
+
+    for issue in report.issues.iter().skip(dialog.scroll).take(visible) {
+        let (symbol, color, detail) = match &issue.kind {
+            VerifyIssueKind::Mismatch => ("≠", theme.warning, String::new()),
+            VerifyIssueKind::Missing => ("→", theme.error, " (missing on right)".to_string()),
+            VerifyIssueKind::Extra => ("←", theme.info, " (only on right)".to_string()),
+            VerifyIssueKind::Error(e) => ("!", theme.error, format!(" ({})", e)),
+        };
+        text.push(Line::from(vec![
+            Span::styled(format!("{} ", symbol), Style::default().fg(color)),
+            Span::raw(issue.path.display().to_string()),
+            Span::styled(detail, Style::default().fg(theme.muted)),
         ]));
     }
 
+    text.push(Line::from(""));
+    text.push(Line::from(vec![
+        Span::styled(" ↑↓ ", theme.key_style()),
+        Span::raw(" Scroll  "),
+        Span::styled(" Esc ", theme.key_style()),
+        Span::raw(" Close"),
+    ]));
+
     frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
 }
 
+/// Renders the keybinding overview of every screen
+pub fn render_help_dialog(frame: &mut Frame, dialog: &HelpDialog, theme: &Theme) {
+    let area = centered_rect(70, frame.area().height.saturating_sub(2), frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Keyboard Shortcuts ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner.inner(Margin::new(1, 0)));
+
+    let section = |title: &str, bindings: &[KeyBinding]| {
+        let mut lines = vec![Line::from(Span::styled(
+            title.to_string(),
+            Style::default().fg(theme.title),
+        ))];
+        for binding in bindings {
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {:<10}", binding.keys), theme.key_style()),
+                Span::raw(format!("  {}", binding.description)),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines
+    };
+
+    let mut text = section("General", keymap::GLOBAL);
+    for &(title, screen) in keymap::HELP_SECTIONS {
+        text.extend(section(title, keymap::bindings(screen)));
+    }
+
+    let scroll = dialog.scroll.min(text.len().saturating_sub(1)) as u16;
+    frame.render_widget(Paragraph::new(text).scroll((scroll, 0)), chunks[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(" ↑↓ ", theme.key_style()),
+            Span::raw(" Scroll  "),
+            Span::styled(" Esc ", theme.key_style()),
+            Span::raw(" Close"),
+        ]))
+        .alignment(Alignment::Center),
+        chunks[1],
+    );
+}
+
 /// Renders disk space warning dialog
-pub fn render_disk_space_warning_dialog(frame: &mut Frame, dialog: &DiskSpaceWarningDialog) {
-    let area = centered_rect(60, 11, frame.area());
+pub fn render_disk_space_warning_dialog(
+    frame: &mut Frame,
+    dialog: &DiskSpaceWarningDialog,
+    theme: &Theme,
+) {
+    let inodes_short = dialog.inodes_short();
+    // A blank row below the keys
+    let height = dialog.line_count() as u16 + 3;
+    let area = centered_rect(60, height, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Low Disk Space ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.warning));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
     let side = if dialog.is_left { "Left" } else { "Right" };
-    let text = vec![
+    let mut text = vec![
         Line::from(""),
         Line::from(format!("{} destination may not have", side)),
         Line::from("enough space:"),
         Line::from(""),
         Line::from(vec![
-            Span::styled("Required:  ", Style::default().fg(Color::DarkGray)),
-            Span::styled(format_bytes(dialog.required), Style::default().fg(Color::Red)),
+            Span::styled("Required:  ", Style::default().fg(theme.muted)),
+            Span::styled(
+                format_bytes(dialog.required),
+                Style::default().fg(theme.error),
+            ),
         ]),
         Line::from(vec![
-            Span::styled("Available: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Available: ", Style::default().fg(theme.muted)),
             Span::styled(
                 format_bytes(dialog.available),
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(theme.warning),
             ),
         ]),
+    ];
+    if let Some(available_inodes) = dialog.available_inodes.filter(|_| inodes_short) {
+        text.extend([
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Files to create: ", Style::default().fg(theme.muted)),
+                Span::styled(
+                    dialog.required_inodes.to_string(),
+                    Style::default().fg(theme.error),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Free inodes:     ", Style::default().fg(theme.muted)),
+                Span::styled(
+                    available_inodes.to_string(),
+                    Style::default().fg(theme.warning),
+                ),
+            ]),
+        ]);
+    }
+    text.extend([
         Line::from(""),
         Line::from(vec![
-            Span::styled(" Y ", Style::default().fg(Color::Black).bg(Color::Yellow)),
+            Span::styled(" Y ", theme.badge(theme.warning)),
             Span::raw(" Continue anyway  "),
-            Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" N ", theme.key_style()),
             Span::raw(" Cancel"),
         ]),
+    ]);
+
+    render_lines_with_hint(frame, text, dialog.scroll, inner);
+}
+
+/// Renders the warning about a systematic mtime offset between the sides
+pub fn render_clock_skew_dialog(frame: &mut Frame, skew: &ClockSkew, theme: &Theme) {
+    let area = centered_rect(60, 13, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Clock Skew ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("File times suggest {}.", skew.describe()),
+            Style::default().fg(theme.warning),
+        )),
+        Line::from(Span::styled(
+            format!("{} files on both sides agree.", skew.samples),
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(""),
+        Line::from(
+            "Edits may look newer or older than they are, and conflicts may be \
+             resolved the wrong way. Fix the clock (or the drive's time zone) \
+             and analyze again, or review the preview carefully before syncing.",
+        ),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Enter ", theme.key_style()),
+            Span::raw(" Review anyway"),
+        ]),
     ];
 
-    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), inner);
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        inner,
+    );
+}
+
+/// Renders the warning about a preview too large to review comfortably
+pub fn render_large_preview_dialog(frame: &mut Frame, count: usize, theme: &Theme) {
+    let area = centered_rect(60, 12, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Large Preview ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.warning));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("The analysis found {} actions.", format_count(count)),
+            Style::default().fg(theme.warning),
+        )),
+        Line::from(""),
+        Line::from(
+            "Reviewing that many in the list is slow. You can go straight to \
+             the sync confirmation, which shows the totals, instead.",
+        ),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Enter ", theme.key_style()),
+            Span::raw(" Review the list  "),
+            Span::styled(" G ", theme.key_style()),
+            Span::raw(" Sync without reviewing  "),
+            Span::styled(" Esc ", theme.key_style()),
+            Span::raw(" Back"),
+        ]),
+    ];
+
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        inner,
+    );
+}
+
+/// Renders the recorded sync metadata of a path next to its scanned state
+pub fn render_metadata_inspect_dialog(
+    frame: &mut Frame,
+    dialog: &MetadataInspectDialog,
+    theme: &Theme,
+) {
+    let mut text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            dialog.path.display().to_string(),
+            Style::default().fg(theme.title),
+        )),
+        Line::from(Span::styled(
+            match dialog.last_sync {
+                Some(time) => format!("Last sync: {}", local_time(time)),
+                None => "No sync recorded yet".to_string(),
+            },
+            Style::default().fg(theme.muted),
+        )),
+    ];
+    text.extend(metadata_side_lines("Left", &dialog.left, theme));
+    text.extend(metadata_side_lines("Right", &dialog.right, theme));
+    text.push(Line::from(""));
+    text.push(Line::from(vec![
+        Span::styled(" Enter ", theme.key_style()),
+        Span::raw(" Close"),
+    ]));
+
+    let area = centered_rect(70, text.len() as u16 + 2, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Sync Metadata ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title));
+
+    let inner = block.inner(area).inner(Margin::new(1, 0));
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(text), inner);
+}
+
+/// Current and recorded values of one side, a row per field
+fn metadata_side_lines(
+    label: &'static str,
+    side: &MetadataSide,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let muted = Style::default().fg(theme.muted);
+    let row = |name: &'static str, current: String, recorded: String, differs: bool| {
+        let style = if differs {
+            Style::default().fg(theme.warning)
+        } else {
+            Style::default()
+        };
+        Line::from(vec![
+            Span::styled(format!("{:<13}", name), muted),
+            Span::styled(format!("{:<22}", current), style),
+            Span::styled(recorded, style),
+        ])
+    };
+
+    let current = side.current;
+    let recorded = side.recorded.as_ref();
+    let size_differs =
+        matches!((current, recorded), (Some(c), Some(r)) if !c.is_dir && c.size != r.size);
+    let mtime_differs = matches!(
+        (current.and_then(|c| c.mtime), recorded),
+        (Some(c), Some(r)) if !times_equal_with_tolerance(c, r.mtime)
+    );
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                format!("{:<13}", label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("{:<22}", "Current"), muted),
+            Span::styled("Recorded", muted),
+        ]),
+        row(
+            "Size",
+            match current {
+                Some(c) if c.is_dir => "directory".to_string(),
+                Some(c) => format_bytes(c.size),
+                None => "missing".to_string(),
+            },
+            recorded.map_or("not recorded".to_string(), |r| format_bytes(r.size)),
+            size_differs,
+        ),
+        row(
+            "Modified",
+            current
+                .and_then(|c| c.mtime)
+                .map_or("-".to_string(), local_time),
+            recorded.map_or("-".to_string(), |r| local_time(r.mtime)),
+            mtime_differs,
+        ),
+        row(
+            "Hash",
+            "-".to_string(),
+            recorded
+                .and_then(|r| r.hash.as_deref())
+                .map_or("not computed".to_string(), short_hash),
+            false,
+        ),
+        row(
+            "Last synced",
+            "-".to_string(),
+            recorded.map_or("-".to_string(), |r| local_time(r.last_synced)),
+            false,
+        ),
+    ];
+    if let Some(deleted) = &side.deleted {
+        lines.push(row(
+            "Deleted",
+            "-".to_string(),
+            format!(
+                "{} ({}, modified {})",
+                local_time(deleted.deleted_at),
+                format_bytes(deleted.size),
+                local_time(deleted.mtime)
+            ),
+            false,
+        ));
+    }
+    lines
+}
+
+/// `time` in the local time zone, to the second
+fn local_time(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// The first 16 hex digits of a hash
+fn short_hash(hash: &str) -> String {
+    match hash.get(..16) {
+        Some(prefix) if prefix.len() < hash.len() => format!("{}…", prefix),
+        _ => hash.to_string(),
+    }
 }
 
 /// Renders file error dialog (locked file, permission denied)
-pub fn render_file_error_dialog(frame: &mut Frame, dialog: &FileErrorDialog) {
+pub fn render_file_error_dialog(frame: &mut Frame, dialog: &FileErrorDialog, theme: &Theme) {
     let area = centered_rect(65, 11, frame.area());
     frame.render_widget(Clear, area);
 
     let (title, title_color) = match dialog.kind {
-        SyncErrorKind::FileLocked => (" File Locked ", Color::Yellow),
-        SyncErrorKind::PermissionDenied => (" Permission Denied ", Color::Red),
-        _ => (" Error ", Color::Red),
+        SyncErrorKind::FileLocked => (" File Locked ", theme.warning),
+        SyncErrorKind::PermissionDenied => (" Permission Denied ", theme.error),
+        _ => (" Error ", theme.error),
     };
 
     let block = Block::default()
@@ -423,27 +1399,30 @@ pub fn render_file_error_dialog(frame: &mut Frame, dialog: &FileErrorDialog) {
             } else {
                 path_str
             },
-            Style::default().fg(Color::Cyan),
+            Style::default().fg(theme.title),
         )),
         Line::from(""),
-        Line::from(Span::styled(&dialog.error, Style::default().fg(Color::Red))),
+        Line::from(Span::styled(
+            &dialog.error,
+            Style::default().fg(theme.error),
+        )),
         Line::from(""),
     ];
 
     if show_retry {
         text.push(Line::from(vec![
-            Span::styled(" R ", Style::default().fg(Color::Black).bg(Color::Yellow)),
+            Span::styled(" R ", theme.badge(theme.warning)),
             Span::raw(" Retry  "),
-            Span::styled(" S ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" S ", theme.key_style()),
             Span::raw(" Skip  "),
-            Span::styled(" C ", Style::default().fg(Color::Black).bg(Color::Red)),
+            Span::styled(" C ", theme.badge(theme.error)),
             Span::raw(" Cancel"),
         ]));
     } else {
         text.push(Line::from(vec![
-            Span::styled(" S ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" S ", theme.key_style()),
             Span::raw(" Skip  "),
-            Span::styled(" C ", Style::default().fg(Color::Black).bg(Color::Red)),
+            Span::styled(" C ", theme.badge(theme.error)),
             Span::raw(" Cancel"),
         ]));
     }
@@ -452,73 +1431,122 @@ pub fn render_file_error_dialog(frame: &mut Frame, dialog: &FileErrorDialog) {
 }
 
 /// Renders project settings dialog
-pub fn render_settings_dialog(frame: &mut Frame, dialog: &SettingsDialog) {
-    let area = centered_rect(55, 14, frame.area());
+pub fn render_settings_dialog(frame: &mut Frame, dialog: &SettingsDialog, theme: &Theme) {
+    let area = centered_rect(55, 24, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Project Settings ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.title));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    // A terminal too short for the full dialog drops the spacing rows, as
+    // the new project dialog does
+    let gap = if inner.height < 20 { 0 } else { 1 };
     let chunks = Layout::vertical([
-        Constraint::Length(1), // spacing
-        Constraint::Length(1), // backup versions
-        Constraint::Length(1), // spacing
-        Constraint::Length(1), // retention days
-        Constraint::Length(1), // spacing
-        Constraint::Length(1), // soft delete
-        Constraint::Length(1), // spacing
-        Constraint::Length(1), // verify hash
-        Constraint::Length(1), // spacing
-        Constraint::Min(1),    // hints/error
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // backup versions
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // retention days
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // min age
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // max age
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // soft delete
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // verify hash
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // skip empty dirs
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // append-only right
+        Constraint::Length(gap), // spacing
+        Constraint::Length(1),   // quick sync
+        Constraint::Length(gap), // spacing
+        Constraint::Min(1),      // hints/error
     ])
     .split(inner.inner(Margin::new(2, 0)));
 
     // Backup versions field
     let backup_style = if dialog.focused_field == SettingsField::BackupVersions {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let backup_line = Line::from(vec![
         Span::styled("Backup versions:    ", backup_style),
         Span::raw(&dialog.backup_versions),
         if dialog.focused_field == SettingsField::BackupVersions {
-            Span::styled("▌", Style::default().fg(Color::White))
+            Span::styled("▌", Style::default().fg(theme.text))
         } else {
             Span::raw("")
         },
-        Span::styled(" (1-100)", Style::default().fg(Color::DarkGray)),
+        Span::styled(" (1-100)", Style::default().fg(theme.muted)),
     ]);
     frame.render_widget(Paragraph::new(backup_line), chunks[1]);
 
     // Retention days field
     let retention_style = if dialog.focused_field == SettingsField::DeletedRetentionDays {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let retention_line = Line::from(vec![
         Span::styled("Deleted retention:  ", retention_style),
         Span::raw(&dialog.deleted_retention_days),
         if dialog.focused_field == SettingsField::DeletedRetentionDays {
-            Span::styled("▌", Style::default().fg(Color::White))
+            Span::styled("▌", Style::default().fg(theme.text))
         } else {
             Span::raw("")
         },
-        Span::styled(" days (0=off)", Style::default().fg(Color::DarkGray)),
+        Span::styled(" days (0=off)", Style::default().fg(theme.muted)),
     ]);
     frame.render_widget(Paragraph::new(retention_line), chunks[3]);
 
+    // Age limit fields
+    for (field, label, value, hint, chunk) in [
+        (
+            SettingsField::MinAgeDays,
+            "Min age:            ",
+            &dialog.min_age_days,
+            " days, skips newer (empty=off)",
+            chunks[5],
+        ),
+        (
+            SettingsField::MaxAgeDays,
+            "Max age:            ",
+            &dialog.max_age_days,
+            " days, skips older (empty=off)",
+            chunks[7],
+        ),
+    ] {
+        let focused = dialog.focused_field == field;
+        let style = if focused {
+            Style::default().fg(theme.focus)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        let line = Line::from(vec![
+            Span::styled(label, style),
+            Span::raw(value.as_str()),
+            if focused {
+                Span::styled("▌", Style::default().fg(theme.text))
+            } else {
+                Span::raw("")
+            },
+            Span::styled(hint, Style::default().fg(theme.muted)),
+        ]);
+        frame.render_widget(Paragraph::new(line), chunk);
+    }
+
     // Soft delete toggle
     let soft_style = if dialog.focused_field == SettingsField::SoftDelete {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let soft_value = if dialog.soft_delete { "Yes" } else { "No " };
     let soft_line = Line::from(vec![
@@ -526,20 +1554,20 @@ pub fn render_settings_dialog(frame: &mut Frame, dialog: &SettingsDialog) {
         Span::styled(
             format!("[{}]", soft_value),
             if dialog.soft_delete {
-                Style::default().fg(Color::Green)
+                Style::default().fg(theme.success)
             } else {
-                Style::default().fg(Color::Red)
+                Style::default().fg(theme.error)
             },
         ),
-        Span::styled(" (Space to toggle)", Style::default().fg(Color::DarkGray)),
+        Span::styled(" (Space to toggle)", Style::default().fg(theme.muted)),
     ]);
-    frame.render_widget(Paragraph::new(soft_line), chunks[5]);
+    frame.render_widget(Paragraph::new(soft_line), chunks[9]);
 
     // Verify hash toggle
     let hash_style = if dialog.focused_field == SettingsField::VerifyHash {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let hash_value = if dialog.verify_hash { "Yes" } else { "No " };
     let hash_line = Line::from(vec![
@@ -547,27 +1575,243 @@ pub fn render_settings_dialog(frame: &mut Frame, dialog: &SettingsDialog) {
         Span::styled(
             format!("[{}]", hash_value),
             if dialog.verify_hash {
-                Style::default().fg(Color::Green)
+                Style::default().fg(theme.success)
+            } else {
+                Style::default().fg(theme.error)
+            },
+        ),
+        Span::styled(" (Space to toggle)", Style::default().fg(theme.muted)),
+    ]);
+    frame.render_widget(Paragraph::new(hash_line), chunks[11]);
+
+    // Skip empty dirs toggle
+    let empty_dirs_style = if dialog.focused_field == SettingsField::SkipEmptyDirs {
+        Style::default().fg(theme.focus)
+    } else {
+        Style::default().fg(theme.muted)
+    };
+    let empty_dirs_value = if dialog.skip_empty_dirs { "Yes" } else { "No " };
+    let empty_dirs_line = Line::from(vec![
+        Span::styled("Skip empty dirs:    ", empty_dirs_style),
+        Span::styled(
+            format!("[{}]", empty_dirs_value),
+            if dialog.skip_empty_dirs {
+                Style::default().fg(theme.success)
             } else {
-                Style::default().fg(Color::Red)
+                Style::default().fg(theme.error)
             },
         ),
-        Span::styled(" (Space to toggle)", Style::default().fg(Color::DarkGray)),
+        Span::styled(" (Space to toggle)", Style::default().fg(theme.muted)),
     ]);
-    frame.render_widget(Paragraph::new(hash_line), chunks[7]);
+    frame.render_widget(Paragraph::new(empty_dirs_line), chunks[13]);
+
+    // Append-only right toggle
+    let append_style = if dialog.focused_field == SettingsField::AppendOnlyRight {
+        Style::default().fg(theme.focus)
+    } else {
+        Style::default().fg(theme.muted)
+    };
+    let append_value = if dialog.append_only_right {
+        "Yes"
+    } else {
+        "No "
+    };
+    let append_line = Line::from(vec![
+        Span::styled("Append-only right:  ", append_style),
+        Span::styled(
+            format!("[{}]", append_value),
+            if dialog.append_only_right {
+                Style::default().fg(theme.success)
+            } else {
+                Style::default().fg(theme.error)
+            },
+        ),
+        Span::styled(" (Space to toggle)", Style::default().fg(theme.muted)),
+    ]);
+    frame.render_widget(Paragraph::new(append_line), chunks[15]);
+
+    // Quick sync toggle
+    let quick_style = if dialog.focused_field == SettingsField::QuickSync {
+        Style::default().fg(theme.focus)
+    } else {
+        Style::default().fg(theme.muted)
+    };
+    let quick_value = if dialog.quick_sync { "Yes" } else { "No " };
+    let quick_line = Line::from(vec![
+        Span::styled("Quick sync:         ", quick_style),
+        Span::styled(
+            format!("[{}]", quick_value),
+            if dialog.quick_sync {
+                Style::default().fg(theme.success)
+            } else {
+                Style::default().fg(theme.error)
+            },
+        ),
+        Span::styled(" (trusts mtimes)", Style::default().fg(theme.muted)),
+    ]);
+    frame.render_widget(Paragraph::new(quick_line), chunks[17]);
 
     // Hints or error
     let hint = if let Some(ref error) = dialog.error {
-        Line::from(Span::styled(error, Style::default().fg(Color::Red)))
+        Line::from(Span::styled(error, Style::default().fg(theme.error)))
     } else {
         Line::from(vec![
-            Span::styled(" Tab ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Tab ", theme.key_style()),
             Span::raw(" Next  "),
-            Span::styled(" Enter ", Style::default().fg(Color::Black).bg(Color::Green)),
+            Span::styled(" Enter ", theme.badge(theme.success)),
             Span::raw(" Save  "),
-            Span::styled(" Esc ", Style::default().fg(Color::Black).bg(Color::Gray)),
+            Span::styled(" Esc ", theme.key_style()),
             Span::raw(" Cancel"),
         ])
     };
-    frame.render_widget(Paragraph::new(hint), chunks[9]);
+    frame.render_widget(Paragraph::new(hint), chunks[19]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::history::PathHistory;
+    use crate::config::project::ProjectSettings;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_new_project_dialog_in_tiny_terminal() {
+        let mut history = PathHistory::default();
+        history.record("/home/me/docs");
+        let mut dialog = NewProjectDialog::with_history(history);
+        dialog.focused_field = DialogField::LeftPath;
+        let theme = Theme::default();
+
+        let render = |width, height| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| render_new_project_dialog(frame, &dialog, &theme))
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+
+        let full = render(80, 24);
+        assert!(full.contains("recent paths"));
+
+        // Too short for the full dialog: the fields and the hint still show
+        let short = render(60, 8);
+        assert!(short.contains("Right path:"));
+        assert!(short.contains("Next"));
+        assert!(!short.contains("recent paths"));
+
+        for (width, height) in [(12, 4), (4, 2), (1, 1), (0, 0)] {
+            render(width, height);
+        }
+    }
+
+    /// The text `draw` leaves in a terminal of the given size
+    fn screen(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(draw).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    }
+
+    #[test]
+    fn test_settings_dialog_in_short_terminal() {
+        let dialog = SettingsDialog::from_settings(&ProjectSettings::default());
+        let theme = Theme::default();
+
+        // Too short for the spacing rows: every field and the hint still show
+        let short = screen(100, 16, |frame| {
+            render_settings_dialog(frame, &dialog, &theme)
+        });
+        assert!(short.contains("Backup versions:"));
+        assert!(short.contains("Quick sync:"));
+        assert!(short.contains("Save"));
+
+        for (width, height) in [(12, 4), (1, 1), (0, 0)] {
+            screen(width, height, |frame| {
+                render_settings_dialog(frame, &dialog, &theme)
+            });
+        }
+    }
+
+    #[test]
+    fn test_sync_confirm_dialog_scrolls_in_short_terminal() {
+        let mut dialog = SyncConfirmDialog {
+            files_to_copy: 3,
+            files_to_overwrite: 1,
+            files_to_delete: 2,
+            bytes_to_transfer: 1024,
+            dirs_to_create: 0,
+            attributes_to_update: 0,
+            conflicts_accepted: 1,
+            backup_versions: 0,
+            soft_delete: false,
+            selection: None,
+            scroll: 0,
+        };
+        let theme = Theme::default();
+
+        let full = screen(80, 24, |frame| {
+            render_sync_confirm_dialog(frame, &dialog, &theme)
+        });
+        assert!(full.contains("Safe"));
+        assert!(full.contains("removed permanently"));
+        assert!(full.contains("Start"));
+
+        // The keys stay in view; the summary above them scrolls
+        let top = screen(80, 10, |frame| {
+            render_sync_confirm_dialog(frame, &dialog, &theme)
+        });
+        assert!(top.contains("Safe"));
+        assert!(!top.contains("removed permanently"));
+        assert!(top.contains("Start"));
+
+        dialog.scroll = dialog.line_count();
+        let bottom = screen(80, 10, |frame| {
+            render_sync_confirm_dialog(frame, &dialog, &theme)
+        });
+        assert!(!bottom.contains("Safe"));
+        assert!(bottom.contains("removed permanently"));
+        assert!(bottom.contains("Start"));
+
+        for (width, height) in [(12, 4), (1, 1), (0, 0)] {
+            screen(width, height, |frame| {
+                render_sync_confirm_dialog(frame, &dialog, &theme)
+            });
+        }
+    }
+
+    #[test]
+    fn test_disk_space_dialog_keeps_keys_in_short_terminal() {
+        let dialog = DiskSpaceWarningDialog {
+            is_left: false,
+            path: PathBuf::from("/mnt/backup"),
+            available: 1024,
+            required: 4096,
+            available_inodes: Some(1),
+            required_inodes: 10,
+            scroll: 0,
+        };
+        let theme = Theme::default();
+
+        let full = screen(80, 24, |frame| {
+            render_disk_space_warning_dialog(frame, &dialog, &theme)
+        });
+        assert!(full.contains("Free inodes:"));
+
+        let short = screen(80, 8, |frame| {
+            render_disk_space_warning_dialog(frame, &dialog, &theme)
+        });
+        assert!(short.contains("Continue anyway"));
+        assert!(!short.contains("Free inodes:"));
+    }
 }
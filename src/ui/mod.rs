@@ -1,16 +1,27 @@
 //! TUI components and widgets
 
+pub mod console;
 pub mod dialogs;
 pub mod screens;
 pub mod sync_ui;
+pub mod terminal;
+pub mod theme;
 pub mod widgets;
 
+pub use console::ConsoleProgress;
 pub use dialogs::{
-    render_cancel_sync_confirm_dialog, render_create_dir_confirm_dialog,
-    render_delete_confirm_dialog, render_disk_space_warning_dialog, render_error_dialog,
-    render_exclusions_info_dialog, render_file_error_dialog, render_new_project_dialog,
-    render_settings_dialog, render_sync_confirm_dialog,
+    render_backup_browser_dialog, render_cancel_sync_confirm_dialog, render_clock_skew_dialog,
+    render_create_dir_confirm_dialog, render_delete_confirm_dialog,
+    render_disk_space_warning_dialog, render_error_dialog, render_exclusions_info_dialog,
+    render_file_error_dialog, render_help_dialog, render_incomplete_transfers_dialog,
+    render_large_preview_dialog, render_metadata_inspect_dialog, render_new_project_dialog,
+    render_project_file_dialog, render_quit_confirm_dialog, render_resolve_all_confirm_dialog,
+    render_script_dialog, render_settings_dialog, render_swap_sides_confirm_dialog,
+    render_sync_confirm_dialog, render_target_status_dialog, render_verify_report_dialog,
+};
+pub use screens::{
+    render_analyze_all, render_history, render_preview, render_project_list, render_project_view,
 };
-pub use screens::{render_preview, render_project_list, render_project_view};
 pub use sync_ui::{render_sync_complete, render_syncing};
-pub use widgets::{centered_rect, field_style, format_bytes, format_duration};
+pub use theme::Theme;
+pub use widgets::{centered_rect, field_style, format_bytes, format_count, format_duration};
@@ -1,8 +1,10 @@
 //! Screen rendering functions
 
+use std::collections::HashSet;
+
 use ratatui::{
     layout::{Constraint, Layout, Margin, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{
         Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
@@ -11,29 +13,35 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{PreviewState, PreviewSummary, UserAction};
+use crate::app::{
+    ActionGroup, AnalyzeAllState, FileStamp, HistoryState, PreviewRow, PreviewState,
+    PreviewSummary, ProjectStatus, UserAction,
+};
 use crate::config::project::Project;
 use crate::sync::differ::{ConflictReason, SyncAction};
-use crate::ui::format_bytes;
+use crate::sync::scanner::ScanStats;
+use crate::ui::{format_bytes, format_count, format_duration, Theme};
 
 /// Render the project list screen
 pub fn render_project_list(
     frame: &mut Frame,
     area: Rect,
     projects: &[String],
+    pinned: &HashSet<String>,
     list_state: &mut ListState,
+    theme: &Theme,
 ) {
     if projects.is_empty() {
         let empty_msg = Paragraph::new(vec![
             Line::from(""),
             Line::from(Span::styled(
                 "No projects configured",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.muted),
             )),
             Line::from(""),
             Line::from(vec![
                 Span::raw("Press "),
-                Span::styled(" N ", Style::default().fg(Color::Black).bg(Color::Gray)),
+                Span::styled(" N ", theme.key_style()),
                 Span::raw(" to create a new project"),
             ]),
         ])
@@ -41,7 +49,7 @@ pub fn render_project_list(
             Block::default()
                 .borders(Borders::ALL)
                 .title(" Projects ")
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(theme.border)),
         );
         frame.render_widget(empty_msg, area);
         return;
@@ -49,7 +57,13 @@ pub fn render_project_list(
 
     let items: Vec<ListItem> = projects
         .iter()
-        .map(|name| ListItem::new(Line::from(format!("  {}  ", name))))
+        .map(|name| {
+            let mut spans = vec![Span::raw(format!("  {}  ", name))];
+            if pinned.contains(name) {
+                spans.push(Span::styled("📌", Style::default().fg(theme.warning)));
+            }
+            ListItem::new(Line::from(spans))
+        })
         .collect();
 
     let list = List::new(items)
@@ -57,12 +71,12 @@ pub fn render_project_list(
             Block::default()
                 .borders(Borders::ALL)
                 .title(format!(" Projects ({}) ", projects.len()))
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(theme.border)),
         )
         .highlight_style(
             Style::default()
-                .bg(Color::DarkGray)
-                .fg(Color::White)
+                .bg(theme.selection_bg)
+                .fg(theme.selection_fg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("▶ ");
@@ -71,33 +85,38 @@ pub fn render_project_list(
 }
 
 /// Render the project view screen
-pub fn render_project_view(frame: &mut Frame, area: Rect, project: Option<&Project>) {
+pub fn render_project_view(
+    frame: &mut Frame,
+    area: Rect,
+    project: Option<&Project>,
+    theme: &Theme,
+) {
     let content = if let Some(project) = project {
         vec![
             Line::from(""),
             Line::from(vec![
-                Span::styled("Name: ", Style::default().fg(Color::DarkGray)),
-                Span::styled(&project.name, Style::default().fg(Color::White)),
+                Span::styled("Name: ", Style::default().fg(theme.muted)),
+                Span::styled(&project.name, Style::default().fg(theme.text)),
             ]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("Left:  ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Left:  ", Style::default().fg(theme.muted)),
                 Span::styled(
                     project.left_path.display().to_string(),
-                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(theme.title),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("Right: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Right: ", Style::default().fg(theme.muted)),
                 Span::styled(
                     project.right_path.display().to_string(),
-                    Style::default().fg(Color::Cyan),
+                    Style::default().fg(theme.title),
                 ),
             ]),
             Line::from(""),
             Line::from(vec![
                 Span::raw("Press "),
-                Span::styled(" A ", Style::default().fg(Color::Black).bg(Color::Green)),
+                Span::styled(" A ", theme.badge(theme.success)),
                 Span::raw(" to analyze"),
             ]),
         ]
@@ -109,24 +128,194 @@ pub fn render_project_view(frame: &mut Frame, area: Rect, project: Option<&Proje
         Block::default()
             .borders(Borders::ALL)
             .title(" Project Details ")
-            .border_style(Style::default().fg(Color::DarkGray)),
+            .border_style(Style::default().fg(theme.border)),
     );
 
     frame.render_widget(paragraph, area);
 }
 
+/// Render the analyze-all overview: pending changes of every project
+pub fn render_analyze_all(frame: &mut Frame, area: Rect, state: &AnalyzeAllState, theme: &Theme) {
+    let name_width = state
+        .entries
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let items: Vec<ListItem> = state
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, (name, status))| {
+            let mut spans = vec![Span::raw(format!(
+                "  {:<width$}  ",
+                name,
+                width = name_width
+            ))];
+            match status {
+                ProjectStatus::Pending if i == state.next => {
+                    spans.push(Span::styled(
+                        "analyzing...",
+                        Style::default().fg(theme.focus),
+                    ));
+                }
+                ProjectStatus::Pending => {
+                    spans.push(Span::styled("waiting", Style::default().fg(theme.muted)));
+                }
+                ProjectStatus::Error(e) => {
+                    spans.push(Span::styled(
+                        format!("error: {}", e),
+                        Style::default().fg(theme.error),
+                    ));
+                }
+                ProjectStatus::Analyzed(summary) if summary.pending_changes() == 0 => {
+                    spans.push(Span::styled("in sync", Style::default().fg(theme.success)));
+                }
+                ProjectStatus::Analyzed(summary) => {
+                    spans.push(Span::styled(
+                        format!(
+                            "→{} ({})  ←{} ({})  del {}  dirs {}",
+                            summary.copy_to_right,
+                            format_bytes(summary.bytes_to_right),
+                            summary.copy_to_left,
+                            format_bytes(summary.bytes_to_left),
+                            summary.delete_right + summary.delete_left,
+                            summary.dirs_to_create,
+                        ),
+                        Style::default().fg(theme.text),
+                    ));
+                    if summary.conflicts > 0 {
+                        spans.push(Span::styled(
+                            format!("  conflicts {}", summary.conflicts),
+                            Style::default().fg(theme.warning),
+                        ));
+                    }
+                }
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let analyzed = state.next.min(state.entries.len());
+    let title = format!(
+        " Analyze All ({}/{}) — {} to copy ",
+        analyzed,
+        state.entries.len(),
+        format_bytes(state.total_bytes())
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection_bg)
+                .fg(theme.selection_fg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▶ ");
+
+    let mut list_state = ListState::default().with_selected(Some(state.selected));
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// Width of the bar showing each run's bytes relative to the largest
+const HISTORY_BAR_WIDTH: usize = 20;
+
+/// Render the past syncs of a project, newest first, with a bar per run
+/// to show how the transferred bytes trend
+pub fn render_history(frame: &mut Frame, area: Rect, state: &HistoryState, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Sync History ({} runs) ", state.runs.len()))
+        .border_style(Style::default().fg(theme.border));
+    if state.runs.is_empty() {
+        let text = Paragraph::new(Span::styled(
+            "  No syncs recorded yet",
+            Style::default().fg(theme.muted),
+        ))
+        .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let largest = state
+        .runs
+        .iter()
+        .map(|run| run.bytes_transferred)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let items: Vec<ListItem> = state
+        .runs
+        .iter()
+        .skip(state.scroll)
+        .map(|run| {
+            let filled = (run.bytes_transferred as f64 / largest as f64 * HISTORY_BAR_WIDTH as f64)
+                .ceil() as usize;
+            let mut spans = vec![
+                Span::raw(format!(
+                    "  {}  ",
+                    run.finished
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                )),
+                Span::styled(
+                    format!("{:<width$}", "█".repeat(filled), width = HISTORY_BAR_WIDTH),
+                    Style::default().fg(theme.focus),
+                ),
+                Span::raw(format!(
+                    "  {:>10}  {:>8} files  {:>8}",
+                    format_bytes(run.bytes_transferred),
+                    format_count(run.completed),
+                    format_duration(run.duration())
+                )),
+            ];
+            if run.failed > 0 {
+                spans.push(Span::styled(
+                    format!("  {} failed", run.failed),
+                    Style::default().fg(theme.error),
+                ));
+            }
+            if run.skipped > 0 {
+                spans.push(Span::styled(
+                    format!("  {} skipped", run.skipped),
+                    Style::default().fg(theme.warning),
+                ));
+            }
+            if run.cancelled {
+                spans.push(Span::styled(
+                    "  cancelled",
+                    Style::default().fg(theme.warning),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    frame.render_widget(List::new(items).block(block), area);
+}
+
 /// Render the preview screen with action list and summary
-pub fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState) {
-    // Split area for list and summary
+pub fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState, theme: &Theme) {
+    // Split area for list, details and summary
+    let details_height = if preview.show_details { 6 } else { 0 };
     let chunks = Layout::vertical([
-        Constraint::Min(5),    // Action list
-        Constraint::Length(4), // Summary
+        Constraint::Min(5),                 // Action list
+        Constraint::Length(details_height), // Selected action details
+        Constraint::Length(5),              // Summary
     ])
     .split(area);
 
     // Render action list
-    let indices = preview.filtered_indices();
+    // Only the visible window of rows becomes list items
+    let rows = preview.rows();
     let visible_height = chunks[0].height.saturating_sub(2) as usize;
+    preview.page_rows.set(visible_height);
 
     // Adjust scroll offset
     let scroll_offset = if preview.selected >= visible_height {
@@ -135,39 +324,69 @@ pub fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState) {
         0
     };
 
-    let items: Vec<ListItem> = indices
+    let range: HashSet<usize> = preview
+        .range_indices()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let items: Vec<ListItem> = rows
         .iter()
         .skip(scroll_offset)
         .take(visible_height)
         .enumerate()
-        .map(|(display_idx, &real_idx)| {
-            let action = &preview.actions[real_idx];
+        .map(|(display_idx, row)| {
             let is_selected = display_idx + scroll_offset == preview.selected;
-            let is_marked = preview.selected_items.contains(&real_idx);
-
-            render_action_item(action, is_selected, is_marked)
+            match *row {
+                PreviewRow::Header { group, count } => {
+                    let collapsed = preview.collapsed.contains(&group);
+                    render_group_header(group, count, collapsed, is_selected, theme)
+                }
+                PreviewRow::Action(real_idx) => {
+                    let action = &preview.actions[real_idx];
+                    let is_marked =
+                        preview.selected_items.contains(&real_idx) || range.contains(&real_idx);
+                    render_action_item(action, is_selected, is_marked, theme)
+                }
+            }
         })
         .collect();
 
-    let list = List::new(items).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(format!(
-                " Actions ({}/{}) ",
-                indices.len(),
-                preview.actions.len()
-            ))
-            .border_style(Style::default().fg(Color::DarkGray)),
-    );
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            " Actions ({}/{}) ",
+            preview.shown_count(),
+            preview.actions.len()
+        ))
+        .border_style(Style::default().fg(theme.border));
+    if preview.range_anchor.is_some() {
+        block = block.title(
+            Line::styled(
+                format!(" range: {} ", range.len()),
+                Style::default().fg(theme.info),
+            )
+            .right_aligned(),
+        );
+    }
+    if let Some((position, count)) = preview.conflict_position() {
+        block = block.title(
+            Line::styled(
+                format!(" conflict {}/{} ", position, count),
+                Style::default().fg(theme.warning),
+            )
+            .right_aligned(),
+        );
+    }
+    let list = List::new(items).block(block);
 
     frame.render_widget(list, chunks[0]);
 
     // Render scrollbar if needed
-    if indices.len() > visible_height {
+    if rows.len() > visible_height {
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
             .end_symbol(None);
-        let mut scrollbar_state = ScrollbarState::new(indices.len()).position(preview.selected);
+        let mut scrollbar_state = ScrollbarState::new(rows.len()).position(preview.selected);
         frame.render_stateful_widget(
             scrollbar,
             chunks[0].inner(Margin::new(0, 1)),
@@ -175,79 +394,279 @@ pub fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState) {
         );
     }
 
+    if preview.show_details {
+        render_action_details(frame, chunks[1], preview, theme);
+    }
+
     // Render summary
     let summary = preview.summary();
-    render_summary(frame, chunks[1], &summary);
+    render_summary(
+        frame,
+        chunks[2],
+        &summary,
+        &preview.left_stats,
+        &preview.right_stats,
+        theme,
+    );
+}
+
+/// Renders size and mtime of both sides of the selected action and how they compare
+fn render_action_details(frame: &mut Frame, area: Rect, preview: &PreviewState, theme: &Theme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Details ")
+        .border_style(Style::default().fg(theme.border));
+
+    let text = match preview.selected_details() {
+        Some((left, right)) => vec![
+            stamp_line("Left:  ", left, theme),
+            stamp_line("Right: ", right, theme),
+            Line::from(Span::styled(
+                compare_stamps(left, right),
+                Style::default().fg(theme.title),
+            )),
+            Line::from(vec![
+                Span::styled("Why:   ", Style::default().fg(theme.muted)),
+                Span::raw(preview.selected_explanation().unwrap_or_default()),
+            ]),
+        ],
+        None => vec![Line::from(Span::styled(
+            "Select an item to see its details",
+            Style::default().fg(theme.muted),
+        ))],
+    };
+
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// One side's size and local mtime, or that the item is missing there
+fn stamp_line(label: &'static str, stamp: Option<FileStamp>, theme: &Theme) -> Line<'static> {
+    let label = Span::styled(label, Style::default().fg(theme.muted));
+    let Some(stamp) = stamp else {
+        return Line::from(vec![
+            label,
+            Span::styled("missing", Style::default().fg(theme.error)),
+        ]);
+    };
+    let size = if stamp.is_dir {
+        "directory".to_string()
+    } else {
+        format_bytes(stamp.size)
+    };
+    let mtime = stamp.mtime.map_or("unknown mtime".to_string(), |t| {
+        t.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    });
+    Line::from(vec![label, Span::raw(format!("{:>10}  {}", size, mtime))])
+}
+
+/// Which side is newer and larger, e.g. "Left is newer by 2h 5m, larger by 1.5 KB"
+fn compare_stamps(left: Option<FileStamp>, right: Option<FileStamp>) -> String {
+    let (Some(left), Some(right)) = (left, right) else {
+        return "Only on one side".to_string();
+    };
+
+    let mut parts = Vec::new();
+    if let (Some(l), Some(r)) = (left.mtime, right.mtime) {
+        let delta = (l - r).num_seconds();
+        if delta != 0 {
+            let side = if delta > 0 { "Left" } else { "Right" };
+            parts.push(format!(
+                "{} is newer by {}",
+                side,
+                format_age(delta.unsigned_abs())
+            ));
+        }
+    }
+    if !left.is_dir && !right.is_dir && left.size != right.size {
+        let (side, delta) = if left.size > right.size {
+            ("Left", left.size - right.size)
+        } else {
+            ("Right", right.size - left.size)
+        };
+        parts.push(format!("{} is larger by {}", side, format_bytes(delta)));
+    }
+
+    if parts.is_empty() {
+        "Same size and mtime".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Compact age of a time difference, e.g. "3d 4h", "2h 5m" or "42s"
+fn format_age(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
 }
 
 /// Render the preview summary
-pub fn render_summary(frame: &mut Frame, area: Rect, summary: &PreviewSummary) {
+pub fn render_summary(
+    frame: &mut Frame,
+    area: Rect,
+    summary: &PreviewSummary,
+    left_stats: &ScanStats,
+    right_stats: &ScanStats,
+    theme: &Theme,
+) {
     let total_bytes = summary.bytes_to_right + summary.bytes_to_left;
 
+    let mut scan_line = vec![
+        Span::styled("Left: ", Style::default().fg(theme.muted)),
+        Span::raw(format_scan_stats(left_stats)),
+        Span::raw("  "),
+        Span::styled("Right: ", Style::default().fg(theme.muted)),
+        Span::raw(format_scan_stats(right_stats)),
+    ];
+    let unreadable = left_stats.unreadable_dirs + right_stats.unreadable_dirs;
+    if unreadable > 0 {
+        scan_line.push(Span::styled(
+            format!(
+                "  ⚠ {} {} unreadable",
+                unreadable,
+                if unreadable == 1 {
+                    "directory"
+                } else {
+                    "directories"
+                }
+            ),
+            Style::default().fg(theme.warning),
+        ));
+    }
+
+    let mut totals_line = vec![
+        Span::styled("Total: ", Style::default().fg(theme.muted)),
+        Span::raw(format_bytes(total_bytes)),
+        Span::raw("  "),
+        Span::styled("Dirs: ", Style::default().fg(theme.muted)),
+        Span::raw(format!("{}", summary.dirs_to_create)),
+        Span::raw("  "),
+        Span::styled("Skip: ", Style::default().fg(theme.muted)),
+        Span::raw(format!("{}", summary.skipped)),
+    ];
+    if summary.attributes_to_update > 0 {
+        totals_line.push(Span::styled(
+            "  Attributes: ",
+            Style::default().fg(theme.muted),
+        ));
+        totals_line.push(Span::raw(format!("{}", summary.attributes_to_update)));
+    }
+    if summary.content_elsewhere > 0 {
+        totals_line.push(Span::styled(
+            format!(
+                "  ≡ {} under another name on the other side",
+                summary.content_elsewhere
+            ),
+            Style::default().fg(theme.warning),
+        ));
+    }
+
     let lines = vec![
         Line::from(vec![
-            Span::styled("→ ", Style::default().fg(Color::Green)),
+            Span::styled("→ ", Style::default().fg(theme.success)),
             Span::raw(format!("{} files ", summary.copy_to_right)),
-            Span::styled("← ", Style::default().fg(Color::Blue)),
+            Span::styled("← ", Style::default().fg(theme.info)),
             Span::raw(format!("{} files ", summary.copy_to_left)),
-            Span::styled("✕ ", Style::default().fg(Color::Red)),
+            Span::styled("✕ ", Style::default().fg(theme.error)),
             Span::raw(format!(
                 "{} del ",
                 summary.delete_left + summary.delete_right
             )),
-            Span::styled("⚠ ", Style::default().fg(Color::Yellow)),
+            Span::styled("⚠ ", Style::default().fg(theme.warning)),
             Span::raw(format!("{} conflicts", summary.conflicts)),
+            Span::styled(" = ", Style::default().fg(theme.success)),
+            Span::raw(format!("{} accepted", summary.accepted)),
         ]),
-        Line::from(vec![
-            Span::styled("Total: ", Style::default().fg(Color::DarkGray)),
-            Span::raw(format_bytes(total_bytes)),
-            Span::raw("  "),
-            Span::styled("Dirs: ", Style::default().fg(Color::DarkGray)),
-            Span::raw(format!("{}", summary.dirs_to_create)),
-            Span::raw("  "),
-            Span::styled("Skip: ", Style::default().fg(Color::DarkGray)),
-            Span::raw(format!("{}", summary.skipped)),
-        ]),
+        Line::from(totals_line),
+        Line::from(scan_line),
     ];
 
     let paragraph = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
             .title(" Summary ")
-            .border_style(Style::default().fg(Color::DarkGray)),
+            .border_style(Style::default().fg(theme.border)),
     );
 
     frame.render_widget(paragraph, area);
 }
 
+/// Formats scan totals as "12,340 files, 45.0 GB"
+fn format_scan_stats(stats: &ScanStats) -> String {
+    format!(
+        "{} files, {}",
+        format_count(stats.total_files),
+        format_bytes(stats.total_bytes)
+    )
+}
+
+/// Render a section header of the grouped preview, e.g. "▾ Copy → (12)"
+fn render_group_header(
+    group: ActionGroup,
+    count: usize,
+    collapsed: bool,
+    is_selected: bool,
+    theme: &Theme,
+) -> ListItem<'static> {
+    let fold = if collapsed { "▸" } else { "▾" };
+    let style = if is_selected {
+        theme.selection_style()
+    } else {
+        Style::default().fg(theme.title)
+    };
+
+    ListItem::new(Line::from(Span::styled(
+        format!("{} {} ({})", fold, group.label(), count),
+        style.add_modifier(Modifier::BOLD),
+    )))
+}
+
 /// Render a single action item in the preview list
 pub fn render_action_item(
     action: &UserAction,
     is_selected: bool,
     is_marked: bool,
+    theme: &Theme,
 ) -> ListItem<'static> {
     let (symbol, color, path_str) = match action {
         UserAction::Original(SyncAction::CopyToRight { path, size }) => (
             "→",
-            Color::Green,
+            theme.success,
             format!("{} ({})", path.display(), format_bytes(*size)),
         ),
         UserAction::Original(SyncAction::CopyToLeft { path, size }) => (
             "←",
-            Color::Blue,
+            theme.info,
             format!("{} ({})", path.display(), format_bytes(*size)),
         ),
         UserAction::Original(SyncAction::DeleteRight { path }) => {
-            ("✕→", Color::Red, path.display().to_string())
+            ("✕→", theme.error, path.display().to_string())
         }
         UserAction::Original(SyncAction::DeleteLeft { path }) => {
-            ("←✕", Color::Red, path.display().to_string())
+            ("←✕", theme.error, path.display().to_string())
         }
         UserAction::Original(SyncAction::CreateDirRight { path }) => {
-            ("📁→", Color::Green, path.display().to_string())
+            ("📁→", theme.success, path.display().to_string())
         }
         UserAction::Original(SyncAction::CreateDirLeft { path }) => {
-            ("←📁", Color::Blue, path.display().to_string())
+            ("←📁", theme.info, path.display().to_string())
+        }
+        UserAction::Original(SyncAction::UpdateAttributesRight { path }) => (
+            "⚙→",
+            theme.success,
+            format!("{} (attributes)", path.display()),
+        ),
+        UserAction::Original(SyncAction::UpdateAttributesLeft { path }) => {
+            ("←⚙", theme.info, format!("{} (attributes)", path.display()))
         }
         UserAction::Original(SyncAction::Conflict { path, reason, .. }) => {
             let reason_str = match reason {
@@ -258,33 +677,48 @@ pub fn render_action_item(
             };
             (
                 "⚠",
-                Color::Yellow,
+                theme.warning,
                 format!("{} ({})", path.display(), reason_str),
             )
         }
         UserAction::Original(SyncAction::Skip { path, .. }) => {
-            ("·", Color::DarkGray, path.display().to_string())
+            ("·", theme.muted, path.display().to_string())
         }
         UserAction::CopyToRight { path, size } => (
             "→*",
-            Color::Green,
+            theme.success,
             format!("{} ({})", path.display(), format_bytes(*size)),
         ),
         UserAction::CopyToLeft { path, size } => (
             "←*",
-            Color::Blue,
+            theme.info,
             format!("{} ({})", path.display(), format_bytes(*size)),
         ),
-        UserAction::DeleteLeft { path } => ("←✕*", Color::Red, path.display().to_string()),
-        UserAction::DeleteRight { path } => ("✕→*", Color::Red, path.display().to_string()),
-        UserAction::Skip { path } => ("·*", Color::DarkGray, path.display().to_string()),
+        UserAction::DeleteLeft { path } => ("←✕*", theme.error, path.display().to_string()),
+        UserAction::DeleteRight { path } => ("✕→*", theme.error, path.display().to_string()),
+        UserAction::Skip { path } => ("·*", theme.muted, path.display().to_string()),
+        UserAction::AcceptCurrent { path } => (
+            "=*",
+            theme.success,
+            format!("{} (accepted as-is)", path.display()),
+        ),
+        UserAction::Original(SyncAction::KeepBoth { path, copy, .. })
+        | UserAction::KeepBoth { path, copy, .. } => (
+            "⇄*",
+            theme.success,
+            format!(
+                "{} (keep both, right as {})",
+                path.display(),
+                copy.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        ),
     };
 
     let marker = if is_marked { "● " } else { "  " };
     let modified_indicator = if action.is_modified() { "*" } else { "" };
 
     let style = if is_selected {
-        Style::default().bg(Color::DarkGray).fg(Color::White)
+        theme.selection_style()
     } else {
         Style::default()
     };
@@ -294,6 +728,38 @@ pub fn render_action_item(
         Span::styled(format!("{:<3}", symbol), Style::default().fg(color)),
         Span::raw(" "),
         Span::styled(path_str, style),
-        Span::styled(modified_indicator, Style::default().fg(Color::Magenta)),
+        Span::styled(modified_indicator, Style::default().fg(theme.modified)),
     ]))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_compare_stamps() {
+        let now = Utc::now();
+        let stamp = |size, mtime| FileStamp {
+            size,
+            mtime: Some(mtime),
+            is_dir: false,
+        };
+
+        assert_eq!(
+            compare_stamps(
+                Some(stamp(3000, now)),
+                Some(stamp(2000, now - Duration::minutes(125)))
+            ),
+            "Left is newer by 2h 5m, Left is larger by 1000 B"
+        );
+        assert_eq!(
+            compare_stamps(Some(stamp(10, now)), Some(stamp(10, now))),
+            "Same size and mtime"
+        );
+        assert_eq!(
+            compare_stamps(None, Some(stamp(10, now))),
+            "Only on one side"
+        );
+    }
+}
@@ -2,23 +2,23 @@
 
 use ratatui::{
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Sparkline},
     Frame,
 };
 
-use crate::app::{SyncCompleteState, SyncingState};
-use crate::sync::differ::SyncAction;
-use crate::ui::{format_bytes, format_duration};
+use crate::app::{CompleteList, SyncCompleteState, SyncingState};
+use crate::ui::{format_bytes, format_duration, Theme};
 
 /// Render the syncing progress screen
-pub fn render_syncing(frame: &mut Frame, area: Rect, syncing: &SyncingState) {
+pub fn render_syncing(frame: &mut Frame, area: Rect, syncing: &SyncingState, theme: &Theme) {
     let chunks = Layout::vertical([
         Constraint::Length(3), // Files progress
         Constraint::Length(3), // Bytes progress
         Constraint::Length(2), // Current file
         Constraint::Length(2), // Time info
+        Constraint::Length(6), // Throughput graph
         Constraint::Min(1),    // Spacer
     ])
     .split(area);
@@ -33,9 +33,9 @@ pub fn render_syncing(frame: &mut Frame, area: Rect, syncing: &SyncingState) {
                     syncing.completed_actions, syncing.total_actions
                 ))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(theme.border)),
         )
-        .gauge_style(Style::default().fg(Color::Green))
+        .gauge_style(Style::default().fg(theme.success))
         .ratio(files_progress);
     frame.render_widget(files_gauge, chunks[0]);
 
@@ -54,15 +54,15 @@ pub fn render_syncing(frame: &mut Frame, area: Rect, syncing: &SyncingState) {
                     format_bytes(syncing.total_bytes)
                 ))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
+                .border_style(Style::default().fg(theme.border)),
         )
-        .gauge_style(Style::default().fg(Color::Cyan))
+        .gauge_style(Style::default().fg(theme.title))
         .ratio(bytes_progress);
     frame.render_widget(bytes_gauge, chunks[1]);
 
     // Current file
     let current_file = Paragraph::new(Line::from(vec![
-        Span::styled("Current: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("Current: ", Style::default().fg(theme.muted)),
         Span::raw(syncing.current_file.display().to_string()),
     ]));
     frame.render_widget(current_file, chunks[2]);
@@ -75,55 +75,110 @@ pub fn render_syncing(frame: &mut Frame, area: Rect, syncing: &SyncingState) {
         .unwrap_or_else(|| "calculating...".to_string());
 
     let time_info = Paragraph::new(Line::from(vec![
-        Span::styled("Elapsed: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("Elapsed: ", Style::default().fg(theme.muted)),
         Span::raw(&elapsed),
         Span::raw("  "),
-        Span::styled("Remaining: ", Style::default().fg(Color::DarkGray)),
+        Span::styled("Remaining: ", Style::default().fg(theme.muted)),
         Span::raw(&remaining),
     ]));
     frame.render_widget(time_info, chunks[3]);
+
+    // Throughput graph, newest sample on the right
+    let width = chunks[4].width.saturating_sub(2) as usize;
+    let samples: Vec<u64> = syncing
+        .throughput
+        .iter()
+        .skip(syncing.throughput.len().saturating_sub(width))
+        .copied()
+        .collect();
+    let current = samples.last().copied().unwrap_or(0);
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!(" Throughput: {}/s ", format_bytes(current)))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        )
+        .style(Style::default().fg(theme.info))
+        .data(&samples);
+    frame.render_widget(sparkline, chunks[4]);
 }
 
 /// Render the sync complete screen
-pub fn render_sync_complete(frame: &mut Frame, area: Rect, complete: &SyncCompleteState) {
-    let has_errors = !complete.failed.is_empty();
+pub fn render_sync_complete(
+    frame: &mut Frame,
+    area: Rect,
+    complete: &SyncCompleteState,
+    theme: &Theme,
+) {
     let has_changed = !complete.changed_during_sync.is_empty();
+    let has_in_use = !complete.in_use.is_empty();
+    let normalized = complete
+        .completed
+        .iter()
+        .filter(|c| c.eol_normalized)
+        .count();
 
     let chunks = Layout::vertical([
         Constraint::Length(7), // Summary
-        if has_errors {
-            Constraint::Min(5)
-        } else {
-            Constraint::Length(0)
-        }, // Errors list
+        Constraint::Min(5),    // Completed, failed or skipped actions
         if has_changed {
             Constraint::Length(3)
         } else {
             Constraint::Length(0)
         }, // Changed files notice
+        if has_in_use {
+            Constraint::Length(complete.in_use.len().min(5) as u16 + 2)
+        } else {
+            Constraint::Length(0)
+        }, // Files skipped as in use
     ])
     .split(area);
 
     // Summary
+    // Totals include an unfinished earlier run the sync continued
+    let earlier = &complete.earlier;
+    let mut completed_line = vec![
+        Span::styled("Completed: ", Style::default().fg(theme.success)),
+        Span::raw(format!(
+            "{} actions",
+            earlier.completed + complete.completed.len()
+        )),
+    ];
+    if earlier.actions() > 0 {
+        completed_line.push(Span::styled(
+            format!("  ({} actions before a restart)", earlier.actions()),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    if normalized > 0 {
+        completed_line.push(Span::styled(
+            format!("  ({} with line endings normalized)", normalized),
+            Style::default().fg(theme.warning),
+        ));
+    }
     let summary_lines = vec![
         Line::from(""),
+        Line::from(completed_line),
         Line::from(vec![
-            Span::styled("Completed: ", Style::default().fg(Color::Green)),
-            Span::raw(format!("{} actions", complete.completed.len())),
-        ]),
-        Line::from(vec![
-            Span::styled("Failed: ", Style::default().fg(Color::Red)),
-            Span::raw(format!("{} actions", complete.failed.len())),
+            Span::styled("Failed: ", Style::default().fg(theme.error)),
+            Span::raw(format!(
+                "{} actions",
+                earlier.failed + complete.failed.len()
+            )),
         ]),
         Line::from(vec![
-            Span::styled("Skipped: ", Style::default().fg(Color::Yellow)),
-            Span::raw(format!("{} actions", complete.skipped.len())),
+            Span::styled("Skipped: ", Style::default().fg(theme.warning)),
+            Span::raw(format!(
+                "{} actions",
+                earlier.skipped + complete.skipped.len()
+            )),
         ]),
         Line::from(vec![
-            Span::styled("Time: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Time: ", Style::default().fg(theme.muted)),
             Span::raw(format_duration(complete.duration)),
             Span::raw("  "),
-            Span::styled("Transferred: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Transferred: ", Style::default().fg(theme.muted)),
             Span::raw(format_bytes(complete.bytes_transferred)),
         ]),
     ];
@@ -132,63 +187,146 @@ pub fn render_sync_complete(frame: &mut Frame, area: Rect, complete: &SyncComple
         Block::default()
             .borders(Borders::ALL)
             .title(" Sync Complete ")
-            .border_style(Style::default().fg(Color::Green)),
+            .border_style(Style::default().fg(theme.success)),
     );
     frame.render_widget(summary, chunks[0]);
 
-    // Errors list
-    if has_errors {
-        let visible_height = chunks[1].height.saturating_sub(2) as usize;
-        let error_items: Vec<ListItem> = complete
-            .failed
-            .iter()
-            .skip(complete.scroll_offset)
-            .take(visible_height)
-            .map(|f| {
-                let path = match &f.action {
-                    SyncAction::CopyToRight { path, .. }
-                    | SyncAction::CopyToLeft { path, .. }
-                    | SyncAction::DeleteRight { path }
-                    | SyncAction::DeleteLeft { path }
-                    | SyncAction::CreateDirRight { path }
-                    | SyncAction::CreateDirLeft { path }
-                    | SyncAction::Conflict { path, .. }
-                    | SyncAction::Skip { path, .. } => path.display().to_string(),
-                };
-                ListItem::new(Line::from(vec![
-                    Span::styled("✗ ", Style::default().fg(Color::Red)),
-                    Span::raw(path),
-                    Span::styled(" - ", Style::default().fg(Color::DarkGray)),
-                    Span::raw(&f.error),
-                ]))
-            })
-            .collect();
-
-        let errors_list = List::new(error_items).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(format!(" Errors ({}) ", complete.failed.len()))
-                .border_style(Style::default().fg(Color::Red)),
-        );
-        frame.render_widget(errors_list, chunks[1]);
-    }
+    render_complete_list(frame, chunks[1], complete, theme);
 
     // Changed files notice
     if has_changed {
         let notice = Paragraph::new(Line::from(vec![
-            Span::styled("⚠ ", Style::default().fg(Color::Yellow)),
+            Span::styled("⚠ ", Style::default().fg(theme.warning)),
             Span::raw(format!(
                 "{} files changed during sync. Press ",
                 complete.changed_during_sync.len()
             )),
-            Span::styled(" R ", Style::default().fg(Color::Black).bg(Color::Yellow)),
+            Span::styled(" R ", theme.badge(theme.warning)),
             Span::raw(" to re-analyze."),
         ]))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
+                .border_style(Style::default().fg(theme.warning)),
         );
         frame.render_widget(notice, chunks[2]);
     }
+
+    // Files skipped as in use
+    if has_in_use {
+        let visible = chunks[3].height.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = complete
+            .in_use
+            .iter()
+            .take(visible)
+            .map(|path| {
+                ListItem::new(Line::from(vec![
+                    Span::styled("⊘ ", Style::default().fg(theme.warning)),
+                    Span::raw(path.display().to_string()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    " Skipped, file in use ({}) - sync again to retry ",
+                    complete.in_use.len()
+                ))
+                .border_style(Style::default().fg(theme.warning)),
+        );
+        frame.render_widget(list, chunks[3]);
+    }
+}
+
+/// Renders the browsed action list of the sync complete screen, with a tab
+/// per list in its title
+fn render_complete_list(
+    frame: &mut Frame,
+    area: Rect,
+    complete: &SyncCompleteState,
+    theme: &Theme,
+) {
+    let color = |list| match list {
+        CompleteList::Completed => theme.success,
+        CompleteList::Failed => theme.error,
+        CompleteList::Skipped => theme.warning,
+    };
+    let mut title = vec![Span::raw(" ")];
+    for list in CompleteList::ALL {
+        let label = format!(" {} ({}) ", list.label(), complete.len_of(list));
+        title.push(if list == complete.list {
+            Span::styled(label, theme.badge(color(list)))
+        } else {
+            Span::styled(label, Style::default().fg(theme.muted))
+        });
+        title.push(Span::raw(" "));
+    }
+
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let offset = complete.scroll_offset();
+    let items: Vec<ListItem> = match complete.list {
+        CompleteList::Completed => complete
+            .completed
+            .iter()
+            .skip(offset)
+            .take(visible_height)
+            .map(|c| {
+                let mut line = vec![
+                    Span::styled("✓ ", Style::default().fg(theme.success)),
+                    Span::raw(c.action.path().display().to_string()),
+                ];
+                if c.bytes_transferred > 0 {
+                    line.push(Span::styled(" - ", Style::default().fg(theme.muted)));
+                    line.push(Span::raw(format_bytes(c.bytes_transferred)));
+                    let secs = c.duration.as_secs_f64();
+                    if secs > 0.0 {
+                        let rate = (c.bytes_transferred as f64 / secs) as u64;
+                        line.push(Span::styled(
+                            format!(" at {}/s", format_bytes(rate)),
+                            Style::default().fg(theme.muted),
+                        ));
+                    }
+                }
+                ListItem::new(Line::from(line))
+            })
+            .collect(),
+        CompleteList::Failed => complete
+            .failed
+            .iter()
+            .skip(offset)
+            .take(visible_height)
+            .map(|f| {
+                ListItem::new(Line::from(vec![
+                    Span::styled("✗ ", Style::default().fg(theme.error)),
+                    Span::raw(f.action.path().display().to_string()),
+                    Span::styled(" - ", Style::default().fg(theme.muted)),
+                    Span::raw(&f.error),
+                ]))
+            })
+            .collect(),
+        CompleteList::Skipped => complete
+            .skipped
+            .iter()
+            .skip(offset)
+            .take(visible_height)
+            .map(|s| {
+                ListItem::new(Line::from(vec![
+                    Span::styled("⊘ ", Style::default().fg(theme.warning)),
+                    Span::raw(s.action.path().display().to_string()),
+                    Span::styled(" - ", Style::default().fg(theme.muted)),
+                    Span::raw(&s.reason),
+                ]))
+            })
+            .collect(),
+    };
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(Line::from(title))
+            .border_style(Style::default().fg(color(complete.list))),
+    );
+    frame.render_widget(list, area);
 }
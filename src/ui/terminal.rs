@@ -0,0 +1,104 @@
+//! Terminal setup and teardown for the TUI
+//!
+//! The TUI needs an interactive terminal that can draw it. Without one
+//! (output piped, `TERM=dumb`) setup is refused before anything is written,
+//! so no escape sequences leak into the output. Setup undoes what it did if
+//! a later step fails, and mouse capture is optional: a terminal that
+//! rejects it still gets the TUI, only without the mouse.
+
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{bail, Context, Result};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use ratatui::DefaultTerminal;
+
+/// Mouse capture was enabled and has to be disabled on restore
+static MOUSE_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+/// Why a terminal can't run the TUI, if it can't
+pub fn unsupported_reason(
+    stdin_is_tty: bool,
+    stdout_is_tty: bool,
+    term: Option<&str>,
+) -> Option<&'static str> {
+    if !stdout_is_tty {
+        Some("output is not a terminal")
+    } else if !stdin_is_tty {
+        Some("input is not a terminal")
+    } else if term == Some("dumb") {
+        Some("the terminal (TERM=dumb) can't draw the interface")
+    } else {
+        None
+    }
+}
+
+/// Prepares the terminal for the TUI: raw mode, alternate screen, and mouse
+/// capture when available. Call [`restore`] when done.
+pub fn init() -> Result<DefaultTerminal> {
+    let term = std::env::var("TERM").ok();
+    if let Some(reason) = unsupported_reason(
+        io::stdin().is_terminal(),
+        io::stdout().is_terminal(),
+        term.as_deref(),
+    ) {
+        bail!(
+            "Cannot start the interface: {}.\n\
+             Run rahzom in an interactive terminal, use `rahzom sync <project>` to sync \
+             a project without it, or `rahzom manifest <dir> <file>` to save a manifest.",
+            reason
+        );
+    }
+
+    let terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            ratatui::restore();
+            return Err(e).context("Failed to initialize the terminal");
+        }
+    };
+
+    if execute!(io::stdout(), EnableMouseCapture).is_ok() {
+        MOUSE_CAPTURED.store(true, Ordering::SeqCst);
+        // ratatui's hook restores the screen on panic; release the mouse too
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            release_mouse();
+            hook(info);
+        }));
+    }
+    Ok(terminal)
+}
+
+/// Returns the terminal to the state it had before [`init`]
+pub fn restore() {
+    release_mouse();
+    ratatui::restore();
+}
+
+fn release_mouse() {
+    if MOUSE_CAPTURED.swap(false, Ordering::SeqCst) {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_terminals() {
+        assert_eq!(unsupported_reason(true, true, Some("xterm-256color")), None);
+        assert_eq!(unsupported_reason(true, true, None), None);
+        assert_eq!(
+            unsupported_reason(true, false, Some("xterm-256color")),
+            Some("output is not a terminal")
+        );
+        assert_eq!(
+            unsupported_reason(false, true, Some("xterm")),
+            Some("input is not a terminal")
+        );
+        assert!(unsupported_reason(true, true, Some("dumb")).is_some());
+    }
+}
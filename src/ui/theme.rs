@@ -0,0 +1,188 @@
+//! Color theme mapping semantic UI roles to terminal colors
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Serialize};
+
+/// Theme file name inside the config directory (~/.rahzom/)
+pub const THEME_FILE: &str = "theme.toml";
+
+/// Colors for each semantic role used by the render functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Titles, paths and informational dialog frames
+    pub title: Color,
+    /// Panel borders
+    pub border: Color,
+    /// Emphasized text and input cursor
+    pub text: Color,
+    /// Labels, hints and skipped items
+    pub muted: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Right-to-left actions
+    pub info: Color,
+    /// Marker for user-modified actions
+    pub modified: Color,
+    /// Focused input field label
+    pub focus: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Text on key hint badges
+    pub key_fg: Color,
+    /// Background of neutral key hint badges
+    pub key_bg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            title: Color::Cyan,
+            border: Color::DarkGray,
+            text: Color::White,
+            muted: Color::DarkGray,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            info: Color::Blue,
+            modified: Color::Magenta,
+            focus: Color::Yellow,
+            selection_fg: Color::White,
+            selection_bg: Color::DarkGray,
+            key_fg: Color::Black,
+            key_bg: Color::Gray,
+        }
+    }
+}
+
+/// Built-in theme a theme file starts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    HighContrast,
+}
+
+/// On-disk theme file: a preset plus optional per-role overrides
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    #[serde(default)]
+    preset: ThemePreset,
+    #[serde(default)]
+    colors: toml::Table,
+}
+
+impl Theme {
+    /// High-contrast theme using bright colors and no dark grays
+    pub fn high_contrast() -> Self {
+        Self {
+            title: Color::LightCyan,
+            border: Color::White,
+            text: Color::White,
+            muted: Color::Gray,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            info: Color::LightBlue,
+            modified: Color::LightMagenta,
+            focus: Color::LightYellow,
+            selection_fg: Color::Black,
+            selection_bg: Color::White,
+            key_fg: Color::Black,
+            key_bg: Color::White,
+        }
+    }
+
+    /// Returns the built-in theme for a preset
+    pub fn from_preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Default => Self::default(),
+            ThemePreset::HighContrast => Self::high_contrast(),
+        }
+    }
+
+    /// Loads `theme.toml` from the config directory.
+    /// Returns the default theme if the file doesn't exist.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = config_dir.join(THEME_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read theme file: {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Invalid theme file: {:?}", path))
+    }
+
+    /// Parses theme file content
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: ThemeFile = toml::from_str(content)?;
+
+        let mut table = toml::Table::try_from(Self::from_preset(file.preset))?;
+        for (role, color) in file.colors {
+            if !table.contains_key(&role) {
+                bail!("Unknown theme color role: {}", role);
+            }
+            table.insert(role, color);
+        }
+
+        Ok(table.try_into()?)
+    }
+
+    /// Style for neutral key hint badges
+    pub fn key_style(&self) -> Style {
+        Style::default().fg(self.key_fg).bg(self.key_bg)
+    }
+
+    /// Style for key hint badges highlighted with a role color
+    pub fn badge(&self, color: Color) -> Style {
+        Style::default().fg(self.key_fg).bg(color)
+    }
+
+    /// Style for the selected row in lists
+    pub fn selection_style(&self) -> Style {
+        Style::default().fg(self.selection_fg).bg(self.selection_bg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_file_uses_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(Theme::load(temp.path()).unwrap(), Theme::default());
+    }
+
+    #[test]
+    fn test_preset_with_overrides() {
+        let theme = Theme::parse(
+            r##"
+preset = "high-contrast"
+
+[colors]
+error = "magenta"
+border = "#808080"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.error, Color::Magenta);
+        assert_eq!(theme.border, Color::Rgb(0x80, 0x80, 0x80));
+        assert_eq!(theme.title, Theme::high_contrast().title);
+    }
+
+    #[test]
+    fn test_unknown_role_rejected() {
+        assert!(Theme::parse("[colors]\nbackground = \"red\"\n").is_err());
+        assert!(Theme::parse("preset = \"neon\"\n").is_err());
+    }
+}
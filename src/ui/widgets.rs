@@ -3,11 +3,14 @@
 use std::time::Duration;
 
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 
-/// Creates a centered rectangle with given width percentage and fixed height
+use super::Theme;
+
+/// Creates a centered rectangle with given width percentage and fixed height,
+/// clamped to `area` when the terminal is smaller than that
 pub fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
-    let popup_width = area.width * percent_x / 100;
+    let popup_width = (u32::from(area.width) * u32::from(percent_x) / 100) as u16;
     let x = (area.width.saturating_sub(popup_width)) / 2;
     let y = (area.height.saturating_sub(height)) / 2;
 
@@ -36,6 +39,19 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Formats count with thousands separators (e.g. 12,340)
+pub fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
 /// Formats duration to human-readable string (M:SS or H:MM:SS)
 pub fn format_duration(duration: Duration) -> String {
     let total_secs = duration.as_secs();
@@ -51,11 +67,11 @@ pub fn format_duration(duration: Duration) -> String {
 }
 
 /// Returns style for dialog input field based on focus state
-pub fn field_style(focused: bool) -> Style {
+pub fn field_style(focused: bool, theme: &Theme) -> Style {
     if focused {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(theme.focus)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     }
 }
 
@@ -73,6 +89,15 @@ mod tests {
         assert_eq!(format_bytes(1073741824), "1.0 GB");
     }
 
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(12340), "12,340");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(Duration::from_secs(0)), "0:00");
@@ -88,5 +113,9 @@ mod tests {
         assert_eq!(centered.height, 10);
         assert_eq!(centered.x, 25);
         assert_eq!(centered.y, 20);
+
+        // Taller than the terminal: the whole height, nothing outside it
+        let short = centered_rect(50, 14, Rect::new(0, 2, 100, 6));
+        assert_eq!((short.y, short.height), (2, 6));
     }
 }
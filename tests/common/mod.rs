@@ -3,6 +3,7 @@ use std::io::Write;
 use std::path::Path;
 
 use chrono::{DateTime, Utc};
+use rahzom::sync::utils::utc_to_system_time;
 use tempfile::TempDir;
 
 /// Content specification for a test file
@@ -58,7 +59,6 @@ impl FileSpec {
     }
 
     /// Set modification time
-    #[allow(dead_code)]
     pub fn mtime(mut self, mtime: DateTime<Utc>) -> Self {
         self.mtime = Some(mtime);
         self
@@ -115,17 +115,7 @@ fn generate_random_bytes(size: usize) -> Vec<u8> {
     bytes
 }
 
-#[allow(dead_code)]
 fn set_file_mtime(path: &Path, mtime: DateTime<Utc>) {
-    use std::time::{Duration, UNIX_EPOCH};
-
-    let timestamp = mtime.timestamp();
-    let system_time = if timestamp >= 0 {
-        UNIX_EPOCH + Duration::from_secs(timestamp as u64)
-    } else {
-        UNIX_EPOCH
-    };
-
-    // TODO: Use filetime crate for proper mtime setting
-    let _ = (path, system_time);
+    let file_time = filetime::FileTime::from_system_time(utc_to_system_time(mtime));
+    filetime::set_file_mtime(path, file_time).expect("Failed to set mtime");
 }
@@ -1,5 +1,6 @@
 mod common;
 
+use chrono::{TimeZone, Utc};
 use common::{create_test_tree, FileSpec, TreeSpec};
 use std::fs;
 
@@ -68,3 +69,19 @@ fn test_create_tree_with_random_content() {
     let content = fs::read(&file_path).unwrap();
     assert_eq!(content.len(), 1024);
 }
+
+#[test]
+fn test_create_tree_with_mtime() {
+    let mtime = Utc.with_ymd_and_hms(2020, 1, 15, 10, 30, 0).unwrap();
+    let spec = TreeSpec {
+        files: vec![FileSpec::new("old.txt").content("old").mtime(mtime)],
+    };
+    let temp = create_test_tree(&spec);
+
+    let modified = fs::metadata(temp.path().join("old.txt"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    let modified: chrono::DateTime<Utc> = modified.into();
+    assert!((modified - mtime).num_seconds().abs() <= 1);
+}
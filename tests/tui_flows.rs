@@ -0,0 +1,188 @@
+//! TUI flows driven through the app's key handling, without a terminal
+
+use std::fs;
+
+use crossterm::event::KeyCode;
+use rahzom::app::drive::drive;
+use rahzom::app::{App, Dialog, Screen};
+use rahzom::config::project::{Project, ProjectManager};
+use tempfile::TempDir;
+
+/// An app whose only project syncs `left` with `right`
+fn app_with_project(config: &TempDir, left: &TempDir, right: &TempDir) -> App {
+    let pm = ProjectManager::with_config_dir(config.path().to_path_buf());
+    pm.save_project(&Project::new(
+        "test",
+        left.path().to_path_buf(),
+        right.path().to_path_buf(),
+    ))
+    .unwrap();
+    App::with_project_manager(pm)
+}
+
+fn read(dir: &TempDir, name: &str) -> String {
+    fs::read_to_string(dir.path().join(name)).unwrap()
+}
+
+#[test]
+fn test_analyze_and_sync() {
+    let (config, left, right) = (
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+    );
+    fs::write(left.path().join("a.txt"), "from left").unwrap();
+    fs::write(right.path().join("b.txt"), "from right").unwrap();
+    let mut app = app_with_project(&config, &left, &right);
+
+    // Open the project, analyze, confirm the sync
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('a')]).unwrap();
+    assert_eq!(app.screen, Screen::Preview);
+    drive(&mut app, &[KeyCode::Char('g'), KeyCode::Enter]).unwrap();
+
+    assert_eq!(app.screen, Screen::SyncComplete);
+    let complete = app.sync_complete.as_ref().unwrap();
+    assert_eq!(complete.completed.len(), 2);
+    assert_eq!(read(&right, "a.txt"), "from left");
+    assert_eq!(read(&left, "b.txt"), "from right");
+
+    // The run is in the project's history
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('h')]).unwrap();
+    assert_eq!(app.screen, Screen::History);
+    let runs = &app.history.as_ref().unwrap().runs;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].completed, 2);
+    assert!(!runs[0].cancelled);
+}
+
+#[test]
+fn test_resolve_conflict_and_sync() {
+    let (config, left, right) = (
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+    );
+    fs::write(left.path().join("c.txt"), "left version").unwrap();
+    fs::write(right.path().join("c.txt"), "right version, longer").unwrap();
+    let mut app = app_with_project(&config, &left, &right);
+
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('a')]).unwrap();
+    let preview = app.preview.as_ref().unwrap();
+    assert_eq!(preview.summary().conflicts, 1);
+
+    // Nothing to sync until the conflict is resolved
+    drive(&mut app, &[KeyCode::Char('g')]).unwrap();
+    drive(&mut app, &[KeyCode::Enter]).unwrap();
+    assert_eq!(app.screen, Screen::Preview);
+
+    // Left wins
+    drive(
+        &mut app,
+        &[KeyCode::Right, KeyCode::Char('g'), KeyCode::Enter],
+    )
+    .unwrap();
+    assert_eq!(app.screen, Screen::SyncComplete);
+    assert_eq!(read(&right, "c.txt"), "left version");
+
+    // Back in the project view, a new analysis has nothing left to do
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('a')]).unwrap();
+    let preview = app.preview.as_ref().unwrap();
+    assert_eq!(preview.summary().conflicts, 0);
+    assert_eq!(preview.summary().copy_to_right, 0);
+}
+
+#[test]
+fn test_save_preview_as_script() {
+    let (config, left, right) = (
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+    );
+    fs::write(left.path().join("a b.txt"), "new").unwrap();
+    let mut app = app_with_project(&config, &left, &right);
+
+    drive(
+        &mut app,
+        &[KeyCode::Enter, KeyCode::Char('a'), KeyCode::Char('x')],
+    )
+    .unwrap();
+    let script = config.path().join("sync-script");
+    let Dialog::Script(ref mut dialog) = app.dialog else {
+        panic!("no script dialog: {:?}", app.dialog);
+    };
+    dialog.path = script.to_string_lossy().to_string();
+    drive(&mut app, &[KeyCode::Enter]).unwrap();
+
+    assert_eq!(app.dialog, Dialog::None);
+    let text = fs::read_to_string(&script).unwrap();
+    assert!(text.contains("LOSSY"), "{}", text);
+    assert!(text.contains("a b.txt"), "{}", text);
+    // Nothing was synced
+    assert!(!right.path().join("a b.txt").exists());
+}
+
+#[test]
+fn test_empty_files_sync_once() {
+    let (config, left, right) = (
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+    );
+    fs::write(left.path().join("empty.txt"), "").unwrap();
+    fs::write(left.path().join("both.txt"), "").unwrap();
+    fs::write(right.path().join("both.txt"), "").unwrap();
+    let mut app = app_with_project(&config, &left, &right);
+
+    // Two empty files are equal; the one-sided one is copied
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('a')]).unwrap();
+    let summary = app.preview.as_ref().unwrap().summary();
+    assert_eq!(summary.copy_to_right, 1);
+    assert_eq!(summary.copy_to_left + summary.conflicts, 0);
+    drive(&mut app, &[KeyCode::Char('g'), KeyCode::Enter]).unwrap();
+    assert_eq!(app.screen, Screen::SyncComplete);
+    assert_eq!(read(&right, "empty.txt"), "");
+
+    // ...and only once
+    drive(&mut app, &[KeyCode::Enter, KeyCode::Char('a')]).unwrap();
+    let summary = app.preview.as_ref().unwrap().summary();
+    assert_eq!(summary.copy_to_right + summary.copy_to_left, 0);
+    assert_eq!(summary.conflicts, 0);
+
+    // A deleted empty file is a deletion, not a file to bring back
+    fs::remove_file(right.path().join("empty.txt")).unwrap();
+    drive(&mut app, &[KeyCode::Esc, KeyCode::Char('a')]).unwrap();
+    let summary = app.preview.as_ref().unwrap().summary();
+    assert_eq!(summary.delete_left, 1);
+    assert_eq!(summary.copy_to_right, 0);
+}
+
+#[test]
+fn test_pin_project_to_top() {
+    let (config, left, right) = (
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+        TempDir::new().unwrap(),
+    );
+    let mut app = app_with_project(&config, &left, &right);
+    app.project_manager
+        .as_ref()
+        .unwrap()
+        .save_project(&Project::new(
+            "archive",
+            left.path().to_path_buf(),
+            right.path().to_path_buf(),
+        ))
+        .unwrap();
+    app.refresh_projects();
+    assert_eq!(app.projects, ["archive", "test"]);
+
+    // Pinning moves it up and keeps it selected
+    drive(&mut app, &[KeyCode::Down, KeyCode::Char('p')]).unwrap();
+    assert_eq!(app.projects, ["test", "archive"]);
+    assert_eq!(app.list_state.selected(), Some(0));
+    assert!(app.pinned_projects.contains("test"));
+
+    drive(&mut app, &[KeyCode::Char('p')]).unwrap();
+    assert_eq!(app.projects, ["archive", "test"]);
+    assert!(app.pinned_projects.is_empty());
+}